@@ -0,0 +1,268 @@
+//! A computer player that searches several turns ahead.
+//!
+//! Every player other than the one searching is assumed to play against it, so the search is a
+//! plain minimax: the searching player maximizes its node count, and everyone else minimizes it.
+//! Alpha-beta pruning skips the turns that can't change the result.
+
+use crate::game::prelude::*;
+
+/// Chooses turns by searching every line of play up to a fixed number of turns ahead.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MinimaxAi {
+    /// The number of turns searched, including the turn being chosen.
+    depth: u8,
+}
+
+impl MinimaxAi {
+    pub const DEFAULT_DEPTH: u8 = 2;
+
+    /// # Panics
+    /// Panics if `depth` is 0.
+    pub fn new(depth: u8) -> Self {
+        assert!(depth > 0, "minimax depth must be at least 1");
+        Self { depth }
+    }
+
+    /// The turn leaving the current player with the most nodes after `depth` turns, assuming every
+    /// other player replies with the turn leaving it with the fewest.
+    ///
+    /// When the rules allow rotating a node several times, every number of rotations is searched.
+    /// Ties go to the first position in row order, and then to the fewest rotations.
+    ///
+    /// # Panics
+    /// Panics if the current player has no legal move.
+    pub fn best_turn(&self, game: &Game) -> Turn {
+        let color = game.turn_color();
+        let mut best = None;
+        let mut alpha = 0;
+        for turn in legal_moves(game) {
+            let score = self.score(game, turn, color, self.depth, alpha, u16::MAX);
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((turn, score));
+                alpha = score;
+            }
+        }
+        best.expect("no legal move").0
+    }
+
+    /// The value to `color` of taking `turn`, with `depth` turns left to search including this
+    /// one.
+    ///
+    /// Values outside of `alpha..beta` are only bounds: a value at or below `alpha` may be higher
+    /// than the true value, and a value at or above `beta` may be lower.
+    fn score(
+        &self,
+        game: &Game,
+        turn: Turn,
+        color: Color,
+        depth: u8,
+        alpha: u16,
+        beta: u16,
+    ) -> u16 {
+        if depth == 1 {
+            // The last turn doesn't need a game to search from.
+            return game
+                .preview_turn(turn)
+                .expect("legal move was not a valid turn")
+                .counts()
+                .for_color(color);
+        }
+
+        let mut future = game.scratch();
+        if future.execute_turn_trusted(turn).is_some() {
+            // Nothing more can happen once the game is over.
+            return future.count(color);
+        }
+        self.value(&future, color, depth - 1, alpha, beta)
+    }
+
+    /// The value to `color` of the current player's best turn in `game`, with `depth` turns left
+    /// to search.
+    fn value(&self, game: &Game, color: Color, depth: u8, mut alpha: u16, mut beta: u16) -> u16 {
+        let maximizing = game.turn_color() == color;
+        let mut best = None;
+        for turn in legal_moves(game) {
+            let score = self.score(game, turn, color, depth, alpha, beta);
+            if maximizing {
+                let best = best.get_or_insert(score);
+                *best = (*best).max(score);
+                alpha = alpha.max(*best);
+            } else {
+                let best = best.get_or_insert(score);
+                *best = (*best).min(score);
+                beta = beta.min(*best);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        // A player without a legal move passes, which ends the search here.
+        best.unwrap_or_else(|| game.count(color))
+    }
+}
+
+impl Default for MinimaxAi {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DEPTH)
+    }
+}
+
+/// The turns the current player can legally take, including every number of rotations the rules
+/// allow.
+fn legal_moves(game: &Game) -> impl Iterator<Item = Turn> + '_ {
+    let turn_color = game.turn_color();
+    game.valid_turns()
+        .filter(move |turn| game.is_legal_move(turn_color, turn.rotate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{legal_moves, MinimaxAi};
+    use crate::game::{Color, Direction, Game, Grid, Node, Position, Rules, Turn};
+    use deranged::RangedU8;
+    use gba_test::test;
+
+    /// Minimax without pruning, giving the value to `color` of the current player's best turn.
+    fn minimax(game: &Game, color: Color, depth: u8) -> u16 {
+        let maximizing = game.turn_color() == color;
+        let mut best = None;
+        for turn in legal_moves(game) {
+            let mut future = game.scratch();
+            let score = if future.execute_turn_trusted(turn).is_some() || depth == 1 {
+                future.count(color)
+            } else {
+                minimax(&future, color, depth - 1)
+            };
+            best = Some(match best {
+                None => score,
+                Some(best) if maximizing => score.max(best),
+                Some(best) => score.min(best),
+            });
+        }
+        best.unwrap_or_else(|| game.count(color))
+    }
+
+    /// The first turn with the best minimax value for the current player.
+    fn unpruned_best_turn(game: &Game, depth: u8) -> Turn {
+        let color = game.turn_color();
+        let mut best = None;
+        for turn in legal_moves(game) {
+            let mut future = game.scratch();
+            let score = if future.execute_turn_trusted(turn).is_some() || depth == 1 {
+                future.count(color)
+            } else {
+                minimax(&future, color, depth - 1)
+            };
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((turn, score));
+            }
+        }
+        best.unwrap().0
+    }
+
+    #[test]
+    fn default_depth() {
+        assert_eq!(MinimaxAi::default().depth, 2);
+        assert_eq!(MinimaxAi::new(4).depth, 4);
+    }
+
+    #[test]
+    fn depth_one_captures_most() {
+        let game = Game::builder().grid(Grid::generate(3)).build();
+
+        let turn = MinimaxAi::new(1).best_turn(&game);
+
+        let best = game
+            .preview_turn(turn)
+            .unwrap()
+            .counts()
+            .for_color(Color::Red);
+        for turn in legal_moves(&game) {
+            let preview = game.preview_turn(turn).unwrap();
+            assert!(preview.counts().for_color(Color::Red) <= best);
+        }
+    }
+
+    #[test]
+    fn pruning_matches_minimax() {
+        for (seed, multi_rotation) in (0..8).flat_map(|seed| [(seed, false), (seed, true)]) {
+            let mut game = Game::builder()
+                .grid(Grid::generate_with_size(seed, 6))
+                .rules(Rules {
+                    multi_rotation,
+                    ..Rules::default()
+                })
+                .build();
+            // Play a few turns in so that the players are in reach of each other.
+            for _ in 0..4 {
+                let turn = MinimaxAi::new(1).best_turn(&game);
+                if game.execute_turn_trusted(turn).is_some() {
+                    break;
+                }
+            }
+            if !game.has_legal_move(game.turn_color()) {
+                continue;
+            }
+
+            for depth in 1..=3 {
+                assert_eq!(
+                    MinimaxAi::new(depth).best_turn(&game),
+                    unpruned_best_turn(&game, depth),
+                    "seed {seed}, multi rotation {multi_rotation}, depth {depth}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn takes_winning_turn() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[5][5] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        nodes[5][6] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Up,
+        };
+        nodes[9][9] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert_eq!(
+            MinimaxAi::default().best_turn(&game).rotate,
+            Position { x: 5, y: 5 }
+        );
+    }
+
+    #[test]
+    fn searches_multiple_rotations() {
+        // Only turning the red arrow twice, from right to left, captures the blue arrow.
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[5][5] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Right,
+        };
+        nodes[5][4] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Up,
+        };
+        let game = Game::builder()
+            .grid(Grid::new(nodes))
+            .rules(Rules {
+                multi_rotation: true,
+                ..Rules::default()
+            })
+            .build();
+
+        assert_eq!(
+            MinimaxAi::default().best_turn(&game),
+            Turn {
+                rotate: Position { x: 5, y: 5 },
+                times: RangedU8::new_static::<2>(),
+            }
+        );
+    }
+}
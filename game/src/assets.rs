@@ -0,0 +1,381 @@
+//! Verification of the embedded binary assets.
+//!
+//! Graphics and sound are exported by external tools and loaded without any checks, so a bad
+//! export shows up as garbled output with no indication of which file is wrong. In debug builds,
+//! every asset is checked against what the code loading it expects once at startup.
+
+use crate::{
+    audio::{self, SAMPLES},
+    include_bytes_aligned,
+};
+use core::ops::Range;
+use log::error;
+
+/// The size of a single 16-color palette bank, in bytes.
+const PALETTE_BANK_LEN: usize = 32;
+/// The size of a single 4bpp tile, in bytes.
+const TILE_4BPP_LEN: usize = 32;
+/// The size of a single 8bpp tile, in bytes.
+const TILE_8BPP_LEN: usize = 64;
+/// The size of a single screen entry, in bytes.
+const SCREEN_ENTRY_LEN: usize = 2;
+/// The size of a sample's header, which holds its sample rate.
+const SAMPLE_HEADER_LEN: usize = 4;
+
+/// What an asset is loaded as.
+#[derive(Debug)]
+enum Kind {
+    Palette {
+        banks: usize,
+    },
+    Tiles4bpp {
+        tiles: usize,
+    },
+    Tiles8bpp {
+        tiles: usize,
+    },
+    /// Screen entries, drawn while only the given tiles and palette banks are loaded.
+    Map {
+        entries: usize,
+        tiles: Range<u16>,
+        palettes: Range<u8>,
+    },
+    /// A sample rate followed by signed 8-bit samples, as played by `audio::play()`.
+    Sample,
+}
+
+/// A problem found in an asset.
+#[derive(Debug, Eq, PartialEq)]
+enum Finding {
+    /// The asset is not the size it is loaded as.
+    Length { expected: usize, actual: usize },
+    /// A screen entry refers to a tile that isn't loaded.
+    TileOutOfRange { entry: usize, tile: u16 },
+    /// A screen entry refers to a palette bank that isn't loaded.
+    PaletteOutOfRange { entry: usize, palette: u8 },
+    /// A sample is too short to contain any samples after its header.
+    Truncated { actual: usize },
+    /// A sample's rate can't be produced by the timer driving playback.
+    SampleRate(u32),
+}
+
+/// Check `bytes` against what it is loaded as, returning the first problem found.
+fn verify(kind: &Kind, bytes: &[u8]) -> Option<Finding> {
+    let expected = match kind {
+        Kind::Palette { banks } => banks * PALETTE_BANK_LEN,
+        Kind::Tiles4bpp { tiles } => tiles * TILE_4BPP_LEN,
+        Kind::Tiles8bpp { tiles } => tiles * TILE_8BPP_LEN,
+        Kind::Map { entries, .. } => entries * SCREEN_ENTRY_LEN,
+        Kind::Sample => return verify_sample(bytes),
+    };
+    if bytes.len() != expected {
+        return Some(Finding::Length {
+            expected,
+            actual: bytes.len(),
+        });
+    }
+
+    if let Kind::Map {
+        tiles, palettes, ..
+    } = kind
+    {
+        for (entry, bytes) in bytes.chunks_exact(SCREEN_ENTRY_LEN).enumerate() {
+            let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+            let tile = value & 1023;
+            if !tiles.contains(&tile) {
+                return Some(Finding::TileOutOfRange { entry, tile });
+            }
+            let palette = (value >> 12) as u8;
+            if !palettes.contains(&palette) {
+                return Some(Finding::PaletteOutOfRange { entry, palette });
+            }
+        }
+    }
+
+    None
+}
+
+fn verify_sample(bytes: &[u8]) -> Option<Finding> {
+    if bytes.len() <= SAMPLE_HEADER_LEN {
+        return Some(Finding::Truncated {
+            actual: bytes.len(),
+        });
+    }
+    let rate = u32::from_le_bytes(bytes[..SAMPLE_HEADER_LEN].try_into().unwrap());
+    // The timer counts up to overflow from a 16-bit reload value of `65536 - CLOCK / rate`.
+    if rate == 0 || rate > audio::CLOCK || audio::CLOCK / rate > 65536 {
+        return Some(Finding::SampleRate(rate));
+    }
+    None
+}
+
+/// An embedded asset, along with how it is loaded.
+#[derive(Debug)]
+struct Asset {
+    name: &'static str,
+    bytes: &'static [u8],
+    kind: Kind,
+}
+
+macro_rules! asset {
+    ($name:literal, $kind:expr) => {
+        Asset {
+            name: $name,
+            bytes: &include_bytes_aligned!(concat!("../res/", $name)).0,
+            kind: $kind,
+        }
+    };
+}
+
+/// Every embedded graphics asset.
+///
+/// Maps are given the tiles and palette banks loaded by the screen drawing them.
+static ASSETS: [Asset; 49] = [
+    asset!("splash_jam.pal", Kind::Palette { banks: 16 }),
+    asset!("splash_jam.8bpp", Kind::Tiles8bpp { tiles: 161 }),
+    asset!(
+        "splash_jam.map",
+        Kind::Map {
+            entries: 600,
+            tiles: 0..161,
+            // The palette bits are ignored in 8bpp mode.
+            palettes: 0..1,
+        }
+    ),
+    asset!("title.pal", Kind::Palette { banks: 1 }),
+    asset!("press_a.pal", Kind::Palette { banks: 1 }),
+    asset!("title.4bpp", Kind::Tiles4bpp { tiles: 75 }),
+    asset!("press_a.4bpp", Kind::Tiles4bpp { tiles: 23 }),
+    asset!(
+        "title.map",
+        Kind::Map {
+            entries: 100,
+            tiles: 0..100,
+            palettes: 0..3,
+        }
+    ),
+    asset!(
+        "press_a.map",
+        Kind::Map {
+            entries: 24,
+            tiles: 0..100,
+            palettes: 0..3,
+        }
+    ),
+    asset!("neutral.pal", Kind::Palette { banks: 1 }),
+    asset!("red.pal", Kind::Palette { banks: 1 }),
+    asset!("blue.pal", Kind::Palette { banks: 1 }),
+    asset!("yellow.pal", Kind::Palette { banks: 1 }),
+    asset!("green.pal", Kind::Palette { banks: 1 }),
+    asset!("cursor.pal", Kind::Palette { banks: 1 }),
+    asset!("cursor.4bpp", Kind::Tiles4bpp { tiles: 4 }),
+    asset!("empty.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("background.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("wall.4bpp", Kind::Tiles4bpp { tiles: 4 }),
+    asset!("arrow_right.4bpp", Kind::Tiles4bpp { tiles: 4 }),
+    asset!("arrow_up.4bpp", Kind::Tiles4bpp { tiles: 4 }),
+    asset!("reverse_arrow_right.4bpp", Kind::Tiles4bpp { tiles: 4 }),
+    asset!("reverse_arrow_up.4bpp", Kind::Tiles4bpp { tiles: 4 }),
+    asset!("arrow_all.4bpp", Kind::Tiles4bpp { tiles: 4 }),
+    asset!("teleporter.4bpp", Kind::Tiles4bpp { tiles: 4 }),
+    asset!("super_arrow_up.4bpp", Kind::Tiles4bpp { tiles: 4 }),
+    asset!("super_arrow_right.4bpp", Kind::Tiles4bpp { tiles: 4 }),
+    asset!("grid0.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid0_left.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid0_up.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid0_left_up.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid1.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid1_right.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid1_up.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid1_right_up.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid2.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid2_left.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid2_down.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid2_left_down.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid3.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid3_right.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid3_down.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("grid3_right_down.4bpp", Kind::Tiles4bpp { tiles: 1 }),
+    asset!("win.pal", Kind::Palette { banks: 1 }),
+    asset!("lose.pal", Kind::Palette { banks: 1 }),
+    asset!("win.4bpp", Kind::Tiles4bpp { tiles: 14 }),
+    asset!("lose.4bpp", Kind::Tiles4bpp { tiles: 16 }),
+    asset!(
+        "win.map",
+        Kind::Map {
+            entries: 16,
+            tiles: 0..88,
+            palettes: 0..7,
+        }
+    ),
+    asset!(
+        "lose.map",
+        Kind::Map {
+            entries: 16,
+            tiles: 0..88,
+            palettes: 0..7,
+        }
+    ),
+];
+
+/// Verify every embedded asset, logging each problem found.
+///
+/// # Panics
+/// If any asset has a problem.
+pub fn verify_all() {
+    let mut valid = true;
+    for asset in &ASSETS {
+        if let Some(finding) = verify(&asset.kind, asset.bytes) {
+            error!("asset {}: {:?}", asset.name, finding);
+            valid = false;
+        }
+    }
+    for sample in &SAMPLES {
+        if let Some(finding) = verify(&Kind::Sample, sample.bytes()) {
+            error!("sample {}: {:?}", sample.name, finding);
+            valid = false;
+        }
+    }
+    assert!(valid, "embedded assets failed verification");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verify, verify_all, Finding, Kind};
+    use gba_test::test;
+
+    #[test]
+    fn embedded_assets_valid() {
+        verify_all();
+    }
+
+    #[test]
+    fn palette_wrong_length() {
+        assert_eq!(
+            verify(&Kind::Palette { banks: 1 }, &[0; 30]),
+            Some(Finding::Length {
+                expected: 32,
+                actual: 30
+            })
+        );
+    }
+
+    #[test]
+    fn tiles_partial_tile() {
+        assert_eq!(
+            verify(&Kind::Tiles4bpp { tiles: 2 }, &[0; 48]),
+            Some(Finding::Length {
+                expected: 64,
+                actual: 48
+            })
+        );
+    }
+
+    #[test]
+    fn tiles_8bpp_valid() {
+        assert_eq!(verify(&Kind::Tiles8bpp { tiles: 2 }, &[0; 128]), None);
+    }
+
+    #[test]
+    fn map_odd_length() {
+        assert_eq!(
+            verify(
+                &Kind::Map {
+                    entries: 2,
+                    tiles: 0..4,
+                    palettes: 0..1,
+                },
+                &[0; 3]
+            ),
+            Some(Finding::Length {
+                expected: 4,
+                actual: 3
+            })
+        );
+    }
+
+    #[test]
+    fn map_tile_out_of_range() {
+        assert_eq!(
+            verify(
+                &Kind::Map {
+                    entries: 3,
+                    tiles: 0..4,
+                    palettes: 0..1,
+                },
+                &[3, 0, 4, 0, 0, 0]
+            ),
+            Some(Finding::TileOutOfRange { entry: 1, tile: 4 })
+        );
+    }
+
+    #[test]
+    fn map_tile_ignores_flip_bits() {
+        assert_eq!(
+            verify(
+                &Kind::Map {
+                    entries: 1,
+                    tiles: 0..4,
+                    palettes: 0..1,
+                },
+                &[3, 0b0000_1100]
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn map_palette_out_of_range() {
+        assert_eq!(
+            verify(
+                &Kind::Map {
+                    entries: 2,
+                    tiles: 0..4,
+                    palettes: 0..3,
+                },
+                &[0, 0x20, 0, 0x30]
+            ),
+            Some(Finding::PaletteOutOfRange {
+                entry: 1,
+                palette: 3
+            })
+        );
+    }
+
+    #[test]
+    fn sample_valid() {
+        assert_eq!(verify(&Kind::Sample, &[0x40, 0x1f, 0, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn sample_header_only() {
+        assert_eq!(
+            verify(&Kind::Sample, &[0x40, 0x1f, 0, 0]),
+            Some(Finding::Truncated { actual: 4 })
+        );
+    }
+
+    #[test]
+    fn sample_rate_zero() {
+        assert_eq!(
+            verify(&Kind::Sample, &[0, 0, 0, 0, 0]),
+            Some(Finding::SampleRate(0))
+        );
+    }
+
+    #[test]
+    fn sample_rate_too_low_for_timer() {
+        assert_eq!(
+            verify(&Kind::Sample, &[255, 0, 0, 0, 0]),
+            Some(Finding::SampleRate(255))
+        );
+    }
+
+    #[test]
+    fn sample_rate_too_high_for_timer() {
+        assert_eq!(
+            verify(&Kind::Sample, &[1, 0, 0, 1, 0]),
+            Some(Finding::SampleRate(0x0100_0001))
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Playback of sound effect samples through direct sound channel A.
+//!
+//! Samples are signed 8-bit PCM, prefixed by their sample rate as a little-endian `u32`. They are
+//! streamed into the FIFO by DMA 1, paced by timer 0.
+
+use crate::{
+    include_bytes_aligned,
+    mmio::{
+        audio::{Control, Enable},
+        dma::{AddressControl, DmaControl, Timing},
+        timer, AUDIO_CONTROL, AUDIO_ENABLE, AUDIO_FIFO_A, DMA1_CNT, DMA1_DESTINATION, DMA1_SOURCE,
+        TIMER0_CONTROL, TIMER0_COUNT,
+    },
+};
+
+/// The frequency of the system clock driving the timers.
+pub const CLOCK: u32 = 1 << 24;
+
+/// The display's refresh rate, rounded down.
+const FRAMES_PER_SECOND: u32 = 59;
+
+/// A sound effect.
+#[derive(Debug)]
+pub struct Sample {
+    pub name: &'static str,
+    /// The sample rate followed by the samples, aligned to 4 bytes for DMA.
+    bytes: &'static [u8],
+}
+
+impl Sample {
+    fn rate(&self) -> u32 {
+        u32::from_le_bytes(self.bytes[..4].try_into().unwrap())
+    }
+
+    /// The sample rate followed by the samples.
+    pub fn bytes(&self) -> &'static [u8] {
+        self.bytes
+    }
+
+    fn samples(&self) -> &'static [u8] {
+        &self.bytes[4..]
+    }
+
+    /// How many frames the sample plays for, rounded up.
+    pub fn frames(&self) -> u32 {
+        (self.samples().len() as u32 * FRAMES_PER_SECOND).div_ceil(self.rate())
+    }
+}
+
+/// Every sound effect in the game.
+pub const SAMPLES: [Sample; 1] = [Sample {
+    name: "CAMERA ZOOM IN",
+    bytes: &include_bytes_aligned!("../res/audio/camera_zoom_in.bin").0,
+}];
+
+/// Start playing `sample`, replacing whatever is playing.
+///
+/// The DMA keeps reading past the end of the sample, so `stop()` must be called once it has played
+/// for `Sample::frames()` frames.
+pub fn play(sample: &Sample) {
+    stop();
+    unsafe {
+        // Sound registers can only be written while sound is enabled.
+        AUDIO_ENABLE.write_volatile(Enable::new().master_enable(true));
+        AUDIO_CONTROL.write_volatile(
+            Control::new()
+                .sound_a_full_volume(true)
+                .sound_a_right(true)
+                .sound_a_left(true)
+                .sound_a_fifo_reset(true),
+        );
+        DMA1_SOURCE.write_volatile(sample.samples().as_ptr());
+        DMA1_DESTINATION.write_volatile(AUDIO_FIFO_A.cast());
+        DMA1_CNT.write_volatile(
+            DmaControl::new()
+                .with_destination_address_control(AddressControl::Fixed)
+                .with_repeat()
+                .with_transfer_32bit()
+                .with_timing(Timing::Special)
+                .with_enabled(),
+        );
+        TIMER0_COUNT.write_volatile((65536 - CLOCK / sample.rate()) as u16);
+        TIMER0_CONTROL.write_volatile(
+            timer::Control::new()
+                .with_prescaler(timer::Prescaler::Freq1)
+                .with_enable(true),
+        );
+    }
+}
+
+/// Stop playback, returning the audio registers to their state at boot.
+pub fn stop() {
+    unsafe {
+        TIMER0_CONTROL.write_volatile(timer::Control::new());
+        DMA1_CNT.write_volatile(DmaControl::new());
+        AUDIO_CONTROL.write_volatile(Control::new());
+        AUDIO_ENABLE.write_volatile(Enable::new());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SAMPLES;
+    use gba_test::test;
+
+    #[test]
+    fn samples_aligned() {
+        for sample in SAMPLES {
+            assert_eq!(sample.samples().as_ptr() as usize % 4, 0);
+        }
+    }
+
+    #[test]
+    fn samples_play_for_some_frames() {
+        for sample in SAMPLES {
+            assert!(sample.frames() > 0);
+        }
+    }
+}
@@ -1,10 +1,19 @@
 //! Functions and types to interract with the GBA BIOS.
 
+#[cfg(not(debug_assertions))]
+use crate::mmio::VCOUNT;
+use crate::mmio::{interrupts::Interrupts, vram::DisplayStatus, DISPSTAT, IE, IME};
 use core::arch::asm;
 
+/// The scanline at which v-blank starts.
+#[cfg(not(debug_assertions))]
+const VBLANK_START: u16 = 160;
+
 /// Waits until a new v-blank interrupt occurs.
 #[instruction_set(arm::t32)]
 pub fn wait_for_vblank() {
+    #[cfg(debug_assertions)]
+    crate::watchdog::check();
     unsafe {
         asm! {
             "swi #0x05",
@@ -14,4 +23,108 @@ pub fn wait_for_vblank() {
             options(preserves_flags),
         }
     };
+    #[cfg(debug_assertions)]
+    crate::watchdog::resume();
+}
+
+/// A reason the v-blank interrupt would never be raised.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Unarmed {
+    /// Interrupts are disabled altogether in IME.
+    Master,
+    /// The v-blank interrupt is not enabled in IE.
+    Enable(Interrupts),
+    /// The display is not requesting v-blank interrupts in DISPSTAT.
+    Display(DisplayStatus),
+}
+
+/// Check that the v-blank interrupt is armed, given the values of the registers controlling it.
+fn check_armed(ime: bool, ie: Interrupts, dispstat: DisplayStatus) -> Result<(), Unarmed> {
+    if !ime {
+        Err(Unarmed::Master)
+    } else if !ie.contains(Interrupts::VBLANK) {
+        Err(Unarmed::Enable(ie))
+    } else if !dispstat.vblank_interrupts_enabled() {
+        Err(Unarmed::Display(dispstat))
+    } else {
+        Ok(())
+    }
+}
+
+/// Waits until a new v-blank occurs, without hanging if the v-blank interrupt isn't armed.
+///
+/// `wait_for_vblank()` never returns if the interrupt is disabled. In debug builds this panics
+/// instead, logging the offending register. In release builds it falls back to polling the
+/// current scanline.
+pub fn wait_for_vblank_checked() {
+    let armed = unsafe {
+        check_armed(
+            IME.read_volatile(),
+            IE.read_volatile(),
+            DISPSTAT.read_volatile(),
+        )
+    };
+    match armed {
+        Ok(()) => wait_for_vblank(),
+        #[cfg(debug_assertions)]
+        Err(unarmed) => panic!("v-blank interrupt is not armed: {:?}", unarmed),
+        #[cfg(not(debug_assertions))]
+        Err(_) => unsafe {
+            // Finish any v-blank in progress, then wait for the next one to start.
+            while VCOUNT.read_volatile() >= VBLANK_START {}
+            while VCOUNT.read_volatile() < VBLANK_START {}
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_armed, Unarmed};
+    use crate::mmio::{interrupts::Interrupts, vram::DisplayStatus};
+    use core::mem::transmute;
+    use gba_test::test;
+
+    #[test]
+    fn armed() {
+        assert_eq!(
+            check_armed(
+                true,
+                Interrupts::VBLANK,
+                DisplayStatus::ENABLE_VBLANK_INTERRUPTS
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn master_disabled() {
+        assert_eq!(
+            check_armed(
+                false,
+                Interrupts::VBLANK,
+                DisplayStatus::ENABLE_VBLANK_INTERRUPTS
+            ),
+            Err(Unarmed::Master)
+        );
+    }
+
+    #[test]
+    fn vblank_not_enabled() {
+        let ie = unsafe { transmute::<u16, Interrupts>(0b0000_0000_0000_0010) };
+
+        assert_eq!(
+            check_armed(true, ie, DisplayStatus::ENABLE_VBLANK_INTERRUPTS),
+            Err(Unarmed::Enable(ie))
+        );
+    }
+
+    #[test]
+    fn display_not_requesting() {
+        let dispstat = unsafe { transmute::<u16, DisplayStatus>(0b0000_0000_0000_0001) };
+
+        assert_eq!(
+            check_armed(true, Interrupts::VBLANK, dispstat),
+            Err(Unarmed::Display(dispstat))
+        );
+    }
 }
@@ -0,0 +1,414 @@
+//! Exporting finished games as text, so they can be shared and played back elsewhere.
+//!
+//! An export is the game's replay serialized to bytes:
+//!
+//! | bytes        | contents                                         |
+//! |--------------|--------------------------------------------------|
+//! | 0..2         | compatibility version, little endian             |
+//! | 2..19        | board code, as produced by `BoardCode::encode()` |
+//! | 19           | color that took the first turn                   |
+//! | 20..28       | final `Game::state_hash()`, little endian        |
+//! | 28..30       | number of turns, little endian                   |
+//! | 30..30+turns | turns, packed one per byte                       |
+//! | last 2       | checksum of everything before it                 |
+//!
+//! The bytes are then written with a 64 character alphabet, 6 bits per character, so that they can
+//! be copied out of a log as plain text.
+
+use crate::{
+    game::{board_code, compat::COMPAT_VERSION, replay::MAX_RECORDED_TURNS, Game, Recording},
+    records::checksum,
+};
+use log::{info, warn};
+
+/// The characters used to encode each 6-bit group.
+const ALPHABET: [u8; 64] = *b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Where the board code starts, after the compatibility version.
+const CODE_START: usize = 2;
+
+/// Where the first color is stored, after the board code.
+const FIRST: usize = CODE_START + board_code::LEN;
+
+/// Where the final hash starts, after the first color.
+const HASH_START: usize = FIRST + 1;
+
+/// The number of bytes before the turns.
+const HEADER_LEN: usize = HASH_START + 8 + 2;
+
+/// The most bytes an export can take up.
+const MAX_LEN: usize = HEADER_LEN + MAX_RECORDED_TURNS + 2;
+
+/// The number of characters logged per line.
+const LINE_LEN: usize = 64;
+
+/// The number of characters needed to encode `len` bytes.
+pub const fn encoded_len(len: usize) -> usize {
+    (len * 4).div_ceil(3)
+}
+
+/// Encode `bytes` with the 6-bit alphabet, returning the number of characters written to `out`.
+///
+/// # Panics
+/// If `out` is shorter than `encoded_len(bytes.len())`.
+pub fn encode(bytes: &[u8], out: &mut [u8]) -> usize {
+    let mut len = 0;
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (index, &byte)| {
+            bits | (byte as u32) << (16 - index * 8)
+        });
+        // Only as many characters as are needed to hold the chunk's bits.
+        for index in 0..=chunk.len() {
+            out[len] = ALPHABET[(bits >> (18 - index * 6)) as usize & 0x3f];
+            len += 1;
+        }
+    }
+    len
+}
+
+/// Serialize a recorded game, returning the number of bytes written to `out`.
+///
+/// # Panics
+/// If `out` is too short to hold the recording.
+pub fn to_bytes(recording: &Recording, final_hash: u64, out: &mut [u8]) -> usize {
+    let turns = recording.packed_turns();
+    out[..CODE_START].copy_from_slice(&COMPAT_VERSION.to_le_bytes());
+    out[CODE_START..FIRST].copy_from_slice(&recording.code().encode());
+    out[FIRST] = recording.first().into();
+    out[HASH_START..HASH_START + 8].copy_from_slice(&final_hash.to_le_bytes());
+    out[HASH_START + 8..HEADER_LEN].copy_from_slice(&(turns.len() as u16).to_le_bytes());
+    out[HEADER_LEN..HEADER_LEN + turns.len()].copy_from_slice(turns);
+    let len = HEADER_LEN + turns.len();
+    let checksum = checksum(&out[..len]);
+    out[len..len + 2].copy_from_slice(&checksum.to_le_bytes());
+    len + 2
+}
+
+/// Write a finished game to the log, split across lines short enough to be logged in one message.
+///
+/// Nothing is logged if the recording is missing turns or rotations, or doesn't play back to the
+/// state whose `Game::state_hash()` is `final_hash`, since it couldn't be played back faithfully.
+pub fn log(recording: &Recording, final_hash: u64) {
+    if recording.is_truncated() {
+        info!("export: recording is missing turns");
+        return;
+    }
+    if recording.has_multiple_rotations() {
+        info!("export: games with multiple rotations can't be exported");
+        return;
+    }
+    match Game::replay(recording.code(), recording.first(), recording.turns()) {
+        Ok(game) if game.state_hash() == final_hash => {}
+        Ok(_) => {
+            warn!("export: recording doesn't play back to the final state");
+            return;
+        }
+        Err(error) => {
+            warn!(
+                "export: recorded turn {} can't be played back: {:?}",
+                error.index, error.error
+            );
+            return;
+        }
+    }
+    let mut bytes = [0; MAX_LEN];
+    let len = to_bytes(recording, final_hash, &mut bytes);
+    let mut text = [0; encoded_len(MAX_LEN)];
+    let len = encode(&bytes[..len], &mut text);
+
+    let lines = len.div_ceil(LINE_LEN);
+    for (index, line) in text[..len].chunks(LINE_LEN).enumerate() {
+        // The alphabet is all ASCII.
+        info!(
+            "export {}/{}: {}",
+            index + 1,
+            lines,
+            core::str::from_utf8(line).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        encode, encoded_len, to_bytes, ALPHABET, CODE_START, FIRST, HASH_START, HEADER_LEN, MAX_LEN,
+    };
+    use crate::{
+        game::{
+            board_code, compat::COMPAT_VERSION, replay::unpack_turn, replay::MAX_RECORDED_TURNS,
+            BoardCode, Color, Playback, Position, Recording, Replay, Turn,
+        },
+        records::checksum,
+    };
+    use gba_test::test;
+
+    // Exports are only read back here, to check that they hold what was written.
+
+    /// Why an export couldn't be read back.
+    #[derive(Debug, Eq, PartialEq)]
+    enum Error {
+        /// The text contained a character outside of the alphabet.
+        InvalidCharacter(u8),
+        /// The text ended partway through a byte.
+        Length(usize),
+        /// The decoded bytes didn't fit in the provided buffer.
+        BufferTooSmall,
+        /// The checksum did not match the rest of the export.
+        Checksum,
+        /// The bytes were not laid out as an export.
+        Malformed,
+        /// The game was played on a build whose games play out differently from this one's.
+        Version(u16),
+        /// The export's board code could not be decoded.
+        BoardCode(board_code::Error),
+    }
+
+    /// Decode text produced by `encode()`, returning the number of bytes written to `out`.
+    fn decode(text: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+        if text.len() % 4 == 1 {
+            return Err(Error::Length(text.len()));
+        }
+        let mut len = 0;
+        for chunk in text.chunks(4) {
+            let mut bits = 0u32;
+            for (index, &character) in chunk.iter().enumerate() {
+                let value = ALPHABET
+                    .iter()
+                    .position(|&symbol| symbol == character)
+                    .ok_or(Error::InvalidCharacter(character))?;
+                bits |= (value as u32) << (18 - index * 6);
+            }
+            for index in 0..chunk.len() - 1 {
+                *out.get_mut(len).ok_or(Error::BufferTooSmall)? = (bits >> (16 - index * 8)) as u8;
+                len += 1;
+            }
+        }
+        Ok(len)
+    }
+
+    /// A game read back from an export.
+    #[derive(Debug, Eq, PartialEq)]
+    struct Exported<'a> {
+        code: BoardCode,
+        first: Color,
+        final_hash: u64,
+        turns: &'a [u8],
+    }
+
+    impl Exported<'_> {
+        fn turns(&self) -> impl Iterator<Item = Position> + '_ {
+            self.turns.iter().map(|&turn| unpack_turn(turn))
+        }
+    }
+
+    /// Read back a game serialized by `to_bytes()`.
+    fn from_bytes(bytes: &[u8]) -> Result<Exported<'_>, Error> {
+        let (data, stored) = bytes.split_last_chunk::<2>().ok_or(Error::Malformed)?;
+        if checksum(data) != u16::from_le_bytes(*stored) {
+            return Err(Error::Checksum);
+        }
+        if data.len() < HEADER_LEN {
+            return Err(Error::Malformed);
+        }
+        // Checked first, since other versions may lay out the rest differently.
+        let version = u16::from_le_bytes([data[0], data[1]]);
+        if version != COMPAT_VERSION {
+            return Err(Error::Version(version));
+        }
+        let (header, turns) = data.split_at(HEADER_LEN);
+        let len = u16::from_le_bytes([header[HEADER_LEN - 2], header[HEADER_LEN - 1]]);
+        if len as usize != turns.len() {
+            return Err(Error::Malformed);
+        }
+        Ok(Exported {
+            code: BoardCode::decode(&header[CODE_START..FIRST]).map_err(Error::BoardCode)?,
+            first: Color::try_from(header[FIRST]).map_err(|_| Error::Malformed)?,
+            final_hash: u64::from_le_bytes(header[HASH_START..HASH_START + 8].try_into().unwrap()),
+            turns,
+        })
+    }
+
+    #[test]
+    fn encode_empty() {
+        assert_eq!(encode(&[], &mut []), 0);
+    }
+
+    #[test]
+    fn encode_known() {
+        let mut out = [0; 4];
+
+        assert_eq!(encode(b"Man", &mut out), 4);
+        assert_eq!(&out, b"TWFu");
+    }
+
+    #[test]
+    fn encode_partial_chunk() {
+        let mut out = [0; 3];
+
+        assert_eq!(encode(b"Ma", &mut out), encoded_len(2));
+        assert_eq!(&out, b"TWE");
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let mut bytes = [0; 256];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = index as u8;
+        }
+        for len in 0..bytes.len() {
+            let mut text = [0; encoded_len(256)];
+            let text_len = encode(&bytes[..len], &mut text);
+            let mut decoded = [0; 256];
+
+            assert_eq!(text_len, encoded_len(len));
+            assert_eq!(decode(&text[..text_len], &mut decoded), Ok(len));
+            assert_eq!(decoded[..len], bytes[..len]);
+        }
+    }
+
+    #[test]
+    fn decode_invalid_character() {
+        assert_eq!(
+            decode(b"TW=u", &mut [0; 3]),
+            Err(Error::InvalidCharacter(b'='))
+        );
+    }
+
+    #[test]
+    fn decode_invalid_length() {
+        assert_eq!(decode(b"TWFuT", &mut [0; 4]), Err(Error::Length(5)));
+    }
+
+    #[test]
+    fn decode_buffer_too_small() {
+        assert_eq!(decode(b"TWFu", &mut [0; 2]), Err(Error::BufferTooSmall));
+    }
+
+    /// Record the demo replay as if it had been played live, along with its final state hash.
+    fn demo_recording() -> (Recording, u64) {
+        let mut recording = Recording::new(Replay::DEMO.code, Color::Red);
+        for &position in Replay::DEMO.turns {
+            recording.record(Turn {
+                rotate: position,
+                times: Turn::ONCE,
+            });
+        }
+        let mut playback = Playback::new(&Replay::DEMO);
+        while playback.step().is_some() {}
+        (recording, playback.game().state_hash())
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let (recording, final_hash) = demo_recording();
+        let mut bytes = [0; MAX_LEN];
+        let len = to_bytes(&recording, final_hash, &mut bytes);
+
+        let exported = from_bytes(&bytes[..len]).unwrap();
+
+        assert_eq!(exported.code, Replay::DEMO.code);
+        assert_eq!(exported.first, Color::Red);
+        assert_eq!(exported.final_hash, final_hash);
+        assert!(exported.turns().eq(Replay::DEMO.turns.iter().copied()));
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let (recording, final_hash) = demo_recording();
+        let mut bytes = [0; MAX_LEN];
+        let len = to_bytes(&recording, final_hash, &mut bytes);
+        let mut text = [0; encoded_len(MAX_LEN)];
+        let text_len = encode(&bytes[..len], &mut text);
+
+        let mut decoded = [0; MAX_LEN];
+        let decoded_len = decode(&text[..text_len], &mut decoded).unwrap();
+
+        assert_eq!(decoded[..decoded_len], bytes[..len]);
+        assert!(from_bytes(&decoded[..decoded_len]).is_ok());
+    }
+
+    #[test]
+    fn from_bytes_corrupted() {
+        let (recording, final_hash) = demo_recording();
+        let mut bytes = [0; MAX_LEN];
+        let len = to_bytes(&recording, final_hash, &mut bytes);
+        bytes[30] ^= 1;
+
+        assert_eq!(from_bytes(&bytes[..len]), Err(Error::Checksum));
+    }
+
+    #[test]
+    fn from_bytes_too_short() {
+        assert_eq!(from_bytes(&[0]), Err(Error::Malformed));
+    }
+
+    #[test]
+    fn from_bytes_wrong_turn_count() {
+        let (recording, final_hash) = demo_recording();
+        let mut bytes = [0; MAX_LEN];
+        let len = to_bytes(&recording, final_hash, &mut bytes);
+        // Claim an extra turn, with a checksum that matches.
+        bytes[HEADER_LEN - 2] += 1;
+        let checksum = checksum(&bytes[..len - 2]);
+        bytes[len - 2..len].copy_from_slice(&checksum.to_le_bytes());
+
+        assert_eq!(from_bytes(&bytes[..len]), Err(Error::Malformed));
+    }
+
+    #[test]
+    fn from_bytes_invalid_first_color() {
+        let (recording, final_hash) = demo_recording();
+        let mut bytes = [0; MAX_LEN];
+        let len = to_bytes(&recording, final_hash, &mut bytes);
+        // No color is encoded as 4.
+        bytes[FIRST] = 4;
+        let checksum = checksum(&bytes[..len - 2]);
+        bytes[len - 2..len].copy_from_slice(&checksum.to_le_bytes());
+
+        assert_eq!(from_bytes(&bytes[..len]), Err(Error::Malformed));
+    }
+
+    #[test]
+    fn to_bytes_embeds_version() {
+        let (recording, final_hash) = demo_recording();
+        let mut bytes = [0; MAX_LEN];
+        to_bytes(&recording, final_hash, &mut bytes);
+
+        assert_eq!(bytes[..2], COMPAT_VERSION.to_le_bytes());
+    }
+
+    #[test]
+    fn from_bytes_other_version() {
+        let (recording, final_hash) = demo_recording();
+        let mut bytes = [0; MAX_LEN];
+        let len = to_bytes(&recording, final_hash, &mut bytes);
+        // A valid export, as made by a different version.
+        bytes[..2].copy_from_slice(&(COMPAT_VERSION + 1).to_le_bytes());
+        let checksum = checksum(&bytes[..len - 2]);
+        bytes[len - 2..len].copy_from_slice(&checksum.to_le_bytes());
+
+        assert_eq!(
+            from_bytes(&bytes[..len]),
+            Err(Error::Version(COMPAT_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn longest_recording_fits() {
+        let mut recording = Recording::new(BoardCode::new(0), Color::Red);
+        for _ in 0..MAX_RECORDED_TURNS {
+            recording.record(Turn {
+                rotate: Position { x: 15, y: 15 },
+                times: Turn::ONCE,
+            });
+        }
+        let mut bytes = [0; MAX_LEN];
+        let len = to_bytes(&recording, 0, &mut bytes);
+
+        assert_eq!(len, bytes.len());
+        assert_eq!(
+            from_bytes(&bytes).map(|exported| exported.turns().count()),
+            Ok(MAX_RECORDED_TURNS)
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Sets of positions, stored as one bit per position.
+
+use super::Position;
+
+/// A set of positions on a full sized board.
+///
+/// This is a cheaper replacement for a `[[bool; 16]; 16]`, both to keep around and to clear.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PositionSet {
+    /// Two rows of the board per word, with bit `x` set for each position in the even row and bit
+    /// `16 + x` for each position in the odd row.
+    words: [u32; 8],
+}
+
+impl PositionSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The word holding `position`, along with the bit for it within that word.
+    fn locate(position: Position) -> (usize, u32) {
+        let index = position.y as usize * 16 + position.x as usize;
+        (index / 32, 1 << (index % 32))
+    }
+
+    /// Add `position` to the set, returning whether it wasn't already in it.
+    pub fn insert(&mut self, position: Position) -> bool {
+        let (word, bit) = Self::locate(position);
+        let inserted = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        inserted
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        let (word, bit) = Self::locate(position);
+        self.words[word] & bit != 0
+    }
+
+    /// The number of positions in the set.
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Remove every position from the set.
+    pub fn clear(&mut self) {
+        self.words = [0; 8];
+    }
+
+    /// Every position in the set, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..16)
+            .flat_map(|y| (0..16).map(move |x| Position { x, y }))
+            .filter(|&position| self.contains(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Position, PositionSet};
+    use gba_test::test;
+
+    #[test]
+    fn empty() {
+        let set = PositionSet::new();
+
+        assert_eq!(set.count(), 0);
+        assert!(!set.contains(Position { x: 0, y: 0 }));
+        assert_eq!(set.iter().count(), 0);
+    }
+
+    #[test]
+    fn insert() {
+        let mut set = PositionSet::new();
+
+        assert!(set.insert(Position { x: 15, y: 15 }));
+        assert!(set.insert(Position { x: 3, y: 0 }));
+        assert!(!set.insert(Position { x: 15, y: 15 }));
+
+        assert_eq!(set.count(), 2);
+        assert!(set.contains(Position { x: 3, y: 0 }));
+        assert!(!set.contains(Position { x: 0, y: 3 }));
+        assert!(set
+            .iter()
+            .eq([Position { x: 3, y: 0 }, Position { x: 15, y: 15 }]));
+    }
+
+    #[test]
+    fn rows_sharing_a_word() {
+        let mut set = PositionSet::new();
+
+        set.insert(Position { x: 15, y: 0 });
+
+        assert!(set.contains(Position { x: 15, y: 0 }));
+        assert!(!set.contains(Position { x: 0, y: 1 }));
+        assert!(!set.contains(Position { x: 15, y: 1 }));
+    }
+
+    #[test]
+    fn every_position() {
+        let mut set = PositionSet::new();
+
+        for y in 0..16 {
+            for x in 0..16 {
+                assert!(set.insert(Position { x, y }));
+            }
+        }
+
+        assert_eq!(set.count(), 256);
+        assert!(set
+            .iter()
+            .eq((0..16).flat_map(|y| (0..16).map(move |x| Position { x, y }))));
+    }
+
+    #[test]
+    fn boundary_rows() {
+        let mut set = PositionSet::new();
+        for x in 0..16 {
+            set.insert(Position { x, y: 0 });
+            set.insert(Position { x, y: 15 });
+        }
+
+        assert_eq!(set.count(), 32);
+        for y in 0..16 {
+            for x in 0..16 {
+                assert_eq!(set.contains(Position { x, y }), y == 0 || y == 15);
+            }
+        }
+        assert!(set.iter().take(16).all(|position| position.y == 0));
+        assert!(set.iter().skip(16).all(|position| position.y == 15));
+    }
+
+    #[test]
+    fn clear() {
+        let mut set = PositionSet::new();
+        for y in 0..16 {
+            for x in 0..16 {
+                set.insert(Position { x, y });
+            }
+        }
+
+        set.clear();
+
+        assert_eq!(set.count(), 0);
+        assert_eq!(set.iter().count(), 0);
+        // Positions can be added again once cleared.
+        assert!(set.insert(Position { x: 15, y: 15 }));
+    }
+}
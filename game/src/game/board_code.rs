@@ -0,0 +1,521 @@
+//! Shareable codes identifying a generated board or a handcrafted level.
+//!
+//! A code packs everything needed to regenerate a board into 16 base-32 characters, followed by a
+//! single check character:
+//!
+//! | bits    | contents                         |
+//! |---------|----------------------------------|
+//! | 76..80  | compatibility version            |
+//! | 72..76  | rules preset                     |
+//! | 70..72  | four minus the number of players |
+//! | 69      | choke point guarantee            |
+//! | 64..69  | board size, or level             |
+//! | 0..64   | seed                             |
+//!
+//! Codes made before the number of players was stored had a full byte for the board size, whose
+//! top bits were always clear, so they still decode as four player boards without the choke point
+//! guarantee.
+//!
+//! A handcrafted level is stored in place of the board size, by its index in `levels`. No level
+//! index is as large as the size of a generated board, so the two never overlap.
+//!
+//! The characters are taken from Crockford's base-32 alphabet, which leaves out letters that are
+//! easily confused with digits.
+
+use super::{compat::COMPAT_VERSION, Grid, Rules};
+use crate::{levels, random::Pcg32Fast};
+use deranged::RangedU8;
+use rand::Rng;
+
+/// The characters used to encode each 5-bit group.
+pub const ALPHABET: [u8; 32] = *b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// The number of characters encoding the board itself.
+const DATA_LEN: usize = 16;
+
+/// The number of characters in a complete code.
+pub const LEN: usize = DATA_LEN + 1;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The code did not have exactly [`LEN`] characters.
+    Length(usize),
+    /// The code contained a character outside of the alphabet.
+    InvalidCharacter(u8),
+    /// The check character did not match the rest of the code.
+    Check,
+    /// The code was made by a build whose games play out differently from this one's.
+    UnsupportedVersion(u8),
+    /// The code uses a rules preset this build does not have.
+    UnsupportedPreset(u8),
+    /// The code uses a board size or level this build does not have.
+    UnsupportedSize(u8),
+    /// The code is for a number of players this build does not support.
+    UnsupportedPlayers(u8),
+}
+
+/// Everything needed to reproduce a generated board.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BoardCode {
+    pub seed: u64,
+    pub rules: Rules,
+    /// How many players the board is for. See `Color::plays()` for which colors play.
+    pub players: RangedU8<2, 4>,
+    /// Whether the board must have a choke point in every quadrant. See
+    /// `Grid::has_choke_point_in_every_quadrant()`.
+    pub choke_points: bool,
+    /// The index of the handcrafted level played instead of a generated board, if any.
+    ///
+    /// The seed still drives the AI, but not the board.
+    pub level: Option<u8>,
+}
+
+impl BoardCode {
+    /// The version stored in codes, which is the compatibility version.
+    pub const VERSION: u8 = COMPAT_VERSION as u8;
+    const SIZE: u8 = 16;
+
+    /// A code for the four player board generated from `seed`, played with the default rules.
+    pub const fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rules: Rules {
+                wraparound: false,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            },
+            players: RangedU8::new_static::<4>(),
+            choke_points: false,
+            level: None,
+        }
+    }
+
+    /// The rules preset `rules` are encoded as.
+    ///
+    /// Each rule is a single bit, with wraparound in the lowest bit, multiple rotations above it,
+    /// and super arrow claims above that.
+    fn preset(rules: Rules) -> u8 {
+        rules.wraparound as u8
+            | (rules.multi_rotation as u8) << 1
+            | (rules.super_arrow_claims as u8) << 2
+    }
+
+    /// The rules a preset stands for, if there is such a preset.
+    fn rules(preset: u8) -> Option<Rules> {
+        (preset < 8).then_some(Rules {
+            wraparound: preset & 1 != 0,
+            multi_rotation: preset & 2 != 0,
+            super_arrow_claims: preset & 4 != 0,
+        })
+    }
+
+    /// Generate the board this code identifies.
+    ///
+    /// A level is played as it was made, with the choke point guarantee ignored. If the level's
+    /// data is corrupt, the board generated from the seed is played instead.
+    ///
+    /// Two players start in opposite corners of a board made for them. Three players play on a
+    /// four player board, with the missing player's corner left unaligned by `Builder::players()`.
+    ///
+    /// If the code guarantees choke points, a board without one in every quadrant is thrown out,
+    /// and another is generated from a seed derived from `seed`.
+    pub fn grid(&self) -> Grid {
+        if let Some(grid) = self.level.and_then(|level| levels::get(level as usize)) {
+            return grid;
+        }
+
+        let generate = |seed| match self.players.get() {
+            2 => Grid::generate_two_player(seed, Self::SIZE),
+            _ => Grid::generate_validated(seed),
+        };
+        let mut grid = generate(self.seed);
+        if self.choke_points {
+            let mut pcg: Pcg32Fast = Pcg32Fast::new(self.seed);
+            while !grid.has_choke_point_in_every_quadrant(self.rules) {
+                grid = generate(pcg.gen());
+            }
+        }
+        grid
+    }
+
+    /// Encode as ASCII characters.
+    pub fn encode(&self) -> [u8; LEN] {
+        let bits = (Self::VERSION as u128) << 76
+            | (Self::preset(self.rules) as u128) << 72
+            | ((4 - self.players.get()) as u128) << 70
+            | (self.choke_points as u128) << 69
+            | (self.level.unwrap_or(Self::SIZE) as u128) << 64
+            | self.seed as u128;
+
+        let mut values = [0; DATA_LEN];
+        for (index, value) in values.iter_mut().enumerate() {
+            *value = (bits >> ((DATA_LEN - 1 - index) * 5)) as u8 & 0x1f;
+        }
+
+        let mut code = [0; LEN];
+        for (character, value) in code.iter_mut().zip(values) {
+            *character = ALPHABET[value as usize];
+        }
+        code[DATA_LEN] = ALPHABET[check(&values) as usize];
+        code
+    }
+
+    /// Decode from ASCII characters, as produced by `encode()`.
+    pub fn decode(code: &[u8]) -> Result<Self, Error> {
+        if code.len() != LEN {
+            return Err(Error::Length(code.len()));
+        }
+
+        let mut values = [0; LEN];
+        for (value, &character) in values.iter_mut().zip(code) {
+            *value = ALPHABET
+                .iter()
+                .position(|&symbol| symbol == character)
+                .ok_or(Error::InvalidCharacter(character))? as u8;
+        }
+        let (data, check_value) = values.split_at(DATA_LEN);
+        if check(data) != check_value[0] {
+            return Err(Error::Check);
+        }
+
+        let bits = data
+            .iter()
+            .fold(0u128, |bits, &value| bits << 5 | value as u128);
+        let version = (bits >> 76) as u8 & 0xf;
+        if version != Self::VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        let preset = (bits >> 72) as u8 & 0xf;
+        let rules = Self::rules(preset).ok_or(Error::UnsupportedPreset(preset))?;
+        let size = (bits >> 64) as u8 & 0x1f;
+        let level = if size == Self::SIZE {
+            None
+        } else if (size as usize) < levels::COUNT {
+            Some(size)
+        } else {
+            return Err(Error::UnsupportedSize(size));
+        };
+        let missing = (bits >> 70) as u8 & 0b11;
+        let players = RangedU8::new(4 - missing).ok_or(Error::UnsupportedPlayers(4 - missing))?;
+
+        Ok(Self {
+            seed: bits as u64,
+            rules,
+            players,
+            choke_points: bits >> 69 & 1 != 0,
+            level,
+        })
+    }
+}
+
+/// Compute the check value for the given 5-bit values.
+///
+/// Every value is multiplied by a distinct odd weight, so changing any single character always
+/// changes the result.
+fn check(values: &[u8]) -> u8 {
+    values
+        .iter()
+        .enumerate()
+        .fold(0u32, |sum, (index, &value)| {
+            sum + value as u32 * (index as u32 * 2 + 1)
+        }) as u8
+        & 0x1f
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoardCode, Error, ALPHABET, LEN};
+    use crate::{
+        game::{Color, Rules},
+        levels,
+        random::Pcg32Fast,
+    };
+    use deranged::RangedU8;
+    use gba_test::test;
+    use rand::Rng;
+
+    #[test]
+    fn round_trip_zero() {
+        let code = BoardCode::new(0);
+
+        assert_eq!(BoardCode::decode(&code.encode()), Ok(code));
+    }
+
+    #[test]
+    fn round_trip_max() {
+        let code = BoardCode::new(u64::MAX);
+
+        assert_eq!(BoardCode::decode(&code.encode()), Ok(code));
+    }
+
+    #[test]
+    fn round_trip_wraparound() {
+        let code = BoardCode {
+            seed: 1234,
+            rules: Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            },
+            players: RangedU8::new_static::<4>(),
+            choke_points: false,
+            level: None,
+        };
+
+        assert_eq!(BoardCode::decode(&code.encode()), Ok(code));
+    }
+
+    #[test]
+    fn round_trip_every_preset() {
+        for wraparound in [false, true] {
+            for multi_rotation in [false, true] {
+                for super_arrow_claims in [false, true] {
+                    let code = BoardCode {
+                        seed: 1234,
+                        rules: Rules {
+                            wraparound,
+                            multi_rotation,
+                            super_arrow_claims,
+                        },
+                        players: RangedU8::new_static::<4>(),
+                        choke_points: false,
+                        level: None,
+                    };
+
+                    assert_eq!(BoardCode::decode(&code.encode()), Ok(code));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_every_player_count() {
+        for players in [
+            RangedU8::new_static::<2>(),
+            RangedU8::new_static::<3>(),
+            RangedU8::new_static::<4>(),
+        ] {
+            let code = BoardCode {
+                players,
+                ..BoardCode::new(1234)
+            };
+
+            assert_eq!(BoardCode::decode(&code.encode()), Ok(code));
+        }
+    }
+
+    #[test]
+    fn round_trip_choke_points() {
+        let code = BoardCode {
+            choke_points: true,
+            ..BoardCode::new(1234)
+        };
+
+        assert_eq!(BoardCode::decode(&code.encode()), Ok(code));
+    }
+
+    #[test]
+    fn round_trip_every_level() {
+        for level in 0..levels::COUNT as u8 {
+            let code = BoardCode {
+                level: Some(level),
+                ..BoardCode::new(1234)
+            };
+
+            assert_eq!(BoardCode::decode(&code.encode()), Ok(code));
+        }
+    }
+
+    #[test]
+    fn encode_two_players() {
+        let code = BoardCode {
+            players: RangedU8::new_static::<2>(),
+            ..BoardCode::new(0)
+        };
+
+        assert_eq!(&code.encode(), b"8280000000000000P");
+    }
+
+    #[test]
+    fn encode_multi_rotation() {
+        let code = BoardCode {
+            seed: 1234,
+            rules: Rules {
+                wraparound: true,
+                multi_rotation: true,
+                super_arrow_claims: false,
+            },
+            players: RangedU8::new_static::<4>(),
+            choke_points: false,
+            level: None,
+        };
+
+        assert_eq!(&code.encode(), b"8C8000000000016JB");
+    }
+
+    #[test]
+    fn round_trip_random_seeds() {
+        let mut pcg: Pcg32Fast = Pcg32Fast::new(42);
+        for _ in 0..1000 {
+            let code = BoardCode::new(pcg.gen());
+
+            assert_eq!(BoardCode::decode(&code.encode()), Ok(code));
+        }
+    }
+
+    #[test]
+    fn encode_known() {
+        assert_eq!(&BoardCode::new(0).encode(), b"8080000000000000G");
+    }
+
+    #[test]
+    fn decode_wrong_length() {
+        assert_eq!(BoardCode::decode(b"20800"), Err(Error::Length(5)));
+    }
+
+    #[test]
+    fn decode_invalid_character() {
+        let mut code = BoardCode::new(0).encode();
+        code[5] = b'I';
+
+        assert_eq!(BoardCode::decode(&code), Err(Error::InvalidCharacter(b'I')));
+    }
+
+    #[test]
+    fn decode_lowercase_rejected() {
+        let mut code = BoardCode::new(0).encode();
+        code[2] = b'g';
+
+        assert_eq!(BoardCode::decode(&code), Err(Error::InvalidCharacter(b'g')));
+    }
+
+    #[test]
+    fn decode_detects_every_single_substitution() {
+        let mut pcg: Pcg32Fast = Pcg32Fast::new(7);
+        for _ in 0..20 {
+            let code = BoardCode::new(pcg.gen()).encode();
+            for index in 0..LEN {
+                for &symbol in ALPHABET.iter() {
+                    if symbol == code[index] {
+                        continue;
+                    }
+                    let mut corrupted = code;
+                    corrupted[index] = symbol;
+
+                    assert_eq!(BoardCode::decode(&corrupted), Err(Error::Check));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn decode_unsupported_version() {
+        assert_eq!(
+            BoardCode::decode(b"A080000000000000J"),
+            Err(Error::UnsupportedVersion(5))
+        );
+    }
+
+    #[test]
+    fn decode_unsupported_preset() {
+        assert_eq!(
+            BoardCode::decode(b"9080000000000000H"),
+            Err(Error::UnsupportedPreset(8))
+        );
+    }
+
+    #[test]
+    fn decode_unsupported_size() {
+        assert_eq!(
+            BoardCode::decode(b"808G0000000000000"),
+            Err(Error::UnsupportedSize(17))
+        );
+    }
+
+    #[test]
+    fn decode_unsupported_level() {
+        let code = BoardCode {
+            level: Some(levels::COUNT as u8),
+            ..BoardCode::new(0)
+        };
+
+        assert_eq!(
+            BoardCode::decode(&code.encode()),
+            Err(Error::UnsupportedSize(levels::COUNT as u8))
+        );
+    }
+
+    #[test]
+    fn decode_unsupported_players() {
+        assert_eq!(
+            BoardCode::decode(b"8380000000000000S"),
+            Err(Error::UnsupportedPlayers(1))
+        );
+    }
+
+    #[test]
+    fn grid_two_players() {
+        let code = BoardCode {
+            players: RangedU8::new_static::<2>(),
+            ..BoardCode::new(1234)
+        };
+        let counts = code.grid().color_counts();
+
+        assert_eq!(counts.for_color(Color::Red), 1);
+        assert_eq!(counts.for_color(Color::Blue), 0);
+        assert_eq!(counts.for_color(Color::Yellow), 0);
+        assert_eq!(counts.for_color(Color::Green), 1);
+    }
+
+    #[test]
+    fn grid_choke_points() {
+        for players in [RangedU8::new_static::<2>(), RangedU8::new_static::<4>()] {
+            for seed in 0..10 {
+                let code = BoardCode {
+                    players,
+                    choke_points: true,
+                    ..BoardCode::new(seed)
+                };
+
+                assert!(code.grid().has_choke_point_in_every_quadrant(code.rules));
+            }
+        }
+    }
+
+    #[test]
+    fn grid_choke_points_keeps_qualifying_board() {
+        // The board from seed 3 already has a choke point in every quadrant.
+        let code = BoardCode {
+            choke_points: true,
+            ..BoardCode::new(3)
+        };
+
+        assert!(code.grid().iter().eq(BoardCode::new(3).grid().iter()));
+    }
+
+    #[test]
+    fn grid_level() {
+        for level in 0..levels::COUNT {
+            let code = BoardCode {
+                level: Some(level as u8),
+                // Levels are played as they were made.
+                choke_points: true,
+                ..BoardCode::new(1234)
+            };
+
+            assert!(code.grid().iter().eq(levels::get(level).unwrap().iter()));
+        }
+    }
+
+    #[test]
+    fn grid_is_reproducible() {
+        let code = BoardCode::decode(&BoardCode::new(1234).encode()).unwrap();
+
+        assert!(code
+            .grid()
+            .iter()
+            .flatten()
+            .zip(BoardCode::new(1234).grid().iter().flatten())
+            .all(|(a, b)| a == b));
+    }
+}
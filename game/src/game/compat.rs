@@ -0,0 +1,144 @@
+//! The version of everything that decides how a game plays out.
+//!
+//! Board codes, exported games, saved games, and saved records all assume that the same inputs
+//! produce the same game on every build. That holds only as long as none of the following change:
+//!
+//! - board generation in `Grid::generate()`, including the random number generator, and which
+//!   boards `Grid::generate_validated()` rejects,
+//! - two player board generation in `Grid::generate_two_player()`,
+//! - which boards `BoardCode::grid()` rejects when a code guarantees choke points, including how
+//!   `Grid::choke_points()` finds them,
+//! - how a turn rotates and fills nodes in `Game::execute_turn()`, under every set of `Rules`,
+//! - the order players take turns in, and when a turn is passed.
+//!
+//! Any change to these must increment [`COMPAT_VERSION`]. The tests in this module pin down the
+//! current behavior, so a change that forgets to do so fails them.
+//!
+//! The AI is deliberately not covered: recordings store the positions chosen for AI turns, not
+//! the AI's decisions, so changing the AI doesn't affect playback.
+
+/// The version of the deterministic game behavior.
+///
+/// Formats that depend on it store it and refuse to load anything made with a different version,
+/// rather than playing out a different game.
+pub const COMPAT_VERSION: u16 = 4;
+
+// Board codes only have room for a 4-bit version.
+const _: () = assert!(COMPAT_VERSION < 16);
+
+#[cfg(test)]
+mod tests {
+    use super::COMPAT_VERSION;
+    use crate::game::{
+        replay::hash, BoardCode, Game, Grid, Playback, Position, Replay, Rules, Turn,
+    };
+    use deranged::RangedU8;
+    use gba_test::test;
+
+    /// Hashes of boards generated by the current version, paired with their seeds.
+    ///
+    /// If these change, `COMPAT_VERSION` must be incremented before updating them.
+    const GENERATED: [(u64, u32); 5] = [
+        (0, 0x533d_0b62),
+        (1234, 0xe363_af86),
+        (u64::MAX, 0xdde5_4d21),
+        // Has both pairs of teleporters.
+        (19, 0xbaa6_b2ab),
+        // Has reverse arrows.
+        (1, 0x52da_ebdc),
+    ];
+
+    /// Hashes of two player boards generated by the current version, paired with their seeds.
+    ///
+    /// If these change, `COMPAT_VERSION` must be incremented before updating them.
+    const GENERATED_TWO_PLAYER: [(u64, u32); 3] = [
+        (0, 0xbe71_3862),
+        (1234, 0xca1e_484b),
+        (u64::MAX, 0x7bac_a0c2),
+    ];
+
+    /// Hashes of boards from codes guaranteeing choke points, paired with their number of players
+    /// and seeds. None of these boards have a choke point in every quadrant without regenerating.
+    ///
+    /// If these change, `COMPAT_VERSION` must be incremented before updating them.
+    const GENERATED_CHOKE_POINTS: [(u8, u64, u32); 3] = [
+        (4, 0, 0x176f_4f00),
+        (4, 1234, 0xb16b_170d),
+        (2, 1, 0xaac6_1aed),
+    ];
+
+    #[test]
+    fn generation_unchanged() {
+        for (seed, expected) in GENERATED {
+            let game = Game::builder().grid(Grid::generate(seed)).build();
+
+            assert_eq!(hash(&game), expected, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn two_player_generation_unchanged() {
+        for (seed, expected) in GENERATED_TWO_PLAYER {
+            // The size board codes use.
+            let game = Game::builder()
+                .grid(Grid::generate_two_player(seed, 16))
+                .build();
+
+            assert_eq!(hash(&game), expected, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn choke_point_generation_unchanged() {
+        for (players, seed, expected) in GENERATED_CHOKE_POINTS {
+            let code = BoardCode {
+                players: RangedU8::new(players).unwrap(),
+                choke_points: true,
+                ..BoardCode::new(seed)
+            };
+            let game = Game::builder().grid(code.grid()).build();
+
+            assert_eq!(hash(&game), expected, "{players} players, seed {seed}");
+        }
+    }
+
+    #[test]
+    fn fill_unchanged() {
+        let mut playback = Playback::new(&Replay::DEMO);
+        while playback.step().is_some() {}
+
+        // If this fails, `COMPAT_VERSION` must be incremented before re-recording the demo.
+        assert!(playback.matches_recording());
+    }
+
+    #[test]
+    fn wraparound_fill_unchanged() {
+        let mut game = Game::builder()
+            .grid(Grid::generate(1234))
+            .rules(Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            })
+            .build();
+        for rotate in [
+            Position { x: 0, y: 0 },
+            Position { x: 15, y: 0 },
+            Position { x: 0, y: 15 },
+            Position { x: 15, y: 15 },
+        ] {
+            game.execute_turn(Turn {
+                rotate,
+                times: Turn::ONCE,
+            })
+            .unwrap();
+        }
+
+        assert_eq!(hash(&game), 0xc654_75b0);
+    }
+
+    #[test]
+    fn board_code_version() {
+        assert_eq!(BoardCode::VERSION as u16, COMPAT_VERSION);
+    }
+}
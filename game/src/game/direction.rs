@@ -8,15 +8,10 @@ pub enum Direction {
 
 impl Direction {
     pub fn rotate(&mut self) {
-        *self = match self {
-            Self::Left => Self::Up,
-            Self::Up => Self::Right,
-            Self::Right => Self::Down,
-            Self::Down => Self::Left,
-        };
+        *self = self.clockwise();
     }
 
-    pub fn opposite(&self) -> Direction {
+    pub const fn opposite(self) -> Direction {
         match self {
             Self::Left => Self::Right,
             Self::Up => Self::Down,
@@ -25,7 +20,7 @@ impl Direction {
         }
     }
 
-    pub fn clockwise(&self) -> Direction {
+    pub const fn clockwise(self) -> Direction {
         match self {
             Self::Left => Self::Up,
             Self::Up => Self::Right,
@@ -34,7 +29,7 @@ impl Direction {
         }
     }
 
-    pub fn counter_clockwise(&self) -> Direction {
+    pub const fn counter_clockwise(self) -> Direction {
         match self {
             Self::Left => Self::Down,
             Self::Up => Self::Left,
@@ -43,3 +38,64 @@ impl Direction {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Direction;
+    use gba_test::test;
+
+    const ALL: [Direction; 4] = [
+        Direction::Left,
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+    ];
+
+    #[test]
+    fn opposite() {
+        assert_eq!(Direction::Left.opposite(), Direction::Right);
+        assert_eq!(Direction::Up.opposite(), Direction::Down);
+        assert_eq!(Direction::Right.opposite(), Direction::Left);
+        assert_eq!(Direction::Down.opposite(), Direction::Up);
+    }
+
+    #[test]
+    fn clockwise() {
+        assert_eq!(Direction::Left.clockwise(), Direction::Up);
+        assert_eq!(Direction::Up.clockwise(), Direction::Right);
+        assert_eq!(Direction::Right.clockwise(), Direction::Down);
+        assert_eq!(Direction::Down.clockwise(), Direction::Left);
+    }
+
+    #[test]
+    fn counter_clockwise() {
+        assert_eq!(Direction::Left.counter_clockwise(), Direction::Down);
+        assert_eq!(Direction::Up.counter_clockwise(), Direction::Left);
+        assert_eq!(Direction::Right.counter_clockwise(), Direction::Up);
+        assert_eq!(Direction::Down.counter_clockwise(), Direction::Right);
+    }
+
+    #[test]
+    fn counter_clockwise_undoes_clockwise() {
+        for direction in ALL {
+            assert_eq!(direction.clockwise().counter_clockwise(), direction);
+        }
+    }
+
+    #[test]
+    fn clockwise_twice_is_opposite() {
+        for direction in ALL {
+            assert_eq!(direction.clockwise().clockwise(), direction.opposite());
+        }
+    }
+
+    #[test]
+    fn rotate_matches_clockwise() {
+        for direction in ALL {
+            let mut rotated = direction;
+            rotated.rotate();
+
+            assert_eq!(rotated, direction.clockwise());
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Records of what happened during a turn.
+
+use super::{Color, Position};
+use core::slice;
+
+/// A node changing color during a turn.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Capture {
+    pub position: Position,
+    /// The color the node had before it was captured.
+    pub previous: Option<Color>,
+}
+
+/// The captures made during a single turn, in the order they happened.
+///
+/// At most [`TurnEvents::CAPACITY`] captures are recorded. Any captures beyond that still happen,
+/// but are not recorded, and the events are marked as truncated. Anything presenting the turn
+/// should fall back to redrawing the whole board in that case.
+#[derive(Clone, Debug)]
+pub struct TurnEvents {
+    captures: [Capture; Self::CAPACITY],
+    len: usize,
+    truncated: bool,
+}
+
+impl TurnEvents {
+    /// The maximum number of captures recorded for a single turn.
+    pub const CAPACITY: usize = 128;
+
+    pub(super) fn new() -> Self {
+        Self {
+            captures: [Capture {
+                position: Position { x: 0, y: 0 },
+                previous: None,
+            }; Self::CAPACITY],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.len = 0;
+        self.truncated = false;
+    }
+
+    pub(super) fn push(&mut self, capture: Capture) {
+        if self.len < Self::CAPACITY {
+            self.captures[self.len] = capture;
+            self.len += 1;
+        } else {
+            self.truncated = true;
+        }
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, Capture> {
+        self.captures[..self.len].iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether captures were left out because there were more than [`TurnEvents::CAPACITY`].
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Capture, TurnEvents};
+    use crate::game::{Color, Position};
+    use gba_test::test;
+
+    fn capture(x: u8) -> Capture {
+        Capture {
+            position: Position { x, y: 0 },
+            previous: Some(Color::Blue),
+        }
+    }
+
+    #[test]
+    fn push() {
+        let mut events = TurnEvents::new();
+
+        events.push(capture(1));
+        events.push(capture(2));
+
+        assert!(events.iter().copied().eq([capture(1), capture(2)]));
+        assert!(!events.is_truncated());
+    }
+
+    #[test]
+    fn push_beyond_capacity() {
+        let mut events = TurnEvents::new();
+
+        for _ in 0..TurnEvents::CAPACITY + 1 {
+            events.push(capture(1));
+        }
+
+        assert_eq!(events.len(), TurnEvents::CAPACITY);
+        assert!(events.is_truncated());
+    }
+
+    #[test]
+    fn clear() {
+        let mut events = TurnEvents::new();
+        for _ in 0..TurnEvents::CAPACITY + 1 {
+            events.push(capture(1));
+        }
+
+        events.clear();
+
+        assert_eq!(events.len(), 0);
+        assert!(!events.is_truncated());
+    }
+}
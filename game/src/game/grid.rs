@@ -1,132 +1,499 @@
-use super::{Color, ColorCounts, Direction, Node, Position};
+use super::{
+    node::TELEPORTER_IDS, Color, ColorCounts, Direction, Node, Position, PositionSet, Rules,
+};
 use crate::random::Pcg32Fast;
-use core::slice;
+use core::{
+    ops::{Index, IndexMut},
+    slice, str,
+};
 use rand::Rng;
 
-#[derive(Debug)]
-pub struct Grid([[Node; 16]; 16]);
+/// The number of nodes a region must have for the node joining it to the rest of the board to be a
+/// choke point.
+pub const CHOKE_POINT_REGION: u16 = 8;
+
+/// The fewest other nodes each starting corner must be able to reach for a generated board to be
+/// fair.
+pub const MIN_REACHABLE: u16 = 8;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Left,
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+];
+
+/// The number of links a node can have: one to each neighbor, and one to a teleporter's twin.
+const LINKS: usize = DIRECTIONS.len() + 1;
+
+/// The dimensions of a board, in nodes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Size {
+    pub width: u8,
+    pub height: u8,
+}
+
+impl Size {
+    /// The largest board, which is also the default.
+    pub const MAX: Self = Self::square(16);
+
+    pub const fn square(length: u8) -> Self {
+        Self {
+            width: length,
+            height: length,
+        }
+    }
+
+    /// Whether `position` is on a board of this size.
+    pub const fn contains(self, position: Position) -> bool {
+        position.x < self.width && position.y < self.height
+    }
+
+    /// The position in the bottom right corner.
+    pub const fn last(self) -> Position {
+        Position {
+            x: self.width - 1,
+            y: self.height - 1,
+        }
+    }
+}
+
+/// How often each kind of node is chosen where `Grid::generate_with_weights()` places a wall or
+/// secret node.
+///
+/// Each weight is relative to the sum of all of them. If every weight is zero, walls are placed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WallWeights {
+    pub all_direction: u8,
+    pub super_arrow: u8,
+    /// Only `TELEPORTER_IDS / 2` groups of teleporters fit on a board. Walls are placed instead
+    /// once they are used up.
+    pub teleporter: u8,
+    pub reverse_arrow: u8,
+    pub wall: u8,
+}
+
+impl WallWeights {
+    fn total(self) -> u32 {
+        self.all_direction as u32
+            + self.super_arrow as u32
+            + self.teleporter as u32
+            + self.reverse_arrow as u32
+            + self.wall as u32
+    }
+}
+
+impl Default for WallWeights {
+    /// The weights every board code is generated with.
+    ///
+    /// These add up to 256, so that a single random byte picks the node directly.
+    fn default() -> Self {
+        Self {
+            all_direction: 64,
+            super_arrow: 64,
+            teleporter: 16,
+            reverse_arrow: 32,
+            wall: 80,
+        }
+    }
+}
+
+/// How a generated board repeats the nodes chosen for one part of it, so that every player starts
+/// out on equal footing.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Symmetry {
+    /// For four players: the top left quarter is repeated at each quarter turn.
+    QuarterTurn,
+    /// For two players in opposite corners: the top half is repeated at a half turn.
+    HalfTurn,
+}
+
+impl Symmetry {
+    /// The number of quarter turns between each image of a node.
+    fn step(self) -> usize {
+        match self {
+            Self::QuarterTurn => 1,
+            Self::HalfTurn => 2,
+        }
+    }
+
+    /// The number of teleporter ids used by a teleporter and its images.
+    fn teleporter_ids(self) -> u8 {
+        match self {
+            Self::QuarterTurn => 2,
+            Self::HalfTurn => 1,
+        }
+    }
+}
+
+/// The nodes of a board.
+///
+/// Boards smaller than `Size::MAX` are stored in the top left corner of a full sized array, with
+/// every node outside of the board left empty.
+#[derive(Clone, Debug)]
+pub struct Grid {
+    nodes: [[Node; 16]; 16],
+    size: Size,
+}
+
+/// A byte in an encoded grid that does not encode any `Node`.
+#[derive(Debug, Eq, PartialEq)]
+pub struct DecodeError {
+    pub position: Position,
+    pub byte: u8,
+}
 
 impl Grid {
     pub fn new(grid: [[Node; 16]; 16]) -> Self {
-        Self(grid)
+        Self {
+            nodes: grid,
+            size: Size::MAX,
+        }
     }
 
-    fn populate_reflected_arrows(&mut self, x: usize, y: usize, direction: Direction) {
-        self.0[y][x] = Node::Arrow {
-            alignment: None,
-            direction,
-        };
-        self.0[15 - x][y] = Node::Arrow {
-            alignment: None,
-            direction: direction.counter_clockwise(),
-        };
-        self.0[x][15 - y] = Node::Arrow {
-            alignment: None,
-            direction: direction.clockwise(),
-        };
-        self.0[15 - y][15 - x] = Node::Arrow {
-            alignment: None,
-            direction: direction.opposite(),
-        };
+    /// A full sized grid with the node at each position given by `f`.
+    ///
+    /// Positions are passed to `f` row by row.
+    #[cfg(test)]
+    pub fn from_fn(f: impl Fn(Position) -> Node) -> Self {
+        Self::new(core::array::from_fn(|y| {
+            core::array::from_fn(|x| {
+                f(Position {
+                    x: x as u8,
+                    y: y as u8,
+                })
+            })
+        }))
+    }
+
+    /// Shrink the board to `size`, clearing every node outside of it.
+    ///
+    /// # Panics
+    /// Panics if `size` is empty or larger than `Size::MAX`.
+    pub fn with_size(mut self, size: Size) -> Self {
+        assert!(
+            (1..=Size::MAX.width).contains(&size.width)
+                && (1..=Size::MAX.height).contains(&size.height),
+            "invalid board size {size:?}"
+        );
+        for (y, row) in self.nodes.iter_mut().enumerate() {
+            for (x, node) in row.iter_mut().enumerate() {
+                if !size.contains(Position {
+                    x: x as u8,
+                    y: y as u8,
+                }) {
+                    *node = Node::Empty;
+                }
+            }
+        }
+        self.size = size;
+        self
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Place `node` at `(x, y)` and at each of the positions `symmetry` repeats it at, turned to
+    /// match.
+    ///
+    /// Under quarter turns, the teleporters a quarter turn away are given the next id, so that each
+    /// teleporter is twinned with the one on the opposite side of the board.
+    fn populate_symmetric(&mut self, symmetry: Symmetry, x: usize, y: usize, node: Node) {
+        let last = self.size.width as usize - 1;
+        for quarter_turns in (0..4).step_by(symmetry.step()) {
+            let (x, y) = match quarter_turns {
+                0 => (x, y),
+                1 => (last - y, x),
+                2 => (last - x, last - y),
+                _ => (y, last - x),
+            };
+            let mut image = node;
+            if let Some(mut direction) = node.direction() {
+                for _ in 0..quarter_turns {
+                    direction = direction.clockwise();
+                }
+                image.set_direction(direction);
+            }
+            if let Node::Teleporter { id, .. } = &mut image {
+                *id += quarter_turns as u8 % 2;
+            }
+            self.nodes[y][x] = image;
+        }
     }
 
-    fn populate_wall(&mut self, x: usize, y: usize, pcg: &mut Pcg32Fast) {
-        match pcg.gen::<u8>() {
-            0..=63 => self.0[y][x] = Node::AllDirection { alignment: None },
-            64..=127 => {
-                self.0[y][x] = Node::SuperArrow {
+    /// Draw the node placed where a wall or secret node goes, according to `weights`.
+    ///
+    /// Each teleporter drawn uses the ids `symmetry` needs for it and its images, starting at
+    /// `next_teleporter_id`. Once the ids run out, walls are placed instead.
+    fn roll_wall(
+        pcg: &mut Pcg32Fast,
+        next_teleporter_id: &mut u8,
+        symmetry: Symmetry,
+        weights: WallWeights,
+    ) -> Node {
+        // Scaling the byte keeps the default weights, which add up to 256, drawing exactly the
+        // nodes they always have.
+        let roll = pcg.gen::<u8>() as u32 * weights.total() / 256;
+        let all_direction = weights.all_direction as u32;
+        let super_arrow = all_direction + weights.super_arrow as u32;
+        let teleporter = super_arrow + weights.teleporter as u32;
+        let reverse_arrow = teleporter + weights.reverse_arrow as u32;
+        let teleporter_ids = symmetry.teleporter_ids();
+        let mut random_direction = || match pcg.gen::<u8>() {
+            0..=63 => Direction::Left,
+            64..=127 => Direction::Up,
+            128..=191 => Direction::Right,
+            192..=255 => Direction::Down,
+        };
+        match roll {
+            roll if roll < all_direction => Node::AllDirection { alignment: None },
+            roll if roll < super_arrow => Node::SuperArrow {
+                alignment: None,
+                direction: random_direction(),
+            },
+            roll if roll < teleporter && *next_teleporter_id + teleporter_ids <= TELEPORTER_IDS => {
+                let id = *next_teleporter_id;
+                *next_teleporter_id += teleporter_ids;
+                Node::Teleporter {
                     alignment: None,
-                    direction: {
-                        match pcg.gen::<u8>() {
-                            0..=63 => Direction::Left,
-                            64..=127 => Direction::Up,
-                            128..=191 => Direction::Right,
-                            192..=255 => Direction::Down,
-                        }
-                    },
+                    id,
+                }
+            }
+            roll if (teleporter..reverse_arrow).contains(&roll) => Node::ReverseArrow {
+                alignment: None,
+                direction: random_direction(),
+            },
+            _ => Node::Wall,
+        }
+    }
+
+    /// Fill in every node of a board symmetric under `symmetry`, other than the starting corners,
+    /// using the random numbers drawn from `seed`.
+    ///
+    /// Nodes are chosen for the part of the board `symmetry` repeats, row by row, and then placed
+    /// along with their images.
+    fn populate(&mut self, seed: u64, symmetry: Symmetry, weights: WallWeights) {
+        let length = self.size.width as usize;
+        let last = length - 1;
+        let (width, height) = match symmetry {
+            Symmetry::QuarterTurn => (length / 2, length / 2),
+            Symmetry::HalfTurn => (length, length / 2),
+        };
+
+        let mut pcg = Pcg32Fast::new(seed);
+        let mut next_teleporter_id = 0;
+        for y in 0..height {
+            for x in 0..width {
+                // Already did the starting positions.
+                if x == 0 && y == 0 {
+                    continue;
                 }
+                let direction = match pcg.gen::<u8>() {
+                    0..=60 if x == 1 && y == 0 => Direction::Up,
+                    0..=60 => Direction::Left,
+                    61..=120 if x == 0 && y == 1 => Direction::Left,
+                    61..=120 => Direction::Up,
+                    121..=180 => Direction::Right,
+                    181..=240 => Direction::Down,
+                    // Walls and secret nodes are never placed on the edges of the board.
+                    241..=255 if x == 0 || x == last => Direction::Down,
+                    241..=255 if y == 0 => Direction::Right,
+                    241..=255 => {
+                        let node =
+                            Self::roll_wall(&mut pcg, &mut next_teleporter_id, symmetry, weights);
+                        self.populate_symmetric(symmetry, x, y, node);
+                        continue;
+                    }
+                };
+                self.populate_symmetric(
+                    symmetry,
+                    x,
+                    y,
+                    Node::Arrow {
+                        alignment: None,
+                        direction,
+                    },
+                );
             }
-            64..=255 => self.0[y][x] = Node::Wall,
         }
     }
 
     /// Generate a random grid.
     pub fn generate(seed: u64) -> Self {
-        let mut grid = Grid([[Node::Empty; 16]; 16]);
+        Self::generate_with_size(seed, 16)
+    }
+
+    /// Generate a random square grid `length` nodes on a side.
+    ///
+    /// The board is symmetric under quarter turns, so `length` must be even. A full sized board is
+    /// the same as one from `generate()`.
+    ///
+    /// # Panics
+    /// Panics if `length` is odd, or not between 4 and 16.
+    pub fn generate_with_size(seed: u64, length: u8) -> Self {
+        Self::generate_with_weights(seed, length, WallWeights::default())
+    }
+
+    /// Generate a random square grid `length` nodes on a side, choosing the walls and secret nodes
+    /// according to `weights`.
+    ///
+    /// Only the kind of node placed where a wall or secret node goes is affected. How many of
+    /// those there are, and every other node, are the same as with `generate_with_size()`.
+    ///
+    /// # Panics
+    /// Panics if `length` is odd, or not between 4 and 16.
+    pub fn generate_with_weights(seed: u64, length: u8, weights: WallWeights) -> Self {
+        assert!(
+            length.is_multiple_of(2) && (4..=Size::MAX.width).contains(&length),
+            "invalid board length {length}"
+        );
+        let mut grid = Grid::new([[Node::Empty; 16]; 16]).with_size(Size::square(length));
+        let last = length as usize - 1;
 
         // Starting positions.
-        grid.0[0][0] = Node::Arrow {
+        grid.nodes[0][0] = Node::Arrow {
             alignment: Some(Color::Red),
             direction: Direction::Up,
         };
-        grid.0[0][15] = Node::Arrow {
+        grid.nodes[0][last] = Node::Arrow {
             alignment: Some(Color::Blue),
             direction: Direction::Right,
         };
-        grid.0[15][0] = Node::Arrow {
+        grid.nodes[last][0] = Node::Arrow {
             alignment: Some(Color::Yellow),
             direction: Direction::Left,
         };
-        grid.0[15][15] = Node::Arrow {
+        grid.nodes[last][last] = Node::Arrow {
             alignment: Some(Color::Green),
             direction: Direction::Down,
         };
 
-        let mut pcg = Pcg32Fast::new(seed);
-        for y in 0..8 {
-            for x in 0..8 {
-                // Already did the starting positions.
-                if x == 0 && y == 0 {
-                    continue;
-                }
-                match pcg.gen::<u8>() {
-                    0..=60 => {
-                        if x == 1 && y == 0 {
-                            grid.populate_reflected_arrows(x, y, Direction::Up);
-                        } else {
-                            grid.populate_reflected_arrows(x, y, Direction::Left);
-                        }
-                    }
-                    61..=120 => {
-                        if x == 0 && y == 1 {
-                            grid.populate_reflected_arrows(x, y, Direction::Left);
-                        } else {
-                            grid.populate_reflected_arrows(x, y, Direction::Up);
-                        }
-                    }
-                    121..=180 => {
-                        grid.populate_reflected_arrows(x, y, Direction::Right);
-                    }
-                    181..=240 => {
-                        grid.populate_reflected_arrows(x, y, Direction::Down);
-                    }
-                    241..=255 => {
-                        if x == 0 {
-                            grid.populate_reflected_arrows(x, y, Direction::Down)
-                        } else if y == 0 {
-                            grid.populate_reflected_arrows(x, y, Direction::Right)
-                        } else {
-                            grid.populate_wall(x, y, &mut pcg);
-                            grid.populate_wall(15 - y, x, &mut pcg);
-                            grid.populate_wall(y, 15 - x, &mut pcg);
-                            grid.populate_wall(15 - x, 15 - y, &mut pcg);
-                        }
-                    }
-                }
-            }
+        grid.populate(seed, Symmetry::QuarterTurn, weights);
+
+        grid
+    }
+
+    /// Generate a random grid where every player has room to expand.
+    ///
+    /// A board where a starting corner can reach fewer than `MIN_REACHABLE` other nodes without
+    /// crossing a wall is thrown out, and another is generated from a seed derived from `seed`.
+    /// Since walls are never placed on the edges, which every corner can reach, `generate()` doesn't
+    /// currently make such boards. This guards against a change to generation making them.
+    pub fn generate_validated(seed: u64) -> Self {
+        let mut pcg: Pcg32Fast = Pcg32Fast::new(seed);
+        let mut grid = Self::generate(seed);
+        while !grid.starts_can_expand() {
+            grid = Self::generate(pcg.gen());
         }
+        grid
+    }
+
+    /// Generate a random square grid for two players `length` nodes on a side, with only red and
+    /// green starting, in opposite corners.
+    ///
+    /// The board is symmetric under a half turn rather than a quarter turn: the bottom half is the
+    /// top half turned around, so each node faces the opposite way of its mirror. Walls and secret
+    /// nodes are chosen with the default weights, and each teleporter is twinned with its mirror.
+    ///
+    /// # Panics
+    /// Panics if `length` is odd, or not between 4 and 16.
+    pub fn generate_two_player(seed: u64, length: u8) -> Self {
+        assert!(
+            length.is_multiple_of(2) && (4..=Size::MAX.width).contains(&length),
+            "invalid board length {length}"
+        );
+        let mut grid = Grid::new([[Node::Empty; 16]; 16]).with_size(Size::square(length));
+        let last = length as usize - 1;
+
+        // Starting positions.
+        grid.nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        grid.nodes[last][last] = Node::Arrow {
+            alignment: Some(Color::Green),
+            direction: Direction::Down,
+        };
+
+        grid.populate(seed, Symmetry::HalfTurn, WallWeights::default());
 
         grid
     }
 
+    /// Whether every starting corner can reach at least `MIN_REACHABLE` other nodes.
+    fn starts_can_expand(&self) -> bool {
+        let last = self.size.last();
+        [
+            Position { x: 0, y: 0 },
+            Position { x: last.x, y: 0 },
+            Position { x: 0, y: last.y },
+            last,
+        ]
+        .into_iter()
+        .all(|start| self.reachable(start) > MIN_REACHABLE)
+    }
+
+    /// The number of nodes reachable from `start` by stepping between neighbors, without crossing
+    /// empty nodes or walls, including `start` itself.
+    fn reachable(&self, start: Position) -> u16 {
+        let passable = |position: &Position| {
+            self.get(*position)
+                .is_some_and(|node| !matches!(node, Node::Empty | Node::Wall))
+        };
+        if !passable(&start) {
+            return 0;
+        }
+
+        let mut visited = PositionSet::new();
+        visited.insert(start);
+        let mut stack = [start; 256];
+        let mut len = 1;
+        while len > 0 {
+            len -= 1;
+            let position = stack[len];
+            for neighbor in position.adjacent().filter(passable) {
+                if visited.insert(neighbor) {
+                    stack[len] = neighbor;
+                    len += 1;
+                }
+            }
+        }
+        visited.count() as u16
+    }
+
+    /// The node at `position`, or `None` if it is off the board.
     pub fn get(&self, position: Position) -> Option<&Node> {
-        self.0.get(position.y as usize)?.get(position.x as usize)
+        if !self.size.contains(position) {
+            return None;
+        }
+        Some(&self.nodes[position.y as usize][position.x as usize])
     }
 
     pub fn get_mut(&mut self, position: Position) -> Option<&mut Node> {
-        self.0
-            .get_mut(position.y as usize)?
-            .get_mut(position.x as usize)
+        if !self.size.contains(position) {
+            return None;
+        }
+        Some(&mut self.nodes[position.y as usize][position.x as usize])
+    }
+
+    /// Whether `color` may rotate the node at `position`.
+    ///
+    /// This is the case when the position is on the board and the node there is aligned with
+    /// `color`. It doesn't check whether the turn would capture anything.
+    pub fn is_valid_turn_position(&self, position: Position, color: Color) -> bool {
+        self.get(position).is_some_and(|node| node.is_color(color))
+    }
+
+    /// The number of nodes aligned with `color`.
+    ///
+    /// This is cheaper than `color_counts()` when only a single color is needed.
+    pub fn count_for_color(&self, color: Color) -> u16 {
+        self.nodes
+            .iter()
+            .flatten()
+            .filter(|node| node.is_color(color))
+            .count() as u16
     }
 
     pub fn color_counts(&self) -> ColorCounts {
@@ -135,7 +502,7 @@ impl Grid {
         let mut yellow_count = 0;
         let mut green_count = 0;
 
-        for row in self.0 {
+        for row in self.nodes {
             for node in row {
                 match node.color() {
                     Some(Color::Red) => red_count += 1,
@@ -155,46 +522,1795 @@ impl Grid {
         }
     }
 
+    /// Render a row as text, using two characters per node.
+    ///
+    /// The first character is the node's color (`R`, `B`, `Y`, `G`, or `.` if it has none) and the
+    /// second is its kind: `<^>v` for arrows, `lurd` for reverse arrows, `LURD` for super arrows,
+    /// `*` for all-direction nodes, and the id for teleporters. Walls are `##` and empty nodes are blank.
+    pub fn ascii_row(&self, y: u8) -> [u8; 32] {
+        let mut row = [b' '; 32];
+        for (x, node) in self.nodes[y as usize].iter().enumerate() {
+            let color = match node.color() {
+                Some(Color::Red) => b'R',
+                Some(Color::Blue) => b'B',
+                Some(Color::Yellow) => b'Y',
+                Some(Color::Green) => b'G',
+                None => b'.',
+            };
+            row[x * 2..x * 2 + 2].copy_from_slice(&match node {
+                Node::Empty => *b"  ",
+                Node::Wall => *b"##",
+                Node::Arrow { direction, .. } => [
+                    color,
+                    match direction {
+                        Direction::Left => b'<',
+                        Direction::Up => b'^',
+                        Direction::Right => b'>',
+                        Direction::Down => b'v',
+                    },
+                ],
+                Node::ReverseArrow { direction, .. } => [
+                    color,
+                    match direction {
+                        Direction::Left => b'l',
+                        Direction::Up => b'u',
+                        Direction::Right => b'r',
+                        Direction::Down => b'd',
+                    },
+                ],
+                Node::AllDirection { .. } => [color, b'*'],
+                Node::SuperArrow { direction, .. } => [
+                    color,
+                    match direction {
+                        Direction::Left => b'L',
+                        Direction::Up => b'U',
+                        Direction::Right => b'R',
+                        Direction::Down => b'D',
+                    },
+                ],
+                Node::Teleporter { id, .. } => [color, b'0' + id],
+            });
+        }
+        row
+    }
+
+    /// Render a row as the player sees it.
+    ///
+    /// This is the same as `ascii_row()`, except that secret nodes nobody has claimed yet are
+    /// shown as `.?`.
+    pub fn visible_row(&self, y: u8) -> [u8; 32] {
+        let mut row = self.ascii_row(y);
+        for (x, node) in self.nodes[y as usize].iter().enumerate() {
+            if node.is_hidden() {
+                row[x * 2..x * 2 + 2].copy_from_slice(b".?");
+            }
+        }
+        row
+    }
+
+    /// Log the board as the player sees it.
+    ///
+    /// Each row is logged separately to stay within the emulator's log message limit.
+    pub fn log(&self) {
+        for y in 0..self.size.height {
+            let row = self.visible_row(y);
+            log::info!(
+                "{}",
+                str::from_utf8(&row[..self.size.width as usize * 2]).unwrap_or("<invalid row>")
+            );
+        }
+    }
+
+    /// Encode every node into a byte, row by row.
+    ///
+    /// Each byte is in the format described by `Node::encode()`. The size of the board is not
+    /// included, so a smaller board encodes as the full sized array it is stored in.
+    pub fn to_bytes(&self, out: &mut [u8; 256]) {
+        for (byte, node) in out.iter_mut().zip(self.nodes.iter().flatten()) {
+            *byte = node.encode();
+        }
+    }
+
+    /// Decode a grid encoded by `to_bytes()`.
+    ///
+    /// The grid is full sized. Fails on the first byte that doesn't encode a node.
+    pub fn from_bytes(bytes: &[u8; 256]) -> Result<Self, DecodeError> {
+        let mut grid = Grid::new([[Node::Empty; 16]; 16]);
+        for (index, (node, &byte)) in grid.nodes.iter_mut().flatten().zip(bytes).enumerate() {
+            *node = Node::decode(byte).map_err(|_| DecodeError {
+                position: Position {
+                    x: (index % 16) as u8,
+                    y: (index / 16) as u8,
+                },
+                byte,
+            })?;
+        }
+        Ok(grid)
+    }
+
     pub fn iter(&self) -> slice::Iter<[Node; 16]> {
-        self.0.iter()
+        self.nodes.iter()
     }
 
-    pub fn weight(&self, position: Position, visited: &mut [[bool; 16]; 16]) -> u8 {
-        log::info!("position: {:?}", position);
-        if visited[position.y as usize][position.x as usize] {
-            0
-        } else {
-            visited[position.y as usize][position.x as usize] = true;
-            if let Some(node) = self.get(position) {
-                if node.is_hidden() {
-                    0
+    /// Every position on the board along with its node, row by row.
+    pub fn iter_positions(&self) -> impl Iterator<Item = (Position, &Node)> + Clone + '_ {
+        let size = self.size;
+        self.nodes
+            .iter()
+            .zip(0..size.height)
+            .flat_map(move |(row, y)| {
+                row.iter()
+                    .zip(0..size.width)
+                    .map(move |(node, x)| (Position { x, y }, node))
+            })
+    }
+
+    /// Every position on the board along with a mutable reference to its node, row by row.
+    pub fn iter_positions_mut(&mut self) -> impl Iterator<Item = (Position, &mut Node)> + '_ {
+        let size = self.size;
+        self.nodes
+            .iter_mut()
+            .zip(0..size.height)
+            .flat_map(move |(row, y)| {
+                row.iter_mut()
+                    .zip(0..size.width)
+                    .map(move |(node, x)| (Position { x, y }, node))
+            })
+    }
+
+    /// Every position holding a node aligned with `color`, row by row.
+    pub fn positions_of_color(&self, color: Color) -> impl Iterator<Item = Position> + Clone + '_ {
+        self.iter_positions()
+            .filter(move |(_, node)| node.is_color(color))
+            .map(|(position, _)| position)
+    }
+
+    /// The other teleporter with the same id as the teleporter at `position`.
+    ///
+    /// Returns `None` if there is no teleporter at `position`, or if it has no twin on the board.
+    pub fn twin(&self, position: Position) -> Option<Position> {
+        let id = self.get(position)?.teleporter_id()?;
+        (0..self.size.height)
+            .flat_map(|y| (0..self.size.width).map(move |x| Position { x, y }))
+            .find(|&other| {
+                other != position
+                    && self.nodes[other.y as usize][other.x as usize].teleporter_id() == Some(id)
+            })
+    }
+
+    /// The node a fill would spread to from `position` in the given direction, if any.
+    ///
+    /// A fill spreads between two neighboring nodes that can both be captured when either of them
+    /// points at the other, unless the node doing the pointing is hidden.
+    fn linked(&self, position: Position, direction: Direction, rules: Rules) -> Option<Position> {
+        let capturable = |node: &Node| !matches!(node, Node::Empty | Node::Wall);
+        let points = |node: &Node, direction: Direction| {
+            !node.is_hidden() && (node.direction() == Some(direction) || node.all_directions())
+        };
+
+        let node = self.get(position).filter(|node| capturable(node))?;
+        let neighbor_position = rules.neighbor(position, direction, self.size)?;
+        let neighbor = self
+            .get(neighbor_position)
+            .filter(|neighbor| capturable(neighbor))?;
+        (points(node, direction) || points(neighbor, direction.opposite()))
+            .then_some(neighbor_position)
+    }
+
+    /// The node a fill would spread to from `position` along link `index`, if any.
+    ///
+    /// The first links are to the neighbors in each of `DIRECTIONS`, and the last is to a
+    /// teleporter's twin. A fill always spreads between twins, since reaching either one captures
+    /// it.
+    fn link(&self, position: Position, index: usize, rules: Rules) -> Option<Position> {
+        match DIRECTIONS.get(index) {
+            Some(&direction) => self.linked(position, direction, rules),
+            None => self.twin(position),
+        }
+    }
+
+    /// Find the nodes that hold large regions of the board together.
+    ///
+    /// A node is a choke point if treating it as a wall would split the nodes a fill could spread
+    /// through into at least two separate regions of `CHOKE_POINT_REGION` nodes or more. Whoever
+    /// owns such a node can cut the board in two.
+    ///
+    /// These are the articulation points of the graph fills spread through, found with an
+    /// iterative depth-first search so that no recursion is needed.
+    pub fn choke_points(&self, rules: Rules) -> PositionSet {
+        // The order each node was discovered in, starting from 1. Undiscovered nodes are 0.
+        let mut discovered = [[0u16; 16]; 16];
+        // The earliest discovered node reachable from each node's subtree using at most one edge
+        // that leaves the subtree.
+        let mut low = [[0u16; 16]; 16];
+        // The number of nodes in each node's subtree.
+        let mut size = [[0u16; 16]; 16];
+        // The number of nodes in the subtrees that would be cut off by removing each node.
+        let mut separated = [[0u16; 16]; 16];
+        // The number of those subtrees that are large enough to count as regions.
+        let mut regions = [[0u8; 16]; 16];
+
+        // Each node on the current path, along with the index of the next link to explore.
+        let mut stack = [(Position { x: 0, y: 0 }, 0u8); 256];
+        // The nodes of the current connected component.
+        let mut component = [Position { x: 0, y: 0 }; 256];
+        let mut count = 0;
+        let mut choke_points = PositionSet::new();
+
+        for root in (0..16).flat_map(|y| (0..16).map(move |x| Position { x, y })) {
+            if !self.size.contains(root)
+                || discovered[root.y as usize][root.x as usize] != 0
+                || matches!(self.get(root), Some(Node::Empty | Node::Wall))
+            {
+                continue;
+            }
+
+            count += 1;
+            discovered[root.y as usize][root.x as usize] = count;
+            low[root.y as usize][root.x as usize] = count;
+            size[root.y as usize][root.x as usize] = 1;
+            stack[0] = (root, 0);
+            let mut depth = 1;
+            let mut component_len = 0;
+
+            while depth > 0 {
+                let (position, next) = stack[depth - 1];
+                if (next as usize) < LINKS {
+                    stack[depth - 1].1 += 1;
+                    let Some(neighbor) = self.link(position, next as usize, rules) else {
+                        continue;
+                    };
+                    let neighbor_discovered = discovered[neighbor.y as usize][neighbor.x as usize];
+                    if neighbor_discovered == 0 {
+                        count += 1;
+                        discovered[neighbor.y as usize][neighbor.x as usize] = count;
+                        low[neighbor.y as usize][neighbor.x as usize] = count;
+                        size[neighbor.y as usize][neighbor.x as usize] = 1;
+                        stack[depth] = (neighbor, 0);
+                        depth += 1;
+                    } else if depth < 2 || stack[depth - 2].0 != neighbor {
+                        let low = &mut low[position.y as usize][position.x as usize];
+                        *low = (*low).min(neighbor_discovered);
+                    }
                 } else {
-                    if let Some(direction) = node.direction() {
-                        if let Some(new_position) = position.r#move(direction) {
-                            1 + self.weight(new_position, visited)
-                        } else {
-                            1
-                        }
-                    } else if node.all_directions() {
-                        let mut weight = 1;
-                        for direction in [
-                            Direction::Left,
-                            Direction::Up,
-                            Direction::Right,
-                            Direction::Down,
-                        ] {
-                            if let Some(new_position) = position.r#move(direction) {
-                                weight += self.weight(new_position, visited);
+                    // Every link has been explored.
+                    depth -= 1;
+                    component[component_len] = position;
+                    component_len += 1;
+                    if let Some(&(parent, _)) = depth.checked_sub(1).and_then(|top| stack.get(top))
+                    {
+                        let (x, y) = (position.x as usize, position.y as usize);
+                        let (parent_x, parent_y) = (parent.x as usize, parent.y as usize);
+                        low[parent_y][parent_x] = low[parent_y][parent_x].min(low[y][x]);
+                        size[parent_y][parent_x] += size[y][x];
+                        if low[y][x] >= discovered[parent_y][parent_x] {
+                            separated[parent_y][parent_x] += size[y][x];
+                            if size[y][x] >= CHOKE_POINT_REGION {
+                                regions[parent_y][parent_x] += 1;
                             }
                         }
-                        weight
-                    } else {
-                        0
                     }
                 }
+            }
+
+            // Whatever isn't cut off below a node stays connected above it.
+            for position in &component[..component_len] {
+                let (x, y) = (position.x as usize, position.y as usize);
+                let remaining = component_len as u16 - 1 - separated[y][x];
+                let regions = regions[y][x] + (remaining >= CHOKE_POINT_REGION) as u8;
+                if regions >= 2 {
+                    choke_points.insert(*position);
+                }
+            }
+        }
+
+        choke_points
+    }
+
+    /// Whether each quarter of the board has at least one of its `choke_points()`.
+    pub fn has_choke_point_in_every_quadrant(&self, rules: Rules) -> bool {
+        let (half_width, half_height) = (self.size.width / 2, self.size.height / 2);
+        let mut quadrants = [false; 4];
+        for position in self.choke_points(rules).iter() {
+            let right = position.x >= half_width;
+            let bottom = position.y >= half_height;
+            quadrants[bottom as usize * 2 + right as usize] = true;
+        }
+        quadrants == [true; 4]
+    }
+
+    /// The number of nodes a fill starting at `position` would follow, not counting any already in
+    /// `visited`.
+    ///
+    /// Arrows are followed in the direction they point, all-direction nodes in every direction,
+    /// and teleporters to their twin. Hidden nodes, walls, and empty nodes end the chain. Every
+    /// node reached is marked in `visited`, so each is only counted once.
+    pub fn weight(&self, position: Position, rules: Rules, visited: &mut PositionSet) -> u16 {
+        // The nodes reached but not yet followed. Nodes are marked as visited when they are pushed,
+        // so each is pushed at most once.
+        let mut stack = [Position { x: 0, y: 0 }; 256];
+        let mut len = 0;
+        if visited.insert(position) {
+            stack[0] = position;
+            len = 1;
+        }
+
+        let mut weight = 0;
+        while len > 0 {
+            len -= 1;
+            let position = stack[len];
+            let Some(node) = self.get(position).filter(|node| !node.is_hidden()) else {
+                continue;
+            };
+            let mut next = [None; DIRECTIONS.len()];
+            if let Some(direction) = node.direction() {
+                next[0] = rules.neighbor(position, direction, self.size);
+            } else if node.all_directions() {
+                for (next, direction) in next.iter_mut().zip(DIRECTIONS) {
+                    *next = rules.neighbor(position, direction, self.size);
+                }
+            } else if node.teleporter_id().is_some() {
+                next[0] = self.twin(position);
             } else {
-                0
+                continue;
+            }
+
+            weight += 1;
+            for next in next.into_iter().flatten() {
+                if visited.insert(next) {
+                    stack[len] = next;
+                    len += 1;
+                }
+            }
+        }
+        weight
+    }
+}
+
+/// Indexing panics if the position is off the board. Use `Grid::get()` where that is expected.
+impl Index<Position> for Grid {
+    type Output = Node;
+
+    fn index(&self, position: Position) -> &Node {
+        self.get(position).unwrap_or_else(|| {
+            panic!(
+                "position ({}, {}) is off a {}x{} board",
+                position.x, position.y, self.size.width, self.size.height
+            )
+        })
+    }
+}
+
+impl IndexMut<Position> for Grid {
+    fn index_mut(&mut self, position: Position) -> &mut Node {
+        let size = self.size;
+        self.get_mut(position).unwrap_or_else(|| {
+            panic!(
+                "position ({}, {}) is off a {}x{} board",
+                position.x, position.y, size.width, size.height
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DecodeError, Grid, Size, WallWeights, CHOKE_POINT_REGION, LINKS, MIN_REACHABLE};
+    use crate::{
+        game::{Color, Direction, Node, Position, PositionSet, Rules},
+        random::Pcg32Fast,
+    };
+    use gba_test::test;
+    use rand::Rng;
+
+    /// Find choke points by removing each node in turn and counting the regions left behind.
+    fn brute_force_choke_points(grid: &Grid, rules: Rules) -> PositionSet {
+        let mut choke_points = PositionSet::new();
+        for y in 0..16 {
+            for x in 0..16 {
+                let removed = Position { x, y };
+                let mut visited = PositionSet::new();
+                visited.insert(removed);
+                let mut regions = 0;
+                for index in 0..LINKS {
+                    let Some(start) = grid.link(removed, index, rules) else {
+                        continue;
+                    };
+                    if !visited.insert(start) {
+                        continue;
+                    }
+                    let mut stack = [start; 256];
+                    let mut len = 1;
+                    let mut size = 0;
+                    while len > 0 {
+                        len -= 1;
+                        let position = stack[len];
+                        size += 1;
+                        for index in 0..LINKS {
+                            if let Some(next) = grid.link(position, index, rules) {
+                                if visited.insert(next) {
+                                    stack[len] = next;
+                                    len += 1;
+                                }
+                            }
+                        }
+                    }
+                    if size >= CHOKE_POINT_REGION {
+                        regions += 1;
+                    }
+                }
+                if regions >= 2 {
+                    choke_points.insert(removed);
+                }
+            }
+        }
+        choke_points
+    }
+
+    #[test]
+    fn ascii_row() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[2][0] = Node::Wall;
+        nodes[2][1] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        nodes[2][2] = Node::AllDirection { alignment: None };
+        nodes[2][3] = Node::SuperArrow {
+            alignment: Some(Color::Green),
+            direction: Direction::Down,
+        };
+        nodes[2][4] = Node::ReverseArrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Up,
+        };
+        nodes[2][5] = Node::Teleporter {
+            alignment: None,
+            id: 3,
+        };
+
+        assert_eq!(
+            &Grid::new(nodes).ascii_row(2),
+            b"##R<.*GDBu.3                    "
+        );
+    }
+
+    #[test]
+    fn visible_row_hides_secret_nodes() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Wall;
+        nodes[0][1] = Node::AllDirection { alignment: None };
+        nodes[0][2] = Node::AllDirection {
+            alignment: Some(Color::Yellow),
+        };
+        nodes[0][3] = Node::SuperArrow {
+            alignment: None,
+            direction: Direction::Right,
+        };
+        nodes[0][4] = Node::Teleporter {
+            alignment: Some(Color::Red),
+            id: 1,
+        };
+        nodes[0][5] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Up,
+        };
+
+        assert_eq!(
+            &Grid::new(nodes).visible_row(0),
+            b"##.?Y*.?R1.^                    "
+        );
+    }
+
+    /// Whether two grids hold the same nodes.
+    fn same_nodes(grid: &Grid, other: &Grid) -> bool {
+        grid.iter().flatten().eq(other.iter().flatten())
+    }
+
+    #[test]
+    fn bytes_round_trip_every_node() {
+        // Every byte that decodes is placed somewhere on the board.
+        let mut nodes = [[Node::Empty; 16]; 16];
+        let mut count = 0;
+        for byte in 0..=u8::MAX {
+            if let Ok(node) = Node::decode(byte) {
+                nodes[count / 16][count % 16] = node;
+                count += 1;
+            }
+        }
+        let grid = Grid::new(nodes);
+        let mut bytes = [0; 256];
+
+        grid.to_bytes(&mut bytes);
+
+        assert!(same_nodes(&Grid::from_bytes(&bytes).unwrap(), &grid));
+    }
+
+    #[test]
+    fn bytes_round_trip_generated() {
+        for seed in 0..20 {
+            let grid = Grid::generate(seed);
+            let mut bytes = [0; 256];
+
+            grid.to_bytes(&mut bytes);
+
+            assert!(same_nodes(&Grid::from_bytes(&bytes).unwrap(), &grid));
+        }
+    }
+
+    #[test]
+    fn bytes_row_major() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[1][2] = Node::Wall;
+        let mut bytes = [0; 256];
+
+        Grid::new(nodes).to_bytes(&mut bytes);
+
+        assert_eq!(bytes[16 + 2], Node::Wall.encode());
+        assert_eq!(bytes.iter().filter(|&&byte| byte != 0).count(), 1);
+    }
+
+    #[test]
+    fn from_bytes_invalid() {
+        let mut bytes = [0; 256];
+        bytes[3 * 16 + 5] = 0b111;
+        bytes[4 * 16] = 0b111;
+
+        assert_eq!(
+            Grid::from_bytes(&bytes).unwrap_err(),
+            DecodeError {
+                position: Position { x: 5, y: 3 },
+                byte: 0b111,
             }
+        );
+    }
+
+    #[test]
+    fn from_bytes_random() {
+        let mut rng: Pcg32Fast = Pcg32Fast::new(0);
+        for _ in 0..200 {
+            let mut bytes = [0; 256];
+            // Mostly valid nodes, so some grids decode all the way through.
+            for byte in &mut bytes {
+                *byte = loop {
+                    let byte = rng.gen::<u8>();
+                    if Node::decode(byte).is_ok() || rng.gen_range(0..1024) == 0 {
+                        break byte;
+                    }
+                };
+            }
+
+            match Grid::from_bytes(&bytes) {
+                Ok(grid) => {
+                    let mut encoded = [0; 256];
+                    grid.to_bytes(&mut encoded);
+                    assert_eq!(encoded, bytes);
+                }
+                Err(DecodeError { position, byte }) => {
+                    let index = position.y as usize * 16 + position.x as usize;
+                    assert_eq!(bytes[index], byte);
+                    assert!(Node::decode(byte).is_err());
+                    assert!(bytes[..index]
+                        .iter()
+                        .all(|&byte| Node::decode(byte).is_ok()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn from_fn() {
+        let grid = Grid::from_fn(|position| {
+            if position.x == position.y {
+                Node::Wall
+            } else {
+                Node::Empty
+            }
+        });
+
+        assert_eq!(grid.size(), Size::MAX);
+        for (position, node) in grid.iter_positions() {
+            assert_eq!(*node == Node::Wall, position.x == position.y);
+        }
+    }
+
+    #[test]
+    fn from_fn_row_major() {
+        let grid = Grid::from_fn(|position| {
+            if position == (Position { x: 3, y: 1 }) {
+                Node::Arrow {
+                    alignment: Some(Color::Blue),
+                    direction: Direction::Up,
+                }
+            } else {
+                Node::Empty
+            }
+        });
+
+        assert!(grid
+            .positions_of_color(Color::Blue)
+            .eq([Position { x: 3, y: 1 }]));
+    }
+
+    #[test]
+    fn with_size_clears_outside() {
+        let grid = Grid::generate(0).with_size(Size {
+            width: 8,
+            height: 12,
+        });
+
+        for y in 0..16 {
+            for x in 0..16 {
+                let position = Position { x, y };
+                if x < 8 && y < 12 {
+                    assert!(grid.get(position).is_some());
+                } else {
+                    assert_eq!(grid.get(position), None);
+                }
+            }
+        }
+        assert!(grid
+            .iter()
+            .flatten()
+            .skip(8)
+            .step_by(16)
+            .all(|&node| node == Node::Empty));
+    }
+
+    #[test]
+    fn generate_with_size_full_matches_generate() {
+        for seed in 0..20 {
+            assert!(same_nodes(
+                &Grid::generate_with_size(seed, 16),
+                &Grid::generate(seed)
+            ));
+        }
+    }
+
+    #[test]
+    fn generate_with_size_small() {
+        for length in [8, 12] {
+            for seed in 0..50 {
+                let grid = Grid::generate_with_size(seed, length);
+                let last = length - 1;
+
+                assert_eq!(grid.size(), Size::square(length));
+                // The starting positions are in the corners of the smaller board.
+                for (position, color) in [
+                    (Position { x: 0, y: 0 }, Color::Red),
+                    (Position { x: last, y: 0 }, Color::Blue),
+                    (Position { x: 0, y: last }, Color::Yellow),
+                    (Position { x: last, y: last }, Color::Green),
+                ] {
+                    assert!(grid[position].is_color(color));
+                }
+                assert_eq!(
+                    grid.iter()
+                        .flatten()
+                        .filter(|node| node.color().is_some())
+                        .count(),
+                    4
+                );
+                for y in 0..16 {
+                    for x in 0..16 {
+                        let node = grid.iter().nth(y as usize).unwrap()[x as usize];
+                        if x >= length || y >= length {
+                            assert_eq!(node, Node::Empty);
+                            continue;
+                        }
+                        // Every node is filled in, and the board looks the same after a quarter
+                        // turn.
+                        assert_ne!(node, Node::Empty);
+                        let rotated = grid[Position { x: last - y, y: x }];
+                        assert_eq!(
+                            node.direction().map(Direction::clockwise),
+                            rotated.direction()
+                        );
+                        assert_eq!(node.is_wall(), rotated.is_wall());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn choke_points_match_brute_force_on_small_grids() {
+        for seed in 0..20 {
+            for length in [8, 12] {
+                let grid = Grid::generate_with_size(seed, length);
+                for rules in [
+                    Rules::default(),
+                    Rules {
+                        wraparound: true,
+                        multi_rotation: false,
+                        super_arrow_claims: false,
+                    },
+                ] {
+                    assert_eq!(
+                        grid.choke_points(rules),
+                        brute_force_choke_points(&grid, rules)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn weight_chain() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        for (x, y, direction) in [
+            (0, 0, Direction::Right),
+            (1, 0, Direction::Right),
+            (2, 0, Direction::Down),
+            (2, 1, Direction::Down),
+        ] {
+            nodes[y][x] = Node::Arrow {
+                alignment: None,
+                direction,
+            };
+        }
+        let grid = Grid::new(nodes);
+
+        // Each arrow counts itself and everything after it, and the chain ends at an empty node.
+        for (x, y, weight) in [(0, 0, 4), (1, 0, 3), (2, 0, 2), (2, 1, 1)] {
+            assert_eq!(
+                grid.weight(Position { x, y }, Rules::default(), &mut PositionSet::new()),
+                weight
+            );
+        }
+    }
+
+    #[test]
+    fn weight_all_direction_hub() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[5][5] = Node::AllDirection {
+            alignment: Some(Color::Red),
+        };
+        // Two arrows leading away on the left, one on the right, and one pointing back up into the
+        // hub from below.
+        nodes[5][4] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Left,
+        };
+        nodes[5][3] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Left,
+        };
+        nodes[5][6] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Right,
+        };
+        nodes[6][5] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Up,
+        };
+        // Walls block the hub, but nothing past them is reached.
+        nodes[4][5] = Node::Wall;
+        nodes[3][5] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Down,
+        };
+        let grid = Grid::new(nodes);
+
+        assert_eq!(
+            grid.weight(
+                Position { x: 5, y: 5 },
+                Rules::default(),
+                &mut PositionSet::new()
+            ),
+            5
+        );
+        // Entering through the arrow below counts the hub and everything it fans out to.
+        assert_eq!(
+            grid.weight(
+                Position { x: 5, y: 6 },
+                Rules::default(),
+                &mut PositionSet::new()
+            ),
+            5
+        );
+    }
+
+    #[test]
+    fn weight_loop() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        for (x, y, direction) in [
+            (0, 0, Direction::Right),
+            (1, 0, Direction::Down),
+            (1, 1, Direction::Left),
+            (0, 1, Direction::Up),
+        ] {
+            nodes[y][x] = Node::Arrow {
+                alignment: None,
+                direction,
+            };
+        }
+        let grid = Grid::new(nodes);
+
+        // Each node in the loop is counted once, wherever the loop is entered.
+        for (x, y) in [(0, 0), (1, 0), (1, 1), (0, 1)] {
+            assert_eq!(
+                grid.weight(Position { x, y }, Rules::default(), &mut PositionSet::new()),
+                4
+            );
+        }
+    }
+
+    #[test]
+    fn weight_skips_visited() {
+        let grid = Grid::from_fn(|position| {
+            if position.y == 0 && position.x < 4 {
+                Node::Arrow {
+                    alignment: None,
+                    direction: Direction::Right,
+                }
+            } else {
+                Node::Empty
+            }
+        });
+        let mut visited = PositionSet::new();
+        visited.insert(Position { x: 2, y: 0 });
+
+        assert_eq!(
+            grid.weight(Position { x: 0, y: 0 }, Rules::default(), &mut visited),
+            2
+        );
+        assert!(visited.iter().eq((0..3).map(|x| Position { x, y: 0 })));
+    }
+
+    #[test]
+    fn weight_whole_board() {
+        // A snake of arrows covering the whole board, weighing more than fits in a byte.
+        let grid = Grid::from_fn(|position| Node::Arrow {
+            alignment: None,
+            direction: match (position.y % 2, position.x) {
+                (0, 15) | (1, 0) => Direction::Down,
+                (0, _) => Direction::Right,
+                _ => Direction::Left,
+            },
+        });
+
+        assert_eq!(
+            grid.weight(
+                Position { x: 0, y: 0 },
+                Rules::default(),
+                &mut PositionSet::new()
+            ),
+            256
+        );
+    }
+
+    #[test]
+    fn weight_stops_at_edge_of_small_board() {
+        let grid = Grid::from_fn(|position| {
+            if position.y == 0 {
+                Node::Arrow {
+                    alignment: None,
+                    direction: Direction::Right,
+                }
+            } else {
+                Node::Empty
+            }
+        })
+        .with_size(Size::square(8));
+
+        assert_eq!(
+            grid.weight(
+                Position { x: 0, y: 0 },
+                Rules::default(),
+                &mut PositionSet::new()
+            ),
+            8
+        );
+        // Wrapping around the smaller board comes straight back to the start.
+        assert_eq!(
+            grid.weight(
+                Position { x: 3, y: 0 },
+                Rules {
+                    wraparound: true,
+                    multi_rotation: false,
+                    super_arrow_claims: false,
+                },
+                &mut PositionSet::new()
+            ),
+            8
+        );
+    }
+
+    #[test]
+    fn weight_through_teleporters() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Right,
+        };
+        nodes[0][1] = Node::Teleporter {
+            alignment: Some(Color::Red),
+            id: 0,
+        };
+        nodes[15][14] = Node::Teleporter {
+            alignment: Some(Color::Red),
+            id: 0,
+        };
+        let grid = Grid::new(nodes);
+
+        // The twins lead to each other, and each is only counted once.
+        assert_eq!(
+            grid.weight(
+                Position { x: 0, y: 0 },
+                Rules::default(),
+                &mut PositionSet::new()
+            ),
+            3
+        );
+        assert_eq!(
+            grid.weight(
+                Position { x: 14, y: 15 },
+                Rules::default(),
+                &mut PositionSet::new()
+            ),
+            2
+        );
+    }
+
+    #[test]
+    fn weight_hidden_teleporter() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Teleporter {
+            alignment: None,
+            id: 0,
+        };
+        nodes[8][8] = Node::Teleporter {
+            alignment: Some(Color::Red),
+            id: 0,
+        };
+        let grid = Grid::new(nodes);
+
+        assert_eq!(
+            grid.weight(
+                Position { x: 0, y: 0 },
+                Rules::default(),
+                &mut PositionSet::new()
+            ),
+            0
+        );
+    }
+
+    #[test]
+    fn twin() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[2][3] = Node::Teleporter {
+            alignment: None,
+            id: 1,
+        };
+        nodes[12][4] = Node::Teleporter {
+            alignment: Some(Color::Blue),
+            id: 1,
+        };
+        nodes[5][5] = Node::Teleporter {
+            alignment: None,
+            id: 2,
+        };
+        let grid = Grid::new(nodes);
+
+        assert_eq!(
+            grid.twin(Position { x: 3, y: 2 }),
+            Some(Position { x: 4, y: 12 })
+        );
+        assert_eq!(
+            grid.twin(Position { x: 4, y: 12 }),
+            Some(Position { x: 3, y: 2 })
+        );
+        // No other teleporter has its id.
+        assert_eq!(grid.twin(Position { x: 5, y: 5 }), None);
+        // Not a teleporter.
+        assert_eq!(grid.twin(Position { x: 0, y: 0 }), None);
+    }
+
+    #[test]
+    fn twin_outside_small_board() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Teleporter {
+            alignment: None,
+            id: 0,
+        };
+        nodes[15][15] = Node::Teleporter {
+            alignment: None,
+            id: 0,
+        };
+        let grid = Grid::new(nodes).with_size(Size::square(8));
+
+        assert_eq!(grid.twin(Position { x: 0, y: 0 }), None);
+    }
+
+    #[test]
+    fn positions_of_color() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[3][9] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Up,
+        };
+        nodes[3][2] = Node::AllDirection {
+            alignment: Some(Color::Blue),
+        };
+        nodes[12][0] = Node::SuperArrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Left,
+        };
+        nodes[5][5] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        nodes[7][7] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Up,
+        };
+        let grid = Grid::new(nodes);
+
+        assert!(grid.positions_of_color(Color::Blue).eq([
+            Position { x: 2, y: 3 },
+            Position { x: 9, y: 3 },
+            Position { x: 0, y: 12 },
+        ]));
+        assert!(grid
+            .positions_of_color(Color::Red)
+            .eq([Position { x: 5, y: 5 }]));
+        assert_eq!(grid.positions_of_color(Color::Green).count(), 0);
+    }
+
+    #[test]
+    fn generate_two_player_is_mirrored() {
+        for seed in 0..8 {
+            let grid = Grid::generate_two_player(seed, 16);
+
+            for (position, node) in grid.iter_positions() {
+                let mut node = *node;
+                let mut mirror = grid[Position {
+                    x: 15 - position.x,
+                    y: 15 - position.y,
+                }];
+                // The starting corners differ only in color.
+                node.clear_color();
+                mirror.clear_color();
+                if let Some(direction) = mirror.direction() {
+                    mirror.set_direction(direction.opposite());
+                }
+
+                assert_eq!(
+                    node, mirror,
+                    "seed {seed}, ({}, {})",
+                    position.x, position.y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn generate_two_player_starts() {
+        for seed in 0..8 {
+            let grid = Grid::generate_two_player(seed, 16);
+            let counts = grid.color_counts();
+
+            assert_eq!(counts.for_color(Color::Red), 1, "seed {seed}");
+            assert_eq!(counts.for_color(Color::Green), 1, "seed {seed}");
+            assert_eq!(counts.for_color(Color::Blue), 0, "seed {seed}");
+            assert_eq!(counts.for_color(Color::Yellow), 0, "seed {seed}");
+            assert!(grid[Position { x: 0, y: 0 }].is_color(Color::Red));
+            assert!(grid[Position { x: 15, y: 15 }].is_color(Color::Green));
+        }
+    }
+
+    #[test]
+    fn generate_two_player_teleporters_are_twinned() {
+        for seed in 0..32 {
+            let grid = Grid::generate_two_player(seed, 16);
+
+            for (position, node) in grid.iter_positions() {
+                if node.teleporter_id().is_some() {
+                    assert_eq!(
+                        grid.twin(position),
+                        Some(Position {
+                            x: 15 - position.x,
+                            y: 15 - position.y,
+                        }),
+                        "seed {seed}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generate_two_player_with_size() {
+        for seed in 0..8 {
+            let grid = Grid::generate_two_player(seed, 8);
+
+            assert_eq!(grid.size(), Size::square(8));
+            assert!(grid[Position { x: 7, y: 7 }].is_color(Color::Green));
+            for (position, node) in grid.iter_positions() {
+                let mut node = *node;
+                let mut mirror = grid[Position {
+                    x: 7 - position.x,
+                    y: 7 - position.y,
+                }];
+                node.clear_color();
+                mirror.clear_color();
+                if let Some(direction) = mirror.direction() {
+                    mirror.set_direction(direction.opposite());
+                }
+
+                assert_eq!(
+                    node, mirror,
+                    "seed {seed}, ({}, {})",
+                    position.x, position.y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn generate_two_player_deterministic() {
+        let mut first = [0; 256];
+        let mut second = [0; 256];
+        Grid::generate_two_player(3, 16).to_bytes(&mut first);
+        Grid::generate_two_player(3, 16).to_bytes(&mut second);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn is_valid_turn_position_owned() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[3][2] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Left,
+        };
+        let grid = Grid::new(nodes);
+
+        assert!(grid.is_valid_turn_position(Position { x: 2, y: 3 }, Color::Blue));
+        assert!(!grid.is_valid_turn_position(Position { x: 2, y: 3 }, Color::Red));
+    }
+
+    #[test]
+    fn is_valid_turn_position_unowned() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Left,
+        };
+        nodes[0][1] = Node::Wall;
+        let grid = Grid::new(nodes);
+
+        for x in 0..3 {
+            assert!(
+                !grid.is_valid_turn_position(Position { x, y: 0 }, Color::Red),
+                "x {x}"
+            );
+        }
+    }
+
+    #[test]
+    fn is_valid_turn_position_off_board() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[10][10] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        let grid = Grid::new(nodes);
+
+        assert!(grid.is_valid_turn_position(Position { x: 10, y: 10 }, Color::Red));
+        assert!(!grid
+            .clone()
+            .with_size(Size::square(8))
+            .is_valid_turn_position(Position { x: 10, y: 10 }, Color::Red));
+        assert!(!grid.is_valid_turn_position(Position { x: 16, y: 0 }, Color::Red));
+    }
+
+    #[test]
+    fn count_for_color_matches_color_counts() {
+        for seed in 0..8 {
+            let grid = Grid::generate(seed);
+            let counts = grid.color_counts();
+
+            for color in Color::ALL {
+                assert_eq!(grid.count_for_color(color), counts.for_color(color));
+            }
+        }
+    }
+
+    #[test]
+    fn count_for_color_empty() {
+        let grid = Grid::new([[Node::Empty; 16]; 16]);
+
+        for color in Color::ALL {
+            assert_eq!(grid.count_for_color(color), 0);
+        }
+    }
+
+    #[test]
+    fn positions_of_color_match_color_counts() {
+        for seed in 0..20 {
+            let grid = Grid::generate(seed);
+            let counts = grid.color_counts();
+            for color in Color::ALL {
+                assert_eq!(
+                    grid.positions_of_color(color).count(),
+                    counts.get(color).map_or(0, |count| count.get() as usize)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn generate_secret_nodes_symmetric() {
+        for seed in 0..200 {
+            let grid = Grid::generate(seed);
+            for y in 0..16 {
+                for x in 0..16 {
+                    let node = grid[Position { x, y }];
+                    // The same position rotated a quarter turn clockwise about the center.
+                    let rotated = grid[Position { x: 15 - y, y: x }];
+                    match node {
+                        Node::Wall => assert_eq!(rotated, Node::Wall),
+                        Node::AllDirection { .. } => {
+                            assert_eq!(rotated, Node::AllDirection { alignment: None })
+                        }
+                        Node::SuperArrow { direction, .. } => assert_eq!(
+                            rotated,
+                            Node::SuperArrow {
+                                alignment: None,
+                                direction: direction.clockwise(),
+                            }
+                        ),
+                        Node::ReverseArrow { direction, .. } => assert_eq!(
+                            rotated,
+                            Node::ReverseArrow {
+                                alignment: None,
+                                direction: direction.clockwise(),
+                            }
+                        ),
+                        Node::Teleporter { .. } => assert!(rotated.is_hidden()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generate_reverse_arrows() {
+        assert!((0..200).any(|seed| {
+            Grid::generate(seed)
+                .iter()
+                .flatten()
+                .any(|node| matches!(node, Node::ReverseArrow { .. }))
+        }));
+    }
+
+    #[test]
+    fn generate_teleporters_twinned_across_center() {
+        let mut teleporters = 0;
+        for seed in 0..200 {
+            let grid = Grid::generate(seed);
+            for y in 0..16 {
+                for x in 0..16 {
+                    let position = Position { x, y };
+                    if grid[position].teleporter_id().is_some() {
+                        teleporters += 1;
+                        assert_eq!(
+                            grid.twin(position),
+                            Some(Position {
+                                x: 15 - x,
+                                y: 15 - y
+                            }),
+                            "seed {seed}"
+                        );
+                    }
+                }
+            }
+        }
+
+        // Teleporters are rare, but not too rare to show up at all.
+        assert!(teleporters > 0);
+    }
+
+    /// The number of all direction, super arrow, teleporter, reverse arrow, and wall nodes on
+    /// every board generated with `weights` from the first `seeds` seeds.
+    fn count_wall_kinds(seeds: u64, weights: WallWeights) -> [u32; 5] {
+        let mut counts = [0; 5];
+        for seed in 0..seeds {
+            for node in Grid::generate_with_weights(seed, 16, weights)
+                .iter()
+                .flatten()
+            {
+                match node {
+                    Node::AllDirection { .. } => counts[0] += 1,
+                    Node::SuperArrow { .. } => counts[1] += 1,
+                    Node::Teleporter { .. } => counts[2] += 1,
+                    Node::ReverseArrow { .. } => counts[3] += 1,
+                    Node::Wall => counts[4] += 1,
+                    _ => {}
+                }
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn generate_with_weights_default_matches_generate() {
+        for seed in 0..20 {
+            assert!(same_nodes(
+                &Grid::generate_with_weights(seed, 16, WallWeights::default()),
+                &Grid::generate(seed)
+            ));
+        }
+    }
+
+    #[test]
+    fn generate_with_weights_frequencies() {
+        let [all_direction, super_arrow, teleporter, reverse_arrow, wall] = count_wall_kinds(
+            200,
+            WallWeights {
+                all_direction: 1,
+                super_arrow: 1,
+                teleporter: 0,
+                reverse_arrow: 0,
+                wall: 2,
+            },
+        );
+
+        assert_eq!(teleporter, 0);
+        assert_eq!(reverse_arrow, 0);
+        // Each kind should come up in proportion to its weight, give or take a quarter.
+        assert!(all_direction * 3 > super_arrow * 2 && all_direction * 2 < super_arrow * 3);
+        assert!(wall * 4 > all_direction * 6 && wall * 4 < all_direction * 10);
+        assert!(wall * 4 > super_arrow * 6 && wall * 4 < super_arrow * 10);
+    }
+
+    #[test]
+    fn generate_with_weights_default_frequencies() {
+        let weights = WallWeights::default();
+        let [all_direction, super_arrow, _, reverse_arrow, _] = count_wall_kinds(200, weights);
+
+        // Teleporters and walls are left out, since walls stand in for teleporters once they run
+        // out.
+        assert!(all_direction * 3 > super_arrow * 2 && all_direction * 2 < super_arrow * 3);
+        assert!(reverse_arrow * 6 > all_direction * 2 && reverse_arrow * 4 < all_direction * 3);
+    }
+
+    #[test]
+    fn generate_with_weights_zero_is_never_placed() {
+        let [all_direction, super_arrow, teleporter, reverse_arrow, wall] = count_wall_kinds(
+            50,
+            WallWeights {
+                all_direction: 0,
+                super_arrow: 0,
+                teleporter: 0,
+                reverse_arrow: 3,
+                wall: 0,
+            },
+        );
+
+        assert_eq!([all_direction, super_arrow, teleporter, wall], [0; 4]);
+        assert!(reverse_arrow > 0);
+    }
+
+    #[test]
+    fn generate_with_weights_all_zero_places_walls() {
+        let [all_direction, super_arrow, teleporter, reverse_arrow, wall] = count_wall_kinds(
+            50,
+            WallWeights {
+                all_direction: 0,
+                super_arrow: 0,
+                teleporter: 0,
+                reverse_arrow: 0,
+                wall: 0,
+            },
+        );
+
+        assert_eq!(
+            [all_direction, super_arrow, teleporter, reverse_arrow],
+            [0; 4]
+        );
+        assert!(wall > 0);
+    }
+
+    #[test]
+    fn index_corners() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Wall;
+        nodes[0][15] = Node::AllDirection { alignment: None };
+        nodes[15][0] = Node::AllDirection {
+            alignment: Some(Color::Blue),
+        };
+        let grid = Grid::new(nodes);
+
+        assert_eq!(grid[Position { x: 0, y: 0 }], Node::Wall);
+        assert_eq!(
+            grid[Position { x: 15, y: 0 }],
+            Node::AllDirection { alignment: None }
+        );
+        assert_eq!(
+            grid[Position { x: 0, y: 15 }],
+            Node::AllDirection {
+                alignment: Some(Color::Blue)
+            }
+        );
+        assert_eq!(grid[Position { x: 15, y: 15 }], Node::Empty);
+    }
+
+    #[test]
+    fn index_matches_get() {
+        let grid = Grid::generate(6).with_size(Size::square(10));
+
+        for (position, node) in grid.iter_positions() {
+            assert_eq!(&grid[position], node);
+        }
+    }
+
+    #[test]
+    fn index_mut_writes() {
+        let mut grid = Grid::new([[Node::Empty; 16]; 16]);
+
+        grid[Position { x: 15, y: 0 }] = Node::Wall;
+
+        assert_eq!(grid.get(Position { x: 15, y: 0 }), Some(&Node::Wall));
+        assert_eq!(grid.get(Position { x: 0, y: 15 }), Some(&Node::Empty));
+    }
+
+    #[test]
+    fn iter_positions_row_major() {
+        let grid = Grid::generate(0);
+
+        let mut expected = (0..16).flat_map(|y| (0..16).map(move |x| Position { x, y }));
+        for (position, node) in grid.iter_positions() {
+            assert_eq!(Some(position), expected.next());
+            assert_eq!(grid.get(position), Some(node));
+        }
+        assert_eq!(expected.next(), None);
+    }
+
+    #[test]
+    fn iter_positions_small_board() {
+        let grid = Grid::generate(0).with_size(Size {
+            width: 8,
+            height: 12,
+        });
+
+        assert_eq!(grid.iter_positions().count(), 96);
+        assert!(grid
+            .iter_positions()
+            .all(|(position, _)| grid.size().contains(position)));
+        assert_eq!(
+            grid.iter_positions().last().map(|(position, _)| position),
+            Some(Position { x: 7, y: 11 })
+        );
+    }
+
+    #[test]
+    fn iter_positions_mut_matches_iter_positions() {
+        let mut grid = Grid::generate(4).with_size(Size::square(10));
+        let expected = grid.clone();
+
+        assert!(grid
+            .iter_positions_mut()
+            .map(|(position, node)| (position, *node))
+            .eq(expected
+                .iter_positions()
+                .map(|(position, node)| (position, *node))));
+    }
+
+    #[test]
+    fn iter_positions_mut_writes() {
+        let mut grid = Grid::new([[Node::Empty; 16]; 16]);
+
+        for (position, node) in grid.iter_positions_mut() {
+            if position.x == position.y {
+                *node = Node::Wall;
+            }
+        }
+
+        for (position, node) in grid.iter_positions() {
+            assert_eq!(
+                *node == Node::Wall,
+                position.x == position.y,
+                "{position:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn reachable_walled_off_corner() {
+        let mut nodes = [[Node::AllDirection { alignment: None }; 16]; 16];
+        nodes[0][1] = Node::Wall;
+        nodes[1][0] = Node::Wall;
+        let grid = Grid::new(nodes);
+
+        assert_eq!(grid.reachable(Position { x: 0, y: 0 }), 1);
+        assert_eq!(grid.reachable(Position { x: 15, y: 15 }), 253);
+        assert!(!grid.starts_can_expand());
+    }
+
+    #[test]
+    fn reachable_small_pocket() {
+        let mut nodes = [[Node::AllDirection { alignment: None }; 16]; 16];
+        // A pocket of exactly `MIN_REACHABLE` other nodes along the top edge.
+        let width = MIN_REACHABLE as usize + 1;
+        nodes[0][width] = Node::Wall;
+        for x in 0..width {
+            nodes[1][x] = Node::Wall;
+        }
+
+        assert!(Grid::new(nodes).starts_can_expand());
+
+        nodes[0][width - 1] = Node::Wall;
+
+        assert!(!Grid::new(nodes).starts_can_expand());
+    }
+
+    #[test]
+    fn reachable_from_wall() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Wall;
+
+        assert_eq!(Grid::new(nodes).reachable(Position { x: 0, y: 0 }), 0);
+    }
+
+    /// A search for a seed that boxes in a starting corner. None has turned up, since walls are
+    /// never placed on the edges.
+    #[test]
+    fn generate_starts_can_expand() {
+        for seed in 0..1000 {
+            assert!(Grid::generate(seed).starts_can_expand(), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn generate_validated_matches_generate() {
+        for seed in 0..200 {
+            assert!(same_nodes(
+                &Grid::generate_validated(seed),
+                &Grid::generate(seed)
+            ));
+        }
+    }
+
+    #[test]
+    fn generate_no_secret_nodes_on_edges() {
+        for seed in 0..200 {
+            let grid = Grid::generate(seed);
+            for i in 0..16 {
+                for position in [
+                    Position { x: i, y: 0 },
+                    Position { x: i, y: 15 },
+                    Position { x: 0, y: i },
+                    Position { x: 15, y: i },
+                ] {
+                    let node = &grid[position];
+                    assert!(!node.is_hidden() && !node.is_wall());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn has_choke_point_in_every_quadrant_matches_choke_points() {
+        for seed in 0..50 {
+            let grid = Grid::generate(seed);
+            let choke_points = grid.choke_points(Rules::default());
+            let in_quadrant = |xs: core::ops::Range<u8>, ys: core::ops::Range<u8>| {
+                choke_points
+                    .iter()
+                    .any(|position| xs.contains(&position.x) && ys.contains(&position.y))
+            };
+
+            assert_eq!(
+                grid.has_choke_point_in_every_quadrant(Rules::default()),
+                in_quadrant(0..8, 0..8)
+                    && in_quadrant(8..16, 0..8)
+                    && in_quadrant(0..8, 8..16)
+                    && in_quadrant(8..16, 8..16),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn has_choke_point_in_every_quadrant_missing_one() {
+        // A path of 32 arrows along the top two rows, as in `choke_points_path()`, only has choke
+        // points in the top right quadrant.
+        let mut nodes = [[Node::Empty; 16]; 16];
+        for x in 0..15 {
+            nodes[0][x] = Node::Arrow {
+                alignment: None,
+                direction: Direction::Right,
+            };
+        }
+        nodes[0][15] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Down,
+        };
+        for x in 0..16 {
+            nodes[1][x] = Node::Arrow {
+                alignment: None,
+                direction: Direction::Left,
+            };
+        }
+
+        assert!(!Grid::new(nodes).has_choke_point_in_every_quadrant(Rules::default()));
+    }
+
+    #[test]
+    fn choke_points_empty() {
+        assert_eq!(
+            Grid::new([[Node::Empty; 16]; 16]).choke_points(Rules::default()),
+            PositionSet::new()
+        );
+    }
+
+    #[test]
+    fn choke_points_path() {
+        // A path of 32 arrows along the top two rows.
+        let mut nodes = [[Node::Empty; 16]; 16];
+        for x in 0..15 {
+            nodes[0][x] = Node::Arrow {
+                alignment: None,
+                direction: Direction::Right,
+            };
+        }
+        nodes[0][15] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Down,
+        };
+        for x in 0..16 {
+            nodes[1][x] = Node::Arrow {
+                alignment: None,
+                direction: Direction::Left,
+            };
+        }
+        let choke_points = Grid::new(nodes).choke_points(Rules::default());
+
+        // Only nodes with at least 8 nodes on either side split the path into two regions.
+        assert!(!choke_points.contains(Position { x: 7, y: 0 }));
+        assert!(choke_points.contains(Position { x: 8, y: 0 }));
+        assert!(choke_points.contains(Position { x: 15, y: 0 }));
+        assert!(choke_points.contains(Position { x: 8, y: 1 }));
+        assert!(!choke_points.contains(Position { x: 7, y: 1 }));
+    }
+
+    #[test]
+    fn choke_points_through_teleporters() {
+        // Two rows of 8 arrows on opposite sides of the board, joined only by a teleporter pair.
+        let mut nodes = [[Node::Empty; 16]; 16];
+        for x in 0..8 {
+            nodes[0][x] = Node::Arrow {
+                alignment: None,
+                direction: Direction::Right,
+            };
+            nodes[15][x + 8] = Node::Arrow {
+                alignment: None,
+                direction: Direction::Left,
+            };
+        }
+        nodes[0][8] = Node::Teleporter {
+            alignment: None,
+            id: 0,
+        };
+        nodes[15][7] = Node::Teleporter {
+            alignment: None,
+            id: 0,
+        };
+        let grid = Grid::new(nodes);
+        let choke_points = grid.choke_points(Rules::default());
+
+        // Only the teleporters have at least 8 nodes on either side.
+        assert!(!choke_points.contains(Position { x: 7, y: 0 }));
+        assert!(choke_points.contains(Position { x: 8, y: 0 }));
+        assert!(choke_points.contains(Position { x: 7, y: 15 }));
+        assert!(!choke_points.contains(Position { x: 8, y: 15 }));
+        assert_eq!(
+            choke_points,
+            brute_force_choke_points(&grid, Rules::default())
+        );
+    }
+
+    #[test]
+    fn choke_points_cycle() {
+        // With wraparound, a full row of arrows is a cycle, which has no choke points.
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0] = [Node::Arrow {
+            alignment: None,
+            direction: Direction::Right,
+        }; 16];
+
+        assert_eq!(
+            Grid::new(nodes).choke_points(Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            }),
+            PositionSet::new()
+        );
+    }
+
+    #[test]
+    fn choke_points_hidden_node_does_not_link() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        for x in 0..16 {
+            nodes[0][x] = Node::Arrow {
+                alignment: None,
+                direction: Direction::Right,
+            };
+        }
+        // A hidden super arrow doesn't spread a fill, and the arrow before it points away.
+        nodes[0][8] = Node::SuperArrow {
+            alignment: None,
+            direction: Direction::Right,
+        };
+        nodes[0][7] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Up,
+        };
+
+        assert_eq!(
+            Grid::new(nodes).choke_points(Rules::default()),
+            PositionSet::new()
+        );
+    }
+
+    #[test]
+    fn choke_points_match_brute_force_on_generated_grids() {
+        for seed in 0..20 {
+            let grid = Grid::generate(seed);
+            for rules in [
+                Rules::default(),
+                Rules {
+                    wraparound: true,
+                    multi_rotation: false,
+                    super_arrow_claims: false,
+                },
+            ] {
+                assert_eq!(
+                    grid.choke_points(rules),
+                    brute_force_choke_points(&grid, rules),
+                    "seed {}, {:?}",
+                    seed,
+                    rules
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn choke_points_match_brute_force_on_random_grids() {
+        let mut pcg: Pcg32Fast = Pcg32Fast::new(3);
+        for _ in 0..20 {
+            let mut nodes = [[Node::Empty; 16]; 16];
+            for node in nodes.iter_mut().flatten() {
+                let direction = match pcg.gen_range(0..4) {
+                    0 => Direction::Left,
+                    1 => Direction::Up,
+                    2 => Direction::Right,
+                    _ => Direction::Down,
+                };
+                *node = match pcg.gen_range(0..8) {
+                    0 => Node::Empty,
+                    1 => Node::Wall,
+                    2 => Node::AllDirection {
+                        alignment: Some(Color::Red),
+                    },
+                    _ => Node::Arrow {
+                        alignment: None,
+                        direction,
+                    },
+                };
+            }
+            let grid = Grid::new(nodes);
+
+            assert_eq!(
+                grid.choke_points(Rules::default()),
+                brute_force_choke_points(&grid, Rules::default())
+            );
         }
     }
 }
@@ -0,0 +1,203 @@
+//! Every turn taken during a game.
+
+use super::{Color, Position};
+use deranged::RangedU8;
+
+/// A single turn in a game's history.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// The color that took the turn.
+    pub color: Color,
+    /// The position that was rotated.
+    pub position: Position,
+    /// How many times the node was rotated.
+    pub times: RangedU8<1, 3>,
+}
+
+impl Entry {
+    /// Pack the entry with the number of rotations in bits 10 and 11, the color in bits 8 and 9,
+    /// `x` in the high nibble of the low byte, and `y` in the low nibble.
+    fn pack(self) -> u16 {
+        (self.times.get() as u16) << 10
+            | (u8::from(self.color) as u16) << 8
+            | (self.position.x as u16) << 4
+            | self.position.y as u16
+    }
+
+    fn unpack(packed: u16) -> Self {
+        Self {
+            color: Color::try_from((packed >> 8) as u8 & 0b11)
+                .expect("history entry had invalid color"),
+            position: Position {
+                x: (packed >> 4) as u8 & 0xf,
+                y: packed as u8 & 0xf,
+            },
+            times: RangedU8::new((packed >> 10) as u8)
+                .expect("history entry had invalid rotations"),
+        }
+    }
+}
+
+/// The turns taken during a game, in the order they were taken.
+///
+/// Passed turns are not recorded, since they are determined by the game state. Together with the
+/// board the game started on, this is enough to play the whole game back.
+///
+/// At most [`History::CAPACITY`] turns are recorded. Turns taken after that are dropped, and the
+/// history is marked as truncated, since a history missing its end can't be played back anyway.
+#[derive(Clone, Debug)]
+pub struct History {
+    entries: [u16; Self::CAPACITY],
+    len: usize,
+    truncated: bool,
+}
+
+impl History {
+    /// The maximum number of turns recorded.
+    pub const CAPACITY: usize = 512;
+
+    pub(super) fn new() -> Self {
+        Self {
+            entries: [0; Self::CAPACITY],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    pub(super) fn push(&mut self, entry: Entry) {
+        match self.entries.get_mut(self.len) {
+            Some(packed) => {
+                *packed = entry.pack();
+                self.len += 1;
+            }
+            None => self.truncated = true,
+        }
+    }
+
+    /// Forget the most recent turn, such as when it is undone.
+    ///
+    /// Once turns have been dropped, there's no telling whether the last one was kept, so a
+    /// truncated history is left as is.
+    pub(super) fn pop(&mut self) {
+        if !self.truncated {
+            self.len = self.len.saturating_sub(1);
+        }
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = Entry> + '_ {
+        self.entries[..self.len].iter().copied().map(Entry::unpack)
+    }
+
+    /// Whether turns were left out because there were more than [`History::CAPACITY`].
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Log every turn, so a game can be followed from an emulator's log.
+    ///
+    /// Each turn is logged separately to stay within the emulator's log message limit.
+    pub fn log(&self) {
+        for (index, entry) in self.iter().enumerate() {
+            log::info!(
+                "{} {:?} ({}, {}) x{}",
+                index + 1,
+                entry.color,
+                entry.position.x,
+                entry.position.y,
+                entry.times.get()
+            );
+        }
+        if self.is_truncated() {
+            log::info!("later turns were not recorded");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, History};
+    use crate::game::{Color, Position, Turn};
+    use deranged::RangedU8;
+    use gba_test::test;
+
+    fn entry(x: u8) -> Entry {
+        Entry {
+            color: Color::Yellow,
+            position: Position { x, y: 15 - x },
+            times: Turn::ONCE,
+        }
+    }
+
+    #[test]
+    fn pack_round_trip() {
+        for color in Color::ALL {
+            for y in 0..16 {
+                for x in 0..16 {
+                    for times in 1..=3 {
+                        let entry = Entry {
+                            color,
+                            position: Position { x, y },
+                            times: RangedU8::new(times).unwrap(),
+                        };
+
+                        assert_eq!(Entry::unpack(entry.pack()), entry);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let history = History::new();
+
+        assert_eq!(history.iter().len(), 0);
+        assert_eq!(history.iter().next(), None);
+        assert!(!history.is_truncated());
+    }
+
+    #[test]
+    fn in_order() {
+        let mut history = History::new();
+        history.push(entry(0));
+        history.push(entry(1));
+
+        assert_eq!(history.iter().len(), 2);
+        assert!(history.iter().eq([entry(0), entry(1)]));
+    }
+
+    #[test]
+    fn pop() {
+        let mut history = History::new();
+        history.push(entry(0));
+        history.push(entry(1));
+
+        history.pop();
+
+        assert!(history.iter().eq([entry(0)]));
+    }
+
+    #[test]
+    fn truncated() {
+        let mut history = History::new();
+        for index in 0..History::CAPACITY + 1 {
+            history.push(entry(index as u8 & 0xf));
+        }
+
+        assert_eq!(history.iter().len(), History::CAPACITY);
+        assert!(history.is_truncated());
+        assert_eq!(history.iter().last(), Some(entry(0xf)));
+    }
+
+    #[test]
+    fn truncated_pop_kept() {
+        let mut history = History::new();
+        for _ in 0..History::CAPACITY + 1 {
+            history.push(entry(0));
+        }
+
+        history.pop();
+
+        assert_eq!(history.iter().len(), History::CAPACITY);
+    }
+}
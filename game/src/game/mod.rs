@@ -1,18 +1,34 @@
 //! The actual gameplay.
 
+mod bitset;
+pub mod board_code;
+pub mod compat;
 mod direction;
+mod events;
 mod grid;
+pub mod history;
 mod node;
 mod position;
+pub mod prelude;
+pub mod replay;
+mod rules;
+mod save;
 mod turn;
 
+pub use bitset::PositionSet;
+pub use board_code::BoardCode;
 pub use direction::Direction;
-pub use grid::Grid;
+pub use events::{Capture, TurnEvents};
+pub use grid::{Grid, Size};
+pub use history::History;
 pub use node::Node;
 pub use position::Position;
+pub use replay::{Playback, Recording, Replay};
+pub use rules::Rules;
 pub use turn::Turn;
 
-use core::num::NonZeroU16;
+use core::{cmp::Reverse, iter, num::NonZeroU16, slice, str};
+use deranged::RangedU8;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Color {
@@ -23,7 +39,87 @@ pub enum Color {
     Green,
 }
 
-#[derive(Debug)]
+impl Color {
+    /// Every player color, in turn order.
+    pub const ALL: [Color; 4] = [Color::Red, Color::Blue, Color::Yellow, Color::Green];
+
+    /// The color whose turn comes after this one.
+    ///
+    /// This does not account for eliminated players.
+    pub const fn next(self) -> Color {
+        match self {
+            Self::Red => Self::Blue,
+            Self::Blue => Self::Yellow,
+            Self::Yellow => Self::Green,
+            Self::Green => Self::Red,
+        }
+    }
+
+    /// Whether this color plays in a game with `players` players.
+    ///
+    /// Two players start in opposite corners. Three players leave out the last color in turn
+    /// order.
+    pub fn plays(self, players: RangedU8<2, 4>) -> bool {
+        match players.get() {
+            2 => matches!(self, Self::Red | Self::Green),
+            3 => self != Self::Green,
+            _ => true,
+        }
+    }
+
+    /// The color's name, as shown on screen.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Red => "RED",
+            Self::Blue => "BLUE",
+            Self::Yellow => "YELLOW",
+            Self::Green => "GREEN",
+        }
+    }
+
+    /// The background palette bank that nodes aligned with this color are drawn with.
+    ///
+    /// Bank 0 is reserved for unaligned nodes.
+    pub const fn palette_bank(self) -> RangedU8<0, 15> {
+        match self {
+            Self::Red => RangedU8::new_static::<1>(),
+            Self::Blue => RangedU8::new_static::<2>(),
+            Self::Yellow => RangedU8::new_static::<3>(),
+            Self::Green => RangedU8::new_static::<4>(),
+        }
+    }
+}
+
+impl From<Color> for u8 {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Red => 0,
+            Color::Blue => 1,
+            Color::Yellow => 2,
+            Color::Green => 3,
+        }
+    }
+}
+
+/// A byte that does not correspond to any `Color`.
+#[derive(Debug, Eq, PartialEq)]
+pub struct InvalidColor(pub u8);
+
+impl TryFrom<u8> for Color {
+    type Error = InvalidColor;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            0 => Ok(Self::Red),
+            1 => Ok(Self::Blue),
+            2 => Ok(Self::Yellow),
+            3 => Ok(Self::Green),
+            _ => Err(InvalidColor(byte)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ColorCounts {
     red: Option<NonZeroU16>,
     blue: Option<NonZeroU16>,
@@ -32,6 +128,49 @@ pub struct ColorCounts {
 }
 
 impl ColorCounts {
+    /// The number of nodes owned by `color`, or `None` if it owns none.
+    pub const fn get(&self, color: Color) -> Option<NonZeroU16> {
+        match color {
+            Color::Red => self.red,
+            Color::Blue => self.blue,
+            Color::Yellow => self.yellow,
+            Color::Green => self.green,
+        }
+    }
+
+    /// The number of nodes owned by `color`, which is 0 once it has been eliminated.
+    pub const fn for_color(&self, color: Color) -> u16 {
+        match self.get(color) {
+            Some(count) => count.get(),
+            None => 0,
+        }
+    }
+
+    /// The number of nodes owned by any color.
+    #[cfg(test)]
+    pub const fn total(&self) -> u16 {
+        self.for_color(Color::Red)
+            + self.for_color(Color::Blue)
+            + self.for_color(Color::Yellow)
+            + self.for_color(Color::Green)
+    }
+
+    /// The only color owning any nodes, if there is exactly one.
+    pub const fn winner(&self) -> Option<Color> {
+        match (
+            self.red.is_some(),
+            self.blue.is_some(),
+            self.yellow.is_some(),
+            self.green.is_some(),
+        ) {
+            (true, false, false, false) => Some(Color::Red),
+            (false, true, false, false) => Some(Color::Blue),
+            (false, false, true, false) => Some(Color::Yellow),
+            (false, false, false, true) => Some(Color::Green),
+            _ => None,
+        }
+    }
+
     fn change(&mut self, increment: Color, decrement: Option<Color>) {
         match increment {
             Color::Red => match self.red.as_mut() {
@@ -71,16 +210,187 @@ impl ColorCounts {
     }
 }
 
+/// How a single color is doing.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Standing {
+    pub color: Color,
+    /// The number of nodes owned by the color.
+    pub count: u16,
+    pub alive: bool,
+}
+
+/// Every color's standing, from most nodes owned to least.
+///
+/// Colors owning the same number of nodes are ordered by turn order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Standings([Standing; 4]);
+
+impl Standings {
+    pub fn iter(&self) -> slice::Iter<'_, Standing> {
+        self.0.iter()
+    }
+}
+
+/// How a game came to an end.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Ending {
+    /// Every other color was eliminated.
+    Winner(Color),
+    /// No remaining color can ever capture another node.
+    ///
+    /// The color owning the most nodes is the leader, unless the lead is shared.
+    Stalemate { leader: Option<Color> },
+    /// The game's turn limit was reached.
+    ///
+    /// The color owning the most nodes wins, unless the lead is shared, in which case the game is
+    /// drawn.
+    TurnLimit { leader: Option<Color> },
+}
+
+/// What a turn would do if it were taken.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TurnPreview {
+    /// The color taking the turn.
+    color: Color,
+    before: ColorCounts,
+    after: ColorCounts,
+}
+
+impl TurnPreview {
+    /// The node counts after the turn.
+    pub fn counts(&self) -> &ColorCounts {
+        &self.after
+    }
+
+    /// The number of nodes the turn would capture from `color`.
+    ///
+    /// `None` gives the number of unaligned nodes captured.
+    #[cfg(test)]
+    pub fn captured_from(&self, color: Option<Color>) -> u16 {
+        match color {
+            Some(color) if color == self.color => 0,
+            Some(color) => self.before.for_color(color) - self.after.for_color(color),
+            None => {
+                self.captured()
+                    - Color::ALL
+                        .into_iter()
+                        .map(|color| self.captured_from(Some(color)))
+                        .sum::<u16>()
+            }
+        }
+    }
+
+    /// The total number of nodes the turn would capture.
+    pub fn captured(&self) -> u16 {
+        self.after.for_color(self.color) - self.before.for_color(self.color)
+    }
+
+    /// Whether the turn would eliminate every other color.
+    pub fn wins(&self) -> bool {
+        Color::ALL
+            .into_iter()
+            .all(|color| color == self.color || self.after.get(color).is_none())
+    }
+}
+
+/// What a turn did once it was taken.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TurnOutcome {
+    ending: Option<Ending>,
+    /// The colors the turn eliminated, in turn order, followed by `None`s.
+    eliminated: [Option<Color>; 3],
+    captured: u16,
+}
+
+impl TurnOutcome {
+    /// How the game ended, if the turn ended it.
+    pub fn ending(&self) -> Option<Ending> {
+        self.ending
+    }
+
+    /// Every color that had nodes before the turn and has none after it, in turn order.
+    pub fn eliminated(&self) -> impl Iterator<Item = Color> + '_ {
+        self.eliminated.iter().flatten().copied()
+    }
+
+    /// The number of nodes the turn captured, including unaligned nodes.
+    pub fn captured(&self) -> u16 {
+        self.captured
+    }
+}
+
+/// Cached state that no longer matches the grid it was derived from.
+#[derive(Debug, Eq, PartialEq)]
+enum Mismatch {
+    ColorCounts {
+        cached: ColorCounts,
+        actual: ColorCounts,
+    },
+}
+
 /// The game state.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Game {
     /// Indicates whose turn it is.
     turn_color: Color,
 
+    /// The number of turns executed so far. Passed turns aren't counted.
+    turn_number: u32,
+
     // These counts must invariantly match with the number of colors in `self.grid`.
     color_counts: ColorCounts,
 
     grid: Grid,
+
+    rules: Rules,
+
+    /// The number of turns after which the game ends, if it is limited.
+    max_turns: Option<NonZeroU16>,
+
+    /// The colors eliminated so far, in the order they were eliminated.
+    eliminations: [Option<Color>; 4],
+
+    /// The captures made during the most recent turn.
+    events: TurnEvents,
+
+    /// The state before the most recent turn, if it can be undone.
+    prev_state: Option<Snapshot>,
+
+    /// The position rotated by the most recent turn, and every position whose color it changed.
+    last_move: Option<(Position, PositionSet)>,
+
+    /// Every turn taken so far.
+    history: History,
+}
+
+/// A game's state between turns, encoded a byte per node so it can be kept around cheaply.
+#[derive(Clone, Debug)]
+struct Snapshot {
+    nodes: [u8; 256],
+    size: Size,
+    turn_color: Color,
+    turn_number: u32,
+    eliminations: [Option<Color>; 4],
+}
+
+impl Snapshot {
+    fn new(game: &Game) -> Self {
+        let mut nodes = [0; 256];
+        game.grid.to_bytes(&mut nodes);
+        Self {
+            nodes,
+            size: game.grid.size(),
+            turn_color: game.turn_color,
+            turn_number: game.turn_number,
+            eliminations: game.eliminations,
+        }
+    }
+
+    fn grid(&self) -> Grid {
+        Grid::from_bytes(&self.nodes)
+            .expect("snapshot was not encoded from a grid")
+            .with_size(self.size)
+    }
 }
 
 impl Game {
@@ -89,76 +399,439 @@ impl Game {
             turn_color: Color::Red,
 
             grid: Grid::new([[Node::Empty; 16]; 16]),
+
+            rules: Rules::default(),
+
+            players: RangedU8::new_static::<4>(),
+
+            max_turns: None,
+        }
+    }
+
+    /// Start building a game on the board `code` identifies, under its rules and with its players,
+    /// with `first` taking the first turn.
+    ///
+    /// Anything not stored in a code, such as a turn limit, can still be set on the builder.
+    pub fn from_code(code: BoardCode, first: Color) -> Builder {
+        Game::builder()
+            .grid(code.grid())
+            .rules(code.rules)
+            .players(code.players)
+            .turn_color(first)
+    }
+
+    /// Rebuild a game played on the board described by `code`, where `first` took the first turn,
+    /// by taking each of `turns` in order.
+    ///
+    /// Passed turns are not included in `turns`. Whenever the current player has no legal move,
+    /// their turn is passed before the next turn is taken.
+    pub fn replay(
+        code: BoardCode,
+        first: Color,
+        turns: impl IntoIterator<Item = Turn>,
+    ) -> Result<Game, replay::Error> {
+        let mut game = Game::from_code(code, first).build();
+        for (index, turn) in turns.into_iter().enumerate() {
+            // Only a color that still owns nodes can be passed to, so this always stops.
+            while !game.has_legal_move(game.turn_color) && game.increment_turn() {}
+            game.execute_turn(turn)
+                .map_err(|error| replay::Error { index, error })?;
         }
+        Ok(game)
     }
 
     pub fn is_eliminated(&self, color: Color) -> bool {
-        match color {
-            Color::Red => self.color_counts.red.is_none(),
-            Color::Blue => self.color_counts.blue.is_none(),
-            Color::Yellow => self.color_counts.yellow.is_none(),
-            Color::Green => self.color_counts.green.is_none(),
+        self.color_counts.get(color).is_none()
+    }
+
+    /// The number of nodes owned by `color`, which is 0 once it has been eliminated.
+    pub fn count(&self, color: Color) -> u16 {
+        self.color_counts.for_color(color)
+    }
+
+    /// The number of nodes owned by every color, in turn order.
+    pub fn counts(&self) -> [(Color, u16); 4] {
+        Color::ALL.map(|color| (color, self.count(color)))
+    }
+
+    pub fn standings(&self) -> Standings {
+        let mut standings = Color::ALL.map(|color| Standing {
+            color,
+            count: self.count(color),
+            alive: !self.is_eliminated(color),
+        });
+        standings
+            .sort_unstable_by_key(|standing| (Reverse(standing.count), u8::from(standing.color)));
+        Standings(standings)
+    }
+
+    /// The colors eliminated so far, from first to last.
+    pub fn eliminations(&self) -> impl DoubleEndedIterator<Item = Color> + '_ {
+        self.eliminations.iter().flatten().copied()
+    }
+
+    /// Every color playing, from first place to last.
+    ///
+    /// Colors still in the game come first, ordered as in `standings()`, so once there is a winner
+    /// it is first. Eliminated colors follow, with those eliminated later placing higher. Colors
+    /// eliminated by the same turn place in reverse turn order. Colors that never played, as in a
+    /// game with fewer than four players, are left out, leaving `None` at the end.
+    pub fn rankings(&self) -> [Option<Color>; 4] {
+        let standings = self.standings();
+        let remaining = standings
+            .iter()
+            .filter(|standing| standing.alive)
+            .map(|standing| standing.color);
+        let mut rankings = [None; 4];
+        for (slot, color) in rankings
+            .iter_mut()
+            .zip(remaining.chain(self.eliminations().rev()))
+        {
+            *slot = Some(color);
         }
+        rankings
     }
 
-    /// Fill in the current color beginning at the given position.
-    fn fill(&mut self, position: Position, visited: &mut [[bool; 16]; 16]) {
-        // Ensure this is a valid position.
-        let node = match self.grid.get_mut(position) {
-            Some(node) => node,
-            None => return,
+    /// The number of colors that have not been eliminated.
+    pub fn players_remaining(&self) -> u8 {
+        Color::ALL
+            .into_iter()
+            .filter(|&color| !self.is_eliminated(color))
+            .count() as u8
+    }
+
+    /// Whether at most one color is left.
+    ///
+    /// A game ending in a stalemate can still have several colors left.
+    pub fn is_over(&self) -> bool {
+        self.players_remaining() <= 1
+    }
+
+    /// A fingerprint of the game state, for telling whether two games have diverged.
+    ///
+    /// This hashes the board size, every node's encoding, the turn color, and every color's count
+    /// with 64-bit FNV-1a. It only depends on the state itself, so it is the same on every build.
+    pub fn state_hash(&self) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let mut write = |byte: u8| {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
         };
+        let size = self.grid.size();
+        write(size.width);
+        write(size.height);
+        for node in self.grid.iter().flatten() {
+            write(node.encode());
+        }
+        write(u8::from(self.turn_color));
+        for color in Color::ALL {
+            for byte in self.count(color).to_le_bytes() {
+                write(byte);
+            }
+        }
+        hash
+    }
 
-        if visited[position.y as usize][position.x as usize] {
-            // We have already visited this position.
-            return;
+    /// Recompute all cached state from the grid and compare it to the cache.
+    fn verify(&self) -> Result<(), Mismatch> {
+        let actual = self.grid.color_counts();
+        if actual != self.color_counts {
+            return Err(Mismatch::ColorCounts {
+                cached: self.color_counts.clone(),
+                actual,
+            });
         }
-        visited[position.y as usize][position.x as usize] = true;
-        let old_color = node.color();
-        if node.set_color(self.turn_color) {
-            self.color_counts.change(self.turn_color, old_color);
-        } else if !node.is_color(self.turn_color) {
-            // This means it's a wall.
-            return;
+        Ok(())
+    }
+
+    /// Assert that all cached state matches the grid.
+    ///
+    /// On a mismatch, the last turn and the grid are logged before panicking so the problem can be
+    /// reproduced. This is called after every turn in debug builds.
+    pub fn debug_verify(&self) {
+        if let Err(mismatch) = self.verify() {
+            log::error!("{:?}", mismatch);
+            if let Some((position, _)) = &self.last_move {
+                log::error!("last turn: {:?}", position);
+            }
+            // Each row is logged separately to stay within the emulator's log message limit.
+            for y in 0..16 {
+                log::error!(
+                    "{}",
+                    str::from_utf8(&self.grid.ascii_row(y)).unwrap_or("<invalid row>")
+                );
+            }
+            panic!("cached game state does not match the grid");
         }
+    }
 
-        // Deal with the node this node points to.
-        if !node.is_hidden() {
-            if let Some(direction) = node.direction() {
-                if let Some(new_position) = position.r#move(direction) {
-                    self.fill(new_position, visited);
-                }
-            } else if node.all_directions() {
-                for direction in [
+    /// Whether `color` rotating the node at `position` would change the state of the game.
+    ///
+    /// Rotating a node with a direction always changes the game. A node without a direction, such
+    /// as an `AllDirection` node, only changes the game if filling from it would capture a node.
+    pub fn is_legal_move(&self, color: Color, position: Position) -> bool {
+        let node = match self.grid.get(position) {
+            Some(node) => node,
+            None => return false,
+        };
+        if !node.is_color(color) {
+            return false;
+        }
+        if node.direction().is_some() {
+            return true;
+        }
+
+        // Fill on a scratch copy of the game to see whether anything would be captured.
+        let mut scratch = Game {
+            turn_color: color,
+            ..self.scratch()
+        };
+        scratch.fill(position, &mut PositionSet::new());
+        scratch.color_counts.get(color) != self.color_counts.get(color)
+    }
+
+    /// Every turn `execute_turn()` would accept from the current player.
+    ///
+    /// This includes turns that would not capture anything, such as turning an `AllDirection`
+    /// node. Use `is_legal_move()` to filter those out. If the rules allow rotating a node several
+    /// times, each position is yielded once for every number of rotations, fewest first.
+    pub fn valid_turns(&self) -> impl Iterator<Item = Turn> + Clone + '_ {
+        let max_times = if self.rules.multi_rotation { 3 } else { 1 };
+        self.grid
+            .positions_of_color(self.turn_color)
+            .flat_map(move |rotate| {
+                (1..=max_times).map(move |times| Turn {
+                    rotate,
+                    times: RangedU8::new(times).unwrap(),
+                })
+            })
+    }
+
+    /// Whether `color` has any move that would change the state of the game.
+    ///
+    /// A player can own nodes without having a legal move, such as when they only own
+    /// `AllDirection` nodes that are already surrounded by their own color.
+    pub fn has_legal_move(&self, color: Color) -> bool {
+        (0..16).any(|y| (0..16).any(|x| self.is_legal_move(color, Position { x, y })))
+    }
+
+    /// Whether no remaining color will ever be able to capture another node.
+    ///
+    /// Nodes only change hands through captures, and a node that is owned is never hidden and can
+    /// be turned to point at any of its neighbors. A color can therefore capture eventually exactly
+    /// when one of its nodes neighbors a node that can be captured but isn't already its own. A
+    /// teleporter's twin counts as one of its neighbors. When `Rules::super_arrow_claims` is set,
+    /// every node on the beam of an owned super arrow counts as well, in any direction it could be
+    /// turned to.
+    pub fn is_stalemate(&self) -> bool {
+        (0..16)
+            .flat_map(|y| (0..16).map(move |x| Position { x, y }))
+            .all(|position| {
+                let Some(node) = self.grid.get(position) else {
+                    return true;
+                };
+                let Some(color) = node.color() else {
+                    return true;
+                };
+                let claims_beam =
+                    self.rules.super_arrow_claims && matches!(node, Node::SuperArrow { .. });
+                let directions = [
                     Direction::Left,
                     Direction::Up,
                     Direction::Right,
                     Direction::Down,
-                ] {
-                    if let Some(new_position) = position.r#move(direction) {
-                        self.fill(new_position, visited);
-                    }
-                }
+                ];
+                directions
+                    .into_iter()
+                    .filter_map(|direction| {
+                        self.rules.neighbor(position, direction, self.grid.size())
+                    })
+                    .chain(self.grid.twin(position))
+                    .chain(
+                        directions
+                            .into_iter()
+                            .filter(|_| claims_beam)
+                            .flat_map(|direction| self.beam(position, direction)),
+                    )
+                    .filter_map(|neighbor| self.grid.get(neighbor))
+                    .all(|neighbor| {
+                        matches!(neighbor, Node::Empty | Node::Wall) || neighbor.is_color(color)
+                    })
+            })
+    }
+
+    /// The color owning the most nodes, if no other color owns as many.
+    pub fn leader(&self) -> Option<Color> {
+        let [first, second, ..] = self.standings().0;
+        (first.count > second.count).then_some(first.color)
+    }
+
+    /// Whether a fill reaching `position` would continue on to the neighbor in `direction`.
+    fn fill_connects(&self, position: Position, direction: Direction) -> bool {
+        let (Some(node), Some(neighbor)) = (
+            self.grid.get(position),
+            self.rules
+                .neighbor(position, direction, self.grid.size())
+                .and_then(|neighbor| self.grid.get(neighbor)),
+        ) else {
+            return false;
+        };
+        let points_at = |node: &Node, direction| {
+            !node.is_hidden() && (node.direction() == Some(direction) || node.all_directions())
+        };
+        points_at(node, direction) || points_at(neighbor, direction.opposite())
+    }
+
+    /// The nodes owned by `color` that are connected to a node `color` could capture.
+    ///
+    /// A node is capturable if it is a node that can have a color but is not `color`. Connected
+    /// means a fill reaching one of the nodes would spread to the other.
+    pub fn frontier(&self, color: Color) -> impl Iterator<Item = Position> + '_ {
+        (0..16)
+            .flat_map(|y| (0..16).map(move |x| Position { x, y }))
+            .filter(move |&position| {
+                self.grid
+                    .get(position)
+                    .is_some_and(|node| node.is_color(color))
+                    && [
+                        Direction::Left,
+                        Direction::Up,
+                        Direction::Right,
+                        Direction::Down,
+                    ]
+                    .into_iter()
+                    .any(|direction| {
+                        self.rules
+                            .neighbor(position, direction, self.grid.size())
+                            .and_then(|neighbor| self.grid.get(neighbor))
+                            .is_some_and(|neighbor| {
+                                matches!(
+                                    neighbor,
+                                    Node::Arrow { .. }
+                                        | Node::ReverseArrow { .. }
+                                        | Node::AllDirection { .. }
+                                        | Node::SuperArrow { .. }
+                                        | Node::Teleporter { .. }
+                                ) && !neighbor.is_color(color)
+                            })
+                            && self.fill_connects(position, direction)
+                    })
+                    || self
+                        .grid
+                        .twin(position)
+                        .and_then(|twin| self.grid.get(twin))
+                        .is_some_and(|twin| !twin.is_color(color))
+            })
+    }
+
+    /// Skip the current player's turn.
+    pub fn pass_turn(&mut self) {
+        self.prev_state = None;
+        self.increment_turn();
+    }
+
+    /// Claim `position` for the current color as a fill reaches it.
+    ///
+    /// Returns whether the fill continues on from `position`. It doesn't if the position has
+    /// already been visited, or if the node there can't have a color.
+    fn enter(&mut self, position: Position, visited: &mut PositionSet) -> bool {
+        // Ensure this is a valid position.
+        if self.grid.get(position).is_none() {
+            return false;
+        }
+
+        if !visited.insert(position) {
+            // We have already visited this position.
+            return false;
+        }
+        self.claim(position)
+    }
+
+    /// Set the node at `position` to the current color, recording the capture.
+    ///
+    /// Returns whether the node is now the current color. It isn't if the node can't have a color.
+    fn claim(&mut self, position: Position) -> bool {
+        let node = &mut self.grid[position];
+        let old_color = node.color();
+        if node.set_color(self.turn_color) {
+            self.color_counts.change(self.turn_color, old_color);
+            self.events.push(Capture {
+                position,
+                previous: old_color,
+            });
+            if let Some((_, changed)) = &mut self.last_move {
+                changed.insert(position);
             }
+            true
+        } else {
+            // Otherwise this is a wall.
+            node.is_color(self.turn_color)
         }
+    }
 
-        // Deal with the nodes pointing to this node.
-        for direction in [
+    /// Fill in the current color beginning at the given position.
+    ///
+    /// Each node reached spreads the fill first to the nodes it points to, and then to the nodes
+    /// pointing to it, trying directions in the order left, up, right, down. A teleporter then
+    /// spreads the fill to its twin. Each neighbor is filled completely before moving on to the
+    /// next, so captures are made depth first.
+    fn fill(&mut self, position: Position, visited: &mut PositionSet) {
+        const DIRECTIONS: [Direction; 4] = [
             Direction::Left,
             Direction::Up,
             Direction::Right,
             Direction::Down,
-        ] {
-            if let Some(new_position) = position.r#move(direction) {
-                if let Some(new_node) = self.grid.get(new_position) {
-                    if !new_node.is_hidden() {
-                        if new_node.direction() == Some(direction.opposite())
-                            || new_node.all_directions()
-                        {
-                            self.fill(new_position, visited);
-                        }
+        ];
+
+        // The nodes the fill is currently spreading from, each with the next of its neighbors to
+        // try. The first four steps are the nodes it points to, the next four are the nodes
+        // pointing to it, and the last is a teleporter's twin. A node is only entered once, so
+        // every node fits at once.
+        let mut stack = [(Position { x: 0, y: 0 }, 0u8); 256];
+        let mut len = 0;
+        if self.enter(position, visited) {
+            stack[0] = (position, 0);
+            len = 1;
+        }
+
+        while len > 0 {
+            let (position, step) = &mut stack[len - 1];
+            let position = *position;
+            if *step as usize == DIRECTIONS.len() * 2 {
+                len -= 1;
+                if let Some(twin) = self.grid.twin(position) {
+                    if self.enter(twin, visited) {
+                        stack[len] = (twin, 0);
+                        len += 1;
                     }
                 }
+                continue;
+            }
+            let direction = DIRECTIONS[*step as usize % DIRECTIONS.len()];
+            let outgoing = (*step as usize) < DIRECTIONS.len();
+            *step += 1;
+
+            let Some(new_position) = self.rules.neighbor(position, direction, self.grid.size())
+            else {
+                continue;
+            };
+            let connected = if outgoing {
+                // Deal with the node this node points to.
+                self.grid.get(position).is_some_and(|node| {
+                    !node.is_hidden()
+                        && (node.direction() == Some(direction) || node.all_directions())
+                })
+            } else {
+                // Deal with the nodes pointing to this node.
+                self.grid.get(new_position).is_some_and(|new_node| {
+                    !new_node.is_hidden()
+                        && (new_node.direction() == Some(direction.opposite())
+                            || new_node.all_directions())
+                })
+            };
+            if connected && self.enter(new_position, visited) {
+                stack[len] = (new_position, 0);
+                len += 1;
             }
         }
     }
@@ -167,119 +840,305 @@ impl Game {
     ///
     /// Returns false if the turn color was not changed.
     fn increment_turn(&mut self) -> bool {
-        self.turn_color = match self.turn_color {
-            Color::Red => {
-                if self.color_counts.blue.is_some() {
-                    Color::Blue
-                } else if self.color_counts.yellow.is_some() {
-                    Color::Yellow
-                } else if self.color_counts.green.is_some() {
-                    Color::Green
-                } else {
-                    return false;
-                }
-            }
-            Color::Blue => {
-                if self.color_counts.yellow.is_some() {
-                    Color::Yellow
-                } else if self.color_counts.green.is_some() {
-                    Color::Green
-                } else if self.color_counts.red.is_some() {
-                    Color::Red
-                } else {
-                    return false;
-                }
-            }
-            Color::Yellow => {
-                if self.color_counts.green.is_some() {
-                    Color::Green
-                } else if self.color_counts.red.is_some() {
-                    Color::Red
-                } else if self.color_counts.blue.is_some() {
-                    Color::Blue
-                } else {
-                    return false;
-                }
-            }
-            Color::Green => {
-                if self.color_counts.red.is_some() {
-                    Color::Red
-                } else if self.color_counts.blue.is_some() {
-                    Color::Blue
-                } else if self.color_counts.yellow.is_some() {
-                    Color::Yellow
-                } else {
-                    return false;
-                }
+        let mut color = self.turn_color;
+        // Every other color is checked once, in turn order.
+        for _ in 1..Color::ALL.len() {
+            color = color.next();
+            if self.color_counts.get(color).is_some() {
+                self.turn_color = color;
+                return true;
             }
-        };
-        true
+        }
+        false
     }
 
-    /// Execute turn for the current player.
-    pub fn execute_turn(&mut self, turn: Turn) -> Result<Option<Color>, turn::Error> {
-        let node = self
-            .grid
-            .get_mut(turn.rotate)
-            .ok_or(turn::Error::InvalidRotationPosition)?;
+    /// Execute turn for the current player, remembering the state before it so that it can be
+    /// undone with `undo()`.
+    ///
+    /// Returns what the turn did, including how the game ended if this turn ended it. A rejected
+    /// turn leaves the game untouched, including any turn that could already be undone.
+    pub fn execute_turn(&mut self, turn: Turn) -> Result<TurnOutcome, turn::Error> {
+        self.validate(&turn)?;
+
+        let prev_state = Snapshot::new(self);
+        let color = self.turn_color;
+        let before = self.color_counts.clone();
+        let ending = self.execute_turn_trusted(turn);
+        // A turn never loses its own color any nodes, so at most the other three are eliminated.
+        let mut eliminated = [None; 3];
+        for (slot, color) in
+            eliminated
+                .iter_mut()
+                .zip(Color::ALL.into_iter().filter(|&color| {
+                    before.get(color).is_some() && self.color_counts.get(color).is_none()
+                }))
+        {
+            *slot = Some(color);
+        }
+        self.prev_state = Some(prev_state);
+        Ok(TurnOutcome {
+            ending,
+            eliminated,
+            captured: self.count(color) - before.for_color(color),
+        })
+    }
+
+    /// Check that the current player may take `turn`.
+    fn validate(&self, turn: &Turn) -> Result<(), turn::Error> {
+        let node = self.grid.get(turn.rotate).ok_or(turn::Error::OutOfBounds)?;
+        if matches!(node, Node::Empty | Node::Wall) {
+            return Err(turn::Error::NotRotatable);
+        }
         if !node.is_color(self.turn_color) {
-            return Err(turn::Error::InvalidRotationPosition);
+            return Err(turn::Error::NotOwned {
+                actual: node.color(),
+            });
         }
+        if turn.times != Turn::ONCE && !self.rules.multi_rotation {
+            return Err(turn::Error::MultipleRotations);
+        }
+        Ok(())
+    }
 
-        node.rotate();
+    /// Rotate the node at `position` `times` times, sweep its beam if it is a super arrow, and fill
+    /// from it.
+    ///
+    /// With `Rules::super_arrow_claims`, the beam also claims every node it passes over before the
+    /// fill.
+    fn play(&mut self, position: Position, times: RangedU8<1, 3>) {
+        let node = &mut self.grid[position];
+        debug_assert!(node.is_color(self.turn_color));
 
-        if let Node::SuperArrow { direction, .. } = node {
-            let direction = *direction;
-            let mut position = turn.rotate;
-            while let Some(new_pos) = position.r#move(direction) {
-                let node = self.grid.get_mut(new_pos).unwrap();
-                if node.is_wall() {
-                    break;
+        for _ in 0..times.get() {
+            node.rotate();
+        }
+
+        if let Node::SuperArrow { direction, .. } = *node {
+            let mut beam = position;
+            while let Some(new_pos) = self.beam_step(beam, direction) {
+                self.grid[new_pos].set_direction(direction);
+                if self.rules.super_arrow_claims {
+                    self.claim(new_pos);
                 }
-                node.set_direction(direction);
-                position = new_pos;
+                beam = new_pos;
             }
         }
 
-        self.fill(turn.rotate, &mut [[false; 16]; 16]);
-
-        self.increment_turn();
+        self.fill(position, &mut PositionSet::new());
+    }
 
-        match (
-            self.color_counts.red.is_some(),
-            self.color_counts.blue.is_some(),
-            self.color_counts.yellow.is_some(),
-            self.color_counts.green.is_some(),
-        ) {
-            (true, false, false, false) => Ok(Some(Color::Red)),
-            (false, true, false, false) => Ok(Some(Color::Blue)),
-            (false, false, true, false) => Ok(Some(Color::Yellow)),
-            (false, false, false, true) => Ok(Some(Color::Green)),
-            _ => Ok(None),
-        }
+    /// The next position a super arrow's beam passes over after `position`, if it goes on.
+    ///
+    /// The beam stops at walls and at the edge of the board, even when it wraps around, since it
+    /// would otherwise come back around to the super arrow itself.
+    fn beam_step(&self, position: Position, direction: Direction) -> Option<Position> {
+        position
+            .r#move(direction)
+            .filter(|&new_pos| self.grid.get(new_pos).is_some_and(|node| !node.is_wall()))
     }
 
-    pub fn grid(&self) -> &Grid {
-        &self.grid
+    /// The positions the beam of a super arrow at `position` passes over when it points in
+    /// `direction`.
+    pub fn beam(
+        &self,
+        position: Position,
+        direction: Direction,
+    ) -> impl Iterator<Item = Position> + '_ {
+        iter::successors(self.beam_step(position, direction), move |&beam| {
+            self.beam_step(beam, direction)
+        })
     }
 
-    pub fn turn_color(&self) -> Color {
-        self.turn_color
+    /// A copy of the game to try moves on, leaving out the last turn's events, undo state, and
+    /// history.
+    ///
+    /// This is much cheaper than `clone()`, so it is what the AI searches with.
+    pub fn scratch(&self) -> Game {
+        Game {
+            turn_color: self.turn_color,
+            turn_number: self.turn_number,
+            color_counts: self.color_counts.clone(),
+            grid: self.grid.clone(),
+            rules: self.rules,
+            max_turns: self.max_turns,
+            eliminations: self.eliminations,
+            events: TurnEvents::new(),
+            prev_state: None,
+            last_move: None,
+            history: History::new(),
+        }
     }
 
-    pub fn weight(&self, position: Position) -> u8 {
-        self.grid.weight(position, &mut [[false; 16]; 16])
+    /// What `turn` would do, without taking it.
+    ///
+    /// This plays the turn on a copy of the grid, which costs about as much as taking the turn
+    /// itself. The copy leaves out the last turn's events and undo state, so it is cheaper than
+    /// cloning the whole game.
+    pub fn preview_turn(&self, turn: Turn) -> Result<TurnPreview, turn::Error> {
+        self.validate(&turn)?;
+
+        let mut scratch = self.scratch();
+        scratch.play(turn.rotate, turn.times);
+        Ok(TurnPreview {
+            color: self.turn_color,
+            before: self.color_counts.clone(),
+            after: scratch.color_counts,
+        })
     }
-}
 
-/// Helper for building game state.
-///
-/// Default values are set when this is constructed. They can be changed if desired.
-#[derive(Debug)]
+    /// Execute a turn for the current player that is already known to be valid.
+    ///
+    /// This is meant for turns that were validated when they were first played, such as turns
+    /// played back from a replay. Executing an invalid turn will panic.
+    ///
+    /// Returns how the game ended, if this turn ended it. Unlike `execute_turn()`, nothing else
+    /// about the turn is worked out and the turn can't be undone, keeping this cheap for searching
+    /// ahead.
+    pub fn execute_turn_trusted(&mut self, turn: Turn) -> Option<Ending> {
+        self.prev_state = None;
+        self.events.clear();
+        self.last_move = Some((turn.rotate, PositionSet::new()));
+        self.history.push(history::Entry {
+            color: self.turn_color,
+            position: turn.rotate,
+            times: turn.times,
+        });
+        let before = self.color_counts.clone();
+        self.play(turn.rotate, turn.times);
+        self.turn_number += 1;
+
+        // Colors eliminated by the same turn are recorded in turn order.
+        for color in Color::ALL {
+            if before.get(color).is_some() && self.is_eliminated(color) {
+                let slot = self
+                    .eliminations
+                    .iter_mut()
+                    .find(|slot| slot.is_none())
+                    .expect("a color was eliminated twice");
+                *slot = Some(color);
+            }
+        }
+
+        self.increment_turn();
+
+        #[cfg(debug_assertions)]
+        self.debug_verify();
+
+        if let Some(winner) = self.color_counts.winner() {
+            return Some(Ending::Winner(winner));
+        }
+        if self.is_turn_limit_reached() {
+            return Some(Ending::TurnLimit {
+                leader: self.leader(),
+            });
+        }
+        self.is_stalemate().then(|| Ending::Stalemate {
+            leader: self.leader(),
+        })
+    }
+
+    /// Restore the state from before the last turn executed with `execute_turn()`.
+    ///
+    /// Returns `false`, leaving the game untouched, if there is no such turn or another turn has
+    /// been taken or passed since.
+    pub fn undo(&mut self) -> bool {
+        let Some(snapshot) = self.prev_state.take() else {
+            return false;
+        };
+        self.grid = snapshot.grid();
+        self.color_counts = self.grid.color_counts();
+        self.turn_color = snapshot.turn_color;
+        self.turn_number = snapshot.turn_number;
+        self.eliminations = snapshot.eliminations;
+        self.events.clear();
+        self.last_move = None;
+        self.history.pop();
+
+        #[cfg(debug_assertions)]
+        self.debug_verify();
+
+        true
+    }
+
+    /// Whether `undo()` would restore an earlier state.
+    pub fn can_undo(&self) -> bool {
+        self.prev_state.is_some()
+    }
+
+    /// The captures made during the most recent turn.
+    pub fn turn_events(&self) -> &TurnEvents {
+        &self.events
+    }
+
+    /// The position rotated by the most recent turn, along with every position whose color the
+    /// turn changed.
+    ///
+    /// Unlike `turn_events()`, the positions are never truncated. Returns `None` before the first
+    /// turn and after an undo.
+    pub fn last_move(&self) -> Option<(Position, &PositionSet)> {
+        self.last_move
+            .as_ref()
+            .map(|(position, changed)| (*position, changed))
+    }
+
+    /// Every turn taken so far.
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn turn_color(&self) -> Color {
+        self.turn_color
+    }
+
+    /// The number of turns executed so far, not counting passed turns.
+    pub fn turn_number(&self) -> u32 {
+        self.turn_number
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// The number of turns left before the turn limit is reached, if the game is limited.
+    pub fn turns_remaining(&self) -> Option<u32> {
+        self.max_turns
+            .map(|max_turns| (max_turns.get() as u32).saturating_sub(self.turn_number()))
+    }
+
+    /// Whether the game has ended by reaching its turn limit.
+    ///
+    /// Once it has, the color with the most nodes is the winner. See `Ending::TurnLimit`.
+    pub fn is_turn_limit_reached(&self) -> bool {
+        self.turns_remaining() == Some(0)
+    }
+
+    /// The number of nodes a fill starting at `position` would follow.
+    ///
+    /// See `Grid::weight()`.
+    pub fn weight(&self, position: Position) -> u16 {
+        self.grid
+            .weight(position, self.rules, &mut PositionSet::new())
+    }
+}
+
+/// Helper for building game state.
+///
+/// Default values are set when this is constructed. They can be changed if desired.
+#[derive(Debug)]
 pub struct Builder {
     turn_color: Color,
 
     grid: Grid,
+
+    rules: Rules,
+
+    players: RangedU8<2, 4>,
+
+    max_turns: Option<NonZeroU16>,
 }
 
 impl Builder {
@@ -293,15 +1152,2973 @@ impl Builder {
         self
     }
 
-    pub fn build(self) -> Game {
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Set how many players the game has.
+    ///
+    /// The nodes of colors that don't play are left unaligned, so that those colors start out
+    /// eliminated. See `Color::plays()` for which colors play.
+    pub fn players(mut self, players: RangedU8<2, 4>) -> Self {
+        self.players = players;
+        self
+    }
+
+    /// Set the number of turns after which the game ends, or `None` for no limit.
+    ///
+    /// Passed turns don't count toward the limit.
+    pub fn max_turns(mut self, max_turns: Option<NonZeroU16>) -> Self {
+        self.max_turns = max_turns;
+        self
+    }
+
+    /// # Panics
+    /// Panics if the turn color doesn't play.
+    pub fn build(mut self) -> Game {
+        assert!(
+            self.turn_color.plays(self.players),
+            "{:?} does not play in a {} player game",
+            self.turn_color,
+            self.players
+        );
+        for (_, node) in self.grid.iter_positions_mut() {
+            if node.color().is_some_and(|color| !color.plays(self.players)) {
+                node.clear_color();
+            }
+        }
         let color_counts = self.grid.color_counts();
 
         Game {
             turn_color: self.turn_color,
 
+            turn_number: 0,
+
             color_counts,
 
             grid: self.grid,
+
+            rules: self.rules,
+
+            max_turns: self.max_turns,
+
+            eliminations: [None; 4],
+
+            events: TurnEvents::new(),
+
+            prev_state: None,
+            last_move: None,
+            history: History::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        history, replay, turn, BoardCode, Color, Direction, Ending, Game, Grid, History,
+        InvalidColor, Mismatch, Node, Position, PositionSet, Recording, Rules, Size, Standing,
+        Turn, TurnEvents,
+    };
+    use crate::random::Pcg32Fast;
+    use core::num::NonZeroU16;
+    use deranged::RangedU8;
+    use gba_test::test;
+    use rand::Rng;
+
+    /// Builds a game where each of the given colors owns a single arrow.
+    fn game_with_colors(colors: &[Color], turn_color: Color) -> Game {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        for (index, color) in colors.iter().enumerate() {
+            nodes[0][index * 2] = Node::Arrow {
+                alignment: Some(*color),
+                direction: Direction::Down,
+            };
+        }
+        Game::builder()
+            .grid(Grid::new(nodes))
+            .turn_color(turn_color)
+            .build()
+    }
+
+    #[test]
+    fn color_all_in_turn_order() {
+        assert_eq!(
+            Color::ALL,
+            [Color::Red, Color::Blue, Color::Yellow, Color::Green]
+        );
+    }
+
+    #[test]
+    fn color_next_cycles() {
+        assert_eq!(Color::Red.next(), Color::Blue);
+        assert_eq!(Color::Blue.next(), Color::Yellow);
+        assert_eq!(Color::Yellow.next(), Color::Green);
+        assert_eq!(Color::Green.next(), Color::Red);
+    }
+
+    #[test]
+    fn color_u8_round_trip() {
+        for color in Color::ALL {
+            assert_eq!(Color::try_from(u8::from(color)), Ok(color));
+        }
+    }
+
+    #[test]
+    fn color_try_from_invalid() {
+        assert_eq!(Color::try_from(4), Err(InvalidColor(4)));
+        assert_eq!(Color::try_from(255), Err(InvalidColor(255)));
+    }
+
+    #[test]
+    fn color_palette_bank() {
+        assert_eq!(Color::Red.palette_bank().get(), 1);
+        assert_eq!(Color::Blue.palette_bank().get(), 2);
+        assert_eq!(Color::Yellow.palette_bank().get(), 3);
+        assert_eq!(Color::Green.palette_bank().get(), 4);
+    }
+
+    #[test]
+    fn increment_turn_all_players() {
+        let mut game = game_with_colors(&Color::ALL, Color::Red);
+
+        for expected in [Color::Blue, Color::Yellow, Color::Green, Color::Red] {
+            assert!(game.increment_turn());
+            assert_eq!(game.turn_color(), expected);
+        }
+    }
+
+    #[test]
+    fn increment_turn_skips_eliminated() {
+        let mut game = game_with_colors(&[Color::Red, Color::Green], Color::Blue);
+
+        assert!(game.increment_turn());
+        assert_eq!(game.turn_color(), Color::Green);
+        assert!(game.increment_turn());
+        assert_eq!(game.turn_color(), Color::Red);
+        assert!(game.increment_turn());
+        assert_eq!(game.turn_color(), Color::Green);
+    }
+
+    #[test]
+    fn increment_turn_every_elimination() {
+        for remaining in 1..16u8 {
+            let mut colors = [Color::Red; 4];
+            let mut len = 0;
+            for (index, color) in Color::ALL.into_iter().enumerate() {
+                if remaining & (1 << index) != 0 {
+                    colors[len] = color;
+                    len += 1;
+                }
+            }
+            for turn_color in Color::ALL {
+                let mut game = game_with_colors(&colors[..len], turn_color);
+                let mut expected = turn_color.next();
+                while !colors[..len].contains(&expected) && expected != turn_color {
+                    expected = expected.next();
+                }
+
+                assert_eq!(
+                    game.increment_turn(),
+                    expected != turn_color,
+                    "remaining {remaining:#06b}, turn {turn_color:?}"
+                );
+                assert_eq!(
+                    game.turn_color(),
+                    expected,
+                    "remaining {remaining:#06b}, turn {turn_color:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn increment_turn_single_player() {
+        let mut game = game_with_colors(&[Color::Yellow], Color::Yellow);
+
+        assert!(!game.increment_turn());
+        assert_eq!(game.turn_color(), Color::Yellow);
+    }
+
+    /// Builds a game where Red only owns an `AllDirection` node boxed in by walls and an empty
+    /// node, while Blue owns a single arrow elsewhere.
+    fn game_without_red_moves() -> Game {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::AllDirection {
+            alignment: Some(Color::Red),
+        };
+        nodes[0][1] = Node::Wall;
+        nodes[1][0] = Node::Wall;
+        nodes[15][15] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Up,
+        };
+        Game::builder().grid(Grid::new(nodes)).build()
+    }
+
+    #[test]
+    fn has_legal_move_with_arrow() {
+        let game = game_with_colors(&[Color::Red, Color::Blue], Color::Red);
+
+        assert!(game.has_legal_move(Color::Red));
+        assert!(game.has_legal_move(Color::Blue));
+    }
+
+    #[test]
+    fn has_legal_move_eliminated() {
+        let game = game_with_colors(&[Color::Red, Color::Blue], Color::Red);
+
+        assert!(!game.has_legal_move(Color::Yellow));
+    }
+
+    #[test]
+    fn has_legal_move_boxed_in_all_direction() {
+        let game = game_without_red_moves();
+
+        assert!(!game.has_legal_move(Color::Red));
+        assert!(game.has_legal_move(Color::Blue));
+    }
+
+    #[test]
+    fn has_legal_move_all_direction_can_capture() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::AllDirection {
+            alignment: Some(Color::Red),
+        };
+        nodes[0][1] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert!(game.has_legal_move(Color::Red));
+        assert!(game.is_legal_move(Color::Red, Position { x: 0, y: 0 }));
+    }
+
+    #[test]
+    fn has_legal_move_does_not_mutate() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::AllDirection {
+            alignment: Some(Color::Red),
+        };
+        nodes[0][1] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        game.has_legal_move(Color::Red);
+
+        assert!(game
+            .grid()
+            .get(Position { x: 1, y: 0 })
+            .unwrap()
+            .is_color(Color::Blue));
+        assert!(!game.is_eliminated(Color::Blue));
+    }
+
+    /// Builds a grid where Red owns every column left of a column of walls, and Blue owns every
+    /// column right of it.
+    fn walled_off(wall: usize) -> [[Node; 16]; 16] {
+        let mut nodes = [[Node::Wall; 16]; 16];
+        for row in nodes.iter_mut() {
+            for (x, node) in row.iter_mut().enumerate() {
+                if x != wall {
+                    *node = Node::Arrow {
+                        alignment: Some(if x < wall { Color::Red } else { Color::Blue }),
+                        direction: Direction::Up,
+                    };
+                }
+            }
+        }
+        nodes
+    }
+
+    #[test]
+    fn is_stalemate_walled_off() {
+        let game = Game::builder().grid(Grid::new(walled_off(8))).build();
+
+        assert!(game.is_stalemate());
+    }
+
+    #[test]
+    fn is_stalemate_wraparound() {
+        // The two sides meet across the edge of the board.
+        let game = Game::builder()
+            .grid(Grid::new(walled_off(8)))
+            .rules(Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            })
+            .build();
+
+        assert!(!game.is_stalemate());
+    }
+
+    #[test]
+    fn is_stalemate_unaligned_neighbor() {
+        let mut nodes = walled_off(8);
+        nodes[5][8] = Node::AllDirection { alignment: None };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert!(!game.is_stalemate());
+    }
+
+    #[test]
+    fn is_stalemate_teleporter_across_wall() {
+        let mut nodes = walled_off(8);
+        nodes[0][0] = Node::Teleporter {
+            alignment: Some(Color::Red),
+            id: 0,
+        };
+        nodes[15][15] = Node::Teleporter {
+            alignment: Some(Color::Blue),
+            id: 0,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert!(!game.is_stalemate());
+        assert!(game.frontier(Color::Red).eq([Position { x: 0, y: 0 }]));
+    }
+
+    #[test]
+    fn is_stalemate_generated() {
+        for seed in 0..20 {
+            let game = Game::builder().grid(Grid::generate(seed)).build();
+
+            assert!(!game.is_stalemate());
+        }
+    }
+
+    #[test]
+    fn execute_turn_stalemate() {
+        let mut nodes = walled_off(8);
+        // The last node Red can capture, with nothing beyond it.
+        nodes[5][8] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Up,
+        };
+        nodes[5][9] = Node::Wall;
+        let mut game = Game::builder().grid(Grid::new(nodes)).build();
+        assert!(!game.is_stalemate());
+
+        let ending = game
+            .execute_turn(Turn {
+                rotate: Position { x: 7, y: 5 },
+                times: Turn::ONCE,
+            })
+            .unwrap()
+            .ending();
+
+        assert_eq!(
+            ending,
+            Some(Ending::Stalemate {
+                leader: Some(Color::Red)
+            })
+        );
+        assert_eq!(game.count(Color::Red), 8 * 16 + 1);
+    }
+
+    #[test]
+    fn execute_turn_stalemate_shared_lead() {
+        let mut nodes = walled_off(7);
+        for row in nodes.iter_mut() {
+            row[15] = Node::Empty;
+        }
+        let mut game = Game::builder().grid(Grid::new(nodes)).build();
+
+        let ending = game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            })
+            .unwrap()
+            .ending();
+
+        assert_eq!(ending, Some(Ending::Stalemate { leader: None }));
+    }
+
+    #[test]
+    fn execute_turn_turn_limit() {
+        let mut game = Game::builder()
+            .grid(Grid::generate(0))
+            .max_turns(NonZeroU16::new(3))
+            .build();
+        assert_eq!(game.turns_remaining(), Some(3));
+
+        let mut endings = [None; 3];
+        for ending in &mut endings {
+            let turn = game
+                .valid_turns()
+                .find(|turn| game.is_legal_move(game.turn_color(), turn.rotate))
+                .unwrap();
+            *ending = game.execute_turn(turn).unwrap().ending();
+        }
+
+        assert_eq!(endings[..2], [None, None]);
+        assert_eq!(
+            endings[2],
+            Some(Ending::TurnLimit {
+                leader: game.leader()
+            })
+        );
+        assert_eq!(game.turns_remaining(), Some(0));
+        assert!(game.is_turn_limit_reached());
+    }
+
+    #[test]
+    fn execute_turn_turn_limit_tie() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        // Each color can capture the arrow below it later, but not this turn.
+        for x in [0, 15] {
+            nodes[1][x] = Node::Arrow {
+                alignment: None,
+                direction: Direction::Down,
+            };
+        }
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Down,
+        };
+        nodes[0][15] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Down,
+        };
+        let mut game = Game::builder()
+            .grid(Grid::new(nodes))
+            .max_turns(NonZeroU16::new(1))
+            .build();
+        assert!(!game.is_stalemate());
+
+        let ending = game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            })
+            .unwrap()
+            .ending();
+
+        assert_eq!(ending, Some(Ending::TurnLimit { leader: None }));
+        assert_eq!(game.count(Color::Red), game.count(Color::Blue));
+    }
+
+    #[test]
+    fn execute_turn_no_turn_limit() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        assert_eq!(game.turns_remaining(), None);
+
+        for _ in 0..20 {
+            let Some(turn) = game
+                .valid_turns()
+                .find(|turn| game.is_legal_move(game.turn_color(), turn.rotate))
+            else {
+                game.pass_turn();
+                continue;
+            };
+            let ending = game.execute_turn(turn).unwrap().ending();
+
+            assert!(!matches!(ending, Some(Ending::TurnLimit { .. })));
+            if ending.is_some() {
+                break;
+            }
         }
+        assert_eq!(game.turns_remaining(), None);
+        assert!(!game.is_turn_limit_reached());
+    }
+
+    #[test]
+    fn execute_turn_no_stalemate() {
+        let mut game = Game::builder()
+            .grid(Grid::new(walled_off(8)))
+            .rules(Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            })
+            .build();
+
+        let ending = game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            })
+            .unwrap()
+            .ending();
+
+        assert_eq!(ending, None);
+    }
+
+    /// Builds a game on Red's turn with one of each kind of node Red can't rotate along the top
+    /// row, after Red's own arrow.
+    fn game_with_rejections() -> Game {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Down,
+        };
+        nodes[0][1] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Down,
+        };
+        nodes[0][2] = Node::AllDirection { alignment: None };
+        nodes[0][3] = Node::Wall;
+        Game::builder().grid(Grid::new(nodes)).build()
+    }
+
+    /// Asserts that Red's turn at `position` is rejected with `error`, leaving the game untouched.
+    fn assert_rejected(position: Position, error: turn::Error) {
+        let mut game = game_with_rejections();
+        let before = game.clone();
+
+        assert_eq!(
+            game.execute_turn(Turn {
+                rotate: position,
+                times: Turn::ONCE
+            })
+            .unwrap_err(),
+            error
+        );
+        assert!(same_state(&game, &before));
+    }
+
+    #[test]
+    fn execute_turn_out_of_bounds() {
+        assert_rejected(Position { x: 16, y: 0 }, turn::Error::OutOfBounds);
+        assert_rejected(Position { x: 0, y: 16 }, turn::Error::OutOfBounds);
+    }
+
+    #[test]
+    fn execute_turn_not_owned_by_other_color() {
+        assert_rejected(
+            Position { x: 1, y: 0 },
+            turn::Error::NotOwned {
+                actual: Some(Color::Blue),
+            },
+        );
+    }
+
+    #[test]
+    fn execute_turn_not_owned_by_anyone() {
+        assert_rejected(
+            Position { x: 2, y: 0 },
+            turn::Error::NotOwned { actual: None },
+        );
+    }
+
+    #[test]
+    fn execute_turn_wall_not_rotatable() {
+        assert_rejected(Position { x: 3, y: 0 }, turn::Error::NotRotatable);
+    }
+
+    #[test]
+    fn execute_turn_empty_not_rotatable() {
+        assert_rejected(Position { x: 4, y: 0 }, turn::Error::NotRotatable);
+    }
+
+    #[test]
+    fn execute_turn_multiple_rotations_not_allowed() {
+        let mut game = game_with_rejections();
+        let before = game.clone();
+
+        assert_eq!(
+            game.execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: RangedU8::new_static::<2>(),
+            }),
+            Err(turn::Error::MultipleRotations)
+        );
+        assert!(same_state(&game, &before));
+    }
+
+    #[test]
+    fn execute_turn_multiple_rotations() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[1][1] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Down,
+        };
+        // Only reachable by turning counter-clockwise, which takes three clockwise rotations.
+        nodes[1][2] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Down,
+        };
+        nodes[15][15] = Node::Arrow {
+            alignment: Some(Color::Green),
+            direction: Direction::Down,
+        };
+        let mut game = Game::builder()
+            .grid(Grid::new(nodes))
+            .rules(Rules {
+                wraparound: false,
+                multi_rotation: true,
+                super_arrow_claims: false,
+            })
+            .build();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 1, y: 1 },
+            times: RangedU8::new_static::<3>(),
+        })
+        .unwrap();
+
+        assert_eq!(
+            game.grid[Position { x: 1, y: 1 }].direction(),
+            Some(Direction::Right)
+        );
+        assert!(game
+            .grid
+            .get(Position { x: 2, y: 1 })
+            .unwrap()
+            .is_color(Color::Red));
+        assert_eq!(
+            game.history().iter().last().map(|entry| entry.times.get()),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn valid_turns_multiple_rotations() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Down,
+        };
+        let game = Game::builder()
+            .grid(Grid::new(nodes))
+            .rules(Rules {
+                wraparound: false,
+                multi_rotation: true,
+                super_arrow_claims: false,
+            })
+            .build();
+
+        assert!(game
+            .valid_turns()
+            .map(|turn| turn.times.get())
+            .eq([1, 2, 3]));
+    }
+
+    #[test]
+    fn execute_turn_accepted() {
+        let mut game = game_with_rejections();
+
+        assert_eq!(
+            game.execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE
+            })
+            .map(|outcome| outcome.ending()),
+            Ok(None)
+        );
+    }
+
+    /// A game where turning red's arrow at (0, 0) right fills through blue and yellow, leaving
+    /// green below it. Green can then take every red node by turning its arrow up.
+    fn two_eliminated_together() -> Game {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        nodes[0][1] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        nodes[0][2] = Node::Arrow {
+            alignment: Some(Color::Yellow),
+            direction: Direction::Right,
+        };
+        nodes[1][0] = Node::Arrow {
+            alignment: Some(Color::Green),
+            direction: Direction::Left,
+        };
+        Game::builder().grid(Grid::new(nodes)).build()
+    }
+
+    #[test]
+    fn execute_turn_eliminates_two_colors() {
+        let mut game = two_eliminated_together();
+
+        let outcome = game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            })
+            .unwrap();
+
+        assert!(outcome.eliminated().eq([Color::Blue, Color::Yellow]));
+        assert_eq!(outcome.captured(), 2);
+        assert_eq!(outcome.ending(), None);
+        assert_eq!(game.turn_color(), Color::Green);
+    }
+
+    #[test]
+    fn rankings_two_eliminated_same_turn() {
+        let mut game = two_eliminated_together();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert!(game.eliminations().eq([Color::Blue, Color::Yellow]));
+        assert_eq!(
+            game.rankings(),
+            [
+                Some(Color::Red),
+                Some(Color::Green),
+                Some(Color::Yellow),
+                Some(Color::Blue)
+            ]
+        );
+
+        let outcome = game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 1 },
+                times: Turn::ONCE,
+            })
+            .unwrap();
+
+        assert_eq!(outcome.ending(), Some(Ending::Winner(Color::Green)));
+        assert!(game
+            .eliminations()
+            .eq([Color::Blue, Color::Yellow, Color::Red]));
+        assert_eq!(
+            game.rankings(),
+            [
+                Some(Color::Green),
+                Some(Color::Red),
+                Some(Color::Yellow),
+                Some(Color::Blue)
+            ]
+        );
+    }
+
+    #[test]
+    fn rankings_leave_out_colors_not_playing() {
+        let game = Game::builder()
+            .grid(Grid::generate(0))
+            .players(RangedU8::new_static::<2>())
+            .build();
+
+        assert_eq!(game.eliminations().next(), None);
+        assert_eq!(
+            game.rankings(),
+            [Some(Color::Red), Some(Color::Green), None, None]
+        );
+    }
+
+    #[test]
+    fn undo_restores_eliminations() {
+        let mut game = two_eliminated_together();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        assert!(game.undo());
+
+        assert_eq!(game.eliminations().next(), None);
+    }
+
+    #[test]
+    fn execute_turn_eliminates_last_color() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        nodes[0][1] = Node::Arrow {
+            alignment: Some(Color::Green),
+            direction: Direction::Right,
+        };
+        nodes[0][2] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Right,
+        };
+        let mut game = Game::builder().grid(Grid::new(nodes)).build();
+
+        let outcome = game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            })
+            .unwrap();
+
+        assert!(outcome.eliminated().eq([Color::Green]));
+        assert_eq!(outcome.captured(), 2);
+        assert_eq!(outcome.ending(), Some(Ending::Winner(Color::Red)));
+    }
+
+    #[test]
+    fn execute_turn_eliminates_nobody() {
+        let mut game = game_with_colors(&Color::ALL, Color::Red);
+
+        let outcome = game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            })
+            .unwrap();
+
+        assert_eq!(outcome.eliminated().count(), 0);
+        assert_eq!(outcome.captured(), 0);
+    }
+
+    #[test]
+    fn preview_turn_matches_execute_turn() {
+        for seed in 0..4 {
+            for rules in [
+                Rules::default(),
+                Rules {
+                    wraparound: true,
+                    multi_rotation: false,
+                    super_arrow_claims: false,
+                },
+                Rules {
+                    wraparound: false,
+                    multi_rotation: true,
+                    super_arrow_claims: false,
+                },
+            ] {
+                let mut game = Game::builder()
+                    .grid(Grid::generate(seed))
+                    .rules(rules)
+                    .build();
+                for _ in 0..40 {
+                    let Some(turn) = game.valid_turns().last() else {
+                        break;
+                    };
+                    let before = game.clone();
+
+                    let preview = game.preview_turn(turn).unwrap();
+
+                    assert!(same_state(&game, &before));
+                    let color = game.turn_color;
+                    let ending = game.execute_turn(turn).unwrap().ending();
+                    assert_eq!(*preview.counts(), game.color_counts);
+                    assert_eq!(preview.wins(), ending == Some(Ending::Winner(color)));
+                    for previous in [None].into_iter().chain(Color::ALL.map(Some)) {
+                        if !game.events.is_truncated() {
+                            assert_eq!(
+                                usize::from(preview.captured_from(previous)),
+                                game.events
+                                    .iter()
+                                    .filter(|capture| capture.previous == previous)
+                                    .count()
+                            );
+                        }
+                    }
+                    if ending.is_some() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn preview_turn_super_arrow() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::SuperArrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        for x in 1..16 {
+            nodes[0][x] = Node::Arrow {
+                alignment: Some(Color::Blue),
+                direction: Direction::Down,
+            };
+        }
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        let preview = game
+            .preview_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            })
+            .unwrap();
+
+        // The beam turns the whole row toward the right, so all of it is captured.
+        assert_eq!(
+            preview.counts().get(Color::Red).map(NonZeroU16::get),
+            Some(16)
+        );
+        assert_eq!(preview.counts().get(Color::Blue), None);
+        assert_eq!(preview.captured(), 15);
+        assert_eq!(preview.captured_from(Some(Color::Blue)), 15);
+        assert_eq!(preview.captured_from(Some(Color::Red)), 0);
+        assert_eq!(preview.captured_from(None), 0);
+        assert!(preview.wins());
+        assert_eq!(game.count(Color::Red), 1);
+    }
+
+    #[test]
+    fn preview_turn_rejected() {
+        let game = game_with_rejections();
+
+        assert_eq!(
+            game.preview_turn(Turn {
+                rotate: Position { x: 1, y: 0 },
+                times: Turn::ONCE
+            })
+            .unwrap_err(),
+            turn::Error::NotOwned {
+                actual: Some(Color::Blue)
+            }
+        );
+    }
+
+    #[test]
+    fn valid_turns_accepted_by_execute_turn() {
+        for seed in 0..4 {
+            let mut game = Game::builder().grid(Grid::generate(seed)).build();
+            // Play a few turns so that several colors own more than their starting node.
+            for _ in 0..12 {
+                for y in 0..16 {
+                    for x in 0..16 {
+                        let position = Position { x, y };
+                        assert_eq!(
+                            game.valid_turns().any(|turn| turn.rotate == position),
+                            game.clone()
+                                .execute_turn(Turn {
+                                    rotate: position,
+                                    times: Turn::ONCE
+                                })
+                                .is_ok()
+                        );
+                    }
+                }
+                let turn = game.valid_turns().last().unwrap();
+                if game.execute_turn(turn).unwrap().ending().is_some() {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn valid_turns_does_not_mutate() {
+        let game = Game::builder().grid(Grid::generate(5)).build();
+        let before = game.clone();
+
+        assert_eq!(game.valid_turns().count(), 1);
+        assert!(same_state(&game, &before));
+        assert_eq!(game.turn_events().iter().count(), 0);
+    }
+
+    #[test]
+    fn pass_turn_keeps_cycle() {
+        let mut game = game_without_red_moves();
+
+        game.pass_turn();
+        assert_eq!(game.turn_color(), Color::Blue);
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 15, y: 15 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        assert_eq!(game.turn_color(), Color::Red);
+
+        game.pass_turn();
+        assert_eq!(game.turn_color(), Color::Blue);
+        assert!(!game.is_eliminated(Color::Red));
+    }
+
+    #[test]
+    fn frontier_arrow_pointing_at_other_color() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[3][3] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Right,
+        };
+        nodes[3][4] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert!(game.frontier(Color::Red).eq([Position { x: 3, y: 3 }]));
+        // Blue's arrow points away from red.
+        assert!(game.frontier(Color::Blue).eq([Position { x: 4, y: 3 }]));
+    }
+
+    #[test]
+    fn frontier_arrow_pointing_away() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[3][3] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        nodes[3][4] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert_eq!(game.frontier(Color::Red).count(), 0);
+    }
+
+    #[test]
+    fn frontier_ignores_walls_and_own_color() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[3][3] = Node::AllDirection {
+            alignment: Some(Color::Red),
+        };
+        nodes[3][4] = Node::Wall;
+        nodes[2][3] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert_eq!(game.frontier(Color::Red).count(), 0);
+    }
+
+    #[test]
+    fn frontier_includes_unowned() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[3][3] = Node::AllDirection {
+            alignment: Some(Color::Red),
+        };
+        nodes[4][3] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Down,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert!(game.frontier(Color::Red).eq([Position { x: 3, y: 3 }]));
+    }
+
+    #[test]
+    fn frontier_not_through_hidden_node() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[3][3] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        // Hidden nodes don't spread a fill, even though they point everywhere.
+        nodes[3][4] = Node::AllDirection { alignment: None };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert_eq!(game.frontier(Color::Red).count(), 0);
+    }
+
+    #[test]
+    fn frontier_nodes_capture_after_random_moves() {
+        let mut rng: Pcg32Fast = Pcg32Fast::new(0);
+        for seed in 0..8 {
+            let mut game = Game::builder().grid(Grid::generate(seed)).build();
+            for _ in 0..20 {
+                for color in Color::ALL {
+                    for position in game.frontier(color) {
+                        // Filling from any frontier node must capture something.
+                        let mut scratch = Game {
+                            turn_color: color,
+                            turn_number: game.turn_number,
+                            color_counts: game.color_counts.clone(),
+                            grid: game.grid.clone(),
+                            events: TurnEvents::new(),
+                            rules: game.rules,
+                            max_turns: game.max_turns,
+                            eliminations: game.eliminations,
+                            prev_state: None,
+                            last_move: None,
+                            history: History::new(),
+                        };
+                        scratch.fill(position, &mut PositionSet::new());
+                        assert_ne!(
+                            scratch.color_counts.get(color),
+                            game.color_counts.get(color)
+                        );
+                    }
+                }
+
+                let turn_color = game.turn_color();
+                let index = rng.gen_range(0..game.count(turn_color) as usize);
+                let Some(position) = game.grid().positions_of_color(turn_color).nth(index) else {
+                    break;
+                };
+                if game
+                    .execute_turn(Turn {
+                        rotate: position,
+                        times: Turn::ONCE,
+                    })
+                    .unwrap()
+                    .ending()
+                    .is_some()
+                {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn verify_fresh_game() {
+        let game = Game::builder().grid(Grid::generate(0)).build();
+
+        assert_eq!(game.verify(), Ok(()));
+    }
+
+    #[test]
+    fn verify_after_turn() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert_eq!(game.verify(), Ok(()));
+    }
+
+    #[test]
+    fn turn_number_counts_executed_turns() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        assert_eq!(game.turn_number(), 0);
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        assert_eq!(game.turn_number(), 1);
+
+        // Neither rejected nor passed turns count.
+        assert!(game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE
+            })
+            .is_err());
+        game.pass_turn();
+        assert_eq!(game.turn_number(), 1);
+
+        game.execute_turn_trusted(Turn {
+            rotate: Position { x: 0, y: 15 },
+            times: Turn::ONCE,
+        });
+        assert_eq!(game.turn_number(), 2);
+    }
+
+    #[test]
+    fn turn_number_restored_by_undo() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert!(game.undo());
+
+        assert_eq!(game.turn_number(), 0);
+    }
+
+    #[test]
+    fn preview_turn_does_not_count() {
+        let game = Game::builder().grid(Grid::generate(0)).build();
+
+        game.preview_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert_eq!(game.turn_number(), 0);
+    }
+
+    #[test]
+    fn clones_diverge() {
+        let original = Game::builder().grid(Grid::generate(0)).build();
+        let mut red = original.clone();
+        let mut blue = original.clone();
+
+        red.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        blue.pass_turn();
+        blue.execute_turn(Turn {
+            rotate: Position { x: 15, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert_eq!(red.turn_color(), Color::Blue);
+        assert_eq!(blue.turn_color(), Color::Yellow);
+        assert!(red.count(Color::Red) > 1);
+        assert_eq!(red.count(Color::Blue), 1);
+        assert_eq!(blue.count(Color::Red), 1);
+        assert!(blue.count(Color::Blue) > 1);
+        assert_eq!(red.verify(), Ok(()));
+        assert_eq!(blue.verify(), Ok(()));
+        // Neither turn reached the game they were cloned from.
+        assert_eq!(original.turn_color(), Color::Red);
+        assert_eq!(
+            original.counts(),
+            [
+                (Color::Red, 1),
+                (Color::Blue, 1),
+                (Color::Yellow, 1),
+                (Color::Green, 1)
+            ]
+        );
+        assert!((0..16).all(|y| original.grid().ascii_row(y)
+            == Game::builder()
+                .grid(Grid::generate(0))
+                .build()
+                .grid()
+                .ascii_row(y)));
+    }
+
+    #[test]
+    fn verify_detects_corrupted_color_counts() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        let actual = game.color_counts.clone();
+        game.color_counts.change(Color::Blue, Some(Color::Red));
+
+        assert_eq!(
+            game.verify(),
+            Err(Mismatch::ColorCounts {
+                cached: game.color_counts.clone(),
+                actual,
+            })
+        );
+    }
+
+    #[test]
+    fn verify_detects_corrupted_grid() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        game.grid
+            .get_mut(Position { x: 0, y: 0 })
+            .unwrap()
+            .set_color(Color::Green);
+
+        assert!(matches!(game.verify(), Err(Mismatch::ColorCounts { .. })));
+    }
+
+    #[test]
+    fn standings_sorted_by_count() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        for (index, (color, count)) in [
+            (Color::Red, 1),
+            (Color::Blue, 3),
+            (Color::Yellow, 2),
+            (Color::Green, 4),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            for x in 0..count {
+                nodes[index][x] = Node::Arrow {
+                    alignment: Some(color),
+                    direction: Direction::Down,
+                };
+            }
+        }
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert!(game.standings().iter().map(|standing| standing.color).eq([
+            Color::Green,
+            Color::Blue,
+            Color::Yellow,
+            Color::Red
+        ]));
+    }
+
+    #[test]
+    fn standings_ties_in_turn_order() {
+        let game = game_with_colors(
+            &[Color::Green, Color::Yellow, Color::Blue, Color::Red],
+            Color::Red,
+        );
+
+        assert!(game.standings().iter().eq(&[
+            Standing {
+                color: Color::Red,
+                count: 1,
+                alive: true,
+            },
+            Standing {
+                color: Color::Blue,
+                count: 1,
+                alive: true,
+            },
+            Standing {
+                color: Color::Yellow,
+                count: 1,
+                alive: true,
+            },
+            Standing {
+                color: Color::Green,
+                count: 1,
+                alive: true,
+            },
+        ]));
+    }
+
+    #[test]
+    fn standings_eliminated_last() {
+        let game = game_with_colors(&[Color::Green, Color::Blue], Color::Blue);
+
+        assert!(game.standings().iter().eq(&[
+            Standing {
+                color: Color::Blue,
+                count: 1,
+                alive: true,
+            },
+            Standing {
+                color: Color::Green,
+                count: 1,
+                alive: true,
+            },
+            Standing {
+                color: Color::Red,
+                count: 0,
+                alive: false,
+            },
+            Standing {
+                color: Color::Yellow,
+                count: 0,
+                alive: false,
+            },
+        ]));
+    }
+
+    #[test]
+    fn state_hash_same_game() {
+        let turns = [
+            Position { x: 0, y: 0 },
+            Position { x: 15, y: 0 },
+            Position { x: 0, y: 15 },
+        ];
+        let game = Game::replay(
+            BoardCode::new(3),
+            Color::Red,
+            turns.map(|rotate| Turn {
+                rotate,
+                times: Turn::ONCE,
+            }),
+        )
+        .unwrap();
+        let other = Game::replay(
+            BoardCode::new(3),
+            Color::Red,
+            turns.map(|rotate| Turn {
+                rotate,
+                times: Turn::ONCE,
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(game.state_hash(), other.state_hash());
+    }
+
+    #[test]
+    fn state_hash_differs_by_alignment() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[4][4] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        nodes[9][9] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Up,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+        nodes[9][9] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        let other = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert_ne!(game.state_hash(), other.state_hash());
+    }
+
+    #[test]
+    fn state_hash_differs_by_turn_color() {
+        let game = Game::builder().grid(Grid::generate(0)).build();
+        let other = Game::builder()
+            .grid(Grid::generate(0))
+            .turn_color(Color::Blue)
+            .build();
+
+        assert_ne!(game.state_hash(), other.state_hash());
+    }
+
+    #[test]
+    fn state_hash_stable() {
+        // If this changes, every stored hash is invalidated.
+        assert_eq!(
+            Game::builder()
+                .grid(Grid::new([[Node::Empty; 16]; 16]))
+                .build()
+                .state_hash(),
+            0x9b70_b17b_b7e5_add7
+        );
+    }
+
+    #[test]
+    fn color_counts_for_color() {
+        let game = game_with_colors(&[Color::Red, Color::Yellow, Color::Red], Color::Red);
+
+        assert_eq!(game.color_counts.for_color(Color::Red), 2);
+        assert_eq!(game.color_counts.for_color(Color::Blue), 0);
+        assert_eq!(game.color_counts.for_color(Color::Yellow), 1);
+        assert_eq!(game.color_counts.for_color(Color::Green), 0);
+    }
+
+    #[test]
+    fn color_counts_total() {
+        let game = game_with_colors(&[Color::Red, Color::Yellow, Color::Red], Color::Red);
+
+        assert_eq!(game.color_counts.total(), 3);
+    }
+
+    #[test]
+    fn color_counts_total_ignores_unaligned() {
+        let game = Game::builder().grid(Grid::generate(0)).build();
+
+        assert_eq!(game.color_counts.total(), 4);
+    }
+
+    #[test]
+    fn color_plays() {
+        for color in Color::ALL {
+            assert!(color.plays(RangedU8::new_static::<4>()));
+        }
+        assert!(Color::ALL
+            .into_iter()
+            .filter(|color| color.plays(RangedU8::new_static::<3>()))
+            .eq([Color::Red, Color::Blue, Color::Yellow]));
+        assert!(Color::ALL
+            .into_iter()
+            .filter(|color| color.plays(RangedU8::new_static::<2>()))
+            .eq([Color::Red, Color::Green]));
+    }
+
+    #[test]
+    fn from_code() {
+        for color in Color::ALL {
+            let game = Game::from_code(BoardCode::new(7), color).build();
+
+            assert_eq!(game.turn_color(), color);
+            assert_eq!(game.turn_number(), 0);
+            assert!(game.grid().iter().eq(Grid::generate_validated(7).iter()));
+        }
+    }
+
+    #[test]
+    fn from_code_rules_and_players() {
+        let code = BoardCode {
+            rules: Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: true,
+            },
+            players: RangedU8::new_static::<3>(),
+            ..BoardCode::new(7)
+        };
+        let game = Game::from_code(code, Color::Blue).build();
+
+        assert_eq!(game.rules(), code.rules);
+        assert_eq!(game.players_remaining(), 3);
+    }
+
+    #[test]
+    fn builder_players() {
+        let game = Game::builder()
+            .grid(Grid::generate(0))
+            .players(RangedU8::new_static::<3>())
+            .build();
+
+        assert_eq!(game.players_remaining(), 3);
+        assert!(game.is_eliminated(Color::Green));
+        assert_eq!(game.grid()[Position { x: 15, y: 15 }].color(), None);
+        assert_eq!(game.verify(), Ok(()));
+    }
+
+    #[test]
+    fn builder_default_rules() {
+        assert_eq!(Game::builder().build().rules(), Rules::default());
+    }
+
+    #[test]
+    fn builder_rules() {
+        let rules = Rules {
+            wraparound: true,
+            multi_rotation: false,
+            super_arrow_claims: true,
+        };
+
+        assert_eq!(Game::builder().rules(rules).build().rules(), rules);
+    }
+
+    #[test]
+    fn two_player_turn_order() {
+        let mut game = Game::builder()
+            .grid(Grid::generate(0))
+            .players(RangedU8::new_static::<2>())
+            .build();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        assert_eq!(game.turn_color(), Color::Green);
+        game.pass_turn();
+        assert_eq!(game.turn_color(), Color::Red);
+    }
+
+    #[test]
+    fn two_player_game_won() {
+        let mut game = Game::builder()
+            .grid(Grid::generate_with_size(1, 6))
+            .players(RangedU8::new_static::<2>())
+            .build();
+
+        let ending = loop {
+            let turn_color = game.turn_color();
+            assert!(matches!(turn_color, Color::Red | Color::Green));
+            let Some(rotate) = game
+                .valid_turns()
+                .map(|turn| turn.rotate)
+                .find(|&position| game.is_legal_move(turn_color, position))
+            else {
+                game.pass_turn();
+                continue;
+            };
+            if let Some(ending) = game
+                .execute_turn(Turn {
+                    rotate,
+                    times: Turn::ONCE,
+                })
+                .unwrap()
+                .ending()
+            {
+                break ending;
+            }
+        };
+
+        assert!(matches!(ending, Ending::Winner(Color::Red | Color::Green)));
+    }
+
+    #[test]
+    fn color_counts_winner() {
+        for color in Color::ALL {
+            assert_eq!(
+                game_with_colors(&[color, color], color)
+                    .color_counts
+                    .winner(),
+                Some(color)
+            );
+        }
+    }
+
+    #[test]
+    fn color_counts_no_winner() {
+        assert_eq!(
+            game_with_colors(&[Color::Blue, Color::Yellow], Color::Blue)
+                .color_counts
+                .winner(),
+            None
+        );
+        assert_eq!(
+            Game::builder()
+                .grid(Grid::generate(0))
+                .build()
+                .color_counts
+                .winner(),
+            None
+        );
+        assert_eq!(
+            game_with_colors(&[], Color::Red).color_counts.winner(),
+            None
+        );
+    }
+
+    #[test]
+    fn players_remaining() {
+        assert_eq!(
+            game_with_colors(&[Color::Green, Color::Blue], Color::Blue).players_remaining(),
+            2
+        );
+        assert_eq!(
+            Game::builder()
+                .grid(Grid::generate(0))
+                .build()
+                .players_remaining(),
+            4
+        );
+    }
+
+    #[test]
+    fn is_over() {
+        assert!(game_with_colors(&[Color::Green], Color::Green).is_over());
+        assert!(!game_with_colors(&[Color::Green, Color::Blue], Color::Blue).is_over());
+        assert!(!Game::builder().grid(Grid::generate(0)).build().is_over());
+    }
+
+    #[test]
+    fn standings_consistent_through_game() {
+        let mut game = Game::builder().grid(Grid::generate(2)).build();
+        for turn in 0..40u8 {
+            let turn_color = game.turn_color();
+            let Some(position) = (0..16)
+                .flat_map(|y| (0..16).map(move |x| Position { x, y }))
+                .filter(|&position| game.is_legal_move(turn_color, position))
+                .nth(turn as usize % 3)
+            else {
+                game.pass_turn();
+                continue;
+            };
+            let winner = game
+                .execute_turn(Turn {
+                    rotate: position,
+                    times: Turn::ONCE,
+                })
+                .unwrap()
+                .ending();
+
+            let counts = game.grid().color_counts();
+            for standing in game.standings().iter() {
+                assert_eq!(
+                    standing.count,
+                    counts.get(standing.color).map_or(0, |count| count.get())
+                );
+                assert_eq!(standing.alive, !game.is_eliminated(standing.color));
+            }
+            assert_eq!(
+                game.players_remaining() as usize,
+                game.standings()
+                    .iter()
+                    .filter(|standing| standing.alive)
+                    .count()
+            );
+
+            if winner.is_some() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn count_eliminated() {
+        let game = game_with_colors(&[Color::Red, Color::Green], Color::Red);
+
+        assert_eq!(game.count(Color::Red), 1);
+        assert_eq!(game.count(Color::Blue), 0);
+    }
+
+    #[test]
+    fn counts_in_turn_order() {
+        let game = game_with_colors(&[Color::Green, Color::Blue, Color::Green], Color::Green);
+
+        assert_eq!(
+            game.counts(),
+            [
+                (Color::Red, 0),
+                (Color::Blue, 1),
+                (Color::Yellow, 0),
+                (Color::Green, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_consistent_through_games() {
+        let mut rng: Pcg32Fast = Pcg32Fast::new(3);
+        let mut super_arrows = 0;
+        for seed in 0..8 {
+            for rules in [
+                Rules::default(),
+                Rules {
+                    wraparound: true,
+                    multi_rotation: false,
+                    super_arrow_claims: false,
+                },
+            ] {
+                let mut game = Game::builder()
+                    .grid(Grid::generate(seed))
+                    .rules(rules)
+                    .build();
+                super_arrows += game
+                    .grid()
+                    .iter()
+                    .flatten()
+                    .filter(|node| matches!(node, Node::SuperArrow { .. }))
+                    .count();
+                for _ in 0..60 {
+                    let turn_color = game.turn_color();
+                    let moves = (0..16)
+                        .flat_map(|y| (0..16).map(move |x| Position { x, y }))
+                        .filter(|&position| game.is_legal_move(turn_color, position));
+                    let Some(position) = moves.clone().nth(rng.gen_range(0..moves.count().max(1)))
+                    else {
+                        game.pass_turn();
+                        continue;
+                    };
+                    let winner = game
+                        .execute_turn(Turn {
+                            rotate: position,
+                            times: Turn::ONCE,
+                        })
+                        .unwrap()
+                        .ending();
+
+                    let expected = game.grid().color_counts();
+                    for (color, count) in game.counts() {
+                        assert_eq!(count, expected.get(color).map_or(0, NonZeroU16::get));
+                        assert_eq!(count, game.count(color));
+                    }
+
+                    if winner.is_some() {
+                        break;
+                    }
+                }
+            }
+        }
+        // Make sure turns through super arrows were covered.
+        assert_ne!(super_arrows, 0);
+    }
+
+    /// A board where every node is part of a single chain leading to the red node.
+    fn maximal_chain() -> Game {
+        // Snake through the board, so every node points to the next one.
+        let grid = Grid::from_fn(|position| {
+            // The red node starts the chain, pointing up until it is rotated.
+            if position == (Position { x: 0, y: 0 }) {
+                return Node::Arrow {
+                    alignment: Some(Color::Red),
+                    direction: Direction::Up,
+                };
+            }
+            Node::Arrow {
+                alignment: Some(Color::Blue),
+                direction: match (position.y % 2, position.x) {
+                    (0, 15) | (1, 0) => Direction::Down,
+                    (0, _) => Direction::Right,
+                    _ => Direction::Left,
+                },
+            }
+        });
+        Game::builder().grid(grid).build()
+    }
+
+    #[test]
+    fn turn_events_records_captures() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        nodes[0][1] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        nodes[0][2] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Up,
+        };
+        let mut game = Game::builder().grid(Grid::new(nodes)).build();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert!(game.turn_events().iter().copied().eq([
+            super::Capture {
+                position: Position { x: 1, y: 0 },
+                previous: Some(Color::Blue),
+            },
+            super::Capture {
+                position: Position { x: 2, y: 0 },
+                previous: None,
+            },
+        ]));
+        assert!(!game.turn_events().is_truncated());
+    }
+
+    #[test]
+    fn execute_turn_reverse_arrow() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[1][1] = Node::ReverseArrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        nodes[1][0] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Up,
+        };
+        nodes[1][2] = Node::Arrow {
+            alignment: Some(Color::Yellow),
+            direction: Direction::Up,
+        };
+        let mut game = Game::builder().grid(Grid::new(nodes)).build();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 1, y: 1 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        // Turning counter-clockwise points left, at blue rather than yellow.
+        assert_eq!(
+            game.grid()
+                .get(Position { x: 1, y: 1 })
+                .unwrap()
+                .direction(),
+            Some(Direction::Left)
+        );
+        assert!(game.is_eliminated(Color::Blue));
+        assert_eq!(game.count(Color::Yellow), 1);
+    }
+
+    #[test]
+    fn fill_crosses_board_through_teleporters() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        nodes[0][1] = Node::Teleporter {
+            alignment: None,
+            id: 0,
+        };
+        nodes[15][14] = Node::Teleporter {
+            alignment: None,
+            id: 0,
+        };
+        nodes[15][15] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Left,
+        };
+        nodes[14][15] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Down,
+        };
+        // Points away from the twin, so it isn't reached.
+        nodes[15][13] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Up,
+        };
+        let mut game = Game::builder().grid(Grid::new(nodes)).build();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert!(game.turn_events().iter().copied().eq([
+            super::Capture {
+                position: Position { x: 1, y: 0 },
+                previous: None,
+            },
+            super::Capture {
+                position: Position { x: 14, y: 15 },
+                previous: None,
+            },
+            super::Capture {
+                position: Position { x: 15, y: 15 },
+                previous: Some(Color::Blue),
+            },
+            super::Capture {
+                position: Position { x: 15, y: 14 },
+                previous: None,
+            },
+        ]));
+        assert_eq!(game.count(Color::Red), 5);
+        assert!(game.is_eliminated(Color::Blue));
+        assert_eq!(game.grid()[Position { x: 13, y: 15 }].color(), None);
+    }
+
+    #[test]
+    fn fill_from_teleporter_reaches_twin() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[3][3] = Node::Teleporter {
+            alignment: Some(Color::Red),
+            id: 1,
+        };
+        nodes[12][12] = Node::Teleporter {
+            alignment: Some(Color::Blue),
+            id: 1,
+        };
+        let mut game = Game::builder().grid(Grid::new(nodes)).build();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 3, y: 3 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert!(game
+            .grid()
+            .get(Position { x: 12, y: 12 })
+            .unwrap()
+            .is_color(Color::Red));
+        assert!(game.is_eliminated(Color::Blue));
+    }
+
+    #[test]
+    fn turn_events_cleared_each_turn() {
+        let mut game = game_with_colors(&[Color::Red, Color::Blue], Color::Red);
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert_eq!(game.turn_events().len(), 0);
+    }
+
+    #[test]
+    fn turn_events_truncated_by_maximal_chain() {
+        let mut game = maximal_chain();
+
+        let winner = game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            })
+            .unwrap()
+            .ending();
+
+        // The whole board is still captured.
+        assert_eq!(winner, Some(Ending::Winner(Color::Red)));
+        assert!(game
+            .grid()
+            .iter()
+            .flatten()
+            .all(|node| node.is_color(Color::Red)));
+        assert_eq!(game.verify(), Ok(()));
+        assert_eq!(game.turn_events().len(), TurnEvents::CAPACITY);
+        assert!(game.turn_events().is_truncated());
+    }
+
+    #[test]
+    fn last_move_not_truncated() {
+        let mut game = maximal_chain();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        let (position, changed) = game.last_move().unwrap();
+        assert_eq!(position, Position { x: 0, y: 0 });
+        // Every node but the one that was rotated.
+        assert_eq!(changed.count(), 255);
+        assert!(!changed.contains(Position { x: 0, y: 0 }));
+    }
+
+    #[test]
+    fn last_move_matches_grid_diff() {
+        for seed in 0..10 {
+            let mut game = Game::builder().grid(Grid::generate(seed)).build();
+            for _ in 0..40 {
+                let color = game.turn_color();
+                let Some(turn) = game
+                    .valid_turns()
+                    .find(|turn| game.is_legal_move(color, turn.rotate))
+                else {
+                    game.pass_turn();
+                    continue;
+                };
+                let rotate = turn.rotate;
+                let before = game.grid().clone();
+                let ending = game.execute_turn(turn).unwrap().ending();
+
+                let (position, changed) = game.last_move().unwrap();
+                assert_eq!(position, rotate);
+                for y in 0..16 {
+                    for x in 0..16 {
+                        let position = Position { x, y };
+                        assert_eq!(
+                            changed.contains(position),
+                            before[position].color() != game.grid()[position].color(),
+                            "seed {seed}, {position:?}"
+                        );
+                    }
+                }
+                if ending.is_some() {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn last_move_kept_after_rejected_turn() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        assert!(game.last_move().is_none());
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        let changed = game.last_move().unwrap().1.clone();
+
+        // It is now blue's turn, so red's corner is rejected.
+        assert!(game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE
+            })
+            .is_err());
+
+        assert_eq!(game.last_move(), Some((Position { x: 0, y: 0 }, &changed)));
+    }
+
+    #[test]
+    fn last_move_cleared_by_undo() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        assert!(game.last_move().is_some());
+
+        assert!(game.undo());
+
+        assert!(game.last_move().is_none());
+    }
+
+    #[test]
+    fn turn_events_counts_balance() {
+        let mut game = maximal_chain();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        let standings = game.standings();
+        let mut standings = standings.iter();
+        assert_eq!(standings.next().unwrap().count, 256);
+        assert!(standings.all(|standing| standing.count == 0));
+    }
+
+    /// A red arrow in the top-left corner that will point off the top of the board once rotated,
+    /// along with a blue chain along the bottom edge.
+    fn corner_arrow_game(rules: Rules) -> Game {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        nodes[15][0] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        nodes[15][1] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        nodes[5][5] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Down,
+        };
+        Game::builder().grid(Grid::new(nodes)).rules(rules).build()
+    }
+
+    #[test]
+    fn corner_arrow_off_board_captures_nothing() {
+        let mut game = corner_arrow_game(Rules::default());
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert_eq!(game.turn_events().len(), 0);
+        assert!(game
+            .grid()
+            .get(Position { x: 1, y: 15 })
+            .unwrap()
+            .is_color(Color::Blue));
+    }
+
+    #[test]
+    fn corner_arrow_off_board_wraparound_captures_opposite_edge() {
+        let mut game = corner_arrow_game(Rules {
+            wraparound: true,
+            multi_rotation: false,
+            super_arrow_claims: false,
+        });
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert_eq!(game.turn_events().len(), 2);
+        assert!(game
+            .grid()
+            .get(Position { x: 0, y: 15 })
+            .unwrap()
+            .is_color(Color::Red));
+        assert!(game
+            .grid()
+            .get(Position { x: 1, y: 15 })
+            .unwrap()
+            .is_color(Color::Red));
+    }
+
+    #[test]
+    fn weight_wraparound() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[3][15] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        nodes[3][0] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Down,
+        };
+        let grid = Grid::new(nodes);
+
+        assert_eq!(
+            Game::builder()
+                .grid(grid.clone())
+                .build()
+                .weight(Position { x: 15, y: 3 }),
+            1
+        );
+        assert_eq!(
+            Game::builder()
+                .grid(grid)
+                .rules(Rules {
+                    wraparound: true,
+                    multi_rotation: false,
+                    super_arrow_claims: false,
+                })
+                .build()
+                .weight(Position { x: 15, y: 3 }),
+            2
+        );
+    }
+
+    /// The recursive fill this module used to have, kept to check that the iterative one makes
+    /// the same captures in the same order.
+    fn recursive_fill(game: &mut Game, position: Position, visited: &mut PositionSet) {
+        if !game.enter(position, visited) {
+            return;
+        }
+        let node = game.grid[position];
+        let directions = [
+            Direction::Left,
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+        ];
+
+        if !node.is_hidden() {
+            if let Some(direction) = node.direction() {
+                if let Some(new_position) =
+                    game.rules.neighbor(position, direction, game.grid.size())
+                {
+                    recursive_fill(game, new_position, visited);
+                }
+            } else if node.all_directions() {
+                for direction in directions {
+                    if let Some(new_position) =
+                        game.rules.neighbor(position, direction, game.grid.size())
+                    {
+                        recursive_fill(game, new_position, visited);
+                    }
+                }
+            }
+        }
+
+        for direction in directions {
+            if let Some(new_position) = game.rules.neighbor(position, direction, game.grid.size()) {
+                let new_node = game.grid[new_position];
+                if !new_node.is_hidden()
+                    && (new_node.direction() == Some(direction.opposite())
+                        || new_node.all_directions())
+                {
+                    recursive_fill(game, new_position, visited);
+                }
+            }
+        }
+    }
+
+    fn copy_for_fill(game: &Game, color: Color) -> Game {
+        Game {
+            turn_color: color,
+            turn_number: game.turn_number,
+            color_counts: game.color_counts.clone(),
+            grid: game.grid.clone(),
+            events: TurnEvents::new(),
+            rules: game.rules,
+            max_turns: game.max_turns,
+            eliminations: game.eliminations,
+            prev_state: None,
+            last_move: None,
+            history: History::new(),
+        }
+    }
+
+    #[test]
+    fn fill_matches_recursive_fill() {
+        let mut rng: Pcg32Fast = Pcg32Fast::new(1);
+        for seed in 0..8 {
+            for rules in [
+                Rules::default(),
+                Rules {
+                    wraparound: true,
+                    multi_rotation: false,
+                    super_arrow_claims: false,
+                },
+            ] {
+                let mut game = Game::builder()
+                    .grid(Grid::generate(seed))
+                    .rules(rules)
+                    .build();
+                // Scramble the board so fills cross every kind of node.
+                for _ in 0..40 {
+                    let position = Position {
+                        x: rng.gen_range(0..16),
+                        y: rng.gen_range(0..16),
+                    };
+                    let color = Color::ALL[rng.gen_range(0..4)];
+                    let mut scratch = copy_for_fill(&game, color);
+                    scratch.fill(position, &mut PositionSet::new());
+                    let mut expected = copy_for_fill(&game, color);
+                    recursive_fill(&mut expected, position, &mut PositionSet::new());
+
+                    assert!(scratch
+                        .grid
+                        .iter()
+                        .flatten()
+                        .eq(expected.grid.iter().flatten()));
+                    assert!(scratch.events.iter().eq(expected.events.iter()));
+                    for color in Color::ALL {
+                        assert_eq!(
+                            scratch.color_counts.get(color),
+                            expected.color_counts.get(color)
+                        );
+                    }
+                    game = scratch;
+                }
+            }
+        }
+    }
+
+    /// The nodes of `maximal_chain()`, with the red node moved to `red` and pointing along the
+    /// chain.
+    fn chain_nodes(red: Position) -> [[Node; 16]; 16] {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        for (y, row) in maximal_chain().grid().iter().enumerate() {
+            for (x, node) in row.iter().enumerate() {
+                nodes[y][x] = Node::Arrow {
+                    alignment: Some(Color::Blue),
+                    direction: node.direction().unwrap(),
+                };
+            }
+        }
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        nodes[red.y as usize][red.x as usize].set_color(Color::Red);
+        nodes
+    }
+
+    #[test]
+    fn fill_follows_whole_board_chain() {
+        let mut game = Game::builder()
+            .grid(Grid::new(chain_nodes(Position { x: 0, y: 0 })))
+            .build();
+
+        game.fill(Position { x: 0, y: 0 }, &mut PositionSet::new());
+
+        assert!(game
+            .grid()
+            .iter()
+            .flatten()
+            .all(|node| node.is_color(Color::Red)));
+        assert_eq!(game.verify(), Ok(()));
+        // Captures are made in the order of the chain.
+        assert!(game
+            .turn_events()
+            .iter()
+            .map(|capture| capture.position)
+            .eq((1..=TurnEvents::CAPACITY).map(|index| {
+                let y = index / 16;
+                let x = if y % 2 == 0 {
+                    index % 16
+                } else {
+                    15 - index % 16
+                };
+                Position {
+                    x: x as u8,
+                    y: y as u8,
+                }
+            })));
+    }
+
+    #[test]
+    fn fill_follows_whole_board_chain_backward() {
+        // The end of the chain, so the rest is only reached through nodes pointing into it.
+        let mut game = Game::builder()
+            .grid(Grid::new(chain_nodes(Position { x: 0, y: 15 })))
+            .build();
+
+        game.fill(Position { x: 0, y: 15 }, &mut PositionSet::new());
+
+        assert!(game
+            .grid()
+            .iter()
+            .flatten()
+            .all(|node| node.is_color(Color::Red)));
+        assert_eq!(game.verify(), Ok(()));
+    }
+
+    #[test]
+    fn fill_from_middle_of_chain_matches_recursive_fill() {
+        let red = Position { x: 7, y: 8 };
+        let game = Game::builder().grid(Grid::new(chain_nodes(red))).build();
+
+        let mut scratch = copy_for_fill(&game, Color::Red);
+        scratch.fill(red, &mut PositionSet::new());
+        let mut expected = copy_for_fill(&game, Color::Red);
+        recursive_fill(&mut expected, red, &mut PositionSet::new());
+
+        assert!(scratch
+            .grid
+            .iter()
+            .flatten()
+            .all(|node| node.is_color(Color::Red)));
+        assert!(same_state(&scratch, &expected));
+        assert!(scratch
+            .turn_events()
+            .iter()
+            .eq(expected.turn_events().iter()));
+    }
+
+    #[test]
+    fn fill_stops_at_edge_of_small_board() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0] = [Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        }; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        let mut game = Game::builder()
+            .grid(Grid::new(nodes).with_size(Size::square(8)))
+            .build();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert_eq!(game.count(Color::Red), 8);
+        assert_eq!(game.count(Color::Blue), 0);
+        assert_eq!(game.verify(), Ok(()));
+    }
+
+    #[test]
+    fn fill_wraps_around_small_board() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[2][7] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        nodes[2][0] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Up,
+        };
+        nodes[2][15] = Node::Arrow {
+            alignment: Some(Color::Green),
+            direction: Direction::Up,
+        };
+        let mut game = Game::builder()
+            .grid(Grid::new(nodes).with_size(Size::square(8)))
+            .rules(Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            })
+            .build();
+
+        // Pointing right off the edge reaches the other side of the smaller board.
+        let ending = game
+            .execute_turn(Turn {
+                rotate: Position { x: 7, y: 2 },
+                times: Turn::ONCE,
+            })
+            .unwrap()
+            .ending();
+
+        assert_eq!(ending, Some(Ending::Winner(Color::Red)));
+        assert!(game
+            .grid()
+            .get(Position { x: 0, y: 2 })
+            .unwrap()
+            .is_color(Color::Red));
+    }
+
+    #[test]
+    fn super_arrow_beam_stops_at_edge_of_small_board() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[4][0] = Node::SuperArrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        for x in 1..16 {
+            nodes[4][x] = Node::Arrow {
+                alignment: None,
+                direction: Direction::Down,
+            };
+        }
+        let mut game = Game::builder()
+            .grid(Grid::new(nodes).with_size(Size::square(8)))
+            .build();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 4 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert_eq!(game.count(Color::Red), 8);
+    }
+
+    /// A red super arrow whose beam crosses blue nodes, some of which the fill can't reach past an
+    /// empty node, before stopping at a wall.
+    fn super_arrow_across_blue() -> Grid {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[4][0] = Node::SuperArrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        for x in [1, 3, 4, 6] {
+            nodes[4][x] = Node::Arrow {
+                alignment: Some(Color::Blue),
+                direction: Direction::Down,
+            };
+        }
+        nodes[4][5] = Node::Wall;
+        nodes[10][10] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Left,
+        };
+        Grid::new(nodes)
+    }
+
+    const SUPER_ARROW_CLAIMS: Rules = Rules {
+        wraparound: false,
+        multi_rotation: false,
+        super_arrow_claims: true,
+    };
+
+    #[test]
+    fn super_arrow_beam_only_turns_without_claims() {
+        let mut game = Game::builder().grid(super_arrow_across_blue()).build();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 4 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert_eq!(game.verify(), Ok(()));
+        assert_eq!(game.count(Color::Red), 2);
+        assert_eq!(game.count(Color::Blue), 4);
+        assert_eq!(
+            game.grid()[Position { x: 3, y: 4 }],
+            Node::Arrow {
+                alignment: Some(Color::Blue),
+                direction: Direction::Right,
+            }
+        );
+    }
+
+    #[test]
+    fn super_arrow_beam_claims() {
+        let mut game = Game::builder()
+            .grid(super_arrow_across_blue())
+            .rules(SUPER_ARROW_CLAIMS)
+            .build();
+
+        let outcome = game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 4 },
+                times: Turn::ONCE,
+            })
+            .unwrap();
+
+        assert_eq!(game.verify(), Ok(()));
+        assert_eq!(outcome.captured(), 3);
+        assert_eq!(game.count(Color::Red), 4);
+        assert_eq!(game.count(Color::Blue), 2);
+        assert_eq!(game.turn_events().len(), 3);
+        for x in [1, 3, 4] {
+            assert!(
+                game.grid()[Position { x, y: 4 }].is_color(Color::Red),
+                "x {x}"
+            );
+        }
+        // The beam stops at the wall.
+        assert!(game.grid()[Position { x: 6, y: 4 }].is_color(Color::Blue));
+    }
+
+    #[test]
+    fn super_arrow_beam_claims_undo() {
+        let mut game = Game::builder()
+            .grid(super_arrow_across_blue())
+            .rules(SUPER_ARROW_CLAIMS)
+            .build();
+        let before = game.clone();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 4 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        assert!(game.undo());
+
+        assert!(same_state(&game, &before));
+    }
+
+    /// Red's super arrow shares a row with Blue's only node, with nothing but empty nodes between.
+    fn super_arrow_facing_blue() -> Grid {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[4][0] = Node::SuperArrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        nodes[4][6] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Down,
+        };
+        Grid::new(nodes)
+    }
+
+    #[test]
+    fn is_stalemate_super_arrow_without_claims() {
+        let game = Game::builder().grid(super_arrow_facing_blue()).build();
+
+        assert!(game.is_stalemate());
+    }
+
+    #[test]
+    fn is_stalemate_super_arrow_claims() {
+        let mut game = Game::builder()
+            .grid(super_arrow_facing_blue())
+            .rules(SUPER_ARROW_CLAIMS)
+            .build();
+
+        assert!(!game.is_stalemate());
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 4 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        assert_eq!(game.count(Color::Red), 2);
+    }
+
+    #[test]
+    fn is_stalemate_super_arrow_claims_blocked_by_wall() {
+        let mut grid = super_arrow_facing_blue();
+        grid[Position { x: 3, y: 4 }] = Node::Wall;
+        let game = Game::builder().grid(grid).rules(SUPER_ARROW_CLAIMS).build();
+
+        assert!(game.is_stalemate());
+    }
+
+    #[test]
+    fn beam_stops_at_wall() {
+        let game = Game::builder().grid(super_arrow_across_blue()).build();
+
+        assert!(game
+            .beam(Position { x: 0, y: 4 }, Direction::Right)
+            .eq((1..5).map(|x| Position { x, y: 4 })));
+    }
+
+    #[test]
+    fn beam_stops_at_edge() {
+        let game = Game::builder().grid(super_arrow_across_blue()).build();
+
+        assert_eq!(
+            game.beam(Position { x: 0, y: 4 }, Direction::Left).next(),
+            None
+        );
+    }
+
+    #[test]
+    fn play_small_boards() {
+        let mut rng: Pcg32Fast = Pcg32Fast::new(2);
+        for length in [8, 12] {
+            for seed in 0..8 {
+                for rules in [
+                    Rules::default(),
+                    Rules {
+                        wraparound: true,
+                        multi_rotation: false,
+                        super_arrow_claims: false,
+                    },
+                    Rules {
+                        wraparound: false,
+                        multi_rotation: false,
+                        super_arrow_claims: true,
+                    },
+                ] {
+                    let mut game = Game::builder()
+                        .grid(Grid::generate_with_size(seed, length))
+                        .rules(rules)
+                        .build();
+                    for _ in 0..300 {
+                        let turns = game.valid_turns().count();
+                        let turn = game.valid_turns().nth(rng.gen_range(0..turns)).unwrap();
+                        assert!(game.grid().size().contains(turn.rotate));
+                        let ending = game.execute_turn(turn).unwrap().ending();
+                        assert_eq!(game.verify(), Ok(()));
+                        if ending.is_some() {
+                            break;
+                        }
+                    }
+                    assert_eq!(game.grid().size(), Size::square(length));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn undo_keeps_small_board() {
+        let mut game = Game::builder().grid(Grid::generate_with_size(3, 8)).build();
+        let before = game.clone();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        assert!(game.undo());
+
+        assert!(same_state(&game, &before));
+        assert_eq!(game.grid().size(), Size::square(8));
+    }
+
+    /// Whether two games are in the same state.
+    fn same_state(game: &Game, other: &Game) -> bool {
+        game.turn_color == other.turn_color
+            && game.color_counts == other.color_counts
+            && game.grid.iter().flatten().eq(other.grid.iter().flatten())
+    }
+
+    #[test]
+    fn undo_restores_state() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        let before = game.clone();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert!(game.can_undo());
+        assert!(game.undo());
+        assert!(same_state(&game, &before));
+        assert_eq!(game.turn_events().len(), 0);
+        assert_eq!(game.verify(), Ok(()));
+    }
+
+    #[test]
+    fn undo_restores_super_arrow_beam() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[3][3] = Node::SuperArrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        for x in 4..8 {
+            nodes[3][x] = Node::Arrow {
+                alignment: Some(Color::Blue),
+                direction: Direction::Down,
+            };
+        }
+        let mut game = Game::builder().grid(Grid::new(nodes)).build();
+        let before = game.clone();
+
+        game.execute_turn(Turn {
+            rotate: Position { x: 3, y: 3 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        assert!(!same_state(&game, &before));
+
+        assert!(game.undo());
+        assert!(same_state(&game, &before));
+    }
+
+    #[test]
+    fn undo_after_trusted_turn() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        game.execute_turn_trusted(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        });
+        let before = game.clone();
+
+        assert!(!game.undo());
+        assert!(same_state(&game, &before));
+    }
+
+    #[test]
+    fn undo_after_next_turn() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        let before = game.clone();
+        // The next player responds.
+        game.execute_turn(Turn {
+            rotate: Position { x: 15, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        // Only the response is undone.
+        assert!(game.undo());
+        assert!(same_state(&game, &before));
+        assert!(!game.undo());
+    }
+
+    #[test]
+    fn undo_after_rejected_turn() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        let before = game.clone();
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        // Red's node, on blue's turn.
+        assert!(game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            })
+            .is_err());
+
+        assert!(game.undo());
+        assert!(same_state(&game, &before));
+    }
+
+    #[test]
+    fn undo_after_pass() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        game.pass_turn();
+
+        assert!(!game.undo());
+    }
+
+    #[test]
+    fn history_records_turns() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+        // Neither rejected nor passed turns are recorded.
+        assert!(game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE
+            })
+            .is_err());
+        game.pass_turn();
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 15 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert!(game.history().iter().eq([
+            history::Entry {
+                color: Color::Red,
+                position: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            },
+            history::Entry {
+                color: Color::Yellow,
+                position: Position { x: 0, y: 15 },
+                times: Turn::ONCE,
+            },
+        ]));
+    }
+
+    #[test]
+    fn history_undone() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert!(game.undo());
+
+        assert_eq!(game.history().iter().len(), 0);
+    }
+
+    #[test]
+    fn history_replays_game() {
+        let mut game = Game::builder().grid(Grid::generate(5)).build();
+        for _ in 0..60 {
+            let Some(turn) = game.valid_turns().last() else {
+                game.pass_turn();
+                continue;
+            };
+            if game.execute_turn(turn).unwrap().ending().is_some() {
+                break;
+            }
+        }
+
+        let mut replayed = Game::builder().grid(Grid::generate(5)).build();
+        for entry in game.history().iter() {
+            while replayed.turn_color() != entry.color {
+                replayed.pass_turn();
+            }
+            replayed
+                .execute_turn(Turn {
+                    rotate: entry.position,
+                    times: entry.times,
+                })
+                .unwrap();
+        }
+
+        assert!(same_state(&game, &replayed));
+    }
+
+    #[test]
+    fn replay_matches_recording() {
+        for seed in 0..4 {
+            let code = BoardCode::new(seed);
+            let mut game = Game::builder().grid(code.grid()).build();
+            let mut recording = Recording::new(code, Color::Red);
+            for turn in 0..80usize {
+                let turn_color = game.turn_color();
+                if !game.has_legal_move(turn_color) {
+                    game.pass_turn();
+                    continue;
+                }
+                let legal_moves = game
+                    .valid_turns()
+                    .map(|turn| turn.rotate)
+                    .filter(|&position| game.is_legal_move(turn_color, position));
+                let turn = Turn {
+                    rotate: legal_moves.clone().nth(turn % legal_moves.count()).unwrap(),
+                    times: Turn::ONCE,
+                };
+                recording.record(turn);
+                if game.execute_turn(turn).unwrap().ending().is_some() {
+                    break;
+                }
+            }
+
+            let replayed = Game::replay(code, Color::Red, recording.turns()).unwrap();
+
+            assert_eq!(replayed.color_counts, game.color_counts);
+            let mut bytes = [0; 256];
+            let mut replayed_bytes = [0; 256];
+            game.grid.to_bytes(&mut bytes);
+            replayed.grid.to_bytes(&mut replayed_bytes);
+            assert_eq!(replayed_bytes, bytes);
+            assert_eq!(replayed.turn_color, game.turn_color);
+        }
+    }
+
+    #[test]
+    fn replay_rejects_illegal_turn() {
+        assert_eq!(
+            Game::replay(
+                BoardCode::new(0),
+                Color::Red,
+                [
+                    Turn {
+                        rotate: Position { x: 0, y: 0 },
+                        times: Turn::ONCE
+                    },
+                    // Red's node, on blue's turn.
+                    Turn {
+                        rotate: Position { x: 0, y: 0 },
+                        times: Turn::ONCE
+                    },
+                ],
+            )
+            .unwrap_err(),
+            replay::Error {
+                index: 1,
+                error: turn::Error::NotOwned {
+                    actual: Some(Color::Red)
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn undo_only_once() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        game.execute_turn(Turn {
+            rotate: Position { x: 0, y: 0 },
+            times: Turn::ONCE,
+        })
+        .unwrap();
+
+        assert!(game.undo());
+        assert!(!game.undo());
+    }
+
+    #[test]
+    fn rejected_turn_not_undoable() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+
+        // Blue's node, on red's turn.
+        assert_eq!(
+            game.execute_turn(Turn {
+                rotate: Position { x: 15, y: 0 },
+                times: Turn::ONCE
+            })
+            .unwrap_err(),
+            turn::Error::NotOwned {
+                actual: Some(Color::Blue)
+            }
+        );
+        assert!(!game.can_undo());
     }
 }
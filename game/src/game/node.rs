@@ -1,6 +1,6 @@
 use super::{Color, Direction};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Node {
     Empty,
     Wall,
@@ -8,6 +8,11 @@ pub enum Node {
         alignment: Option<Color>,
         direction: Direction,
     },
+    /// An arrow that rotates counter-clockwise instead of clockwise.
+    ReverseArrow {
+        alignment: Option<Color>,
+        direction: Direction,
+    },
     // "Secret" nodes.
     AllDirection {
         alignment: Option<Color>,
@@ -16,14 +21,30 @@ pub enum Node {
         alignment: Option<Color>,
         direction: Direction,
     },
+    /// Warps a fill to the other teleporter with the same `id`, as though the two were neighbors.
+    ///
+    /// Only `TELEPORTER_IDS` ids fit in a node's encoding.
+    Teleporter {
+        alignment: Option<Color>,
+        id: u8,
+    },
 }
 
+/// The number of distinct teleporter ids, and so of teleporter pairs a board can hold.
+pub const TELEPORTER_IDS: u8 = 4;
+
+/// A byte that does not encode any `Node`.
+#[derive(Debug, Eq, PartialEq)]
+pub struct InvalidNode(pub u8);
+
 impl Node {
     pub fn color(&self) -> Option<Color> {
         match self {
             Self::Arrow { alignment, .. }
+            | Self::ReverseArrow { alignment, .. }
             | Self::AllDirection { alignment }
-            | Self::SuperArrow { alignment, .. } => *alignment,
+            | Self::SuperArrow { alignment, .. }
+            | Self::Teleporter { alignment, .. } => *alignment,
             _ => None,
         }
     }
@@ -31,8 +52,10 @@ impl Node {
     pub fn is_color(&self, color: Color) -> bool {
         match self {
             Self::Arrow { alignment, .. }
+            | Self::ReverseArrow { alignment, .. }
             | Self::AllDirection { alignment }
-            | Self::SuperArrow { alignment, .. } => *alignment == Some(color),
+            | Self::SuperArrow { alignment, .. }
+            | Self::Teleporter { alignment, .. } => *alignment == Some(color),
             _ => false,
         }
     }
@@ -42,13 +65,16 @@ impl Node {
             Self::Arrow { direction, .. } | Self::SuperArrow { direction, .. } => {
                 direction.rotate()
             }
+            Self::ReverseArrow { direction, .. } => *direction = direction.counter_clockwise(),
             _ => {}
         }
     }
 
     pub fn set_direction(&mut self, new_direction: Direction) {
         match self {
-            Self::Arrow { direction, .. } | Self::SuperArrow { direction, .. } => {
+            Self::Arrow { direction, .. }
+            | Self::ReverseArrow { direction, .. }
+            | Self::SuperArrow { direction, .. } => {
                 *direction = new_direction;
             }
             _ => {}
@@ -57,7 +83,7 @@ impl Node {
 
     pub fn direction(&self) -> Option<Direction> {
         if let Node::Arrow { direction, .. }
-        | Self::SuperArrow { direction, .. }
+        | Self::ReverseArrow { direction, .. }
         | Self::SuperArrow { direction, .. } = self
         {
             Some(*direction)
@@ -75,8 +101,10 @@ impl Node {
     /// If the alignment was already `color`, then `false` is returned.
     pub fn set_color(&mut self, color: Color) -> bool {
         if let Node::Arrow { alignment, .. }
+        | Self::ReverseArrow { alignment, .. }
         | Self::AllDirection { alignment }
-        | Self::SuperArrow { alignment, .. } = self
+        | Self::SuperArrow { alignment, .. }
+        | Self::Teleporter { alignment, .. } = self
         {
             if *alignment == Some(color) {
                 false
@@ -89,11 +117,23 @@ impl Node {
         }
     }
 
+    /// Remove the node's alignment, if it has one.
+    pub fn clear_color(&mut self) {
+        if let Node::Arrow { alignment, .. }
+        | Self::ReverseArrow { alignment, .. }
+        | Self::AllDirection { alignment }
+        | Self::SuperArrow { alignment, .. }
+        | Self::Teleporter { alignment, .. } = self
+        {
+            *alignment = None;
+        }
+    }
+
     pub fn is_hidden(&self) -> bool {
         match self {
-            Node::AllDirection { alignment } | Self::SuperArrow { alignment, .. } => {
-                alignment.is_none()
-            }
+            Node::AllDirection { alignment }
+            | Self::SuperArrow { alignment, .. }
+            | Self::Teleporter { alignment, .. } => alignment.is_none(),
             _ => false,
         }
     }
@@ -101,4 +141,325 @@ impl Node {
     pub fn is_wall(&self) -> bool {
         matches!(self, Self::Wall)
     }
+
+    /// The id shared with this node's twin, if it is a teleporter.
+    pub fn teleporter_id(&self) -> Option<u8> {
+        if let Self::Teleporter { id, .. } = self {
+            Some(*id)
+        } else {
+            None
+        }
+    }
+
+    /// Encode into a single byte.
+    ///
+    /// The low three bits are the kind of node (empty, wall, arrow, all-direction, super arrow,
+    /// teleporter, and reverse arrow, in that order), the next three are the alignment (0 for none, otherwise the
+    /// color's value plus one), and the top two are the direction, or a teleporter's id. Nodes
+    /// without an alignment or direction leave those bits clear.
+    ///
+    /// # Panics
+    /// Panics if a teleporter's id is not below `TELEPORTER_IDS`.
+    ///
+    /// This format is saved, so changing it needs a bump of `compat::COMPAT_VERSION`.
+    pub fn encode(self) -> u8 {
+        let (kind, alignment, top) = match self {
+            Self::Empty => (0, None, 0),
+            Self::Wall => (1, None, 0),
+            Self::Arrow {
+                alignment,
+                direction,
+            } => (2, alignment, direction as u8),
+            Self::AllDirection { alignment } => (3, alignment, 0),
+            Self::SuperArrow {
+                alignment,
+                direction,
+            } => (4, alignment, direction as u8),
+            Self::Teleporter { alignment, id } => {
+                assert!(id < TELEPORTER_IDS, "teleporter id {id} can't be encoded");
+                (5, alignment, id)
+            }
+            Self::ReverseArrow {
+                alignment,
+                direction,
+            } => (6, alignment, direction as u8),
+        };
+        kind | alignment.map_or(0, |color| u8::from(color) + 1) << 3 | top << 6
+    }
+
+    /// Decode a byte produced by `encode()`.
+    ///
+    /// Every byte `encode()` can't produce is rejected, including ones giving an alignment or
+    /// direction to a node that can't have one.
+    pub fn decode(byte: u8) -> Result<Self, InvalidNode> {
+        let alignment = match (byte >> 3) & 0b111 {
+            0 => None,
+            color => Some(Color::try_from(color - 1).map_err(|_| InvalidNode(byte))?),
+        };
+        let top = byte >> 6;
+        let direction = match top {
+            0 => Direction::Left,
+            1 => Direction::Up,
+            2 => Direction::Right,
+            _ => Direction::Down,
+        };
+        let node = match byte & 0b111 {
+            0 => Self::Empty,
+            1 => Self::Wall,
+            2 => Self::Arrow {
+                alignment,
+                direction,
+            },
+            3 => Self::AllDirection { alignment },
+            4 => Self::SuperArrow {
+                alignment,
+                direction,
+            },
+            5 => Self::Teleporter { alignment, id: top },
+            6 => Self::ReverseArrow {
+                alignment,
+                direction,
+            },
+            _ => return Err(InvalidNode(byte)),
+        };
+        // Anything left over belongs to a field the node doesn't have.
+        if node.encode() != byte {
+            return Err(InvalidNode(byte));
+        }
+        Ok(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InvalidNode, Node, TELEPORTER_IDS};
+    use crate::game::{Color, Direction};
+    use gba_test::test;
+
+    const HIDDEN_SUPER_ARROW: Node = Node::SuperArrow {
+        alignment: None,
+        direction: Direction::Up,
+    };
+
+    #[test]
+    fn super_arrow_hidden_until_captured() {
+        let mut node = HIDDEN_SUPER_ARROW;
+        assert!(node.is_hidden());
+
+        assert!(node.set_color(Color::Red));
+
+        assert!(!node.is_hidden());
+        assert_eq!(node.color(), Some(Color::Red));
+        assert!(node.is_color(Color::Red));
+        assert!(!node.is_color(Color::Blue));
+    }
+
+    #[test]
+    fn super_arrow_set_color_unchanged() {
+        let mut node = Node::SuperArrow {
+            alignment: Some(Color::Green),
+            direction: Direction::Left,
+        };
+
+        assert!(!node.set_color(Color::Green));
+    }
+
+    #[test]
+    fn super_arrow_rotate() {
+        let mut node = HIDDEN_SUPER_ARROW;
+
+        node.rotate();
+
+        assert_eq!(node.direction(), Some(Direction::Right));
+    }
+
+    #[test]
+    fn super_arrow_set_direction() {
+        let mut node = HIDDEN_SUPER_ARROW;
+
+        node.set_direction(Direction::Down);
+
+        assert_eq!(node.direction(), Some(Direction::Down));
+        assert!(!node.all_directions());
+    }
+
+    #[test]
+    fn encode_round_trip() {
+        let round_trips = |node: Node| Node::decode(node.encode()) == Ok(node);
+
+        assert!(round_trips(Node::Empty));
+        assert!(round_trips(Node::Wall));
+        for alignment in [
+            None,
+            Some(Color::Red),
+            Some(Color::Blue),
+            Some(Color::Yellow),
+            Some(Color::Green),
+        ] {
+            assert!(round_trips(Node::AllDirection { alignment }));
+            for id in 0..TELEPORTER_IDS {
+                assert!(round_trips(Node::Teleporter { alignment, id }));
+            }
+            for direction in [
+                Direction::Left,
+                Direction::Up,
+                Direction::Right,
+                Direction::Down,
+            ] {
+                assert!(round_trips(Node::Arrow {
+                    alignment,
+                    direction
+                }));
+                assert!(round_trips(Node::SuperArrow {
+                    alignment,
+                    direction
+                }));
+                assert!(round_trips(Node::ReverseArrow {
+                    alignment,
+                    direction
+                }));
+            }
+        }
+    }
+
+    #[test]
+    fn encode_golden() {
+        assert_eq!(Node::Empty.encode(), 0b00_000_000);
+        assert_eq!(Node::Wall.encode(), 0b00_000_001);
+        assert_eq!(
+            Node::Arrow {
+                alignment: Some(Color::Red),
+                direction: Direction::Up,
+            }
+            .encode(),
+            0b01_001_010
+        );
+        assert_eq!(
+            Node::AllDirection {
+                alignment: Some(Color::Green),
+            }
+            .encode(),
+            0b00_100_011
+        );
+        assert_eq!(
+            Node::SuperArrow {
+                alignment: None,
+                direction: Direction::Down,
+            }
+            .encode(),
+            0b11_000_100
+        );
+        assert_eq!(
+            Node::Teleporter {
+                alignment: Some(Color::Blue),
+                id: 2,
+            }
+            .encode(),
+            0b10_010_101
+        );
+        assert_eq!(
+            Node::ReverseArrow {
+                alignment: Some(Color::Yellow),
+                direction: Direction::Right,
+            }
+            .encode(),
+            0b10_011_110
+        );
+    }
+
+    #[test]
+    fn decode_every_byte() {
+        let mut valid = 0;
+        for byte in 0..=u8::MAX {
+            match Node::decode(byte) {
+                Ok(node) => {
+                    assert_eq!(node.encode(), byte);
+                    valid += 1;
+                }
+                Err(error) => assert_eq!(error, InvalidNode(byte)),
+            }
+        }
+        // Empty and wall, plus every alignment of an all-direction node, every alignment and
+        // direction of an arrow, a super arrow, and a reverse arrow, and every alignment and id of
+        // a teleporter.
+        assert_eq!(valid, 2 + 5 + 5 * 4 * 3 + 5 * TELEPORTER_IDS as usize);
+    }
+
+    #[test]
+    fn decode_invalid_kind() {
+        assert_eq!(Node::decode(0b00_000_111), Err(InvalidNode(0b00_000_111)));
+    }
+
+    #[test]
+    fn decode_invalid_color() {
+        assert_eq!(Node::decode(0b00_101_010), Err(InvalidNode(0b00_101_010)));
+    }
+
+    #[test]
+    fn decode_wall_with_color() {
+        assert_eq!(Node::decode(0b00_001_001), Err(InvalidNode(0b00_001_001)));
+    }
+
+    #[test]
+    fn decode_all_direction_with_direction() {
+        assert_eq!(Node::decode(0b10_000_011), Err(InvalidNode(0b10_000_011)));
+    }
+
+    #[test]
+    fn reverse_arrow_rotates_counter_clockwise() {
+        let mut node = Node::ReverseArrow {
+            alignment: None,
+            direction: Direction::Up,
+        };
+        assert!(!node.is_hidden());
+
+        node.rotate();
+        assert_eq!(node.direction(), Some(Direction::Left));
+        node.rotate();
+        assert_eq!(node.direction(), Some(Direction::Down));
+    }
+
+    #[test]
+    fn reverse_arrow_set_color() {
+        let mut node = Node::ReverseArrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Left,
+        };
+
+        assert!(node.set_color(Color::Red));
+        assert!(!node.set_color(Color::Red));
+        assert!(node.is_color(Color::Red));
+        assert_eq!(node.color(), Some(Color::Red));
+    }
+
+    #[test]
+    fn teleporter_hidden_until_captured() {
+        let mut node = Node::Teleporter {
+            alignment: None,
+            id: 1,
+        };
+        assert!(node.is_hidden());
+
+        assert!(node.set_color(Color::Yellow));
+        node.rotate();
+
+        assert!(!node.is_hidden());
+        assert_eq!(node.color(), Some(Color::Yellow));
+        assert_eq!(node.direction(), None);
+        assert!(!node.all_directions());
+        assert_eq!(node.teleporter_id(), Some(1));
+    }
+
+    #[test]
+    fn wall_has_no_color_or_direction() {
+        let mut node = Node::Wall;
+
+        assert!(!node.set_color(Color::Red));
+        node.rotate();
+
+        assert_eq!(node.color(), None);
+        assert_eq!(node.direction(), None);
+        assert!(!node.is_hidden());
+        assert!(node.is_wall());
+    }
 }
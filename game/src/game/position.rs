@@ -1,6 +1,6 @@
-use super::Direction;
+use super::{Direction, Size};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Position {
     pub x: u8,
     pub y: u8,
@@ -31,6 +31,52 @@ impl Position {
         }
     }
 
+    /// Move one step in the given direction, wrapping around to the opposite edge of a board of
+    /// `size`.
+    pub fn move_wrapping(self, direction: Direction, size: Size) -> Position {
+        match direction {
+            Direction::Left => Position {
+                x: self.x.checked_sub(1).unwrap_or(size.width - 1),
+                y: self.y,
+            },
+            Direction::Up => Position {
+                x: self.x,
+                y: self.y.checked_sub(1).unwrap_or(size.height - 1),
+            },
+            Direction::Right => Position {
+                x: (self.x + 1) % size.width,
+                y: self.y,
+            },
+            Direction::Down => Position {
+                x: self.x,
+                y: (self.y + 1) % size.height,
+            },
+        }
+    }
+
+    /// The positions one step away in each direction, skipping any that are out of bounds.
+    ///
+    /// Neighbors are given in the order left, up, right, down. This doesn't wrap around or account
+    /// for smaller boards; use `Rules::neighbor()` for that.
+    pub fn adjacent(self) -> impl Iterator<Item = Position> {
+        [
+            Direction::Left,
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+        ]
+        .into_iter()
+        .filter_map(move |direction| self.r#move(direction))
+    }
+
+    /// The number of steps between two positions, moving only horizontally and vertically.
+    ///
+    /// This doesn't wrap around the edges of the board.
+    pub fn manhattan_distance(self, other: Position) -> u16 {
+        (self.x as i16 - other.x as i16).unsigned_abs()
+            + (self.y as i16 - other.y as i16).unsigned_abs()
+    }
+
     pub fn move_saturating(self, direction: Direction, max: Position) -> Position {
         if let Some(new_position) = self.r#move(direction) {
             if new_position.x <= max.x && new_position.y <= max.y {
@@ -43,3 +89,76 @@ impl Position {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Position;
+    use gba_test::test;
+
+    #[test]
+    fn adjacent_corner() {
+        assert!(Position { x: 0, y: 0 }
+            .adjacent()
+            .eq([Position { x: 1, y: 0 }, Position { x: 0, y: 1 }]));
+        assert!(Position { x: 15, y: 15 }
+            .adjacent()
+            .eq([Position { x: 14, y: 15 }, Position { x: 15, y: 14 }]));
+    }
+
+    #[test]
+    fn adjacent_edge() {
+        assert_eq!(Position { x: 7, y: 0 }.adjacent().count(), 3);
+    }
+
+    #[test]
+    fn adjacent_center() {
+        assert!(Position { x: 7, y: 8 }.adjacent().eq([
+            Position { x: 6, y: 8 },
+            Position { x: 7, y: 7 },
+            Position { x: 8, y: 8 },
+            Position { x: 7, y: 9 },
+        ]));
+    }
+
+    #[test]
+    fn manhattan_distance_same_position() {
+        let position = Position { x: 4, y: 9 };
+
+        assert_eq!(position.manhattan_distance(position), 0);
+    }
+
+    #[test]
+    fn manhattan_distance_adjacent() {
+        let position = Position { x: 4, y: 9 };
+
+        for other in position.adjacent() {
+            assert_eq!(position.manhattan_distance(other), 1);
+            assert_eq!(other.manhattan_distance(position), 1);
+        }
+    }
+
+    #[test]
+    fn manhattan_distance_corners() {
+        assert_eq!(
+            Position { x: 0, y: 0 }.manhattan_distance(Position { x: 15, y: 15 }),
+            30
+        );
+        assert_eq!(
+            Position { x: 15, y: 0 }.manhattan_distance(Position { x: 0, y: 15 }),
+            30
+        );
+    }
+
+    #[test]
+    fn manhattan_distance_does_not_wrap() {
+        // These are neighbors when the board wraps around, but not here.
+        assert_eq!(
+            Position { x: 0, y: 3 }.manhattan_distance(Position { x: 15, y: 3 }),
+            15
+        );
+        assert_eq!(
+            Position { x: 3, y: 15 }.manhattan_distance(Position { x: 3, y: 0 }),
+            15
+        );
+    }
+}
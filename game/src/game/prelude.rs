@@ -0,0 +1,45 @@
+//! The types needed to set up and play a game.
+//!
+//! Code driving the rules engine, such as the AIs and the board renderer, can use
+//! `crate::game::prelude::*` instead of naming each of these.
+
+pub use super::{Color, Direction, Ending, Game, Grid, Node, Position, Rules, Turn};
+
+#[cfg(test)]
+mod tests {
+    use super::{Color, Direction, Game, Grid, Node, Position, Rules, Turn};
+    use gba_test::test;
+
+    #[test]
+    fn prelude_plays_two_turns() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Right,
+        };
+        nodes[0][2] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Left,
+        };
+        let mut game = Game::builder()
+            .grid(Grid::new(nodes))
+            .rules(Rules::default())
+            .turn_color(Color::Red)
+            .build();
+
+        assert!(game
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE
+            })
+            .is_ok());
+        assert_eq!(game.turn_color(), Color::Blue);
+        assert!(game
+            .execute_turn(Turn {
+                rotate: Position { x: 2, y: 0 },
+                times: Turn::ONCE
+            })
+            .is_ok());
+        assert_eq!(game.turn_color(), Color::Red);
+    }
+}
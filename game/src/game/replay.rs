@@ -0,0 +1,411 @@
+//! Recorded games that can be played back.
+
+use super::{turn, BoardCode, Color, Game, Position, Turn};
+
+/// A recorded game.
+#[derive(Debug)]
+pub struct Replay {
+    /// The board the game was played on.
+    pub code: BoardCode,
+    /// Every position rotated, in order.
+    ///
+    /// Passed turns are not recorded, since they are determined by the game state.
+    pub turns: &'static [Position],
+    /// The hash of the game state after all turns were played.
+    pub final_hash: u32,
+}
+
+impl Replay {
+    /// The opening turns of a game between four greedy players.
+    pub const DEMO: Replay = Replay {
+        code: BoardCode::new(0),
+        turns: &[
+            Position { x: 0, y: 0 },
+            Position { x: 15, y: 0 },
+            Position { x: 0, y: 15 },
+            Position { x: 15, y: 15 },
+            Position { x: 0, y: 0 },
+            Position { x: 14, y: 1 },
+            Position { x: 0, y: 15 },
+            Position { x: 14, y: 14 },
+            Position { x: 0, y: 0 },
+            Position { x: 10, y: 0 },
+            Position { x: 0, y: 13 },
+            Position { x: 13, y: 11 },
+            Position { x: 0, y: 0 },
+            Position { x: 10, y: 0 },
+            Position { x: 0, y: 13 },
+            Position { x: 13, y: 11 },
+            Position { x: 0, y: 0 },
+            Position { x: 9, y: 0 },
+            Position { x: 2, y: 11 },
+            Position { x: 12, y: 9 },
+            Position { x: 0, y: 0 },
+            Position { x: 9, y: 0 },
+            Position { x: 5, y: 9 },
+            Position { x: 9, y: 10 },
+            Position { x: 0, y: 0 },
+            Position { x: 9, y: 0 },
+            Position { x: 7, y: 10 },
+            Position { x: 8, y: 10 },
+            Position { x: 0, y: 0 },
+            Position { x: 6, y: 1 },
+            Position { x: 5, y: 8 },
+            Position { x: 6, y: 6 },
+            Position { x: 0, y: 0 },
+            Position { x: 8, y: 3 },
+            Position { x: 4, y: 7 },
+            Position { x: 7, y: 5 },
+            Position { x: 0, y: 0 },
+            Position { x: 8, y: 3 },
+            Position { x: 0, y: 11 },
+            Position { x: 6, y: 5 },
+            Position { x: 0, y: 0 },
+            Position { x: 9, y: 3 },
+            Position { x: 3, y: 11 },
+            Position { x: 6, y: 9 },
+            Position { x: 0, y: 0 },
+            Position { x: 10, y: 6 },
+            Position { x: 1, y: 9 },
+            Position { x: 5, y: 7 },
+        ],
+        final_hash: 0xf108_79d7,
+    };
+}
+
+/// Why a sequence of turns couldn't be replayed.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Error {
+    /// The index of the first turn that was rejected.
+    pub index: usize,
+    /// Why the turn was rejected.
+    pub error: turn::Error,
+}
+
+/// What happened when a replay was stepped.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Step {
+    /// The given position was rotated.
+    Turn(Position),
+    /// The current player had no legal move and their turn was passed.
+    Pass,
+}
+
+/// Plays back a replay one turn at a time.
+#[derive(Debug)]
+pub struct Playback {
+    replay: &'static Replay,
+    game: Game,
+    /// The index of the next turn in `replay.turns`.
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(replay: &'static Replay) -> Self {
+        Self {
+            replay,
+            // Replays are always recorded with red going first.
+            game: Game::from_code(replay.code, Color::Red).build(),
+            next: 0,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Whether every recorded turn has been played.
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.replay.turns.len()
+    }
+
+    /// Play the next turn.
+    ///
+    /// Returns `None` once the replay is finished.
+    pub fn step(&mut self) -> Option<Step> {
+        let position = *self.replay.turns.get(self.next)?;
+        if !self.game.has_legal_move(self.game.turn_color()) {
+            self.game.pass_turn();
+            return Some(Step::Pass);
+        }
+        self.game.execute_turn_trusted(Turn {
+            rotate: position,
+            times: Turn::ONCE,
+        });
+        self.next += 1;
+        Some(Step::Turn(position))
+    }
+
+    /// Whether the game state matches the state the replay was recorded with.
+    ///
+    /// This is only meaningful once the replay is finished.
+    pub fn matches_recording(&self) -> bool {
+        hash(&self.game) == self.replay.final_hash
+    }
+}
+
+/// The most turns a `Recording` can hold.
+pub const MAX_RECORDED_TURNS: usize = 1024;
+
+/// The turns of a game as it is played, so that it can be exported once it's over.
+///
+/// Turns are packed into a single byte each, with `x` in the high nibble and `y` in the low nibble.
+/// There is no room for the number of rotations, so only turns rotating their node once are
+/// recorded faithfully.
+#[derive(Debug)]
+pub struct Recording {
+    code: BoardCode,
+    /// The color that took the first turn.
+    first: Color,
+    turns: [u8; MAX_RECORDED_TURNS],
+    len: usize,
+    /// Whether any turns are missing, either because they were played after the recording was
+    /// full or because they were played before the recording started.
+    truncated: bool,
+    /// Whether a turn rotated its node more than once.
+    multiple_rotations: bool,
+}
+
+impl Recording {
+    pub fn new(code: BoardCode, first: Color) -> Self {
+        Self {
+            code,
+            first,
+            turns: [0; MAX_RECORDED_TURNS],
+            len: 0,
+            truncated: false,
+            multiple_rotations: false,
+        }
+    }
+
+    pub fn code(&self) -> BoardCode {
+        self.code
+    }
+
+    pub fn first(&self) -> Color {
+        self.first
+    }
+
+    /// Record `turn`.
+    ///
+    /// Turns played once the recording is full are dropped, and the recording is marked as
+    /// truncated. A turn rotating its node more than once is recorded as its position alone, and
+    /// the recording is marked as having multiple rotations.
+    pub fn record(&mut self, turn: Turn) {
+        if turn.times != Turn::ONCE {
+            self.multiple_rotations = true;
+        }
+        match self.turns.get_mut(self.len) {
+            Some(packed) => {
+                *packed = turn.rotate.x << 4 | turn.rotate.y;
+                self.len += 1;
+            }
+            None => self.truncated = true,
+        }
+    }
+
+    /// Mark the recording as missing the turns before it started, such as when a saved game is
+    /// resumed.
+    pub fn truncate(&mut self) {
+        self.truncated = true;
+    }
+
+    /// Forget the most recently recorded turn, such as when it is undone.
+    ///
+    /// Once turns have been dropped, there's no telling whether the last one was kept, so a
+    /// truncated recording is left as is. It can't be played back either way.
+    pub fn remove_last(&mut self) {
+        if !self.truncated {
+            self.len = self.len.saturating_sub(1);
+        }
+    }
+
+    /// The recorded turns, packed one per byte.
+    pub fn packed_turns(&self) -> &[u8] {
+        &self.turns[..self.len]
+    }
+
+    /// The recorded turns, in the order they were taken.
+    pub fn turns(&self) -> impl Iterator<Item = Turn> + '_ {
+        self.packed_turns().iter().map(|&turn| Turn {
+            rotate: unpack_turn(turn),
+            times: Turn::ONCE,
+        })
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Whether any turn rotated its node more than once, in which case the recording can't be
+    /// played back.
+    pub fn has_multiple_rotations(&self) -> bool {
+        self.multiple_rotations
+    }
+}
+
+/// Unpack a turn packed by `Recording`.
+pub fn unpack_turn(turn: u8) -> Position {
+    Position {
+        x: turn >> 4,
+        y: turn & 0xf,
+    }
+}
+
+/// Hash the game state, using 32-bit FNV-1a.
+pub fn hash(game: &Game) -> u32 {
+    let mut hash = 0x811c_9dc5u32;
+    let mut write = |byte: u8| {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    };
+    write(u8::from(game.turn_color()));
+    for y in 0..16 {
+        for byte in game.grid().ascii_row(y) {
+            write(byte);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash, unpack_turn, Playback, Recording, Replay, Step, MAX_RECORDED_TURNS};
+    use crate::game::{BoardCode, Color, Game, Grid, Position, Turn};
+    use deranged::RangedU8;
+    use gba_test::test;
+
+    #[test]
+    fn hash_differs_by_turn_color() {
+        let red = Game::builder().grid(Grid::generate(0)).build();
+        let blue = Game::builder()
+            .grid(Grid::generate(0))
+            .turn_color(Color::Blue)
+            .build();
+
+        assert_ne!(hash(&red), hash(&blue));
+    }
+
+    #[test]
+    fn hash_differs_by_grid() {
+        let game = Game::builder().grid(Grid::generate(0)).build();
+        let mut other = Game::builder().grid(Grid::generate(0)).build();
+        other
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            })
+            .unwrap();
+
+        assert_ne!(hash(&game), hash(&other));
+    }
+
+    #[test]
+    fn demo_plays_to_completion() {
+        let mut playback = Playback::new(&Replay::DEMO);
+
+        let mut turns = 0;
+        while let Some(step) = playback.step() {
+            if let Step::Turn(_) = step {
+                turns += 1;
+            }
+        }
+
+        assert!(playback.is_finished());
+        assert_eq!(turns, Replay::DEMO.turns.len());
+        assert!(playback.matches_recording());
+    }
+
+    #[test]
+    fn recording_round_trip() {
+        let mut recording = Recording::new(BoardCode::new(0), Color::Red);
+        for &position in Replay::DEMO.turns {
+            recording.record(Turn {
+                rotate: position,
+                times: Turn::ONCE,
+            });
+        }
+
+        assert!(recording
+            .packed_turns()
+            .iter()
+            .map(|&turn| unpack_turn(turn))
+            .eq(Replay::DEMO.turns.iter().copied()));
+        assert!(!recording.is_truncated());
+        assert!(!recording.has_multiple_rotations());
+    }
+
+    #[test]
+    fn recording_multiple_rotations() {
+        let mut recording = Recording::new(BoardCode::new(0), Color::Red);
+        recording.record(Turn {
+            rotate: Position { x: 1, y: 2 },
+            times: RangedU8::new_static::<3>(),
+        });
+
+        assert!(recording.has_multiple_rotations());
+        assert!(recording
+            .packed_turns()
+            .iter()
+            .map(|&turn| unpack_turn(turn))
+            .eq([Position { x: 1, y: 2 }]));
+    }
+
+    #[test]
+    fn recording_truncated_when_full() {
+        let mut recording = Recording::new(BoardCode::new(0), Color::Red);
+        for _ in 0..=MAX_RECORDED_TURNS {
+            recording.record(Turn {
+                rotate: Position { x: 15, y: 15 },
+                times: Turn::ONCE,
+            });
+        }
+
+        assert_eq!(recording.packed_turns().len(), MAX_RECORDED_TURNS);
+        assert!(recording.is_truncated());
+    }
+
+    #[test]
+    fn recording_truncate() {
+        let mut recording = Recording::new(BoardCode::new(0), Color::Red);
+
+        recording.truncate();
+        recording.record(Turn {
+            rotate: Position { x: 1, y: 2 },
+            times: Turn::ONCE,
+        });
+
+        assert!(recording.is_truncated());
+        assert_eq!(recording.packed_turns().len(), 1);
+    }
+
+    #[test]
+    fn recording_remove_last() {
+        let mut recording = Recording::new(BoardCode::new(0), Color::Red);
+        recording.record(Turn {
+            rotate: Position { x: 1, y: 2 },
+            times: Turn::ONCE,
+        });
+        recording.record(Turn {
+            rotate: Position { x: 3, y: 4 },
+            times: Turn::ONCE,
+        });
+
+        recording.remove_last();
+
+        assert!(recording
+            .packed_turns()
+            .iter()
+            .map(|&turn| unpack_turn(turn))
+            .eq([Position { x: 1, y: 2 }]));
+    }
+
+    #[test]
+    fn recording_remove_last_empty() {
+        let mut recording = Recording::new(BoardCode::new(0), Color::Red);
+
+        recording.remove_last();
+
+        assert!(recording.packed_turns().is_empty());
+    }
+}
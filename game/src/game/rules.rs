@@ -0,0 +1,109 @@
+use super::{Direction, Position, Size};
+
+/// Optional rules a game can be played with.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rules {
+    /// Whether nodes pointing off an edge of the board point at the node on the opposite edge
+    /// instead.
+    pub wraparound: bool,
+    /// Whether a turn can rotate its node up to three times instead of once.
+    pub multi_rotation: bool,
+    /// Whether a super arrow's beam claims the nodes it passes over for the current color, rather
+    /// than only turning them.
+    pub super_arrow_claims: bool,
+}
+
+impl Rules {
+    /// The position one step away from `position` in the given direction on a board of `size`,
+    /// if there is one.
+    pub fn neighbor(
+        &self,
+        position: Position,
+        direction: Direction,
+        size: Size,
+    ) -> Option<Position> {
+        if self.wraparound {
+            Some(position.move_wrapping(direction, size))
+        } else {
+            position
+                .r#move(direction)
+                .filter(|&position| size.contains(position))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rules;
+    use crate::game::{Direction, Position, Size};
+    use gba_test::test;
+
+    #[test]
+    fn neighbor_off_board() {
+        assert_eq!(
+            Rules::default().neighbor(Position { x: 0, y: 3 }, Direction::Left, Size::MAX),
+            None
+        );
+    }
+
+    #[test]
+    fn neighbor_off_board_wraparound() {
+        assert_eq!(
+            Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            }
+            .neighbor(Position { x: 0, y: 3 }, Direction::Left, Size::MAX),
+            Some(Position { x: 15, y: 3 })
+        );
+    }
+
+    #[test]
+    fn neighbor_on_board_wraparound() {
+        assert_eq!(
+            Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            }
+            .neighbor(Position { x: 4, y: 3 }, Direction::Down, Size::MAX),
+            Some(Position { x: 4, y: 4 })
+        );
+    }
+
+    #[test]
+    fn neighbor_off_small_board() {
+        assert_eq!(
+            Rules::default().neighbor(Position { x: 7, y: 3 }, Direction::Right, Size::square(8)),
+            None
+        );
+    }
+
+    #[test]
+    fn neighbor_off_small_board_wraparound() {
+        let size = Size {
+            width: 8,
+            height: 12,
+        };
+
+        assert_eq!(
+            Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            }
+            .neighbor(Position { x: 7, y: 3 }, Direction::Right, size),
+            Some(Position { x: 0, y: 3 })
+        );
+        assert_eq!(
+            Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            }
+            .neighbor(Position { x: 2, y: 0 }, Direction::Up, size),
+            Some(Position { x: 2, y: 11 })
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! A game in progress, kept in SRAM so it can be resumed later.
+
+use super::{compat::COMPAT_VERSION, Color, Game, Grid, Rules, Size};
+use crate::{
+    mmio::{sram_read, sram_write},
+    records::checksum,
+};
+use core::num::NonZeroU16;
+
+/// Identifies a valid saved game, and its layout version.
+const MAGIC: [u8; 4] = *b"TORG";
+
+/// Where the game is stored in SRAM, after the rating.
+const OFFSET: usize = 0x200;
+
+/// The number of bytes used by the encoded nodes, which come first.
+const NODES_LEN: usize = 256;
+
+/// The number of bytes used by a saved game: the nodes, the magic, the compatibility version, the
+/// turn color, the board's width and height, the rules, the turn number, the turn limit, the
+/// elimination order, and a checksum.
+const LEN: usize = NODES_LEN + MAGIC.len() + 2 + 1 + 2 + 1 + 4 + 2 + 4 + 2;
+
+/// Stands in for a color in the elimination order, once every eliminated color is listed.
+const NOT_ELIMINATED: u8 = 0xff;
+
+/// Bits of the byte storing the rules.
+const WRAPAROUND: u8 = 0b01;
+const MULTI_ROTATION: u8 = 0b10;
+const SUPER_ARROW_CLAIMS: u8 = 0b100;
+
+impl Game {
+    fn to_save_bytes(&self) -> [u8; LEN] {
+        let mut bytes = [0; LEN];
+        let (nodes, rest) = bytes.split_at_mut(NODES_LEN);
+        self.grid.to_bytes(nodes.try_into().unwrap());
+        rest[..MAGIC.len()].copy_from_slice(&MAGIC);
+        rest[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&COMPAT_VERSION.to_le_bytes());
+        let rest = &mut rest[MAGIC.len() + 2..];
+        rest[0] = u8::from(self.turn_color);
+        rest[1] = self.grid.size().width;
+        rest[2] = self.grid.size().height;
+        rest[3] = (self.rules.wraparound as u8 * WRAPAROUND)
+            | (self.rules.multi_rotation as u8 * MULTI_ROTATION)
+            | (self.rules.super_arrow_claims as u8 * SUPER_ARROW_CLAIMS);
+        rest[4..8].copy_from_slice(&self.turn_number.to_le_bytes());
+        // No limit is stored as 0.
+        rest[8..10].copy_from_slice(&self.max_turns.map_or(0, NonZeroU16::get).to_le_bytes());
+        for (byte, color) in rest[10..14].iter_mut().zip(self.eliminations) {
+            *byte = color.map_or(NOT_ELIMINATED, u8::from);
+        }
+        let checksum = checksum(&bytes[..LEN - 2]);
+        bytes[LEN - 2..].copy_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Returns `None` if the bytes are not a valid saved game, or if the game was saved by a version
+    /// that plays out differently.
+    ///
+    /// The loaded game has no history or undo, since neither is saved.
+    fn from_save_bytes(bytes: &[u8; LEN]) -> Option<Self> {
+        let (nodes, rest) = bytes.split_at(NODES_LEN);
+        if rest[..MAGIC.len()] != MAGIC
+            || checksum(&bytes[..LEN - 2]).to_le_bytes() != bytes[LEN - 2..]
+        {
+            return None;
+        }
+        let version = u16::from_le_bytes([rest[MAGIC.len()], rest[MAGIC.len() + 1]]);
+        if version != COMPAT_VERSION {
+            return None;
+        }
+        let rest = &rest[MAGIC.len() + 2..];
+        let turn_color = Color::try_from(rest[0]).ok()?;
+        let size = Size {
+            width: rest[1],
+            height: rest[2],
+        };
+        if !(1..=Size::MAX.width).contains(&size.width)
+            || !(1..=Size::MAX.height).contains(&size.height)
+            || rest[3] & !(WRAPAROUND | MULTI_ROTATION | SUPER_ARROW_CLAIMS) != 0
+        {
+            return None;
+        }
+        let rules = Rules {
+            wraparound: rest[3] & WRAPAROUND != 0,
+            multi_rotation: rest[3] & MULTI_ROTATION != 0,
+            super_arrow_claims: rest[3] & SUPER_ARROW_CLAIMS != 0,
+        };
+        let grid = Grid::from_bytes(nodes.try_into().unwrap())
+            .ok()?
+            .with_size(size);
+
+        let mut game = Game::builder()
+            .grid(grid)
+            .rules(rules)
+            .turn_color(turn_color)
+            .max_turns(NonZeroU16::new(u16::from_le_bytes(
+                rest[8..10].try_into().unwrap(),
+            )))
+            .build();
+        game.turn_number = u32::from_le_bytes(rest[4..8].try_into().unwrap());
+        for (color, &byte) in game.eliminations.iter_mut().zip(&rest[10..14]) {
+            *color = match byte {
+                NOT_ELIMINATED => None,
+                byte => Some(Color::try_from(byte).ok()?),
+            };
+        }
+        Some(game)
+    }
+
+    /// Save the game to SRAM, replacing any game saved before.
+    pub fn save(&self) {
+        for (index, byte) in self.to_save_bytes().into_iter().enumerate() {
+            sram_write(OFFSET + index, byte);
+        }
+    }
+
+    /// Load the game saved to SRAM.
+    ///
+    /// Returns `None` if SRAM doesn't hold a valid saved game, such as when no game has been saved
+    /// yet.
+    pub fn load() -> Option<Self> {
+        let mut bytes = [0; LEN];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = sram_read(OFFSET + index);
+        }
+        Self::from_save_bytes(&bytes)
+    }
+
+    /// Delete the game saved to SRAM, so that `load()` finds nothing.
+    pub fn delete_save() {
+        for index in NODES_LEN..NODES_LEN + MAGIC.len() {
+            sram_write(OFFSET + index, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LEN;
+    use crate::{
+        game::{compat::COMPAT_VERSION, Color, Game, Grid, Rules, Size},
+        records::checksum,
+    };
+    use core::num::NonZeroU16;
+    use gba_test::test;
+
+    #[test]
+    fn round_trip() {
+        let mut game = Game::builder()
+            .grid(Grid::generate_with_size(4, 12))
+            .rules(Rules {
+                wraparound: true,
+                multi_rotation: true,
+                super_arrow_claims: true,
+            })
+            .turn_color(Color::Yellow)
+            .max_turns(NonZeroU16::new(50))
+            .build();
+        let turn = game.valid_turns().next().unwrap();
+        game.execute_turn(turn).unwrap();
+
+        let loaded = Game::from_save_bytes(&game.to_save_bytes()).unwrap();
+
+        assert_eq!(loaded.state_hash(), game.state_hash());
+        assert_eq!(loaded.grid().size(), Size::square(12));
+        assert_eq!(loaded.rules(), game.rules());
+        assert_eq!(loaded.turn_color(), game.turn_color());
+        assert_eq!(loaded.turn_number(), 1);
+        assert_eq!(loaded.turns_remaining(), Some(49));
+    }
+
+    #[test]
+    fn round_trip_generated() {
+        for seed in 0..8 {
+            let game = Game::builder().grid(Grid::generate(seed)).build();
+
+            assert_eq!(
+                Game::from_save_bytes(&game.to_save_bytes())
+                    .unwrap()
+                    .state_hash(),
+                game.state_hash(),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_eliminations() {
+        let mut game = Game::builder().grid(Grid::generate(0)).build();
+        game.eliminations = [Some(Color::Blue), Some(Color::Yellow), None, None];
+
+        let loaded = Game::from_save_bytes(&game.to_save_bytes()).unwrap();
+
+        assert!(loaded.eliminations().eq([Color::Blue, Color::Yellow]));
+    }
+
+    #[test]
+    fn blank_sram() {
+        assert!(Game::from_save_bytes(&[0; LEN]).is_none());
+        assert!(Game::from_save_bytes(&[0xff; LEN]).is_none());
+    }
+
+    #[test]
+    fn corrupted() {
+        let bytes = Game::builder()
+            .grid(Grid::generate(0))
+            .build()
+            .to_save_bytes();
+
+        for index in 0..LEN {
+            let mut corrupted = bytes;
+            corrupted[index] ^= 0x10;
+
+            assert!(Game::from_save_bytes(&corrupted).is_none(), "byte {index}");
+        }
+    }
+
+    #[test]
+    fn invalid_size() {
+        let mut bytes = Game::builder()
+            .grid(Grid::generate(0))
+            .build()
+            .to_save_bytes();
+        // The width, just after the magic, version, and turn color.
+        bytes[256 + 4 + 2 + 1] = 17;
+        let checksum = checksum(&bytes[..LEN - 2]);
+        bytes[LEN - 2..].copy_from_slice(&checksum.to_le_bytes());
+
+        assert!(Game::from_save_bytes(&bytes).is_none());
+    }
+
+    #[test]
+    fn other_version() {
+        let mut bytes = Game::builder()
+            .grid(Grid::generate(0))
+            .build()
+            .to_save_bytes();
+        // A valid save, as made by a different version.
+        bytes[256 + 4..256 + 4 + 2].copy_from_slice(&(COMPAT_VERSION + 1).to_le_bytes());
+        let checksum = checksum(&bytes[..LEN - 2]);
+        bytes[LEN - 2..].copy_from_slice(&checksum.to_le_bytes());
+
+        assert!(Game::from_save_bytes(&bytes).is_none());
+    }
+}
@@ -1,12 +1,30 @@
-use super::position::Position;
+use super::{position::Position, Color};
+use deranged::RangedU8;
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Turn {
     /// The position to be rotated.
     pub rotate: Position,
+    /// How many times the node is rotated.
+    ///
+    /// Rotating more than once is only allowed by `Rules::multi_rotation`.
+    pub times: RangedU8<1, 3>,
 }
 
-#[derive(Debug)]
+impl Turn {
+    /// A single rotation, which every game allows.
+    pub const ONCE: RangedU8<1, 3> = RangedU8::new_static::<1>();
+}
+
+/// Why a turn was rejected.
+#[derive(Debug, Eq, PartialEq)]
 pub enum Error {
-    InvalidRotationPosition,
+    /// The position is not on the board.
+    OutOfBounds,
+    /// The node at the position belongs to another player, or to nobody.
+    NotOwned { actual: Option<Color> },
+    /// The position holds an empty node or a wall, which can't be rotated.
+    NotRotatable,
+    /// The turn rotates more than once, but the rules only allow a single rotation.
+    MultipleRotations,
 }
@@ -0,0 +1,95 @@
+//! Handcrafted boards, embedded in the ROM.
+//!
+//! Each level is stored as the 256 bytes `Grid::to_bytes()` encodes a full sized board as, and is
+//! only decoded when it is played.
+
+use crate::{align::Align4, game::Grid, include_bytes_aligned};
+use log::error;
+
+/// Every level, in the order they are played.
+static LEVELS: [Align4<[u8; 256]>; 2] = [
+    // Rings of arrows turning in alternating directions, walled off from the middle.
+    include_bytes_aligned!("../res/levels/level0.bin"),
+    // Arrows running toward the diagonals, which are broken up by walls and secret nodes.
+    include_bytes_aligned!("../res/levels/level1.bin"),
+];
+
+/// The number of levels.
+pub const COUNT: usize = LEVELS.len();
+
+/// The level at `index`.
+///
+/// Returns `None` if there is no such level, or if its data is corrupt. Corrupt data is logged.
+pub fn get(index: usize) -> Option<Grid> {
+    decode(index, &LEVELS.get(index)?.0)
+}
+
+fn decode(index: usize, bytes: &[u8; 256]) -> Option<Grid> {
+    match Grid::from_bytes(bytes) {
+        Ok(grid) => Some(grid),
+        Err(error) => {
+            error!(
+                "level {index} has invalid byte {:#04x} at ({}, {})",
+                error.byte, error.position.x, error.position.y
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, get, COUNT, LEVELS};
+    use crate::game::{Color, Grid};
+    use gba_test::test;
+
+    #[test]
+    fn every_level_decodes() {
+        for index in 0..COUNT {
+            assert!(get(index).is_some(), "level {index}");
+        }
+    }
+
+    #[test]
+    fn get_past_last_level() {
+        assert!(get(COUNT).is_none());
+    }
+
+    #[test]
+    fn every_level_round_trips() {
+        for (index, level) in LEVELS.iter().enumerate() {
+            let mut bytes = [0; 256];
+            get(index).unwrap().to_bytes(&mut bytes);
+
+            assert_eq!(bytes, level.0, "level {index}");
+        }
+    }
+
+    #[test]
+    fn every_level_has_every_color() {
+        for index in 0..COUNT {
+            let counts = get(index).unwrap().color_counts();
+
+            for color in Color::ALL {
+                assert_eq!(counts.for_color(color), 1, "level {index}, {color:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn decode_corrupt() {
+        let mut bytes = LEVELS[0].0;
+        // No kind of node is encoded as 7.
+        bytes[17] = 7;
+
+        assert!(decode(0, &bytes).is_none());
+    }
+
+    #[test]
+    fn decode_generated() {
+        let mut bytes = [0; 256];
+        Grid::generate(0).to_bytes(&mut bytes);
+
+        assert!(decode(0, &bytes).is_some());
+    }
+}
@@ -4,17 +4,33 @@
 #![cfg_attr(test, test_runner(gba_test::runner))]
 #![cfg_attr(test, reexport_test_harness_main = "test_harness")]
 
+mod ai;
 mod align;
+#[cfg(debug_assertions)]
+mod assets;
+mod audio;
 mod bios;
+mod export;
 mod game;
+mod levels;
+mod math;
 mod mmio;
+mod platform;
 mod random;
+mod rating;
+mod records;
 #[cfg(not(test))]
 mod runtime;
 mod screen;
+mod settings;
+mod text;
+mod thumbnail;
+mod timing;
+mod watchdog;
 
 use log::error;
 use mmio::{interrupts::Interrupts, vram::DisplayStatus, DISPSTAT, IE, IME};
+use platform::Platform;
 use screen::Screen;
 
 static mut VBLANKS_REMAINING: u16 = 0;
@@ -23,9 +39,16 @@ static mut VBLANKS_REMAINING: u16 = 0;
 ///
 /// Note that we don't actually care if either of these loggers fails to initialize. We just want
 /// one of them initialized if at all possible to make debugging easier.
-pub fn init_log() {
-    if mgba_log::init().is_err() {
-        let _ = nocash_gba_log::init();
+pub fn init_log(platform: Platform) {
+    match platform {
+        Platform::Mgba => {
+            let _ = mgba_log::init();
+        }
+        Platform::NoCash => {
+            let _ = nocash_gba_log::init();
+        }
+        // There is nowhere to log to.
+        Platform::HardwareOrUnknown => {}
     }
 }
 
@@ -49,9 +72,15 @@ fn panic(info: &core::panic::PanicInfo) -> ! {
 pub fn main() -> ! {
     // Initialize the global logger.
     //
-    // This logging only works in mGBA. It is only enabled in debug builds.
+    // This logging only works in emulators. It is only enabled in debug builds, since detecting
+    // the emulator writes to its debug registers.
     #[cfg(debug_assertions)]
-    init_log();
+    {
+        let platform = platform::detect();
+        init_log(platform);
+        log::info!("running on {:?}", platform);
+        assets::verify_all();
+    }
 
     // // Audio test.
     // unsafe {
@@ -89,6 +118,6 @@ pub fn main() -> ! {
 #[cfg(test)]
 #[no_mangle]
 pub fn main() {
-    init_log();
+    init_log(platform::detect());
     test_harness()
 }
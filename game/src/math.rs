@@ -0,0 +1,47 @@
+//! Fixed-point arithmetic.
+
+/// An unsigned fixed-point number with 8 fractional bits.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Fixed(u32);
+
+impl Fixed {
+    pub const FRACTIONAL_BITS: u32 = 8;
+
+    pub const fn from_int(value: u16) -> Self {
+        Self((value as u32) << Self::FRACTIONAL_BITS)
+    }
+
+    pub const fn from_raw(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+
+    /// The integer part, with the fractional part truncated.
+    pub const fn trunc(self) -> u16 {
+        (self.0 >> Self::FRACTIONAL_BITS) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fixed;
+    use gba_test::test;
+
+    #[test]
+    fn from_int_round_trip() {
+        assert_eq!(Fixed::from_int(300).trunc(), 300);
+    }
+
+    #[test]
+    fn trunc_discards_fraction() {
+        assert_eq!(Fixed::from_raw((5 << 8) + 255).trunc(), 5);
+    }
+
+    #[test]
+    fn raw() {
+        assert_eq!(Fixed::from_int(2).raw(), 512);
+    }
+}
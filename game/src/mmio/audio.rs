@@ -21,6 +21,10 @@ impl Control {
         Self(0)
     }
 
+    pub const fn sound_a_full_volume(self, set: bool) -> Self {
+        Self(self.0 & !(1 << 2) | ((set as u16) << 2))
+    }
+
     pub const fn sound_a_right(self, set: bool) -> Self {
         Self(self.0 & !(1 << 8) | ((set as u16) << 8))
     }
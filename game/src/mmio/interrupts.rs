@@ -1,7 +1,33 @@
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct Interrupts(u16);
 
 impl Interrupts {
     pub const VBLANK: Self = Self(0b0000_0000_0000_0001);
+
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interrupts;
+    use gba_test::test;
+
+    #[test]
+    fn contains_self() {
+        assert!(Interrupts::VBLANK.contains(Interrupts::VBLANK));
+    }
+
+    #[test]
+    fn contains_among_others() {
+        assert!(Interrupts(0b0000_0000_0000_0011).contains(Interrupts::VBLANK));
+    }
+
+    #[test]
+    fn empty_does_not_contain() {
+        assert!(!Interrupts(0).contains(Interrupts::VBLANK));
+        assert!(!Interrupts(0b0000_0000_0000_0010).contains(Interrupts::VBLANK));
+    }
 }
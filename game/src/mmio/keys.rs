@@ -18,11 +18,79 @@ impl KeyInput {
     pub const fn contains(self, other: Self) -> bool {
         (Self::NONE.0 ^ self.0) & (Self::NONE.0 ^ other.0) == (Self::NONE.0 ^ other.0)
     }
+
+    /// Whether any key is held.
+    ///
+    /// Only the bits for the ten keys are checked, since the register's unused upper bits aren't
+    /// guaranteed to be set.
+    pub const fn any_pressed(self) -> bool {
+        self.0 & Self::NONE.0 != Self::NONE.0
+    }
+
+    /// Whether `key` is held now but wasn't held in `previous`.
+    pub const fn just_pressed(self, previous: Self, key: Self) -> bool {
+        self.contains(key) && !previous.contains(key)
+    }
+
+    /// Whether `key` was held in `previous` but isn't held now.
+    pub const fn just_released(self, previous: Self, key: Self) -> bool {
+        !self.contains(key) && previous.contains(key)
+    }
+}
+
+/// Turns held keys into repeated presses.
+///
+/// A key fires on the frame it is pressed, and then again every `interval` frames for as long as it
+/// is held.
+#[derive(Debug)]
+pub struct KeyRepeat {
+    interval: u8,
+    /// The number of frames each key has been held, indexed by the key's bit.
+    held: [u8; 10],
+}
+
+impl KeyRepeat {
+    pub const DEFAULT_INTERVAL: u8 = 6;
+
+    /// # Panics
+    /// Panics if `interval` is 0.
+    pub fn new(interval: u8) -> Self {
+        assert!(interval > 0, "key repeat interval must be at least 1");
+        Self {
+            interval,
+            held: [0; 10],
+        }
+    }
+
+    /// Handle a single frame of input.
+    ///
+    /// Returns the keys firing this frame.
+    pub fn update(&mut self, keys: KeyInput) -> KeyInput {
+        let mut fired = KeyInput::NONE.0;
+        for (bit, held) in self.held.iter_mut().enumerate() {
+            if keys.0 & (1 << bit) != 0 {
+                *held = 0;
+                continue;
+            }
+            // Counting wraps back around to the first interval rather than overflowing.
+            *held = if *held == self.interval { 1 } else { *held + 1 };
+            if *held == 1 {
+                fired &= !(1 << bit);
+            }
+        }
+        KeyInput(fired)
+    }
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_INTERVAL)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::KeyInput;
+    use super::{KeyInput, KeyRepeat};
     use gba_test::test;
 
     #[test]
@@ -54,4 +122,85 @@ mod tests {
     fn key_input_all_contains_none() {
         assert!(KeyInput(0).contains(KeyInput::NONE))
     }
+
+    #[test]
+    fn key_input_none_not_any_pressed() {
+        assert!(!KeyInput::NONE.any_pressed())
+    }
+
+    #[test]
+    fn key_input_a_any_pressed() {
+        assert!(KeyInput::A.any_pressed())
+    }
+
+    #[test]
+    fn key_input_l_any_pressed() {
+        assert!(KeyInput::L.any_pressed())
+    }
+
+    #[test]
+    fn key_input_upper_bits_not_any_pressed() {
+        assert!(!KeyInput(0b1111_1111_1111_1111).any_pressed())
+    }
+
+    #[test]
+    fn key_input_just_pressed() {
+        assert!(KeyInput::A.just_pressed(KeyInput::NONE, KeyInput::A));
+        assert!(!KeyInput::A.just_pressed(KeyInput::A, KeyInput::A));
+        assert!(!KeyInput::NONE.just_pressed(KeyInput::A, KeyInput::A));
+        assert!(!KeyInput::B.just_pressed(KeyInput::NONE, KeyInput::A));
+    }
+
+    #[test]
+    fn key_input_just_pressed_with_other_key_held() {
+        let a_b = KeyInput(0b0000_0011_1111_1100);
+
+        assert!(a_b.just_pressed(KeyInput::A, KeyInput::B));
+        assert!(!a_b.just_pressed(KeyInput::A, KeyInput::A));
+    }
+
+    #[test]
+    fn key_input_just_released() {
+        assert!(KeyInput::NONE.just_released(KeyInput::A, KeyInput::A));
+        assert!(!KeyInput::A.just_released(KeyInput::A, KeyInput::A));
+        assert!(!KeyInput::A.just_released(KeyInput::NONE, KeyInput::A));
+        assert!(!KeyInput::NONE.just_released(KeyInput::B, KeyInput::A));
+    }
+
+    #[test]
+    fn key_repeat_fires_on_press() {
+        let mut repeat = KeyRepeat::default();
+
+        assert_eq!(repeat.update(KeyInput::A), KeyInput::A);
+    }
+
+    #[test]
+    fn key_repeat_fires_every_interval() {
+        let mut repeat = KeyRepeat::new(3);
+
+        let fired = [(); 7].map(|()| repeat.update(KeyInput::UP) == KeyInput::UP);
+
+        assert_eq!(fired, [true, false, false, true, false, false, true]);
+    }
+
+    #[test]
+    fn key_repeat_restarts_on_release() {
+        let mut repeat = KeyRepeat::new(3);
+        repeat.update(KeyInput::UP);
+        repeat.update(KeyInput::UP);
+
+        assert_eq!(repeat.update(KeyInput::NONE), KeyInput::NONE);
+        assert_eq!(repeat.update(KeyInput::UP), KeyInput::UP);
+    }
+
+    #[test]
+    fn key_repeat_keys_independent() {
+        let mut repeat = KeyRepeat::new(2);
+        let up_left = KeyInput(KeyInput::UP.0 & KeyInput::LEFT.0);
+        repeat.update(KeyInput::UP);
+
+        // Up is partway through its interval, while left was just pressed.
+        assert_eq!(repeat.update(up_left), KeyInput::LEFT);
+        assert_eq!(repeat.update(up_left), KeyInput::UP);
+    }
 }
@@ -12,12 +12,13 @@ use dma::DmaControl;
 use interrupts::Interrupts;
 use keys::KeyInput;
 use vram::{
-    BackgroundControl, BlendControl, Color, DisplayControl, DisplayStatus, ObjectAttributes,
-    TextScreenEntry,
+    AlphaControl, BackgroundControl, BlendControl, Color, DisplayControl, DisplayStatus,
+    ObjectAttributes, TextScreenEntry,
 };
 
 pub const DISPCNT: *mut DisplayControl = 0x0400_0000 as *mut DisplayControl;
 pub const DISPSTAT: *mut DisplayStatus = 0x0400_0004 as *mut DisplayStatus;
+pub const VCOUNT: *mut u16 = 0x0400_0006 as *mut u16;
 pub const BG0CNT: *mut BackgroundControl = 0x0400_0008 as *mut BackgroundControl;
 pub const BG1CNT: *mut BackgroundControl = 0x0400_000A as *mut BackgroundControl;
 pub const BG2CNT: *mut BackgroundControl = 0x0400_000C as *mut BackgroundControl;
@@ -26,7 +27,9 @@ pub const BG1HOFS: *mut RangedU16<0, 511> = 0x0400_0014 as *mut RangedU16<0, 511
 pub const BG1VOFS: *mut RangedU16<0, 511> = 0x0400_0016 as *mut RangedU16<0, 511>;
 pub const BG2HOFS: *mut RangedU16<0, 511> = 0x0400_0018 as *mut RangedU16<0, 511>;
 pub const BG2VOFS: *mut RangedU16<0, 511> = 0x0400_001A as *mut RangedU16<0, 511>;
+pub const BG3VOFS: *mut RangedU16<0, 511> = 0x0400_001E as *mut RangedU16<0, 511>;
 pub const BLDCNT: *mut BlendControl = 0x0400_0050 as *mut BlendControl;
+pub const BLDALPHA: *mut AlphaControl = 0x0400_0052 as *mut AlphaControl;
 pub const BLDY: *mut RangedU8<0, 16> = 0x0400_0054 as *mut RangedU8<0, 16>;
 pub const AUDIO_CONTROL: *mut audio::Control = 0x0400_0082 as *mut audio::Control;
 pub const AUDIO_ENABLE: *mut audio::Enable = 0x0400_0084 as *mut audio::Enable;
@@ -39,6 +42,8 @@ pub const TIMER0_CONTROL: *mut timer::Control = 0x0400_0102 as *mut timer::Contr
 pub const KEYINPUT: *mut KeyInput = 0x0400_0130 as *mut KeyInput;
 pub const IE: *mut Interrupts = 0x0400_0200 as *mut Interrupts;
 pub const IME: *mut bool = 0x0400_0208 as *mut bool;
+pub const MGBA_DEBUG_ENABLE: *mut u16 = 0x04FF_F780 as *mut u16;
+pub const NOCASH_SIGNATURE: *mut u8 = 0x04FF_FA00 as *mut u8;
 pub const BG_PALETTE: *mut [Color; 16] = 0x0500_0000 as *mut [Color; 16];
 pub const OBJ_PALETTE: *mut [Color; 16] = 0x0500_0200 as *mut [Color; 16];
 pub const CHARBLOCK0: *mut [u32; 8] = 0x0600_0000 as *mut [u32; 8];
@@ -49,3 +54,28 @@ pub const TEXT_SCREENBLOCK24: *mut TextScreenEntry = 0x0600_C000 as *mut TextScr
 pub const TEXT_SCREENBLOCK28: *mut TextScreenEntry = 0x0600_E000 as *mut TextScreenEntry;
 pub const OBJ_TILES: *mut [u32; 8] = 0x0601_0000 as *mut [u32; 8];
 pub const OBJ_ATTRS: *mut ObjectAttributes = 0x0700_0000 as *mut ObjectAttributes;
+/// Battery-backed save memory.
+///
+/// **SRAM is on an 8-bit bus and must only be accessed a byte at a time.** 16- and 32-bit reads
+/// and writes don't work, so prefer `sram_read()` and `sram_write()` over using this directly.
+pub const SRAM: *mut u8 = 0x0E00_0000 as *mut u8;
+/// The number of bytes in `SRAM`.
+pub const SRAM_SIZE: usize = 0x8000;
+
+/// Read the byte at `offset` into SRAM.
+///
+/// # Panics
+/// Panics if `offset` is not less than `SRAM_SIZE`.
+pub fn sram_read(offset: usize) -> u8 {
+    assert!(offset < SRAM_SIZE, "SRAM offset {offset:#x} out of range");
+    unsafe { SRAM.add(offset).read_volatile() }
+}
+
+/// Write `value` to the byte at `offset` into SRAM.
+///
+/// # Panics
+/// Panics if `offset` is not less than `SRAM_SIZE`.
+pub fn sram_write(offset: usize, value: u8) {
+    assert!(offset < SRAM_SIZE, "SRAM offset {offset:#x} out of range");
+    unsafe { SRAM.add(offset).write_volatile(value) }
+}
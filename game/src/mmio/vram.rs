@@ -1,11 +1,16 @@
 use deranged::{RangedU16, RangedU8};
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct DisplayStatus(u16);
 
 impl DisplayStatus {
     pub const ENABLE_VBLANK_INTERRUPTS: Self = Self(0b0000_0000_0000_1000);
+
+    /// Whether the display requests an interrupt when v-blank starts.
+    pub const fn vblank_interrupts_enabled(&self) -> bool {
+        self.0 & Self::ENABLE_VBLANK_INTERRUPTS.0 != 0
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -124,13 +129,64 @@ impl BlendControl {
     pub const fn with_color_effect(self, effect: ColorEffect) -> Self {
         Self(self.0 & !(3 << 6) | (effect as u16) << 6)
     }
+
+    pub const fn with_target2_bg0(self, set: bool) -> Self {
+        Self(self.0 & !(1 << 8) | (set as u16) << 8)
+    }
+
+    pub const fn with_target2_bg1(self, set: bool) -> Self {
+        Self(self.0 & !(1 << 9) | (set as u16) << 9)
+    }
+
+    pub const fn with_target2_bg2(self, set: bool) -> Self {
+        Self(self.0 & !(1 << 10) | (set as u16) << 10)
+    }
+
+    pub const fn with_target2_bg3(self, set: bool) -> Self {
+        Self(self.0 & !(1 << 11) | (set as u16) << 11)
+    }
+
+    pub const fn with_target2_obj(self, set: bool) -> Self {
+        Self(self.0 & !(1 << 12) | (set as u16) << 12)
+    }
+
+    pub const fn with_target2_backdrop(self, set: bool) -> Self {
+        Self(self.0 & !(1 << 13) | (set as u16) << 13)
+    }
 }
 
-#[derive(Debug)]
+/// The coefficients used by `ColorEffect::Blend`, each in sixteenths.
+#[derive(Debug, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct AlphaControl(u16);
+
+impl AlphaControl {
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// The weight of the first target.
+    pub const fn with_eva(self, coeff: RangedU8<0, 16>) -> Self {
+        Self(self.0 & !31 | coeff.get() as u16)
+    }
+
+    /// The weight of the second target.
+    pub const fn with_evb(self, coeff: RangedU8<0, 16>) -> Self {
+        Self(self.0 & !(31 << 8) | (coeff.get() as u16) << 8)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct Color(u16);
 
-#[derive(Clone, Copy, Debug)]
+impl Color {
+    pub const fn new(red: RangedU8<0, 31>, green: RangedU8<0, 31>, blue: RangedU8<0, 31>) -> Self {
+        Self(red.get() as u16 | (green.get() as u16) << 5 | (blue.get() as u16) << 10)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct TextScreenEntry(u16);
 
@@ -156,7 +212,7 @@ impl TextScreenEntry {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct ObjectAttributes(u64);
 
@@ -193,7 +249,10 @@ impl ObjectAttributes {
 
 #[cfg(test)]
 mod tests {
-    use super::{BackgroundControl, BlendControl, ColorEffect, DisplayControl};
+    use super::{
+        AlphaControl, BackgroundControl, BlendControl, Color, ColorEffect, DisplayControl,
+        DisplayStatus,
+    };
     use deranged::RangedU8;
     use gba_test::test;
 
@@ -237,6 +296,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn display_status_vblank_interrupts_enabled() {
+        assert!(DisplayStatus::ENABLE_VBLANK_INTERRUPTS.vblank_interrupts_enabled());
+        assert!(!DisplayStatus(0).vblank_interrupts_enabled());
+        // The v-blank flag itself is a different bit.
+        assert!(!DisplayStatus(0b0000_0000_0000_0001).vblank_interrupts_enabled());
+    }
+
     #[test]
     fn display_control_bg_mode() {
         assert_eq!(
@@ -348,4 +415,90 @@ mod tests {
             BlendControl(0b0000_0000_1100_0000)
         );
     }
+
+    #[test]
+    fn blend_control_target2_bg0() {
+        assert_eq!(
+            BlendControl::new().with_target2_bg0(true),
+            BlendControl(0b0000_0001_0000_0000)
+        );
+    }
+
+    #[test]
+    fn blend_control_target2_bg1() {
+        assert_eq!(
+            BlendControl::new().with_target2_bg1(true),
+            BlendControl(0b0000_0010_0000_0000)
+        );
+    }
+
+    #[test]
+    fn blend_control_target2_bg2() {
+        assert_eq!(
+            BlendControl::new().with_target2_bg2(true),
+            BlendControl(0b0000_0100_0000_0000)
+        );
+    }
+
+    #[test]
+    fn blend_control_target2_bg3() {
+        assert_eq!(
+            BlendControl::new().with_target2_bg3(true),
+            BlendControl(0b0000_1000_0000_0000)
+        );
+    }
+
+    #[test]
+    fn blend_control_target2_obj() {
+        assert_eq!(
+            BlendControl::new().with_target2_obj(true),
+            BlendControl(0b0001_0000_0000_0000)
+        );
+    }
+
+    #[test]
+    fn blend_control_target2_backdrop() {
+        assert_eq!(
+            BlendControl::new().with_target2_backdrop(true),
+            BlendControl(0b0010_0000_0000_0000)
+        );
+    }
+
+    #[test]
+    fn alpha_control_eva() {
+        assert_eq!(
+            AlphaControl::new().with_eva(RangedU8::new_static::<16>()),
+            AlphaControl(0b0000_0000_0001_0000)
+        );
+    }
+
+    #[test]
+    fn alpha_control_evb() {
+        assert_eq!(
+            AlphaControl::new().with_evb(RangedU8::new_static::<16>()),
+            AlphaControl(0b0001_0000_0000_0000)
+        );
+    }
+
+    #[test]
+    fn alpha_control_eva_and_evb() {
+        assert_eq!(
+            AlphaControl::new()
+                .with_eva(RangedU8::new_static::<7>())
+                .with_evb(RangedU8::new_static::<9>()),
+            AlphaControl(0b0000_1001_0000_0111)
+        );
+    }
+
+    #[test]
+    fn color_new() {
+        assert_eq!(
+            Color::new(
+                RangedU8::new_static::<31>(),
+                RangedU8::new_static::<1>(),
+                RangedU8::new_static::<16>()
+            ),
+            Color(0b0100_0000_0011_1111)
+        );
+    }
 }
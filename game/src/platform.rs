@@ -0,0 +1,59 @@
+//! Detection of the platform the game is running on.
+
+use crate::mmio::{MGBA_DEBUG_ENABLE, NOCASH_SIGNATURE};
+
+/// The value written to mGBA's debug enable register to turn on its debug output.
+const MGBA_REQUEST: u16 = 0xC0DE;
+/// The value mGBA's debug enable register reads as once debug output is on.
+const MGBA_RESPONSE: u16 = 0x1DEA;
+
+/// The emulator identification string no$gba exposes.
+const NOCASH_ID: &[u8; 6] = b"no$gba";
+
+/// Where the game is running.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Platform {
+    Mgba,
+    NoCash,
+    /// Real hardware, or an emulator that can't be identified.
+    HardwareOrUnknown,
+}
+
+/// Probe the emulators' debug registers to find out where the game is running.
+///
+/// Both registers lie in unused I/O space on hardware, where writes are ignored and reads return
+/// whatever was last on the bus. Each is accessed a fixed number of times, so this never hangs.
+pub fn detect() -> Platform {
+    unsafe {
+        MGBA_DEBUG_ENABLE.write_volatile(MGBA_REQUEST);
+        if MGBA_DEBUG_ENABLE.read_volatile() == MGBA_RESPONSE {
+            return Platform::Mgba;
+        }
+    }
+
+    if NOCASH_ID
+        .iter()
+        .enumerate()
+        // Read a byte at a time, since the string isn't guaranteed to be aligned for wider reads.
+        .all(|(index, &byte)| unsafe { NOCASH_SIGNATURE.add(index).read_volatile() } == byte)
+    {
+        Platform::NoCash
+    } else {
+        Platform::HardwareOrUnknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::detect;
+    use gba_test::test;
+
+    #[test]
+    fn detect_is_stable() {
+        let platform = detect();
+
+        for _ in 0..10 {
+            assert_eq!(detect(), platform);
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! The player's skill rating, updated after every game against the AI and kept in SRAM.
+//!
+//! Ratings work like Elo ratings: beating opponents rated above you gains more than beating
+//! opponents rated below you. All math is done in integers, with probabilities and scores given in
+//! thousandths.
+
+use crate::{
+    game::{Color, Standings},
+    mmio::{sram_read, sram_write},
+    records::{checksum, Outcome},
+};
+
+/// Identifies a valid rating, and its layout version.
+const MAGIC: [u8; 4] = *b"TORR";
+
+/// Where the rating is stored in SRAM, after the records table.
+const OFFSET: usize = 0x100;
+
+/// The number of bytes used by a serialized rating.
+const LEN: usize = MAGIC.len() + 2 + 2;
+
+/// How much a single game can move the rating, in rating points per whole point of score.
+const K: i32 = 32;
+
+/// The chance of winning against an opponent rated lower by each multiple of 50 points, in
+/// thousandths.
+///
+/// This is `1 / (1 + 10^(-difference / 400))`, the curve used by Elo ratings.
+const EXPECTED: [u16; 17] = [
+    500, 571, 640, 703, 760, 808, 849, 882, 909, 930, 947, 960, 969, 977, 983, 987, 990,
+];
+
+/// The difference in rating between consecutive entries of `EXPECTED`.
+const EXPECTED_STEP: u16 = 50;
+
+/// The expected score of a player rated `player` against an opponent rated `opponent`, in
+/// thousandths.
+pub fn expected(player: Rating, opponent: Rating) -> u16 {
+    let (higher, lower) = if player >= opponent {
+        (player.0, opponent.0)
+    } else {
+        (opponent.0, player.0)
+    };
+    let difference = higher - lower;
+    let index = (difference / EXPECTED_STEP) as usize;
+    let favorite = if index + 1 >= EXPECTED.len() {
+        EXPECTED[EXPECTED.len() - 1]
+    } else {
+        // Interpolate between the two nearest entries.
+        let remainder = difference % EXPECTED_STEP;
+        EXPECTED[index] + (EXPECTED[index + 1] - EXPECTED[index]) * remainder / EXPECTED_STEP
+    };
+    if player >= opponent {
+        favorite
+    } else {
+        1000 - favorite
+    }
+}
+
+/// The score the player earned in a finished game, in thousandths.
+///
+/// A win scores at least half a point and a loss at most half a point. Within that, the score is
+/// scaled by the player's share of the nodes owned at the end of the game, so a narrow win scores
+/// less than a rout. A draw scores exactly half a point, whatever the share.
+pub fn score(standings: &Standings, player: Color, outcome: Outcome) -> u16 {
+    let total: u32 = standings.iter().map(|standing| standing.count as u32).sum();
+    let owned = standings
+        .iter()
+        .find(|standing| standing.color == player)
+        .map_or(0, |standing| standing.count as u32);
+    let share = (owned * 500).checked_div(total).unwrap_or(250) as u16;
+    match outcome {
+        Outcome::Won => 500 + share,
+        Outcome::Lost => share,
+        Outcome::Drawn => 500,
+    }
+}
+
+/// A skill rating, between 0 and 9999.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Rating(u16);
+
+impl Rating {
+    /// The rating of a player who hasn't played yet.
+    pub const INITIAL: Self = Self(1000);
+    pub const MAX: Self = Self(9999);
+
+    pub const fn new(rating: u16) -> Self {
+        if rating > Self::MAX.0 {
+            Self::MAX
+        } else {
+            Self(rating)
+        }
+    }
+
+    pub fn get(self) -> u16 {
+        self.0
+    }
+
+    /// The rating after a game against `opponents` in which the player scored `score`
+    /// thousandths of a point.
+    ///
+    /// Each opponent counts equally, so the rating moves by at most `K` points per game.
+    pub fn update(self, opponents: &[Rating], score: u16) -> Self {
+        if opponents.is_empty() {
+            return self;
+        }
+        let expected = opponents
+            .iter()
+            .map(|&opponent| expected(self, opponent) as i32)
+            .sum::<i32>()
+            / opponents.len() as i32;
+        let change = K * (score as i32 - expected);
+        // Round to the nearest point, away from zero on ties.
+        let delta = (change + 500 * change.signum()) / 1000;
+        Self::new((self.0 as i32 + delta).clamp(0, Self::MAX.0 as i32) as u16)
+    }
+
+    fn to_bytes(self) -> [u8; LEN] {
+        let mut bytes = [0; LEN];
+        bytes[..MAGIC.len()].copy_from_slice(&MAGIC);
+        bytes[MAGIC.len()..LEN - 2].copy_from_slice(&self.0.to_le_bytes());
+        let checksum = checksum(&bytes[..LEN - 2]);
+        bytes[LEN - 2..].copy_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Returns `None` if the bytes are not a valid rating.
+    fn from_bytes(bytes: &[u8; LEN]) -> Option<Self> {
+        if bytes[..MAGIC.len()] != MAGIC
+            || checksum(&bytes[..LEN - 2]).to_le_bytes() != bytes[LEN - 2..]
+        {
+            return None;
+        }
+        Some(Self::new(u16::from_le_bytes(
+            bytes[MAGIC.len()..LEN - 2].try_into().unwrap(),
+        )))
+    }
+
+    /// Load the rating from SRAM.
+    ///
+    /// If SRAM doesn't hold a valid rating, such as on the first boot, the initial rating is
+    /// returned.
+    pub fn load() -> Self {
+        let mut bytes = [0; LEN];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = sram_read(OFFSET + index);
+        }
+        Self::from_bytes(&bytes).unwrap_or(Self::INITIAL)
+    }
+
+    /// Save the rating to SRAM.
+    pub fn save(self) {
+        for (index, byte) in self.to_bytes().into_iter().enumerate() {
+            sram_write(OFFSET + index, byte);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expected, score, Rating, K, LEN};
+    use crate::{
+        game::{Color, Direction, Game, Grid, Node, Standings},
+        records::Outcome,
+    };
+    use gba_test::test;
+
+    /// The standings of a board where each color owns the given number of arrows.
+    fn standings(counts: [u8; 4]) -> Standings {
+        let grid = Grid::from_fn(|position| match Color::try_from(position.y) {
+            Ok(color) if position.x < counts[position.y as usize] => Node::Arrow {
+                alignment: Some(color),
+                direction: Direction::Down,
+            },
+            _ => Node::Empty,
+        });
+        Game::builder().grid(grid).build().standings()
+    }
+
+    #[test]
+    fn expected_equal_ratings() {
+        assert_eq!(expected(Rating::new(1234), Rating::new(1234)), 500);
+    }
+
+    #[test]
+    fn expected_table_entry() {
+        assert_eq!(expected(Rating::new(1400), Rating::new(1000)), 909);
+    }
+
+    #[test]
+    fn expected_interpolated() {
+        assert_eq!(expected(Rating::new(1025), Rating::new(1000)), 535);
+    }
+
+    #[test]
+    fn expected_capped() {
+        assert_eq!(expected(Rating::new(9999), Rating::new(0)), 990);
+        assert_eq!(expected(Rating::new(0), Rating::new(9999)), 10);
+    }
+
+    #[test]
+    fn expected_symmetric() {
+        for player in (0..3000).step_by(37) {
+            for opponent in (0..3000).step_by(41) {
+                let player = Rating::new(player);
+                let opponent = Rating::new(opponent);
+
+                assert_eq!(
+                    expected(player, opponent) + expected(opponent, player),
+                    1000
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn expected_increases_with_rating() {
+        let opponent = Rating::new(1500);
+        for player in 0..3000 {
+            assert!(
+                expected(Rating::new(player), opponent)
+                    <= expected(Rating::new(player + 1), opponent)
+            );
+        }
+    }
+
+    #[test]
+    fn score_total_win() {
+        assert_eq!(
+            score(&standings([10, 0, 0, 0]), Color::Red, Outcome::Won),
+            1000
+        );
+    }
+
+    #[test]
+    fn score_narrow_win() {
+        assert_eq!(
+            score(&standings([5, 5, 0, 0]), Color::Red, Outcome::Won),
+            750
+        );
+    }
+
+    #[test]
+    fn score_eliminated() {
+        assert_eq!(
+            score(&standings([0, 4, 4, 2]), Color::Red, Outcome::Lost),
+            0
+        );
+    }
+
+    #[test]
+    fn score_close_loss() {
+        assert_eq!(
+            score(&standings([4, 4, 2, 0]), Color::Red, Outcome::Lost),
+            200
+        );
+    }
+
+    #[test]
+    fn score_draw() {
+        assert_eq!(
+            score(&standings([4, 4, 2, 0]), Color::Red, Outcome::Drawn),
+            500
+        );
+    }
+
+    #[test]
+    fn score_draw_beats_loss() {
+        let standings = standings([4, 4, 2, 0]);
+
+        assert!(
+            score(&standings, Color::Red, Outcome::Drawn)
+                > score(&standings, Color::Red, Outcome::Lost)
+        );
+    }
+
+    #[test]
+    fn update_even_result_against_equals() {
+        let rating = Rating::new(1200);
+
+        assert_eq!(rating.update(&[rating; 3], 500), rating);
+    }
+
+    #[test]
+    fn update_zero_sum_against_equals() {
+        let rating = Rating::new(1200);
+        let gained = rating.update(&[rating; 3], 1000).get() - rating.get();
+        let lost = rating.get() - rating.update(&[rating; 3], 0).get();
+
+        assert_eq!(gained, K as u16 / 2);
+        assert_eq!(gained, lost);
+    }
+
+    #[test]
+    fn update_upset_gains_more() {
+        let rating = Rating::new(1000);
+        let against_weaker = rating.update(&[Rating::new(800)], 1000);
+        let against_stronger = rating.update(&[Rating::new(1200)], 1000);
+
+        assert!(against_stronger > against_weaker);
+    }
+
+    #[test]
+    fn update_no_opponents() {
+        assert_eq!(Rating::INITIAL.update(&[], 1000), Rating::INITIAL);
+    }
+
+    #[test]
+    fn update_clamped_at_floor() {
+        assert_eq!(Rating::new(5).update(&[Rating::new(5)], 0), Rating::new(0));
+    }
+
+    #[test]
+    fn update_clamped_at_ceiling() {
+        assert_eq!(Rating::new(9990).update(&[Rating::MAX], 1000), Rating::MAX);
+    }
+
+    #[test]
+    fn new_clamped() {
+        assert_eq!(Rating::new(u16::MAX), Rating::MAX);
+    }
+
+    #[test]
+    fn update_converges_to_performance() {
+        // A player who always performs like a 1500 rated player against 1000 rated opponents.
+        let opponents = [Rating::new(1000); 3];
+        let performance = expected(Rating::new(1500), opponents[0]);
+        let mut rating = Rating::INITIAL;
+        for _ in 0..500 {
+            rating = rating.update(&opponents, performance);
+        }
+
+        assert!(rating.get().abs_diff(1500) < 50, "rating was {:?}", rating);
+    }
+
+    #[test]
+    fn update_converges_from_above() {
+        let opponents = [Rating::new(1000); 3];
+        let performance = expected(Rating::new(1100), opponents[0]);
+        let mut rating = Rating::new(2000);
+        for _ in 0..500 {
+            rating = rating.update(&opponents, performance);
+        }
+
+        assert!(rating.get().abs_diff(1100) < 50, "rating was {:?}", rating);
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let rating = Rating::new(1234);
+
+        assert_eq!(Rating::from_bytes(&rating.to_bytes()), Some(rating));
+    }
+
+    #[test]
+    fn from_bytes_blank_sram() {
+        assert_eq!(Rating::from_bytes(&[0xff; LEN]), None);
+    }
+
+    #[test]
+    fn from_bytes_corrupted() {
+        let mut bytes = Rating::new(1234).to_bytes();
+        bytes[4] ^= 1;
+
+        assert_eq!(Rating::from_bytes(&bytes), None);
+    }
+}
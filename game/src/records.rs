@@ -0,0 +1,379 @@
+//! The player's best result on each of the most recently played boards, kept in SRAM.
+
+use crate::{
+    align::Align4,
+    game::compat::COMPAT_VERSION,
+    mmio::{sram_read, sram_write},
+};
+use core::cmp::Ordering;
+use log::warn;
+
+/// Marks the cartridge as using SRAM, so emulators and flash carts provide it.
+#[used]
+static SAVE_TYPE: Align4<[u8; 12]> = Align4(*b"SRAM_V113\0\0\0");
+
+/// Identifies a valid table, and its layout version.
+const MAGIC: [u8; 4] = *b"TOR2";
+
+/// The number of bytes before the entries: the magic, followed by the compatibility version.
+///
+/// Records are keyed by seed, and a different version may generate a different board from the
+/// same seed, so records from other versions are discarded.
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+/// The number of boards remembered.
+const CAPACITY: usize = 16;
+
+const ENTRY_LEN: usize = 11;
+
+/// The number of bytes used by a serialized table.
+const LEN: usize = HEADER_LEN + CAPACITY * ENTRY_LEN + 2;
+
+/// How a game ended for the player.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Lost = 1,
+    Won = 2,
+    /// The game ended early with the lead shared.
+    Drawn = 3,
+}
+
+/// The player's result on a single board.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Record {
+    pub seed: u64,
+    /// The number of turns the player took.
+    pub turns: u16,
+    pub outcome: Outcome,
+}
+
+impl Record {
+    /// Compare how good two results are.
+    ///
+    /// Winning is better than drawing, which is better than losing. Winning in fewer turns is
+    /// better, while drawing or losing after more turns is better.
+    fn cmp_result(&self, other: &Self) -> Ordering {
+        match (self.outcome, other.outcome) {
+            (Outcome::Won, Outcome::Won) => other.turns.cmp(&self.turns),
+            (Outcome::Drawn, Outcome::Drawn) | (Outcome::Lost, Outcome::Lost) => {
+                self.turns.cmp(&other.turns)
+            }
+            (Outcome::Won, _) | (Outcome::Drawn, Outcome::Lost) => Ordering::Greater,
+            (_, Outcome::Won) | (Outcome::Lost, Outcome::Drawn) => Ordering::Less,
+        }
+    }
+
+    fn write(&self, bytes: &mut [u8]) {
+        bytes[..8].copy_from_slice(&self.seed.to_le_bytes());
+        bytes[8..10].copy_from_slice(&self.turns.to_le_bytes());
+        bytes[10] = self.outcome as u8;
+    }
+
+    /// Returns `None` for an unused entry.
+    fn read(bytes: &[u8]) -> Option<Self> {
+        let outcome = match bytes[10] {
+            1 => Outcome::Lost,
+            2 => Outcome::Won,
+            3 => Outcome::Drawn,
+            _ => return None,
+        };
+        Some(Self {
+            seed: u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            turns: u16::from_le_bytes(bytes[8..10].try_into().unwrap()),
+            outcome,
+        })
+    }
+}
+
+/// Fletcher-16 checksum.
+pub fn checksum(bytes: &[u8]) -> u16 {
+    let (a, b) = bytes.iter().fold((0u16, 0u16), |(a, b), &byte| {
+        let a = (a + byte as u16) % 255;
+        (a, (b + a) % 255)
+    });
+    b << 8 | a
+}
+
+/// The best results on the most recently played boards.
+///
+/// Entries are ordered from most to least recently played. Once full, playing a new board forgets
+/// the least recently played one.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Records {
+    entries: [Option<Record>; CAPACITY],
+}
+
+impl Records {
+    pub fn new() -> Self {
+        Self {
+            entries: [None; CAPACITY],
+        }
+    }
+
+    /// Whether no games have been recorded, as for a new player.
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(Option::is_none)
+    }
+
+    /// The best result on the board with the given seed, if it has been played recently.
+    pub fn lookup(&self, seed: u64) -> Option<Record> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|record| record.seed == seed)
+            .copied()
+    }
+
+    /// Record a finished game, keeping only the best result for the board.
+    pub fn update(&mut self, record: Record) {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.is_some_and(|entry| entry.seed == record.seed))
+            // Not seen recently, so evict the least recently played board.
+            .unwrap_or(CAPACITY - 1);
+        let best = match self.entries[index] {
+            Some(previous)
+                if previous.seed == record.seed
+                    && previous.cmp_result(&record) == Ordering::Greater =>
+            {
+                previous
+            }
+            _ => record,
+        };
+        // Move the board to the front.
+        self.entries[..=index].rotate_right(1);
+        self.entries[0] = Some(best);
+    }
+
+    fn to_bytes(&self) -> [u8; LEN] {
+        let mut bytes = [0; LEN];
+        bytes[..MAGIC.len()].copy_from_slice(&MAGIC);
+        bytes[MAGIC.len()..HEADER_LEN].copy_from_slice(&COMPAT_VERSION.to_le_bytes());
+        for (entry, chunk) in self
+            .entries
+            .iter()
+            .zip(bytes[HEADER_LEN..LEN - 2].chunks_exact_mut(ENTRY_LEN))
+        {
+            if let Some(record) = entry {
+                record.write(chunk);
+            }
+        }
+        let checksum = checksum(&bytes[..LEN - 2]);
+        bytes[LEN - 2..].copy_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Returns `None` if the bytes are not a valid table.
+    fn from_bytes(bytes: &[u8; LEN]) -> Option<Self> {
+        if bytes[..MAGIC.len()] != MAGIC
+            || checksum(&bytes[..LEN - 2]).to_le_bytes() != bytes[LEN - 2..]
+        {
+            return None;
+        }
+        let version = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]);
+        if version != COMPAT_VERSION {
+            warn!("records: discarding records from compatibility version {version}");
+            return None;
+        }
+        let mut records = Self::new();
+        for (entry, chunk) in records
+            .entries
+            .iter_mut()
+            .zip(bytes[HEADER_LEN..LEN - 2].chunks_exact(ENTRY_LEN))
+        {
+            *entry = Record::read(chunk);
+        }
+        Some(records)
+    }
+
+    /// Load the table from SRAM.
+    ///
+    /// If SRAM doesn't hold a valid table, such as on the first boot or after an update that changed
+    /// the compatibility version, an empty table is returned.
+    pub fn load() -> Self {
+        let mut bytes = [0; LEN];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = sram_read(index);
+        }
+        Self::from_bytes(&bytes).unwrap_or_else(Self::new)
+    }
+
+    /// Save the table to SRAM.
+    pub fn save(&self) {
+        for (index, byte) in self.to_bytes().into_iter().enumerate() {
+            sram_write(index, byte);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checksum, Outcome, Record, Records, CAPACITY, HEADER_LEN, LEN, MAGIC};
+    use crate::game::compat::COMPAT_VERSION;
+    use gba_test::test;
+
+    fn record(seed: u64, turns: u16, outcome: Outcome) -> Record {
+        Record {
+            seed,
+            turns,
+            outcome,
+        }
+    }
+
+    #[test]
+    fn is_empty_new() {
+        assert!(Records::new().is_empty());
+    }
+
+    #[test]
+    fn is_empty_after_update() {
+        let mut records = Records::new();
+
+        records.update(record(5, 23, Outcome::Lost));
+
+        assert!(!records.is_empty());
+    }
+
+    #[test]
+    fn lookup_empty() {
+        assert_eq!(Records::new().lookup(5), None);
+    }
+
+    #[test]
+    fn lookup_after_update() {
+        let mut records = Records::new();
+
+        records.update(record(5, 23, Outcome::Won));
+
+        assert_eq!(records.lookup(5), Some(record(5, 23, Outcome::Won)));
+        assert_eq!(records.lookup(6), None);
+    }
+
+    #[test]
+    fn update_keeps_faster_win() {
+        let mut records = Records::new();
+
+        records.update(record(5, 23, Outcome::Won));
+        records.update(record(5, 30, Outcome::Won));
+
+        assert_eq!(records.lookup(5), Some(record(5, 23, Outcome::Won)));
+    }
+
+    #[test]
+    fn update_replaces_with_faster_win() {
+        let mut records = Records::new();
+
+        records.update(record(5, 23, Outcome::Won));
+        records.update(record(5, 20, Outcome::Won));
+
+        assert_eq!(records.lookup(5), Some(record(5, 20, Outcome::Won)));
+    }
+
+    #[test]
+    fn update_win_beats_loss() {
+        let mut records = Records::new();
+
+        records.update(record(5, 10, Outcome::Lost));
+        records.update(record(5, 50, Outcome::Won));
+        records.update(record(5, 60, Outcome::Lost));
+
+        assert_eq!(records.lookup(5), Some(record(5, 50, Outcome::Won)));
+    }
+
+    #[test]
+    fn update_draw_between_win_and_loss() {
+        let mut records = Records::new();
+
+        records.update(record(5, 10, Outcome::Lost));
+        records.update(record(5, 40, Outcome::Drawn));
+        records.update(record(5, 60, Outcome::Lost));
+        assert_eq!(records.lookup(5), Some(record(5, 40, Outcome::Drawn)));
+
+        records.update(record(5, 50, Outcome::Won));
+        records.update(record(5, 60, Outcome::Drawn));
+        assert_eq!(records.lookup(5), Some(record(5, 50, Outcome::Won)));
+    }
+
+    #[test]
+    fn update_keeps_longer_draw() {
+        let mut records = Records::new();
+
+        records.update(record(5, 30, Outcome::Drawn));
+        records.update(record(5, 10, Outcome::Drawn));
+
+        assert_eq!(records.lookup(5), Some(record(5, 30, Outcome::Drawn)));
+    }
+
+    #[test]
+    fn update_keeps_longer_loss() {
+        let mut records = Records::new();
+
+        records.update(record(5, 30, Outcome::Lost));
+        records.update(record(5, 10, Outcome::Lost));
+
+        assert_eq!(records.lookup(5), Some(record(5, 30, Outcome::Lost)));
+    }
+
+    #[test]
+    fn update_evicts_least_recently_played() {
+        let mut records = Records::new();
+
+        for seed in 0..CAPACITY as u64 {
+            records.update(record(seed, 1, Outcome::Won));
+        }
+        // Playing the oldest board again makes it the most recent.
+        records.update(record(0, 1, Outcome::Won));
+        records.update(record(100, 1, Outcome::Won));
+
+        assert!(records.lookup(0).is_some());
+        assert!(records.lookup(1).is_none());
+        assert!(records.lookup(100).is_some());
+        assert!((2..CAPACITY as u64).all(|seed| records.lookup(seed).is_some()));
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut records = Records::new();
+        records.update(record(u64::MAX, 23, Outcome::Won));
+        records.update(record(7, 40, Outcome::Lost));
+        records.update(record(9, 12, Outcome::Drawn));
+
+        assert_eq!(Records::from_bytes(&records.to_bytes()), Some(records));
+    }
+
+    #[test]
+    fn from_bytes_blank_sram() {
+        assert_eq!(Records::from_bytes(&[0xff; LEN]), None);
+    }
+
+    #[test]
+    fn from_bytes_corrupted() {
+        let mut records = Records::new();
+        records.update(record(5, 23, Outcome::Won));
+        let mut bytes = records.to_bytes();
+        bytes[HEADER_LEN + 2] ^= 1;
+
+        assert_eq!(Records::from_bytes(&bytes), None);
+    }
+
+    #[test]
+    fn to_bytes_embeds_version() {
+        let bytes = Records::new().to_bytes();
+
+        assert_eq!(bytes[MAGIC.len()..HEADER_LEN], COMPAT_VERSION.to_le_bytes());
+    }
+
+    #[test]
+    fn from_bytes_other_version() {
+        let mut records = Records::new();
+        records.update(record(5, 23, Outcome::Won));
+        let mut bytes = records.to_bytes();
+        // A valid table, as saved by a different version.
+        bytes[MAGIC.len()..HEADER_LEN].copy_from_slice(&(COMPAT_VERSION + 1).to_le_bytes());
+        let checksum = checksum(&bytes[..LEN - 2]);
+        bytes[LEN - 2..].copy_from_slice(&checksum.to_le_bytes());
+
+        assert_eq!(Records::from_bytes(&bytes), None);
+    }
+}
@@ -104,6 +104,13 @@ global_asm! {
     "orr  r1, r1, r0",
     "strh r1, [r2]",
 
+    // Count v-blanks for the watchdog
+    "tst r0, #1",
+    "ldrne r1, ={frame_count}",
+    "ldrne r2, [r1]",
+    "addne r2, r2, #1",
+    "strne r2, [r1]",
+
     // Restore initial IME setting and return
     "swp r3, r3, [r12]",
     "bx lr",
@@ -111,6 +118,7 @@ global_asm! {
     ".code 16",
 
     ime_offset = const IME_OFFSET,
+    frame_count = sym crate::watchdog::FRAME_COUNT,
 }
 
 #[no_mangle]
@@ -0,0 +1,263 @@
+use super::{ColorSelect, Screen, Title};
+use crate::{
+    bios::wait_for_vblank_checked,
+    game::{
+        board_code::{self, ALPHABET},
+        BoardCode,
+    },
+    mmio::{
+        keys::KeyInput,
+        vram::{BackgroundControl, DisplayControl},
+        BG3CNT, BLDY, DISPCNT, KEYINPUT, TEXT_SCREENBLOCK28,
+    },
+    settings::Settings,
+    text,
+};
+use core::str;
+use deranged::RangedU8;
+
+/// The column the code starts at, centered on the screen.
+const CODE_X: usize = (30 - board_code::LEN) / 2;
+
+/// The row the code is drawn on. The character being changed is marked on the row below it.
+const CODE_ROW: usize = 8;
+
+/// The row explaining why the entered code was rejected.
+const ERROR_ROW: usize = 11;
+
+/// Where a board code shared by another player is typed in, to play the same board with the same
+/// rules.
+pub struct CodeEntry {
+    code: [u8; board_code::LEN],
+    /// The index of the character being changed.
+    selected: usize,
+    prev_keys: KeyInput,
+}
+
+impl CodeEntry {
+    /// Start entering a code, beginning from `code`.
+    pub fn new(code: BoardCode) -> Self {
+        wait_for_vblank_checked();
+
+        unsafe {
+            BG3CNT.write_volatile(
+                BackgroundControl::new()
+                    .with_screenblock(RangedU8::new_static::<28>())
+                    .with_priority(RangedU8::new_static::<0>()),
+            );
+            DISPCNT.write_volatile(DisplayControl::new().with_bg3(true));
+        }
+        text::load();
+        for y in 0..32 {
+            text::clear(TEXT_SCREENBLOCK28, 0, y, 32);
+        }
+        text::draw(TEXT_SCREENBLOCK28, 10, 2, "ENTER CODE");
+        text::draw(TEXT_SCREENBLOCK28, 4, 15, "LEFT/RIGHT: MOVE");
+        text::draw(TEXT_SCREENBLOCK28, 4, 16, "UP/DOWN: CHANGE");
+        text::draw(TEXT_SCREENBLOCK28, 4, 17, "A: PLAY");
+        text::draw(TEXT_SCREENBLOCK28, 4, 18, "B: BACK");
+
+        let code_entry = Self {
+            code: code.encode(),
+            selected: 0,
+            // L is still held from the title screen.
+            prev_keys: KeyInput::L,
+        };
+        code_entry.draw_code();
+        code_entry.draw_selection();
+
+        // Fade in.
+        for fade in (0..31).rev() {
+            wait_for_vblank_checked();
+            unsafe {
+                BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+            }
+        }
+
+        code_entry
+    }
+
+    /// Draw the code, clearing any error left from the last attempt to play it.
+    fn draw_code(&self) {
+        text::clear(TEXT_SCREENBLOCK28, 0, ERROR_ROW, 30);
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            CODE_X,
+            CODE_ROW,
+            // The code is always ASCII.
+            str::from_utf8(&self.code).unwrap(),
+        );
+    }
+
+    fn draw_selection(&self) {
+        text::clear(TEXT_SCREENBLOCK28, CODE_X, CODE_ROW + 1, board_code::LEN);
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            CODE_X + self.selected,
+            CODE_ROW + 1,
+            "^",
+        );
+    }
+
+    fn pressed(&self, keys: KeyInput, key: KeyInput) -> bool {
+        keys.just_pressed(self.prev_keys, key)
+    }
+
+    pub fn run(&mut self) -> Option<Screen> {
+        let keys = unsafe { KEYINPUT.read_volatile() };
+
+        if self.pressed(keys, KeyInput::A) {
+            match BoardCode::decode(&self.code) {
+                Ok(code) => {
+                    // Fade out.
+                    wait_for_vblank_checked();
+                    for fade in 0..31 {
+                        wait_for_vblank_checked();
+                        unsafe {
+                            BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+                        }
+                    }
+
+                    return Some(Screen::ColorSelect(ColorSelect::new(
+                        Settings::default(),
+                        code,
+                    )));
+                }
+                Err(error) => {
+                    wait_for_vblank_checked();
+                    text::draw_centered(TEXT_SCREENBLOCK28, ERROR_ROW, &[error_message(&error)]);
+                }
+            }
+        }
+
+        if self.pressed(keys, KeyInput::B) {
+            // Fade out.
+            wait_for_vblank_checked();
+            for fade in 0..31 {
+                wait_for_vblank_checked();
+                unsafe {
+                    BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+                }
+            }
+
+            return Some(Screen::Title(Title::new()));
+        }
+
+        wait_for_vblank_checked();
+        if self.pressed(keys, KeyInput::LEFT) && self.selected > 0 {
+            self.selected -= 1;
+            self.draw_selection();
+        }
+        if self.pressed(keys, KeyInput::RIGHT) && self.selected < board_code::LEN - 1 {
+            self.selected += 1;
+            self.draw_selection();
+        }
+        if self.pressed(keys, KeyInput::UP) {
+            self.code[self.selected] = cycle(self.code[self.selected], true);
+            self.draw_code();
+        }
+        if self.pressed(keys, KeyInput::DOWN) {
+            self.code[self.selected] = cycle(self.code[self.selected], false);
+            self.draw_code();
+        }
+
+        self.prev_keys = keys;
+
+        None
+    }
+}
+
+/// The character after `character` in the code alphabet, or the one before it if `forward` is
+/// false, wrapping around at either end.
+///
+/// A character outside of the alphabet is treated as its first character.
+fn cycle(character: u8, forward: bool) -> u8 {
+    let index = ALPHABET
+        .iter()
+        .position(|&symbol| symbol == character)
+        .unwrap_or(0);
+    let index = if forward {
+        (index + 1) % ALPHABET.len()
+    } else {
+        (index + ALPHABET.len() - 1) % ALPHABET.len()
+    };
+    ALPHABET[index]
+}
+
+/// Why a code was rejected, short enough to fit on one line.
+fn error_message(error: &board_code::Error) -> &'static str {
+    match error {
+        board_code::Error::Length(_) | board_code::Error::InvalidCharacter(_) => "NOT A CODE",
+        board_code::Error::Check => "CHECK FAILED - TYPO?",
+        board_code::Error::UnsupportedVersion(_) => "MADE BY ANOTHER VERSION",
+        board_code::Error::UnsupportedPreset(_) => "UNKNOWN RULES",
+        board_code::Error::UnsupportedSize(_) => "UNKNOWN BOARD",
+        board_code::Error::UnsupportedPlayers(_) => "UNKNOWN PLAYER COUNT",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cycle, error_message};
+    use crate::game::{
+        board_code::{self, ALPHABET},
+        BoardCode,
+    };
+    use gba_test::test;
+
+    #[test]
+    fn cycle_forward() {
+        assert_eq!(cycle(b'0', true), b'1');
+        assert_eq!(cycle(b'H', true), b'J');
+    }
+
+    #[test]
+    fn cycle_backward() {
+        assert_eq!(cycle(b'1', false), b'0');
+        assert_eq!(cycle(b'J', false), b'H');
+    }
+
+    #[test]
+    fn cycle_wraps_around() {
+        assert_eq!(cycle(b'Z', true), b'0');
+        assert_eq!(cycle(b'0', false), b'Z');
+    }
+
+    #[test]
+    fn cycle_undoes_itself() {
+        for character in ALPHABET {
+            assert_eq!(cycle(cycle(character, true), false), character);
+        }
+    }
+
+    #[test]
+    fn cycle_outside_alphabet() {
+        assert_eq!(cycle(b'I', true), b'1');
+    }
+
+    #[test]
+    fn typo_fails_check() {
+        let mut code = BoardCode::new(0).encode();
+        code[5] = cycle(code[5], true);
+
+        assert_eq!(
+            error_message(&BoardCode::decode(&code).unwrap_err()),
+            error_message(&board_code::Error::Check)
+        );
+    }
+
+    #[test]
+    fn error_messages_fit() {
+        for error in [
+            board_code::Error::Length(0),
+            board_code::Error::InvalidCharacter(b'I'),
+            board_code::Error::Check,
+            board_code::Error::UnsupportedVersion(0),
+            board_code::Error::UnsupportedPreset(0),
+            board_code::Error::UnsupportedSize(0),
+            board_code::Error::UnsupportedPlayers(0),
+        ] {
+            assert!(error_message(&error).len() <= 30, "{error:?}");
+        }
+    }
+}
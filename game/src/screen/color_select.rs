@@ -0,0 +1,180 @@
+use super::{Screen, Setup};
+use crate::{
+    bios::wait_for_vblank_checked,
+    game::{BoardCode, Color},
+    mmio::{
+        keys::KeyInput,
+        vram::{BackgroundControl, DisplayControl},
+        BG3CNT, BLDY, DISPCNT, KEYINPUT, TEXT_SCREENBLOCK28,
+    },
+    settings::Settings,
+    text,
+};
+use deranged::RangedU8;
+
+/// The column the color names are drawn at.
+const NAME_X: usize = 12;
+
+/// The row the AI difficulty is drawn on, below the colors.
+const DIFFICULTY_ROW: usize = 15;
+
+/// Where the player chooses which color they play as and how strong the AI is, before setting up
+/// the rest of the game.
+pub struct ColorSelect {
+    settings: Settings,
+    code: BoardCode,
+    selected: Color,
+    prev_keys: KeyInput,
+}
+
+impl ColorSelect {
+    pub fn new(settings: Settings, code: BoardCode) -> Self {
+        wait_for_vblank_checked();
+
+        unsafe {
+            BG3CNT.write_volatile(
+                BackgroundControl::new()
+                    .with_screenblock(RangedU8::new_static::<28>())
+                    .with_priority(RangedU8::new_static::<0>()),
+            );
+            DISPCNT.write_volatile(DisplayControl::new().with_bg3(true));
+        }
+        text::load();
+        for y in 0..32 {
+            text::clear(TEXT_SCREENBLOCK28, 0, y, 32);
+        }
+        text::draw(TEXT_SCREENBLOCK28, 9, 2, "PLAY AS");
+        // A code entered for fewer players leaves out the colors that do not play on it.
+        for color in Color::ALL {
+            if color.plays(code.players) {
+                text::draw(TEXT_SCREENBLOCK28, NAME_X, Self::row(color), color.name());
+            }
+        }
+        text::draw(TEXT_SCREENBLOCK28, 7, DIFFICULTY_ROW, "AI");
+        text::draw(TEXT_SCREENBLOCK28, 4, 17, "D-PAD: CHANGE");
+        text::draw(TEXT_SCREENBLOCK28, 4, 18, "L/R: DIFFICULTY");
+        text::draw(TEXT_SCREENBLOCK28, 4, 19, "A: CONTINUE");
+
+        let color_select = Self {
+            settings,
+            code,
+            // Red plays with any number of players.
+            selected: Color::Red,
+            // A is still held from the title or code entry screen.
+            prev_keys: KeyInput::A,
+        };
+        color_select.draw_selection();
+        color_select.draw_difficulty();
+
+        // Fade in.
+        for fade in (0..31).rev() {
+            wait_for_vblank_checked();
+            unsafe {
+                BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+            }
+        }
+
+        color_select
+    }
+
+    fn row(color: Color) -> usize {
+        6 + u8::from(color) as usize * 2
+    }
+
+    fn draw_selection(&self) {
+        for color in Color::ALL {
+            if color == self.selected {
+                text::draw(TEXT_SCREENBLOCK28, NAME_X - 2, Self::row(color), ">");
+            } else {
+                text::clear(TEXT_SCREENBLOCK28, NAME_X - 2, Self::row(color), 1);
+            }
+        }
+    }
+
+    fn draw_difficulty(&self) {
+        text::clear(TEXT_SCREENBLOCK28, NAME_X, DIFFICULTY_ROW, 6);
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            NAME_X,
+            DIFFICULTY_ROW,
+            self.settings.difficulty.name(),
+        );
+    }
+
+    fn pressed(&self, keys: KeyInput, key: KeyInput) -> bool {
+        keys.just_pressed(self.prev_keys, key)
+    }
+
+    pub fn run(&mut self) -> Option<Screen> {
+        let keys = unsafe { KEYINPUT.read_volatile() };
+
+        if self.pressed(keys, KeyInput::A) {
+            // Fade out.
+            wait_for_vblank_checked();
+            for fade in 0..31 {
+                wait_for_vblank_checked();
+                unsafe {
+                    BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+                }
+            }
+
+            return Some(Screen::Setup(Setup::new(
+                self.settings,
+                self.code,
+                self.selected,
+            )));
+        }
+
+        wait_for_vblank_checked();
+        if self.pressed(keys, KeyInput::UP) || self.pressed(keys, KeyInput::LEFT) {
+            self.selected = previous(self.selected);
+            while !self.selected.plays(self.code.players) {
+                self.selected = previous(self.selected);
+            }
+            self.draw_selection();
+        }
+        if self.pressed(keys, KeyInput::DOWN) || self.pressed(keys, KeyInput::RIGHT) {
+            self.selected = self.selected.next();
+            while !self.selected.plays(self.code.players) {
+                self.selected = self.selected.next();
+            }
+            self.draw_selection();
+        }
+        if self.pressed(keys, KeyInput::L) {
+            self.settings.difficulty = self.settings.difficulty.previous();
+            self.draw_difficulty();
+        }
+        if self.pressed(keys, KeyInput::R) {
+            self.settings.difficulty = self.settings.difficulty.next();
+            self.draw_difficulty();
+        }
+
+        self.prev_keys = keys;
+
+        None
+    }
+}
+
+/// The color before `color`, wrapping around from the first to the last.
+fn previous(color: Color) -> Color {
+    Color::ALL[(u8::from(color) as usize + Color::ALL.len() - 1) % Color::ALL.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::previous;
+    use crate::game::Color;
+    use gba_test::test;
+
+    #[test]
+    fn previous_undoes_next() {
+        for color in Color::ALL {
+            assert_eq!(previous(color.next()), color);
+        }
+    }
+
+    #[test]
+    fn previous_wraps_around() {
+        assert_eq!(previous(Color::Red), Color::Green);
+    }
+}
@@ -0,0 +1,235 @@
+//! Scrolling credits.
+
+use super::{Screen, Title};
+use crate::{
+    bios::wait_for_vblank_checked,
+    mmio::{
+        keys::KeyInput,
+        vram::{BackgroundControl, DisplayControl},
+        BG3CNT, BG3VOFS, BLDY, DISPCNT, KEYINPUT, TEXT_SCREENBLOCK28,
+    },
+    text,
+};
+use core::ops::Range;
+use deranged::{RangedU16, RangedU8};
+
+/// The lines of the credits, each at most 30 characters.
+const CREDITS: &[&str] = &[
+    "TUG OF ORB",
+    "",
+    "",
+    "PROGRAMMING AND ART",
+    "ANDERS429",
+    "",
+    "",
+    "BUILT WITH",
+    "",
+    "DERANGED",
+    "RAND",
+    "LOG",
+    "MGBA_LOG",
+    "NOCASH_GBA_LOG",
+    "GBA_TEST",
+    "",
+    "",
+    "THANK YOU FOR PLAYING!",
+];
+
+/// The number of blank rows before the first line, so the credits scroll in from the bottom.
+const PADDING: u32 = 20;
+
+/// The number of tile rows in the screenblock, after which rows are reused.
+const SCREENBLOCK_ROWS: u32 = 32;
+
+/// The height of the screen in pixels.
+const SCREEN_HEIGHT: u32 = 160;
+
+/// The rows, counted from the top of the scrolling text, that must be drawn once the text has
+/// scrolled `offset` pixels, given that rows before `drawn` are already drawn.
+///
+/// Every row that is at least partially on screen is included.
+fn rows_to_draw(drawn: u32, offset: u32) -> Range<u32> {
+    drawn..drawn.max((offset + SCREEN_HEIGHT).div_ceil(8))
+}
+
+/// The line of the credits drawn on `row`, if the row isn't blank.
+fn line(row: u32) -> Option<&'static str> {
+    row.checked_sub(PADDING)
+        .and_then(|index| CREDITS.get(index as usize))
+        .copied()
+}
+
+/// Whether the last line has scrolled off the top of the screen.
+fn is_finished(offset: u32) -> bool {
+    offset >= (PADDING + CREDITS.len() as u32) * 8
+}
+
+/// Scrolls the credits up the screen.
+///
+/// Only the rows about to come on screen are drawn, each into the screenblock row it wraps to, so
+/// the credits can be longer than a screenblock. Holding A speeds up the scrolling and B exits
+/// to the title screen.
+pub struct Credits {
+    /// The scroll position, in half pixels.
+    ticks: u32,
+    /// The number of rows drawn so far.
+    drawn: u32,
+}
+
+impl Credits {
+    pub fn new() -> Self {
+        wait_for_vblank_checked();
+
+        unsafe {
+            BG3CNT.write_volatile(
+                BackgroundControl::new()
+                    .with_screenblock(RangedU8::new_static::<28>())
+                    .with_priority(RangedU8::new_static::<0>()),
+            );
+            BG3VOFS.write_volatile(RangedU16::new_static::<0>());
+            DISPCNT.write_volatile(DisplayControl::new().with_bg3(true));
+        }
+        text::load();
+        for y in 0..32 {
+            text::clear(TEXT_SCREENBLOCK28, 0, y, 32);
+        }
+
+        let mut credits = Self { ticks: 0, drawn: 0 };
+        credits.draw_rows();
+
+        // Fade in.
+        for fade in (0..31).rev() {
+            wait_for_vblank_checked();
+            unsafe {
+                BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+            }
+        }
+
+        credits
+    }
+
+    fn offset(&self) -> u32 {
+        self.ticks / 2
+    }
+
+    /// Draw the rows that have come on screen since the last call.
+    fn draw_rows(&mut self) {
+        let rows = rows_to_draw(self.drawn, self.offset());
+        self.drawn = rows.end;
+        for row in rows {
+            let y = (row % SCREENBLOCK_ROWS) as usize;
+            text::clear(TEXT_SCREENBLOCK28, 0, y, 30);
+            if let Some(line) = line(row) {
+                text::draw(TEXT_SCREENBLOCK28, (30 - line.len()) / 2, y, line);
+            }
+        }
+    }
+
+    pub fn run(&mut self) -> Option<Screen> {
+        let keys = unsafe { KEYINPUT.read_volatile() };
+
+        if keys.contains(KeyInput::B) || is_finished(self.offset()) {
+            // Fade out.
+            wait_for_vblank_checked();
+            for fade in 0..31 {
+                wait_for_vblank_checked();
+                unsafe {
+                    BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+                }
+            }
+            unsafe {
+                BG3VOFS.write_volatile(RangedU16::new_static::<0>());
+            }
+
+            return Some(Screen::Title(Title::new()));
+        }
+
+        self.ticks += if keys.contains(KeyInput::A) { 8 } else { 1 };
+        self.draw_rows();
+
+        wait_for_vblank_checked();
+        unsafe {
+            // The screenblock is 256 pixels tall, so the offset wraps around with it.
+            BG3VOFS.write_volatile(RangedU16::new_unchecked((self.offset() % 256) as u16));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_finished, line, rows_to_draw, CREDITS, PADDING, SCREENBLOCK_ROWS};
+    use gba_test::test;
+
+    #[test]
+    fn rows_to_draw_initial_screen() {
+        assert_eq!(rows_to_draw(0, 0), 0..20);
+    }
+
+    #[test]
+    fn rows_to_draw_partial_row() {
+        // A single pixel of scrolling brings the next row partially on screen.
+        assert_eq!(rows_to_draw(20, 1), 20..21);
+    }
+
+    #[test]
+    fn rows_to_draw_nothing_new() {
+        assert_eq!(rows_to_draw(21, 7), 21..21);
+        assert_eq!(rows_to_draw(21, 8), 21..21);
+    }
+
+    #[test]
+    fn rows_to_draw_fast_scroll() {
+        assert_eq!(rows_to_draw(21, 32), 21..24);
+    }
+
+    #[test]
+    fn rows_to_draw_never_redraws() {
+        let mut drawn = 0;
+        for offset in 0..1000 {
+            let rows = rows_to_draw(drawn, offset);
+
+            assert_eq!(rows.start, drawn);
+            drawn = rows.end;
+        }
+    }
+
+    #[test]
+    fn rows_drawn_off_screen() {
+        // A row must never be drawn over a row that is still on screen.
+        let mut drawn = 0;
+        for offset in 0..1000 {
+            let top = offset / 8;
+            for row in rows_to_draw(drawn, offset) {
+                assert!(row - top < SCREENBLOCK_ROWS);
+            }
+            drawn = rows_to_draw(drawn, offset).end;
+        }
+    }
+
+    #[test]
+    fn line_padding() {
+        assert_eq!(line(0), None);
+        assert_eq!(line(PADDING - 1), None);
+        assert_eq!(line(PADDING), Some(CREDITS[0]));
+    }
+
+    #[test]
+    fn line_past_end() {
+        assert_eq!(line(PADDING + CREDITS.len() as u32), None);
+    }
+
+    #[test]
+    fn lines_fit_on_screen() {
+        assert!(CREDITS.iter().all(|line| line.len() <= 30));
+    }
+
+    #[test]
+    fn is_finished_after_last_line() {
+        let end = (PADDING + CREDITS.len() as u32) * 8;
+
+        assert!(!is_finished(end - 1));
+        assert!(is_finished(end));
+    }
+}
@@ -0,0 +1,229 @@
+//! A hidden screen for testing hardware features, opened by holding SELECT while pressing B on the
+//! title screen.
+
+use super::{Screen, Title};
+use crate::{
+    audio::{self, SAMPLES},
+    bios::wait_for_vblank_checked,
+    mmio::{
+        keys::KeyInput,
+        vram::{BackgroundControl, DisplayControl},
+        BG3CNT, BLDY, DISPCNT, KEYINPUT, TEXT_SCREENBLOCK28,
+    },
+    text, watchdog,
+};
+use deranged::RangedU8;
+
+/// The row the first menu item is drawn at.
+const MENU_Y: usize = 4;
+
+/// What selecting a menu item does.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Action {
+    /// Play the sample at this index of `audio::SAMPLES`.
+    Play(usize),
+    Stop,
+    Exit,
+}
+
+/// The number of items in the menu.
+const ITEM_COUNT: usize = SAMPLES.len() + 2;
+
+/// The menu item at `index`, as its label and action.
+fn item(index: usize) -> Option<(&'static str, Action)> {
+    match index.checked_sub(SAMPLES.len()) {
+        None => Some((SAMPLES[index].name, Action::Play(index))),
+        Some(0) => Some(("STOP", Action::Stop)),
+        Some(1) => Some(("EXIT", Action::Exit)),
+        Some(_) => None,
+    }
+}
+
+/// Move the selection up or down, wrapping around at either end.
+fn move_selection(selected: usize, down: bool) -> usize {
+    if down {
+        (selected + 1) % ITEM_COUNT
+    } else {
+        (selected + ITEM_COUNT - 1) % ITEM_COUNT
+    }
+}
+
+pub struct Debug {
+    selected: usize,
+    /// The number of frames until the playing sample ends, if one is playing.
+    playing: Option<u32>,
+    prev_keys: KeyInput,
+}
+
+impl Debug {
+    pub fn new() -> Self {
+        wait_for_vblank_checked();
+
+        unsafe {
+            BG3CNT.write_volatile(
+                BackgroundControl::new()
+                    .with_screenblock(RangedU8::new_static::<28>())
+                    .with_priority(RangedU8::new_static::<0>()),
+            );
+            DISPCNT.write_volatile(DisplayControl::new().with_bg3(true));
+        }
+        text::load();
+        for y in 0..32 {
+            text::clear(TEXT_SCREENBLOCK28, 0, y, 32);
+        }
+        text::draw(TEXT_SCREENBLOCK28, 2, 1, "SOUND TEST");
+        for index in 0..ITEM_COUNT {
+            if let Some((label, _)) = item(index) {
+                text::draw(TEXT_SCREENBLOCK28, 4, MENU_Y + index, label);
+            }
+        }
+        // Only counted in debug builds.
+        text::draw(TEXT_SCREENBLOCK28, 2, 16, "OVERRUNS");
+        let mut buffer = [0; 5];
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            11,
+            16,
+            text::format_number(watchdog::overruns(), &mut buffer),
+        );
+
+        let debug = Self {
+            selected: 0,
+            playing: None,
+            // B is still held from the title screen.
+            prev_keys: KeyInput::B,
+        };
+        debug.draw_selection();
+
+        // Fade in.
+        for fade in (0..31).rev() {
+            wait_for_vblank_checked();
+            unsafe {
+                BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+            }
+        }
+
+        debug
+    }
+
+    fn draw_selection(&self) {
+        for index in 0..ITEM_COUNT {
+            if index == self.selected {
+                text::draw(TEXT_SCREENBLOCK28, 2, MENU_Y + index, ">");
+            } else {
+                text::clear(TEXT_SCREENBLOCK28, 2, MENU_Y + index, 1);
+            }
+        }
+    }
+
+    fn stop(&mut self) {
+        audio::stop();
+        self.playing = None;
+        text::clear(TEXT_SCREENBLOCK28, 2, 18, 7);
+    }
+
+    fn pressed(&self, keys: KeyInput, key: KeyInput) -> bool {
+        keys.just_pressed(self.prev_keys, key)
+    }
+
+    pub fn run(&mut self) -> Option<Screen> {
+        let keys = unsafe { KEYINPUT.read_volatile() };
+
+        let action = if self.pressed(keys, KeyInput::A) {
+            item(self.selected).map(|(_, action)| action)
+        } else if self.pressed(keys, KeyInput::B) {
+            Some(Action::Exit)
+        } else {
+            None
+        };
+        match action {
+            Some(Action::Play(index)) => {
+                audio::play(&SAMPLES[index]);
+                self.playing = Some(SAMPLES[index].frames());
+                text::draw(TEXT_SCREENBLOCK28, 2, 18, "PLAYING");
+            }
+            Some(Action::Stop) => self.stop(),
+            Some(Action::Exit) => {
+                // Leave the audio registers the way the rest of the game expects them.
+                self.stop();
+
+                // Fade out.
+                wait_for_vblank_checked();
+                for fade in 0..31 {
+                    wait_for_vblank_checked();
+                    unsafe {
+                        BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+                    }
+                }
+
+                return Some(Screen::Title(Title::new()));
+            }
+            None => {}
+        }
+
+        wait_for_vblank_checked();
+        if self.pressed(keys, KeyInput::UP) {
+            self.selected = move_selection(self.selected, false);
+            self.draw_selection();
+        }
+        if self.pressed(keys, KeyInput::DOWN) {
+            self.selected = move_selection(self.selected, true);
+            self.draw_selection();
+        }
+        match self.playing {
+            Some(0) => self.stop(),
+            Some(frames) => self.playing = Some(frames - 1),
+            None => {}
+        }
+
+        self.prev_keys = keys;
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{item, move_selection, Action, ITEM_COUNT};
+    use crate::audio::SAMPLES;
+    use gba_test::test;
+
+    #[test]
+    fn item_samples_first() {
+        assert_eq!(item(0), Some((SAMPLES[0].name, Action::Play(0))));
+    }
+
+    #[test]
+    fn item_stop_and_exit_last() {
+        assert_eq!(item(ITEM_COUNT - 2), Some(("STOP", Action::Stop)));
+        assert_eq!(item(ITEM_COUNT - 1), Some(("EXIT", Action::Exit)));
+    }
+
+    #[test]
+    fn item_past_end() {
+        assert_eq!(item(ITEM_COUNT), None);
+    }
+
+    #[test]
+    fn every_sample_playable() {
+        for index in 0..SAMPLES.len() {
+            assert!((0..ITEM_COUNT).any(|item_index| item(item_index)
+                .is_some_and(|(_, action)| action == Action::Play(index))));
+        }
+    }
+
+    #[test]
+    fn move_selection_down_wraps() {
+        assert_eq!(move_selection(ITEM_COUNT - 1, true), 0);
+    }
+
+    #[test]
+    fn move_selection_up_wraps() {
+        assert_eq!(move_selection(0, false), ITEM_COUNT - 1);
+    }
+
+    #[test]
+    fn move_selection_down() {
+        assert_eq!(move_selection(0, true), 1);
+    }
+}
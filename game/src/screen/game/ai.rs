@@ -0,0 +1,860 @@
+//! Turns taken by the computer-controlled players.
+
+use super::{
+    board::BoardRenderer,
+    camera::CameraController,
+    clear_banner,
+    clock::{elapsed, Clock},
+    cursor::CursorSprite,
+    draw_banner,
+    transposition::TranspositionTable,
+    MEGA_CAPTURE,
+};
+use crate::{
+    ai::MinimaxAi,
+    bios::wait_for_vblank_checked,
+    game::{self, prelude::*, PositionSet, TurnOutcome},
+    random::Pcg32Fast,
+    rating::Rating,
+    settings::Difficulty,
+    timing,
+};
+use core::cmp::Reverse;
+use deranged::RangedU8;
+use rand::Rng;
+
+/// How many nodes a choke point is worth to the lookahead AI.
+const CHOKE_POINT_BONUS: usize = 2;
+
+/// How an AI player chooses its moves.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AiLevel {
+    /// Plays any legal move.
+    Random,
+    /// Plays the move that looks like it captures the most nodes.
+    Greedy,
+    /// Plays the move that leaves it with the most nodes after the next player replies, assuming
+    /// they reply with the move leaving it with the fewest.
+    Minimax,
+    /// Plays the move that leaves it with the most nodes after the next player replies.
+    Lookahead,
+}
+
+impl AiLevel {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Random => "RANDOM",
+            Self::Greedy => "GREEDY",
+            Self::Minimax => "MINIMAX",
+            Self::Lookahead => "LOOKAHEAD",
+        }
+    }
+
+    /// A single character identifying the level in the HUD.
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Random => "R",
+            Self::Greedy => "G",
+            Self::Minimax => "M",
+            Self::Lookahead => "L",
+        }
+    }
+
+    /// The rating a player needs to be expected to score half a point against this level.
+    pub fn rating(self) -> Rating {
+        match self {
+            Self::Random => Rating::new(600),
+            Self::Greedy => Rating::new(1000),
+            Self::Minimax => Rating::new(1200),
+            Self::Lookahead => Rating::new(1300),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Random => Self::Greedy,
+            Self::Greedy => Self::Minimax,
+            Self::Minimax => Self::Lookahead,
+            Self::Lookahead => Self::Random,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            Self::Random => Self::Lookahead,
+            Self::Greedy => Self::Random,
+            Self::Minimax => Self::Greedy,
+            Self::Lookahead => Self::Minimax,
+        }
+    }
+}
+
+impl From<Difficulty> for AiLevel {
+    fn from(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::Easy => Self::Random,
+            Difficulty::Normal => Self::Greedy,
+            Difficulty::Hard => Self::Minimax,
+        }
+    }
+}
+
+/// The positions the current player can legally rotate.
+fn legal_moves(game: &game::Game) -> impl Iterator<Item = Position> + Clone + '_ {
+    let turn_color = game.turn_color();
+    // Whether rotating a position is legal doesn't depend on how many times it is rotated.
+    game.valid_turns()
+        .filter(|turn| turn.times == Turn::ONCE)
+        .map(|turn| turn.rotate)
+        .filter(move |&position| game.is_legal_move(turn_color, position))
+}
+
+/// The number of nodes owned by `color`.
+fn count(game: &game::Game, color: Color) -> usize {
+    game.grid().count_for_color(color) as usize
+}
+
+/// The number of choke points owned by `color`.
+fn count_choke_points(game: &game::Game, color: Color, choke_points: &PositionSet) -> usize {
+    choke_points
+        .iter()
+        .filter(|&position| game.grid()[position].is_color(color))
+        .count()
+}
+
+/// Choose any legal move for the current player.
+fn choose_random_move(game: &game::Game, rng: &mut Pcg32Fast) -> Position {
+    let mut moves = legal_moves(game);
+    let index = rng.gen_range(0..moves.clone().count());
+    moves.nth(index).expect("no legal move")
+}
+
+/// Choose the move that leaves the current player with the most nodes once the next player has
+/// replied with their greedy move.
+///
+/// Each choke point held after the move is worth `CHOKE_POINT_BONUS` nodes, since holding one
+/// keeps a whole region of the board within reach.
+///
+/// Scores already in `table` are reused instead of simulating the reply again.
+fn choose_lookahead_move(
+    game: &game::Game,
+    mut table: Option<&mut TranspositionTable>,
+) -> Position {
+    let turn_color = game.turn_color();
+    let choke_points = game.grid().choke_points(game.rules());
+    let mut best = None;
+    for position in legal_moves(game) {
+        let mut future = game.scratch();
+        if let Some(Ending::Winner(_)) = future
+            .execute_turn(Turn {
+                rotate: position,
+                times: Turn::ONCE,
+            })
+            .unwrap()
+            .ending()
+        {
+            // Winning can't be beaten.
+            return position;
+        }
+        // The bonus is kept out of the table, which only stores node counts.
+        let bonus = count_choke_points(&future, turn_color, &choke_points) * CHOKE_POINT_BONUS;
+        let key = future.state_hash();
+        let score = match table
+            .as_deref_mut()
+            .and_then(|table| table.get(key, turn_color))
+        {
+            Some(score) => score,
+            None => {
+                if future.turn_color() != turn_color && future.has_legal_move(future.turn_color()) {
+                    future.execute_turn(choose_move(&future)).unwrap();
+                }
+                let score = count(&future, turn_color);
+                if let Some(table) = table.as_deref_mut() {
+                    table.insert(key, turn_color, score);
+                }
+                score
+            }
+        } + bonus;
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((position, score));
+        }
+    }
+    best.expect("no legal move").0
+}
+
+/// The Manhattan distance from every position to the nearest node owned by a color other than
+/// `color`, or `u8::MAX` if there are no such nodes.
+fn distances_to_opponents(game: &game::Game, color: Color) -> [[u8; 16]; 16] {
+    let mut distances = [[u8::MAX; 16]; 16];
+    for opponent in Color::ALL.into_iter().filter(|&other| other != color) {
+        for position in game.grid().positions_of_color(opponent) {
+            distances[position.y as usize][position.x as usize] = 0;
+        }
+    }
+    // Each pass carries distances along in two directions, so together they cover all four.
+    for y in 0..16 {
+        for x in 0..16 {
+            if x > 0 {
+                distances[y][x] = distances[y][x].min(distances[y][x - 1].saturating_add(1));
+            }
+            if y > 0 {
+                distances[y][x] = distances[y][x].min(distances[y - 1][x].saturating_add(1));
+            }
+        }
+    }
+    for y in (0..16).rev() {
+        for x in (0..16).rev() {
+            if x < 15 {
+                distances[y][x] = distances[y][x].min(distances[y][x + 1].saturating_add(1));
+            }
+            if y < 15 {
+                distances[y][x] = distances[y][x].min(distances[y + 1][x].saturating_add(1));
+            }
+        }
+    }
+    distances
+}
+
+/// Determine the best move for the current player.
+///
+/// This is the move whose rotated node points at the most nodes to capture. Ties go to the node
+/// closest to another player's nodes, so the AI pushes toward its opponents rather than filling in
+/// whichever corner it happens to look at first. When the rules allow rotating a node several
+/// times, every number of rotations is considered, with ties going to the fewest. When super arrows
+/// claim their beams, a super arrow is worth every node its beam would claim, along with the nodes
+/// each of those points at.
+fn choose_move(game: &game::Game) -> Turn {
+    let turn_color = game.turn_color();
+    let distances = distances_to_opponents(game, turn_color);
+    let max_times = if game.rules().multi_rotation { 3 } else { 1 };
+    let mut best_turn = None;
+    let mut best_score = None;
+    // Shared by every super arrow's beam, and cleared before each.
+    let mut visited = PositionSet::new();
+    for (position, node) in game
+        .grid()
+        .iter_positions()
+        .filter(|(_, node)| node.is_color(turn_color))
+    {
+        // Where the node will point once it is rotated.
+        let mut rotated = *node;
+        for times in 1..=max_times {
+            rotated.rotate();
+            let Some(direction) = rotated.direction() else {
+                continue;
+            };
+            let weight =
+                if game.rules().super_arrow_claims && matches!(rotated, Node::SuperArrow { .. }) {
+                    // The beam claims every node it passes over, and each spreads the fill further.
+                    visited.clear();
+                    visited.insert(position);
+                    game.beam(position, direction)
+                        .filter(|&new_pos| {
+                            let node = game.grid()[new_pos];
+                            !matches!(node, Node::Empty) && !node.is_color(turn_color)
+                        })
+                        .map(|new_pos| {
+                            game.grid()
+                                .weight(new_pos, game.rules(), &mut visited)
+                                .max(1)
+                        })
+                        .sum()
+                } else {
+                    game.rules()
+                        .neighbor(position, direction, game.grid().size())
+                        .filter(|&new_pos| {
+                            game.grid()
+                                .get(new_pos)
+                                .is_some_and(|node| !node.is_color(turn_color))
+                        })
+                        .map_or(0, |new_pos| game.weight(new_pos))
+                };
+            let score = (
+                weight,
+                Reverse(distances[position.y as usize][position.x as usize]),
+                Reverse(times),
+            );
+            if best_score.is_none_or(|best_score| score > best_score) {
+                best_score = Some(score);
+                best_turn = Some(Turn {
+                    rotate: position,
+                    times: RangedU8::new(times).unwrap(),
+                });
+            }
+        }
+    }
+
+    // If none of the nodes with a direction were considered, fall back to any node that can still
+    // capture something.
+    best_turn.unwrap_or_else(|| Turn {
+        rotate: legal_moves(game).next().expect("no legal move"),
+        times: Turn::ONCE,
+    })
+}
+
+#[derive(Debug)]
+enum Phase {
+    Idle,
+    /// Scrolling the camera to the position that was rotated.
+    Scrolling(Position),
+    /// Redrawing the board with the result of the turn.
+    Reveal,
+    /// Pausing until the given tick so the player can see what happened.
+    Cooldown(u32),
+}
+
+/// Whether a cooldown lasting until tick `until` is over at tick `now`.
+pub(super) fn cooldown_finished(until: u32, now: u32) -> bool {
+    // Ticks past `until` wrap around to a huge number of ticks remaining.
+    elapsed(now, until) == 0 || elapsed(now, until) > u32::MAX / 2
+}
+
+/// Plays turns for the AI players and presents them to the player.
+///
+/// The turn itself happens all at once, but the presentation of it is spread across frames, one
+/// frame per call to `present()`.
+#[derive(Debug)]
+pub(super) struct AiDriver {
+    phase: Phase,
+    rng: Pcg32Fast,
+    /// Scores evaluated by the lookahead AI, kept between turns.
+    table: TranspositionTable,
+    /// The level used for the most recently chosen move.
+    #[cfg(test)]
+    last_level: Option<AiLevel>,
+}
+
+impl AiDriver {
+    pub(super) fn new(seed: u64) -> Self {
+        Self {
+            phase: Phase::Idle,
+            rng: Pcg32Fast::new(seed),
+            table: TranspositionTable::new(),
+            #[cfg(test)]
+            last_level: None,
+        }
+    }
+
+    /// Choose a move for the current player using the strategy for `level`.
+    fn choose_move(&mut self, state: &game::Game, level: AiLevel) -> Turn {
+        #[cfg(test)]
+        {
+            self.last_level = Some(level);
+        }
+        let position = match level {
+            AiLevel::Random => choose_random_move(state, &mut self.rng),
+            AiLevel::Greedy if cfg!(feature = "minimax") => {
+                return MinimaxAi::default().best_turn(state)
+            }
+            AiLevel::Greedy => return choose_move(state),
+            AiLevel::Minimax => return MinimaxAi::default().best_turn(state),
+            AiLevel::Lookahead => choose_lookahead_move(state, Some(&mut self.table)),
+        };
+        Turn {
+            rotate: position,
+            times: Turn::ONCE,
+        }
+    }
+
+    /// Whether a turn is still being presented.
+    pub(super) fn is_busy(&self) -> bool {
+        !matches!(self.phase, Phase::Idle)
+    }
+
+    /// Take the current player's turn and begin presenting it.
+    ///
+    /// Returns the turn that was taken, along with what it did.
+    pub(super) fn take_turn(
+        &mut self,
+        state: &mut game::Game,
+        level: AiLevel,
+    ) -> (Turn, TurnOutcome) {
+        let turn = self.choose_move(state, level);
+        let outcome = state.execute_turn(turn).unwrap();
+        self.present_turn(turn.rotate);
+        (turn, outcome)
+    }
+
+    /// Begin presenting a turn that rotated `position` and has already been executed.
+    pub(super) fn present_turn(&mut self, position: Position) {
+        self.phase = Phase::Scrolling(position);
+        wait_for_vblank_checked();
+    }
+
+    /// Present a single frame of the turn, at the current tick of `clock`.
+    ///
+    /// A clock running faster than normal scrolls and pauses proportionally faster.
+    ///
+    /// Returns `true` once the presentation has completed.
+    pub(super) fn present(
+        &mut self,
+        camera: &mut CameraController,
+        cursor_sprite: &CursorSprite,
+        cursor: Position,
+        board: &mut BoardRenderer,
+        state: &game::Game,
+        clock: &Clock,
+    ) -> bool {
+        let now = clock.now();
+        wait_for_vblank_checked();
+        self.phase = match self.phase {
+            Phase::Idle => Phase::Idle,
+            Phase::Scrolling(position) => {
+                // Sparks left over from the previous turn.
+                board.hide_sparks();
+                let completed = camera.scroll_to_position(
+                    position,
+                    timing::scaled_velocity(timing::AI_SCROLL, clock.speed())
+                        .saturating_mul(clock.rate() as u16),
+                );
+                cursor_sprite.draw(camera, cursor);
+                if completed {
+                    Phase::Reveal
+                } else {
+                    Phase::Scrolling(position)
+                }
+            }
+            Phase::Reveal => {
+                board.draw(state);
+                if state.turn_events().is_truncated() {
+                    // The capture was too big to present node by node.
+                    draw_banner(&[MEGA_CAPTURE]);
+                    Phase::Cooldown(
+                        now.wrapping_add(timing::scaled(
+                            timing::MEGA_CAPTURE_COOLDOWN,
+                            clock.speed(),
+                        ) as u32),
+                    )
+                } else {
+                    Phase::Cooldown(
+                        now.wrapping_add(timing::scaled(timing::AI_COOLDOWN, clock.speed()) as u32),
+                    )
+                }
+            }
+            Phase::Cooldown(until) => {
+                board.draw_sparks(camera, state, now);
+                if !cooldown_finished(until, now) {
+                    Phase::Cooldown(until)
+                } else {
+                    clear_banner();
+                    board.hide_sparks();
+                    Phase::Idle
+                }
+            }
+        };
+        !self.is_busy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        choose_lookahead_move, choose_move, choose_random_move, cooldown_finished,
+        count_choke_points, distances_to_opponents, legal_moves, AiDriver, AiLevel,
+    };
+    use crate::{
+        game::{Color, Direction, Game, Grid, Node, Position, PositionSet, Rules, Turn},
+        random::Pcg32Fast,
+        screen::game::transposition::TranspositionTable,
+        settings::{Difficulty, GameSpeed},
+        timing,
+    };
+    use gba_test::test;
+
+    #[test]
+    fn choose_move_prefers_heavier_chain() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        // Rotating this node points it off the board.
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        // Rotating this node points it at a chain of two blue nodes.
+        nodes[5][5] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        nodes[4][5] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Up,
+        };
+        nodes[3][5] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Up,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert_eq!(choose_move(&game).rotate, Position { x: 5, y: 5 });
+    }
+
+    #[test]
+    fn choose_move_reverse_arrow() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        // Rotating this arrow points it up at a chain of two blue nodes.
+        nodes[5][5] = Node::ReverseArrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Right,
+        };
+        nodes[4][5] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Up,
+        };
+        nodes[3][5] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Up,
+        };
+        // Rotating this arrow points it down at a single blue node, which is where a reverse arrow
+        // pointing right would point if it turned clockwise.
+        nodes[10][10] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Right,
+        };
+        nodes[11][10] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert_eq!(choose_move(&game).rotate, Position { x: 5, y: 5 });
+    }
+
+    #[test]
+    fn choose_move_tie_goes_toward_opponent() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        // Neither node can capture anything, but this one is far from blue.
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        nodes[10][10] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        nodes[12][12] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Left,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert_eq!(choose_move(&game).rotate, Position { x: 10, y: 10 });
+    }
+
+    #[test]
+    fn distances_to_opponents_manhattan() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[2][3] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Left,
+        };
+        nodes[14][14] = Node::AllDirection {
+            alignment: Some(Color::Green),
+        };
+        // Red's own nodes don't count.
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        let distances = distances_to_opponents(&game, Color::Red);
+
+        for y in 0..16u8 {
+            for x in 0..16u8 {
+                let expected = (x.abs_diff(3) + y.abs_diff(2)).min(x.abs_diff(14) + y.abs_diff(14));
+                assert_eq!(distances[y as usize][x as usize], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn distances_to_opponents_none() {
+        let game = Game::builder()
+            .grid(Grid::new([[Node::Empty; 16]; 16]))
+            .build();
+
+        assert!(distances_to_opponents(&game, Color::Red)
+            .iter()
+            .flatten()
+            .all(|&distance| distance == u8::MAX));
+    }
+
+    /// The first node with a direction found by scanning column by column, which is how the AI
+    /// used to play.
+    fn choose_naive_move(game: &Game) -> Position {
+        let turn_color = game.turn_color();
+        (0..16)
+            .flat_map(|x| (0..16).map(move |y| Position { x, y }))
+            .find(|&position| {
+                let node = &game.grid()[position];
+                node.is_color(turn_color) && node.direction().is_some()
+            })
+            .or_else(|| legal_moves(game).next())
+            .expect("no legal move")
+    }
+
+    /// Play out a game where red chooses its moves with `red` and everyone else plays naively,
+    /// returning the number of nodes red ends up with.
+    fn play_against_naive(seed: u64, red: fn(&Game) -> Position) -> u16 {
+        let mut game = Game::builder().grid(Grid::generate(seed)).build();
+        for _ in 0..400 {
+            let turn_color = game.turn_color();
+            if !game.has_legal_move(turn_color) {
+                game.pass_turn();
+                continue;
+            }
+            let position = match turn_color {
+                Color::Red => red(&game),
+                _ => choose_naive_move(&game),
+            };
+            if game
+                .execute_turn(Turn {
+                    rotate: position,
+                    times: Turn::ONCE,
+                })
+                .unwrap()
+                .ending()
+                .is_some()
+            {
+                break;
+            }
+        }
+        game.count(Color::Red)
+    }
+
+    #[test]
+    fn choose_move_beats_naive_move() {
+        let mut greedy_wins = 0;
+        let mut naive_wins = 0;
+        for seed in 0..16 {
+            let greedy = play_against_naive(seed, |game| choose_move(game).rotate);
+            let naive = play_against_naive(seed, choose_naive_move);
+
+            assert!(greedy >= naive);
+            // Red only has nodes left at the end if it won or the game ran out of turns.
+            greedy_wins += (greedy > 0) as u8;
+            naive_wins += (naive > 0) as u8;
+        }
+        assert!(greedy_wins > naive_wins);
+    }
+
+    #[test]
+    fn choose_move_all_direction_fallback() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[7][7] = Node::AllDirection {
+            alignment: Some(Color::Red),
+        };
+        nodes[7][8] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert_eq!(choose_move(&game).rotate, Position { x: 7, y: 7 });
+    }
+
+    fn super_arrow_or_arrow(rules: Rules) -> Game {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        // Rotating this super arrow sweeps its beam across three blue nodes, none of which it would
+        // reach by filling.
+        nodes[8][0] = Node::SuperArrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+        for x in [3, 6, 9] {
+            nodes[8][x] = Node::Arrow {
+                alignment: Some(Color::Blue),
+                direction: Direction::Down,
+            };
+        }
+        // Rotating this arrow points it at a single blue node.
+        nodes[12][12] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        nodes[11][12] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Left,
+        };
+        Game::builder().grid(Grid::new(nodes)).rules(rules).build()
+    }
+
+    #[test]
+    fn choose_move_super_arrow_without_claims() {
+        let game = super_arrow_or_arrow(Rules::default());
+
+        assert_eq!(choose_move(&game).rotate, Position { x: 12, y: 12 });
+    }
+
+    #[test]
+    fn choose_move_super_arrow_claims() {
+        let game = super_arrow_or_arrow(Rules {
+            wraparound: false,
+            multi_rotation: false,
+            super_arrow_claims: true,
+        });
+
+        assert_eq!(choose_move(&game).rotate, Position { x: 0, y: 8 });
+    }
+
+    #[test]
+    fn cooldown_finished_at_deadline() {
+        assert!(!cooldown_finished(30, 29));
+        assert!(cooldown_finished(30, 30));
+        assert!(cooldown_finished(30, 31));
+    }
+
+    #[test]
+    fn cooldown_finished_across_wraparound() {
+        let until = 10u32.wrapping_sub(20);
+
+        assert!(!cooldown_finished(until, until.wrapping_sub(1)));
+        assert!(cooldown_finished(until, 10));
+    }
+
+    #[test]
+    fn cooldown_finished_immediately_at_instant_speed() {
+        let now = 100;
+        let until = now + timing::scaled(timing::AI_COOLDOWN, GameSpeed::Instant) as u32;
+
+        assert!(cooldown_finished(until, now));
+    }
+
+    #[test]
+    fn ai_level_next_previous() {
+        for level in [
+            AiLevel::Random,
+            AiLevel::Greedy,
+            AiLevel::Minimax,
+            AiLevel::Lookahead,
+        ] {
+            assert_eq!(level.next().previous(), level);
+        }
+    }
+
+    #[test]
+    fn ai_level_from_difficulty() {
+        assert_eq!(AiLevel::from(Difficulty::Easy), AiLevel::Random);
+        assert_eq!(AiLevel::from(Difficulty::Normal), AiLevel::Greedy);
+        assert_eq!(AiLevel::from(Difficulty::Hard), AiLevel::Minimax);
+    }
+
+    #[test]
+    fn choose_random_move_is_legal() {
+        let game = Game::builder().grid(Grid::generate(3)).build();
+        let mut rng: Pcg32Fast = Pcg32Fast::new(0);
+
+        for _ in 0..20 {
+            let position = choose_random_move(&game, &mut rng);
+
+            assert!(game.is_legal_move(Color::Red, position));
+        }
+    }
+
+    #[test]
+    fn count_choke_points_only_owned() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Right,
+        };
+        nodes[0][1] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        nodes[0][2] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Right,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+        let mut choke_points = PositionSet::new();
+        choke_points.insert(Position { x: 1, y: 0 });
+        choke_points.insert(Position { x: 2, y: 0 });
+
+        assert_eq!(count_choke_points(&game, Color::Red, &choke_points), 1);
+    }
+
+    #[test]
+    fn choose_lookahead_move_is_legal() {
+        let game = Game::builder().grid(Grid::generate(3)).build();
+
+        assert!(legal_moves(&game).any(|position| position == choose_lookahead_move(&game, None)));
+    }
+
+    #[test]
+    fn choose_lookahead_move_takes_win() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        // Rotating this node clockwise points it at the only blue node.
+        nodes[5][5] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        nodes[4][5] = Node::Arrow {
+            alignment: Some(Color::Blue),
+            direction: Direction::Right,
+        };
+        nodes[10][10] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Left,
+        };
+        let game = Game::builder().grid(Grid::new(nodes)).build();
+
+        assert_eq!(choose_lookahead_move(&game, None), Position { x: 5, y: 5 });
+    }
+
+    #[test]
+    fn choose_lookahead_move_same_with_table() {
+        let mut table = TranspositionTable::new();
+        for seed in 0..4 {
+            let mut game = Game::builder().grid(Grid::generate(seed)).build();
+            // Play into the middle of the game.
+            for _ in 0..12 {
+                game.execute_turn(choose_move(&game)).unwrap();
+            }
+
+            assert_eq!(
+                choose_lookahead_move(&game, Some(&mut table)),
+                choose_lookahead_move(&game, None)
+            );
+        }
+    }
+
+    #[test]
+    fn choose_lookahead_move_reuses_table() {
+        let mut game = Game::builder().grid(Grid::generate(2)).build();
+        for _ in 0..12 {
+            game.execute_turn(choose_move(&game)).unwrap();
+        }
+        let mut table = TranspositionTable::new();
+
+        let first = choose_lookahead_move(&game, Some(&mut table));
+        let hits = table.hits();
+        let second = choose_lookahead_move(&game, Some(&mut table));
+
+        assert_eq!(first, second);
+        // Searching the same position again is answered from the table.
+        assert!(table.hits() > hits);
+    }
+
+    #[test]
+    fn driver_uses_level_for_each_color() {
+        let levels = [
+            AiLevel::Greedy,
+            AiLevel::Random,
+            AiLevel::Lookahead,
+            AiLevel::Greedy,
+        ];
+        let mut game = Game::builder().grid(Grid::generate(5)).build();
+        let mut driver = AiDriver::new(0);
+
+        for _ in 0..8 {
+            let level = levels[u8::from(game.turn_color()) as usize];
+            let turn = driver.choose_move(&game, level);
+
+            assert_eq!(driver.last_level, Some(level));
+
+            if game.execute_turn(turn).unwrap().ending().is_some() {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Numbers drawn with 16x16 sprites, for values that need to be readable at a glance.
+
+use super::palette::ObjectPalette;
+use crate::{
+    mmio::{vram::ObjectAttributes, OBJ_ATTRS, OBJ_TILES},
+    text,
+};
+use deranged::{RangedU16, RangedU8};
+
+/// The most digits a number can be drawn with.
+const DIGITS: usize = 3;
+
+/// The OAM slot of the leftmost digit. Slots 1 through 4 are the sparks.
+const FIRST_SLOT: usize = 5;
+
+/// The object tile of the numeral `0`, followed by the other numerals in order. Tiles 4 through 19
+/// are the sparks.
+const FIRST_TILE: u16 = 20;
+
+/// The width of a single digit, in pixels.
+const DIGIT_WIDTH: u16 = 16;
+
+/// Draw the numerals into object tile memory.
+///
+/// Each numeral is the font's glyph with every pixel doubled, so no art is needed for them.
+pub(super) fn load_tiles() {
+    for numeral in 0..10 {
+        let glyph = text::glyph((b'0' + numeral) as char);
+        for tile in 0..4 {
+            unsafe {
+                OBJ_TILES
+                    .add(FIRST_TILE as usize + numeral as usize * 4 + tile)
+                    .write_volatile(doubled_tile(glyph, tile));
+            }
+        }
+    }
+}
+
+/// One quarter of a glyph doubled to 16x16, in the order sprite tiles are stored: top left, top
+/// right, bottom left, then bottom right.
+fn doubled_tile(glyph: [u8; 8], tile: usize) -> [u32; 8] {
+    let mut rows = [0u32; 8];
+    for (row, pixels) in rows.iter_mut().enumerate() {
+        let source = glyph[(tile / 2) * 4 + row / 2];
+        for column in 0..8 {
+            if source & (1 << ((tile % 2) * 4 + column / 2)) != 0 {
+                *pixels |= 1 << (column * 4);
+            }
+        }
+    }
+    rows
+}
+
+/// The digits of `value`, right-aligned, with leading positions left blank.
+///
+/// Values too large to be drawn are shown as the largest value that can be.
+fn digits(value: u16) -> [Option<u8>; DIGITS] {
+    let mut value = value.min(10u16.pow(DIGITS as u32) - 1);
+    let mut digits = [None; DIGITS];
+    for digit in digits.iter_mut().rev() {
+        *digit = Some((value % 10) as u8);
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    digits
+}
+
+/// Which digit positions need to be redrawn to go from showing `old` to showing `new`.
+fn dirty(old: Option<[Option<u8>; DIGITS]>, new: [Option<u8>; DIGITS]) -> [bool; DIGITS] {
+    let mut dirty = [true; DIGITS];
+    if let Some(old) = old {
+        for ((dirty, old), new) in dirty.iter_mut().zip(old).zip(new) {
+            *dirty = old != new;
+        }
+    }
+    dirty
+}
+
+/// The attributes of the numeral `digit` with its top left corner at (`x`, `y`).
+fn digit_attributes(x: u16, y: u8, digit: u8) -> ObjectAttributes {
+    ObjectPalette::Ui
+        .attributes()
+        .with_x(x)
+        .with_y(y)
+        .with_tile(unsafe { RangedU16::new_unchecked(FIRST_TILE + digit as u16 * 4) })
+        .with_size(RangedU8::new_static::<1>())
+}
+
+/// A number of up to three digits drawn with sprites.
+#[derive(Debug)]
+pub(super) struct BigNumber {
+    /// The screen position of the right edge of the number.
+    right: u16,
+    y: u8,
+    /// The digits currently drawn, if the number is showing.
+    shown: Option<[Option<u8>; DIGITS]>,
+}
+
+impl BigNumber {
+    /// A number right-aligned so that its last digit ends at `right`, with its top at `y`.
+    pub(super) fn new(right: u16, y: u8) -> Self {
+        Self {
+            right,
+            y,
+            shown: None,
+        }
+    }
+
+    /// Show `value`, redrawing only the digits that changed.
+    pub(super) fn set(&mut self, value: u16) {
+        let new = digits(value);
+        for (index, (digit, dirty)) in new.into_iter().zip(dirty(self.shown, new)).enumerate() {
+            if !dirty {
+                continue;
+            }
+            let attributes = digit.map_or(ObjectAttributes::new().with_disabled(true), |digit| {
+                digit_attributes(
+                    self.right - (DIGITS - index) as u16 * DIGIT_WIDTH,
+                    self.y,
+                    digit,
+                )
+            });
+            unsafe {
+                OBJ_ATTRS.add(FIRST_SLOT + index).write_volatile(attributes);
+            }
+        }
+        self.shown = Some(new);
+    }
+
+    /// Stop showing the number.
+    pub(super) fn hide(&mut self) {
+        self.shown = None;
+        unsafe {
+            OBJ_ATTRS
+                .add(FIRST_SLOT)
+                .cast::<[ObjectAttributes; DIGITS]>()
+                .write_volatile([ObjectAttributes::new().with_disabled(true); DIGITS]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{digit_attributes, digits, dirty, doubled_tile};
+    use crate::screen::game::palette::ObjectPalette;
+    use gba_test::test;
+
+    #[test]
+    fn digit_attributes_use_ui_palette() {
+        let attributes = digit_attributes(100, 8, 7);
+
+        assert_eq!(
+            attributes,
+            attributes.with_palette(ObjectPalette::Ui.bank())
+        );
+    }
+
+    #[test]
+    fn digits_single() {
+        assert_eq!(digits(7), [None, None, Some(7)]);
+    }
+
+    #[test]
+    fn digits_zero() {
+        assert_eq!(digits(0), [None, None, Some(0)]);
+    }
+
+    #[test]
+    fn digits_inner_zero() {
+        assert_eq!(digits(305), [Some(3), Some(0), Some(5)]);
+    }
+
+    #[test]
+    fn digits_clamped() {
+        assert_eq!(digits(1234), [Some(9), Some(9), Some(9)]);
+    }
+
+    #[test]
+    fn dirty_when_not_shown() {
+        assert_eq!(dirty(None, digits(5)), [true; 3]);
+    }
+
+    #[test]
+    fn dirty_only_changed_digits() {
+        assert_eq!(dirty(Some(digits(120)), digits(119)), [false, true, true]);
+    }
+
+    #[test]
+    fn dirty_when_digit_count_shrinks() {
+        assert_eq!(dirty(Some(digits(10)), digits(9)), [false, true, true]);
+    }
+
+    #[test]
+    fn dirty_unchanged() {
+        assert_eq!(dirty(Some(digits(42)), digits(42)), [false; 3]);
+    }
+
+    #[test]
+    fn doubled_tile_top_left_pixel() {
+        let tile = doubled_tile([0x01, 0, 0, 0, 0, 0, 0, 0], 0);
+
+        assert_eq!(tile[0], 0x0000_0011);
+        assert_eq!(tile[1], 0x0000_0011);
+        assert_eq!(tile[2], 0);
+    }
+
+    #[test]
+    fn doubled_tile_bottom_right_pixel() {
+        let tile = doubled_tile([0, 0, 0, 0, 0, 0, 0, 0x80], 3);
+
+        assert_eq!(tile[6], 0x1100_0000);
+        assert_eq!(tile[7], 0x1100_0000);
+        assert_eq!(tile[5], 0);
+    }
+
+    #[test]
+    fn doubled_tile_other_quadrants_empty() {
+        for tile in 1..4 {
+            assert_eq!(doubled_tile([0x01, 0, 0, 0, 0, 0, 0, 0], tile), [0; 8]);
+        }
+    }
+}
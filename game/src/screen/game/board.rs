@@ -0,0 +1,655 @@
+//! Drawing of the game board.
+
+use super::{camera::CameraController, sparks::Sparks};
+use crate::{
+    game::{self, prelude::*},
+    mmio::{vram::TextScreenEntry, TEXT_SCREENBLOCK0},
+};
+use core::ops::BitOrAssign;
+use deranged::{RangedU16, RangedU8};
+
+#[derive(Clone, Copy, Debug)]
+struct Edges(u8);
+
+impl Edges {
+    const LEFT: Edges = Edges(0b0000_0001);
+    const UP: Edges = Edges(0b0000_0010);
+    const RIGHT: Edges = Edges(0b0000_0100);
+    const DOWN: Edges = Edges(0b0000_1000);
+
+    fn new() -> Self {
+        Self(0)
+    }
+
+    fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl From<Direction> for Edges {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Left => Edges::LEFT,
+            Direction::Up => Edges::UP,
+            Direction::Right => Edges::RIGHT,
+            Direction::Down => Edges::DOWN,
+        }
+    }
+}
+
+impl BitOrAssign for Edges {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Sets an individual screenblock.
+///
+/// This is basically just writing a single 8x8 tile.
+fn set_block(x: usize, y: usize, tile: RangedU16<0, 1023>, frame: usize, palette: RangedU8<0, 15>) {
+    set_entry(
+        x,
+        y,
+        frame,
+        TextScreenEntry::new().with_tile(tile).with_palette(palette),
+    );
+}
+
+fn set_entry(x: usize, y: usize, frame: usize, entry: TextScreenEntry) {
+    unsafe {
+        TEXT_SCREENBLOCK0
+            .byte_add(frame * 0x800)
+            .add(y * 32 + x)
+            .write_volatile(entry);
+    }
+}
+
+/// Set the tiles for an (x, y) position to a single tile.
+///
+/// This tile will be used four times, as an (x, y) position occupies four tile spaces.
+pub(super) fn set_tile(
+    x: usize,
+    y: usize,
+    tile: RangedU16<0, 1023>,
+    frame: usize,
+    palette: RangedU8<0, 15>,
+) {
+    set_block(x * 2, y * 2, tile, frame, palette);
+    set_block(x * 2 + 1, y * 2, tile, frame, palette);
+    set_block(x * 2, y * 2 + 1, tile, frame, palette);
+    set_block(x * 2 + 1, y * 2 + 1, tile, frame, palette);
+}
+
+/// The screen entries drawing a group of four sequential tiles, optionally flipped, in the order
+/// top left, top right, bottom left, bottom right.
+///
+/// Flipping a group flips each tile and also swaps the tiles' places.
+fn tile_group_entries(
+    tile_start: RangedU16<0, 1023>,
+    palette: RangedU8<0, 15>,
+    hflip: bool,
+    vflip: bool,
+) -> [TextScreenEntry; 4] {
+    let mut entries = [TextScreenEntry::new(); 4];
+    for (index, entry) in entries.iter_mut().enumerate() {
+        let column = (index % 2) ^ hflip as usize;
+        let row = (index / 2) ^ vflip as usize;
+        *entry = TextScreenEntry::new()
+            .with_tile(tile_start.saturating_add((row * 2 + column) as u16))
+            .with_hflip(hflip)
+            .with_vflip(vflip)
+            .with_palette(palette);
+    }
+    entries
+}
+
+/// Set the tiles for an (x, y) position to group of four sequential tiles.
+fn set_tile_group(
+    x: usize,
+    y: usize,
+    tile_start: RangedU16<0, 1023>,
+    frame: usize,
+    palette: RangedU8<0, 15>,
+) {
+    set_tile_group_flipped(x, y, tile_start, frame, palette, false, false);
+}
+
+/// Set the tiles for an (x, y) position to a group of four sequential tiles, mirrored
+/// horizontally and/or vertically.
+fn set_tile_group_flipped(
+    x: usize,
+    y: usize,
+    tile_start: RangedU16<0, 1023>,
+    frame: usize,
+    palette: RangedU8<0, 15>,
+    hflip: bool,
+    vflip: bool,
+) {
+    for (index, entry) in tile_group_entries(tile_start, palette, hflip, vflip)
+        .into_iter()
+        .enumerate()
+    {
+        set_entry(x * 2 + index % 2, y * 2 + index / 2, frame, entry);
+    }
+}
+
+/// Draw an arrow pointing in `direction`.
+///
+/// Only the right and up pointing art is stored. Left and down pointing arrows are their mirror
+/// images.
+fn set_arrow(
+    x: usize,
+    y: usize,
+    right: RangedU16<0, 1023>,
+    up: RangedU16<0, 1023>,
+    direction: Direction,
+    frame: usize,
+    palette: RangedU8<0, 15>,
+) {
+    match direction {
+        Direction::Left => set_tile_group_flipped(x, y, right, frame, palette, true, false),
+        Direction::Right => set_tile_group(x, y, right, frame, palette),
+        Direction::Down => set_tile_group_flipped(x, y, up, frame, palette, false, true),
+        Direction::Up => set_tile_group(x, y, up, frame, palette),
+    }
+}
+
+// Returns x, y, and frame.
+fn get_screen_location(mut x: usize, mut y: usize, mut frame: usize) -> (usize, usize, usize) {
+    x = x + 8;
+    y = y + 8;
+    if x >= 16 {
+        x -= 16;
+        frame += 1;
+    }
+    if y >= 16 {
+        y -= 16;
+        frame += 2;
+    }
+    (x, y, frame)
+}
+
+/// Calculate the connections drawn between each node and the nodes it points at.
+///
+/// When the board wraps around, nodes on the boundary pointing off the board are connected to the
+/// node on the opposite edge.
+fn edges(grid: &Grid, rules: Rules) -> [[Edges; 16]; 16] {
+    let mut edges = [[Edges::new(); 16]; 16];
+    let mut connect = |position: Position, direction: Direction| {
+        // Nodes pointing off the board aren't connected to anything, unless it wraps around.
+        let Some(neighbor) = rules.neighbor(position, direction, grid.size()) else {
+            return;
+        };
+        edges[position.y as usize][position.x as usize] |= direction.into();
+        // Update the edges of the pointed-at node.
+        edges[neighbor.y as usize][neighbor.x as usize] |= direction.opposite().into();
+    };
+    for (position, node) in grid.iter_positions() {
+        if !node.is_hidden() {
+            if let Some(direction) = node.direction() {
+                connect(position, direction);
+            } else if node.all_directions() {
+                for direction in [
+                    Direction::Left,
+                    Direction::Up,
+                    Direction::Right,
+                    Direction::Down,
+                ] {
+                    connect(position, direction);
+                }
+            }
+        }
+    }
+    edges
+}
+
+/// Draws the board onto BG1 (edges) and BG2 (nodes).
+///
+/// Drawing is deferred until the board is marked dirty, so that it happens at most once per frame
+/// no matter how many times the game state changes. Every redraw flashes sparks over the nodes the
+/// most recent turn captured.
+#[derive(Debug)]
+pub(super) struct BoardRenderer {
+    dirty: bool,
+    sparks: Sparks,
+}
+
+impl BoardRenderer {
+    pub(super) fn new() -> Self {
+        Self {
+            dirty: false,
+            sparks: Sparks::new(),
+        }
+    }
+
+    /// Draw the sparks over the most recent turn's captures as they are at tick `now`.
+    pub(super) fn draw_sparks(&mut self, camera: &CameraController, state: &game::Game, now: u32) {
+        self.sparks.draw(camera, state.turn_events(), now);
+    }
+
+    /// Stop showing sparks until the board is drawn again.
+    pub(super) fn hide_sparks(&mut self) {
+        self.sparks.hide();
+    }
+
+    /// Request that the board be redrawn on the next call to `draw_if_dirty()`.
+    pub(super) fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Redraw the board, but only if it has been marked dirty since it was last drawn.
+    ///
+    /// Returns whether the board was redrawn.
+    pub(super) fn draw_if_dirty(&mut self, state: &game::Game) -> bool {
+        let dirty = self.dirty;
+        if dirty {
+            self.draw(state);
+        }
+        dirty
+    }
+
+    /// Redraw the entire board.
+    pub(super) fn draw(&mut self, state: &game::Game) {
+        self.dirty = false;
+        self.sparks.start();
+
+        let grid = state.grid();
+        for (y, row) in grid.iter().zip(edges(grid, state.rules())).enumerate() {
+            for (x, (node, edges)) in row.0.iter().zip(row.1).enumerate() {
+                let (x, y, frame) = get_screen_location(x, y, 24);
+
+                // Draw node.
+                let palette = match node {
+                    Node::Empty => {
+                        set_tile(
+                            x,
+                            y,
+                            RangedU16::new_static::<0>(),
+                            frame,
+                            RangedU8::new_static::<0>(),
+                        );
+                        RangedU8::new_static::<0>()
+                    }
+                    Node::Wall => {
+                        set_tile_group(
+                            x,
+                            y,
+                            RangedU16::new_static::<1>(),
+                            frame,
+                            RangedU8::new_static::<0>(),
+                        );
+                        RangedU8::new_static::<0>()
+                    }
+                    Node::Arrow {
+                        direction,
+                        alignment,
+                    } => {
+                        let palette = alignment
+                            .map_or(RangedU8::new_static::<0>(), game::Color::palette_bank);
+                        set_arrow(
+                            x,
+                            y,
+                            RangedU16::new_static::<5>(),
+                            RangedU16::new_static::<17>(),
+                            *direction,
+                            frame,
+                            palette,
+                        );
+                        palette
+                    }
+                    Node::ReverseArrow {
+                        direction,
+                        alignment,
+                    } => {
+                        let palette = alignment
+                            .map_or(RangedU8::new_static::<0>(), game::Color::palette_bank);
+                        set_arrow(
+                            x,
+                            y,
+                            RangedU16::new_static::<9>(),
+                            RangedU16::new_static::<13>(),
+                            *direction,
+                            frame,
+                            palette,
+                        );
+                        palette
+                    }
+                    Node::AllDirection { alignment } => {
+                        let palette = alignment
+                            .map_or(RangedU8::new_static::<0>(), game::Color::palette_bank);
+                        if alignment.is_some() {
+                            set_tile_group(x, y, RangedU16::new_static::<38>(), frame, palette);
+                        } else {
+                            set_tile_group(x, y, RangedU16::new_static::<1>(), frame, palette);
+                        }
+                        palette
+                    }
+                    Node::SuperArrow {
+                        alignment,
+                        direction,
+                    } => {
+                        let palette = alignment
+                            .map_or(RangedU8::new_static::<0>(), game::Color::palette_bank);
+                        if alignment.is_some() {
+                            set_arrow(
+                                x,
+                                y,
+                                RangedU16::new_static::<50>(),
+                                RangedU16::new_static::<46>(),
+                                *direction,
+                                frame,
+                                palette,
+                            );
+                        } else {
+                            set_tile_group(x, y, RangedU16::new_static::<1>(), frame, palette);
+                        }
+                        palette
+                    }
+                    Node::Teleporter { alignment, .. } => {
+                        let palette = alignment
+                            .map_or(RangedU8::new_static::<0>(), game::Color::palette_bank);
+                        if alignment.is_some() {
+                            set_tile_group(x, y, RangedU16::new_static::<42>(), frame, palette);
+                        } else {
+                            set_tile_group(x, y, RangedU16::new_static::<1>(), frame, palette);
+                        }
+                        palette
+                    }
+                };
+
+                // Handle each corner of the edge tile separately.
+
+                // Top left
+                match (edges.contains(Edges::LEFT), edges.contains(Edges::UP)) {
+                    (false, false) => set_block(
+                        2 * x,
+                        2 * y,
+                        RangedU16::new_static::<21>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (true, false) => set_block(
+                        2 * x,
+                        2 * y,
+                        RangedU16::new_static::<22>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (false, true) => set_block(
+                        2 * x,
+                        2 * y,
+                        RangedU16::new_static::<23>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (true, true) => set_block(
+                        2 * x,
+                        2 * y,
+                        RangedU16::new_static::<24>(),
+                        frame - 8,
+                        palette,
+                    ),
+                }
+                // Top right
+                match (edges.contains(Edges::RIGHT), edges.contains(Edges::UP)) {
+                    (false, false) => set_block(
+                        2 * x + 1,
+                        2 * y,
+                        RangedU16::new_static::<25>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (true, false) => set_block(
+                        2 * x + 1,
+                        2 * y,
+                        RangedU16::new_static::<26>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (false, true) => set_block(
+                        2 * x + 1,
+                        2 * y,
+                        RangedU16::new_static::<27>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (true, true) => set_block(
+                        2 * x + 1,
+                        2 * y,
+                        RangedU16::new_static::<28>(),
+                        frame - 8,
+                        palette,
+                    ),
+                }
+                // Bottom left
+                match (edges.contains(Edges::LEFT), edges.contains(Edges::DOWN)) {
+                    (false, false) => set_block(
+                        2 * x,
+                        2 * y + 1,
+                        RangedU16::new_static::<29>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (true, false) => set_block(
+                        2 * x,
+                        2 * y + 1,
+                        RangedU16::new_static::<30>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (false, true) => set_block(
+                        2 * x,
+                        2 * y + 1,
+                        RangedU16::new_static::<31>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (true, true) => set_block(
+                        2 * x,
+                        2 * y + 1,
+                        RangedU16::new_static::<32>(),
+                        frame - 8,
+                        palette,
+                    ),
+                }
+                // Bottom right
+                match (edges.contains(Edges::RIGHT), edges.contains(Edges::DOWN)) {
+                    (false, false) => set_block(
+                        2 * x + 1,
+                        2 * y + 1,
+                        RangedU16::new_static::<33>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (true, false) => set_block(
+                        2 * x + 1,
+                        2 * y + 1,
+                        RangedU16::new_static::<34>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (false, true) => set_block(
+                        2 * x + 1,
+                        2 * y + 1,
+                        RangedU16::new_static::<35>(),
+                        frame - 8,
+                        palette,
+                    ),
+                    (true, true) => set_block(
+                        2 * x + 1,
+                        2 * y + 1,
+                        RangedU16::new_static::<36>(),
+                        frame - 8,
+                        palette,
+                    ),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edges, get_screen_location, tile_group_entries, Edges};
+    use crate::{
+        game::{Color, Direction, Grid, Node, Rules},
+        mmio::vram::TextScreenEntry,
+    };
+    use core::mem::transmute;
+    use deranged::{RangedU16, RangedU8};
+    use gba_test::test;
+
+    /// The raw bits of each entry drawing tiles 5 through 8 with palette 2.
+    fn raw_group(hflip: bool, vflip: bool) -> [u16; 4] {
+        tile_group_entries(
+            RangedU16::new_static::<5>(),
+            RangedU8::new_static::<2>(),
+            hflip,
+            vflip,
+        )
+        .map(|entry| unsafe { transmute::<TextScreenEntry, u16>(entry) })
+    }
+
+    #[test]
+    fn tile_group_entries_unflipped() {
+        assert_eq!(raw_group(false, false), [0x2005, 0x2006, 0x2007, 0x2008]);
+    }
+
+    #[test]
+    fn tile_group_entries_hflip() {
+        // The columns swap, and every tile is mirrored.
+        assert_eq!(raw_group(true, false), [0x2406, 0x2405, 0x2408, 0x2407]);
+    }
+
+    #[test]
+    fn tile_group_entries_vflip() {
+        // The rows swap, and every tile is mirrored.
+        assert_eq!(raw_group(false, true), [0x2807, 0x2808, 0x2805, 0x2806]);
+    }
+
+    #[test]
+    fn tile_group_entries_both_flips() {
+        assert_eq!(raw_group(true, true), [0x2c08, 0x2c07, 0x2c06, 0x2c05]);
+    }
+
+    /// The pixels of a 16x16 image stored as four 4bpp tiles.
+    fn pixels(tiles: &[u8]) -> [[u8; 16]; 16] {
+        let mut pixels = [[0; 16]; 16];
+        for (y, row) in pixels.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let tile = &tiles[((y / 8) * 2 + x / 8) * 32..];
+                let byte = tile[(y % 8) * 4 + (x % 8) / 2];
+                *pixel = (byte >> ((x % 2) * 4)) & 0xf;
+            }
+        }
+        pixels
+    }
+
+    /// The arrow art is mirror symmetric, so flipping it draws the other directions.
+    #[test]
+    fn arrow_art_is_symmetric() {
+        for (right, up) in [
+            (
+                &include_bytes!("../../../res/arrow_right.4bpp")[..],
+                &include_bytes!("../../../res/arrow_up.4bpp")[..],
+            ),
+            (
+                &include_bytes!("../../../res/super_arrow_right.4bpp")[..],
+                &include_bytes!("../../../res/super_arrow_up.4bpp")[..],
+            ),
+        ] {
+            let right = pixels(right);
+            let up = pixels(up);
+            for y in 0..16 {
+                for x in 0..16 {
+                    // Turning the up arrow clockwise points it right.
+                    assert_eq!(up[15 - x][y], right[y][x]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn get_screen_location_top_left() {
+        assert_eq!(get_screen_location(0, 0, 24), (8, 8, 24));
+    }
+
+    #[test]
+    fn get_screen_location_bottom_right() {
+        assert_eq!(get_screen_location(15, 15, 24), (7, 7, 27));
+    }
+
+    #[test]
+    fn edges_arrow() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[3][4] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Right,
+        };
+
+        let edges = edges(&Grid::new(nodes), Rules::default());
+
+        assert!(edges[3][4].contains(Edges::RIGHT));
+        assert!(!edges[3][4].contains(Edges::LEFT));
+        assert!(edges[3][5].contains(Edges::LEFT));
+    }
+
+    #[test]
+    fn edges_arrow_pointing_off_board() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Up,
+        };
+
+        let edges = edges(&Grid::new(nodes), Rules::default());
+
+        assert_eq!(edges[0][0].0, 0);
+    }
+
+    #[test]
+    fn edges_arrow_pointing_off_board_wraparound() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: None,
+            direction: Direction::Up,
+        };
+
+        let edges = edges(
+            &Grid::new(nodes),
+            Rules {
+                wraparound: true,
+                multi_rotation: false,
+                super_arrow_claims: false,
+            },
+        );
+
+        assert!(edges[0][0].contains(Edges::UP));
+        assert!(edges[15][0].contains(Edges::DOWN));
+    }
+
+    #[test]
+    fn edges_all_direction() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::AllDirection {
+            alignment: Some(Color::Blue),
+        };
+
+        let edges = edges(&Grid::new(nodes), Rules::default());
+
+        assert_eq!(edges[0][0].0, (Edges::RIGHT.0 | Edges::DOWN.0));
+        assert!(edges[0][1].contains(Edges::LEFT));
+        assert!(edges[1][0].contains(Edges::UP));
+    }
+
+    #[test]
+    fn edges_hidden_node() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[5][5] = Node::AllDirection { alignment: None };
+
+        let edges = edges(&Grid::new(nodes), Rules::default());
+
+        assert_eq!(edges[5][5].0, 0);
+        assert_eq!(edges[5][6].0, 0);
+    }
+}
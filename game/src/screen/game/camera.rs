@@ -0,0 +1,301 @@
+//! Scrolling of the board layers.
+
+use crate::{
+    game::Position,
+    math::Fixed,
+    mmio::{BG1HOFS, BG1VOFS, BG2HOFS, BG2VOFS},
+    settings::GameSpeed,
+    timing,
+};
+use deranged::RangedU16;
+
+/// Moves the camera one frame from `position` toward `target` in a straight line.
+///
+/// The axis with further to go moves by `velocity` pixels, while the other moves proportionally,
+/// so both arrive on the same frame. The target is reached exactly once it is within `velocity`.
+fn step_toward(position: (Fixed, Fixed), target: (Fixed, Fixed), velocity: u16) -> (Fixed, Fixed) {
+    let delta = (
+        target.0.raw() as i32 - position.0.raw() as i32,
+        target.1.raw() as i32 - position.1.raw() as i32,
+    );
+    let distance = delta.0.abs().max(delta.1.abs());
+    let velocity = Fixed::from_int(velocity).raw() as i32;
+    if distance <= velocity {
+        return target;
+    }
+    let step = |position: Fixed, delta: i32| {
+        Fixed::from_raw(
+            position
+                .raw()
+                .wrapping_add_signed(delta * velocity / distance),
+        )
+    };
+    (step(position.0, delta.0), step(position.1, delta.1))
+}
+
+/// Scrolls the board layers toward positions on the board.
+#[derive(Debug)]
+pub(super) struct CameraController {
+    /// The scroll position, kept with sub-pixel precision so diagonal scrolling is smooth.
+    position: (Fixed, Fixed),
+    /// Whether to scroll quickly until the cursor is reached.
+    ///
+    /// This is set at the start of the player's turn so the camera catches back up to the cursor
+    /// after following the AI.
+    catching_up: bool,
+}
+
+impl CameraController {
+    pub(super) fn new(position: Position) -> Self {
+        Self {
+            position: Self::position_to_fixed_location(position),
+            catching_up: false,
+        }
+    }
+
+    fn position_to_pixel_location(position: Position) -> (u16, u16) {
+        (position.x as u16 * 8 + 76, position.y as u16 * 12 + 86)
+    }
+
+    fn position_to_fixed_location(position: Position) -> (Fixed, Fixed) {
+        let location = Self::position_to_pixel_location(position);
+        (Fixed::from_int(location.0), Fixed::from_int(location.1))
+    }
+
+    /// The whole pixel the camera is scrolled to.
+    fn pixel_position(&self) -> (u16, u16) {
+        (self.position.0.trunc(), self.position.1.trunc())
+    }
+
+    /// Move the camera one frame toward `position` without touching the scroll registers.
+    ///
+    /// Returns whether the camera has reached the position.
+    fn step(&mut self, position: Position, velocity: u16) -> bool {
+        self.position = step_toward(
+            self.position,
+            Self::position_to_fixed_location(position),
+            velocity,
+        );
+        self.pixel_position() == Self::position_to_pixel_location(position)
+    }
+
+    /// Write the camera's position to the scroll registers.
+    pub(super) fn apply(&self) {
+        let (x, y) = self.pixel_position();
+        unsafe {
+            BG1HOFS.write_volatile(RangedU16::new_unchecked(x));
+            BG1VOFS.write_volatile(RangedU16::new_unchecked(y));
+            BG2HOFS.write_volatile(RangedU16::new_unchecked(x));
+            BG2VOFS.write_volatile(RangedU16::new_unchecked(y));
+        }
+    }
+
+    /// Scroll one frame toward `position`.
+    ///
+    /// Returns whether the camera has reached the position.
+    pub(super) fn scroll_to_position(&mut self, position: Position, velocity: u16) -> bool {
+        let completed = self.step(position, velocity);
+        self.apply();
+        completed
+    }
+
+    /// Scroll one frame toward the player's cursor.
+    pub(super) fn follow(&mut self, cursor: Position, speed: GameSpeed) {
+        if self.catching_up {
+            self.catching_up = !self.scroll_to_position(
+                cursor,
+                timing::scaled_velocity(timing::CATCH_UP_SCROLL, speed),
+            );
+        } else {
+            self.scroll_to_position(
+                cursor,
+                timing::scaled_velocity(timing::FOLLOW_SCROLL, speed),
+            );
+        }
+    }
+
+    /// Catch up to the cursor quickly on the following frames.
+    pub(super) fn start_player_turn(&mut self) {
+        self.catching_up = true;
+    }
+
+    /// The screen location of a sprite drawn over `position`, if it is on screen.
+    pub(super) fn relative_sprite_location(&self, position: Position) -> Option<(u16, u16)> {
+        let target = (position.x as u16 * 8 + 52, position.y as u16 * 4 + 42);
+        let top_left = Self::position_to_pixel_location(position);
+        // Truncated the same way as the scroll registers, so sprites stay fixed to the board.
+        let camera = self.pixel_position();
+
+        let x = {
+            let x = target
+                .0
+                .wrapping_add_signed(top_left.0 as i16 - camera.0 as i16);
+            if x.wrapping_add(32) > 512 {
+                return None;
+            }
+            x
+        };
+        let y = {
+            let y = target
+                .1
+                .wrapping_add_signed(top_left.1 as i16 - camera.1 as i16);
+            if y.wrapping_add(32) > 256 {
+                return None;
+            }
+            y
+        };
+
+        Some((x, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{step_toward, CameraController};
+    use crate::{game::Position, math::Fixed, settings::GameSpeed, timing};
+    use gba_test::test;
+
+    #[test]
+    fn step_toward_at_target() {
+        let target = (Fixed::from_int(100), Fixed::from_int(100));
+
+        assert_eq!(step_toward(target, target, 2), target);
+    }
+
+    #[test]
+    fn step_toward_forward() {
+        assert_eq!(
+            step_toward(
+                (Fixed::from_int(100), Fixed::from_int(100)),
+                (Fixed::from_int(110), Fixed::from_int(100)),
+                2
+            ),
+            (Fixed::from_int(102), Fixed::from_int(100))
+        );
+    }
+
+    #[test]
+    fn step_toward_backward() {
+        assert_eq!(
+            step_toward(
+                (Fixed::from_int(110), Fixed::from_int(100)),
+                (Fixed::from_int(100), Fixed::from_int(100)),
+                2
+            ),
+            (Fixed::from_int(108), Fixed::from_int(100))
+        );
+    }
+
+    #[test]
+    fn step_toward_closer_than_velocity() {
+        let target = (Fixed::from_int(101), Fixed::from_int(99));
+
+        assert_eq!(
+            step_toward((Fixed::from_int(100), Fixed::from_int(100)), target, 2),
+            target
+        );
+    }
+
+    #[test]
+    fn step_toward_diagonal_sub_pixel() {
+        // Three pixels across for every one pixel down.
+        let position = step_toward(
+            (Fixed::from_int(0), Fixed::from_int(0)),
+            (Fixed::from_int(30), Fixed::from_int(10)),
+            1,
+        );
+
+        assert_eq!(position.0, Fixed::from_int(1));
+        assert_eq!(position.1.raw(), 85);
+    }
+
+    #[test]
+    fn step_toward_diagonal_arrives_together() {
+        let target = (Fixed::from_int(37), Fixed::from_int(11));
+        let mut position = (Fixed::from_int(0), Fixed::from_int(0));
+
+        let mut frames = 0;
+        while position != target {
+            position = step_toward(position, target, 1);
+            frames += 1;
+            // The shorter axis never overshoots while the longer one is still moving.
+            assert!(position.1 <= target.1);
+        }
+
+        assert_eq!(frames, 37);
+    }
+
+    #[test]
+    fn step_toward_awkward_distance() {
+        let target = (Fixed::from_int(207), Fixed::from_int(3));
+        let mut position = (Fixed::from_int(200), Fixed::from_int(100));
+
+        // 97 pixels at 2 pixels per frame takes 49 frames, the last one shorter.
+        for _ in 0..48 {
+            position = step_toward(position, target, 2);
+        }
+        assert_ne!(position, target);
+        position = step_toward(position, target, 2);
+
+        assert_eq!(position, target);
+    }
+
+    #[test]
+    fn step_reaches_target() {
+        let mut camera = CameraController::new(Position { x: 0, y: 0 });
+        let target = Position { x: 1, y: 1 };
+
+        // 8 pixels horizontally and 12 pixels vertically at 2 pixels per frame.
+        for _ in 0..5 {
+            assert!(!camera.step(target, 2));
+        }
+        assert!(camera.step(target, 2));
+        assert_eq!(
+            camera.pixel_position(),
+            CameraController::position_to_pixel_location(target)
+        );
+    }
+
+    #[test]
+    fn step_reaches_target_at_instant_speed() {
+        let mut camera = CameraController::new(Position { x: 0, y: 0 });
+        let target = Position { x: 15, y: 15 };
+
+        assert!(camera.step(
+            target,
+            timing::scaled_velocity(timing::AI_SCROLL, GameSpeed::Instant)
+        ));
+    }
+
+    #[test]
+    fn relative_sprite_location_centered() {
+        let camera = CameraController::new(Position { x: 3, y: 4 });
+
+        assert_eq!(
+            camera.relative_sprite_location(Position { x: 3, y: 4 }),
+            Some((3 * 8 + 52, 4 * 4 + 42))
+        );
+    }
+
+    #[test]
+    fn relative_sprite_location_off_screen() {
+        let camera = CameraController::new(Position { x: 0, y: 0 });
+
+        assert_eq!(
+            camera.relative_sprite_location(Position { x: 15, y: 15 }),
+            None
+        );
+    }
+
+    #[test]
+    fn relative_sprite_location_truncates_like_scroll() {
+        let mut camera = CameraController::new(Position { x: 3, y: 4 });
+        // Part of the way to the next pixel, which the scroll registers don't show yet.
+        camera.position.0 = Fixed::from_raw(camera.position.0.raw() + 200);
+
+        assert_eq!(
+            camera.relative_sprite_location(Position { x: 3, y: 4 }),
+            Some((3 * 8 + 52, 4 * 4 + 42))
+        );
+    }
+}
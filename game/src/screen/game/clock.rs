@@ -0,0 +1,116 @@
+//! Timing for animations that can be paused.
+
+use crate::settings::GameSpeed;
+
+/// Counts the ticks that animations have been running for.
+///
+/// Animations take the current tick as input instead of counting frames themselves, so stopping
+/// the clock pauses all of them at once, and each picks up exactly where it left off.
+#[derive(Debug)]
+pub(super) struct Clock {
+    ticks: u32,
+    /// The number of ticks the clock advanced by on the last frame.
+    rate: u8,
+    paused: bool,
+    /// How much to scale presentation waits and scrolling by.
+    speed: GameSpeed,
+}
+
+impl Clock {
+    pub(super) fn new(speed: GameSpeed) -> Self {
+        Self {
+            ticks: 0,
+            rate: 0,
+            paused: false,
+            speed,
+        }
+    }
+
+    pub(super) fn now(&self) -> u32 {
+        self.ticks
+    }
+
+    /// Advance the clock by `ticks`, unless it is paused.
+    ///
+    /// This should be called once per frame. Advancing by more than one tick speeds animations up.
+    pub(super) fn advance(&mut self, ticks: u8) {
+        self.rate = if self.paused { 0 } else { ticks };
+        self.ticks = self.ticks.wrapping_add(self.rate as u32);
+    }
+
+    /// How many times faster than normal the clock is running.
+    ///
+    /// This is for animations that move a fixed amount each frame rather than being driven by the
+    /// current tick.
+    pub(super) fn rate(&self) -> u8 {
+        self.rate
+    }
+
+    /// The speed presentation timings should be scaled by.
+    pub(super) fn speed(&self) -> GameSpeed {
+        self.speed
+    }
+
+    pub(super) fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub(super) fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+}
+
+/// The number of ticks from `start` to `now`, accounting for wraparound.
+pub(super) fn elapsed(start: u32, now: u32) -> u32 {
+    now.wrapping_sub(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{elapsed, Clock};
+    use crate::settings::GameSpeed;
+    use gba_test::test;
+
+    #[test]
+    fn advance() {
+        let mut clock = Clock::new(GameSpeed::Normal);
+
+        clock.advance(1);
+        clock.advance(4);
+
+        assert_eq!(clock.now(), 5);
+        assert_eq!(clock.rate(), 4);
+    }
+
+    #[test]
+    fn paused_does_not_advance() {
+        let mut clock = Clock::new(GameSpeed::Normal);
+        clock.advance(1);
+
+        clock.set_paused(true);
+        for _ in 0..10 {
+            clock.advance(1);
+        }
+
+        assert_eq!(clock.now(), 1);
+        assert_eq!(clock.rate(), 0);
+    }
+
+    #[test]
+    fn resumes_where_paused() {
+        let mut clock = Clock::new(GameSpeed::Normal);
+        clock.advance(3);
+        clock.set_paused(true);
+        clock.advance(1);
+
+        clock.set_paused(false);
+        clock.advance(1);
+
+        assert_eq!(clock.now(), 4);
+    }
+
+    #[test]
+    fn elapsed_wraps() {
+        assert_eq!(elapsed(u32::MAX, 2), 3);
+    }
+}
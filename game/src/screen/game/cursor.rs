@@ -0,0 +1,808 @@
+//! The player's cursor.
+
+use super::{camera::CameraController, palette::ObjectPalette};
+use crate::{
+    game::{Color, Direction, Position, Size, Turn},
+    mmio::{
+        keys::{KeyInput, KeyRepeat},
+        vram::ObjectAttributes,
+        OBJ_ATTRS, OBJ_TILES,
+    },
+    settings::Confirm,
+};
+use core::num::NonZeroU16;
+use deranged::{RangedU16, RangedU8};
+
+/// The furthest the cursor can go on a full sized board.
+const MAX_POSITION: Position = Size::MAX.last();
+
+/// The number of frames A must be held to take a turn when turns are confirmed by holding.
+pub(super) const HOLD_FRAMES: u8 = 20;
+
+/// The OAM slot of the hold progress bar. Slots 5 through 7 are the big number.
+const PROGRESS_SLOT: usize = 8;
+
+/// The object tile of the first progress bar frame. Tiles 20 through 59 are the big numerals.
+const PROGRESS_TILE: u16 = 60;
+
+/// The number of progress bar frames, one for each row of the bar that can be filled.
+const PROGRESS_FRAMES: u8 = 8;
+
+/// Draw the hold progress bar frames into object tile memory.
+pub(super) fn load_tiles() {
+    for frame in 0..PROGRESS_FRAMES {
+        unsafe {
+            OBJ_TILES
+                .add(PROGRESS_TILE as usize + frame as usize)
+                .write_volatile(progress_tile(frame + 1));
+        }
+    }
+}
+
+/// A narrow upright bar with its bottom `filled` rows filled in.
+fn progress_tile(filled: u8) -> [u32; 8] {
+    let mut rows = [0u32; 8];
+    for (row, pixels) in rows.iter_mut().enumerate() {
+        *pixels = if row >= (8 - filled) as usize {
+            // Columns 2 through 5.
+            0x0011_1100
+        } else {
+            // Only the outline, in columns 2 and 5.
+            0x0010_0100
+        };
+    }
+    rows
+}
+
+/// The progress bar frame shown after A has been held for `held` frames.
+fn progress_frame(held: u8) -> u8 {
+    ((held as u16 * PROGRESS_FRAMES as u16 / HOLD_FRAMES as u16) as u8).clamp(1, PROGRESS_FRAMES)
+        - 1
+}
+
+/// The position in `positions` closest to `target`, by Manhattan distance.
+///
+/// Ties go to whichever position comes first.
+pub(super) fn nearest(
+    positions: impl Iterator<Item = Position>,
+    target: Position,
+) -> Option<Position> {
+    positions.min_by_key(|position| position.manhattan_distance(target))
+}
+
+/// Translates the player's input into cursor movement and turn submissions.
+#[derive(Debug)]
+pub(super) struct CursorController {
+    position: Position,
+    prev_keys: KeyInput,
+    /// Repeats cursor movement while a direction is held.
+    repeat: KeyRepeat,
+    /// Number of consecutive frames without any keys held.
+    idle_frames: u16,
+    /// Whether the A press that submitted the last turn is still held.
+    ///
+    /// A turn is only submitted on a press of A that hasn't submitted a turn yet, no matter how many
+    /// frames pass between updates.
+    a_latched: bool,
+    /// Whether a submitted turn is still being executed and presented.
+    turn_in_flight: bool,
+    confirm: Confirm,
+    /// The number of frames A has been held toward confirming a turn.
+    ///
+    /// Only used when turns are confirmed by holding.
+    held: u8,
+    /// The bottom right corner of the board, past which the cursor can't move.
+    max_position: Position,
+    /// Whether R can be used to rotate a node more than once.
+    multi_rotation: bool,
+    /// How many times the next submitted turn rotates its node.
+    rotations: RangedU8<1, 3>,
+}
+
+impl CursorController {
+    pub(super) fn new(position: Position) -> Self {
+        Self {
+            position,
+            prev_keys: KeyInput::NONE,
+            repeat: KeyRepeat::default(),
+            idle_frames: 0,
+            a_latched: false,
+            turn_in_flight: false,
+            confirm: Confirm::Tap,
+            held: 0,
+            max_position: MAX_POSITION,
+            multi_rotation: false,
+            rotations: Turn::ONCE,
+        }
+    }
+
+    /// Keep the cursor on a board of `size`.
+    pub(super) fn set_size(&mut self, size: Size) {
+        self.max_position = size.last();
+        self.position = Position {
+            x: self.position.x.min(self.max_position.x),
+            y: self.position.y.min(self.max_position.y),
+        };
+    }
+
+    pub(super) fn set_confirm(&mut self, confirm: Confirm) {
+        self.confirm = confirm;
+        self.held = 0;
+    }
+
+    /// Allow choosing how many times a turn rotates its node, as `Rules::multi_rotation` does.
+    pub(super) fn set_multi_rotation(&mut self, multi_rotation: bool) {
+        self.multi_rotation = multi_rotation;
+        self.rotations = Turn::ONCE;
+    }
+
+    /// How many times the next submitted turn rotates its node.
+    pub(super) fn rotations(&self) -> RangedU8<1, 3> {
+        self.rotations
+    }
+
+    /// The number of frames A has been held toward confirming a turn, if a hold is in progress.
+    pub(super) fn hold_progress(&self) -> Option<u8> {
+        (self.held > 0).then_some(self.held)
+    }
+
+    pub(super) fn position(&self) -> Position {
+        self.position
+    }
+
+    pub(super) fn set_position(&mut self, position: Position) {
+        self.position = position;
+    }
+
+    fn pressed(&self, keys: KeyInput, key: KeyInput) -> bool {
+        keys.just_pressed(self.prev_keys, key)
+    }
+
+    /// Handle a single frame of input.
+    ///
+    /// Returns the turn submitted by the player, if there is one.
+    pub(super) fn update(&mut self, keys: KeyInput) -> Option<Turn> {
+        let repeated = self.repeat.update(keys);
+        for (key, direction) in [
+            (KeyInput::RIGHT, Direction::Right),
+            (KeyInput::UP, Direction::Up),
+            (KeyInput::LEFT, Direction::Left),
+            (KeyInput::DOWN, Direction::Down),
+        ] {
+            // Directions don't repeat while A is held, since that would start a hold over.
+            if self.pressed(keys, key) || (repeated.contains(key) && !keys.contains(KeyInput::A)) {
+                self.position = self.position.move_saturating(direction, self.max_position);
+                // A hold confirms the node it started on, so moving starts it over.
+                self.held = 0;
+            }
+        }
+        if self.multi_rotation && self.pressed(keys, KeyInput::R) {
+            // Cycles back around to a single rotation after three.
+            self.rotations = RangedU8::new(self.rotations.get() % 3 + 1).unwrap();
+        }
+        let turn = if keys.contains(KeyInput::A) {
+            if self.a_latched || self.turn_in_flight {
+                None
+            } else {
+                if self.confirm == Confirm::Hold {
+                    self.held += 1;
+                }
+                if self.confirm == Confirm::Tap || self.held >= HOLD_FRAMES {
+                    self.held = 0;
+                    self.a_latched = true;
+                    self.turn_in_flight = true;
+                    let times = self.rotations;
+                    self.rotations = Turn::ONCE;
+                    Some(Turn {
+                        rotate: self.position,
+                        times,
+                    })
+                } else {
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        if keys.just_released(self.prev_keys, KeyInput::A) {
+            // Releasing A early cancels the hold.
+            self.held = 0;
+            self.a_latched = false;
+        }
+
+        self.prev_keys = keys;
+        if !keys.any_pressed() {
+            self.idle_frames = self.idle_frames.saturating_add(1);
+        } else {
+            self.idle_frames = 0;
+        }
+
+        turn
+    }
+
+    /// Ignore A until it is released, such as when the press was meant for something else.
+    ///
+    /// A must be held when this is called, since only its release ends the latch.
+    pub(super) fn latch_a(&mut self) {
+        self.a_latched = true;
+        self.held = 0;
+    }
+
+    /// Allow turns to be submitted again once the last submitted turn is done.
+    ///
+    /// This must be called after every turn returned by `update()`, whether it succeeded or not.
+    pub(super) fn turn_complete(&mut self) {
+        self.turn_in_flight = false;
+    }
+
+    /// Whether the player has gone at least `threshold` frames without input.
+    ///
+    /// A `threshold` of `None` means the player is never considered idle.
+    pub(super) fn is_idle(&self, threshold: Option<NonZeroU16>) -> bool {
+        threshold.is_some_and(|threshold| self.idle_frames >= threshold.get())
+    }
+
+    /// Start counting idle frames from zero again.
+    pub(super) fn reset_idle(&mut self) {
+        self.idle_frames = 0;
+    }
+}
+
+/// The cursor's object in OAM.
+#[derive(Debug)]
+pub(super) struct CursorSprite {
+    /// The OAM slot the cursor is drawn with.
+    slot: usize,
+    /// The object palette the cursor is drawn with.
+    palette: ObjectPalette,
+}
+
+impl CursorSprite {
+    pub(super) fn new() -> Self {
+        Self {
+            slot: 0,
+            palette: ObjectPalette::Cursor,
+        }
+    }
+
+    /// Draw the cursor in the color of the player whose turn it is.
+    ///
+    /// `None` draws the neutral cursor, which is used when there is only a single human player.
+    pub(super) fn set_player(&mut self, color: Option<Color>) {
+        self.palette = color.map_or(ObjectPalette::Cursor, ObjectPalette::Player);
+    }
+
+    fn attributes(&self, obj_pixel_pos: (u16, u16)) -> ObjectAttributes {
+        self.palette
+            .attributes()
+            .with_x(obj_pixel_pos.0)
+            .with_y(obj_pixel_pos.1 as u8)
+            .with_tile(RangedU16::new_static::<0>())
+            .with_size(RangedU8::new_static::<1>())
+    }
+
+    fn progress_attributes(&self, obj_pixel_pos: (u16, u16), held: u8) -> ObjectAttributes {
+        // To the right of the cursor, centered vertically.
+        self.palette
+            .attributes()
+            .with_x(obj_pixel_pos.0 + 16)
+            .with_y(obj_pixel_pos.1 as u8 + 4)
+            .with_tile(unsafe {
+                RangedU16::new_unchecked(PROGRESS_TILE + progress_frame(held) as u16)
+            })
+    }
+
+    /// Draw the cursor over `position`, relative to the camera.
+    ///
+    /// Nothing is written if the position is off screen.
+    pub(super) fn draw(&self, camera: &CameraController, position: Position) {
+        if let Some(obj_pixel_pos) = camera.relative_sprite_location(position) {
+            unsafe {
+                OBJ_ATTRS
+                    .add(self.slot)
+                    .write_volatile(self.attributes(obj_pixel_pos));
+            }
+        }
+    }
+
+    /// Draw the progress of holding A next to the cursor over `position`.
+    ///
+    /// The progress bar is hidden if `held` is `None` or the position is off screen.
+    pub(super) fn draw_progress(
+        &self,
+        camera: &CameraController,
+        position: Position,
+        held: Option<u8>,
+    ) {
+        let attributes = held.zip(camera.relative_sprite_location(position)).map_or(
+            ObjectAttributes::new().with_disabled(true),
+            |(held, obj_pixel_pos)| self.progress_attributes(obj_pixel_pos, held),
+        );
+        unsafe {
+            OBJ_ATTRS.add(PROGRESS_SLOT).write_volatile(attributes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        nearest, progress_frame, progress_tile, CursorController, CursorSprite, HOLD_FRAMES,
+        PROGRESS_FRAMES,
+    };
+    use crate::{
+        game::{Color, Position, Size, Turn},
+        mmio::{
+            keys::{KeyInput, KeyRepeat},
+            vram::ObjectAttributes,
+            OBJ_ATTRS,
+        },
+        screen::game::{camera::CameraController, palette::ObjectPalette},
+        settings::Confirm,
+    };
+    use core::{mem::transmute, num::NonZeroU16};
+    use deranged::RangedU8;
+    use gba_test::test;
+
+    #[test]
+    fn update_moves_on_press() {
+        let mut cursor = CursorController::new(Position { x: 5, y: 5 });
+
+        cursor.update(KeyInput::RIGHT);
+
+        assert_eq!(cursor.position(), Position { x: 6, y: 5 });
+    }
+
+    #[test]
+    fn update_does_not_move_while_held() {
+        let mut cursor = CursorController::new(Position { x: 5, y: 5 });
+
+        cursor.update(KeyInput::DOWN);
+        cursor.update(KeyInput::DOWN);
+        cursor.update(KeyInput::DOWN);
+
+        assert_eq!(cursor.position(), Position { x: 5, y: 6 });
+    }
+
+    #[test]
+    fn update_repeats_while_held() {
+        let mut cursor = CursorController::new(Position { x: 5, y: 5 });
+
+        for _ in 0..=KeyRepeat::DEFAULT_INTERVAL * 2 {
+            cursor.update(KeyInput::DOWN);
+        }
+
+        assert_eq!(cursor.position(), Position { x: 5, y: 8 });
+    }
+
+    #[test]
+    fn update_moves_on_each_press() {
+        let mut cursor = CursorController::new(Position { x: 5, y: 5 });
+
+        cursor.update(KeyInput::LEFT);
+        cursor.update(KeyInput::NONE);
+        cursor.update(KeyInput::LEFT);
+
+        assert_eq!(cursor.position(), Position { x: 3, y: 5 });
+    }
+
+    #[test]
+    fn update_stays_on_board() {
+        let mut cursor = CursorController::new(Position { x: 0, y: 15 });
+
+        cursor.update(KeyInput::LEFT);
+        cursor.update(KeyInput::NONE);
+        cursor.update(KeyInput::DOWN);
+
+        assert_eq!(cursor.position(), Position { x: 0, y: 15 });
+    }
+
+    #[test]
+    fn update_stays_on_small_board() {
+        let mut cursor = CursorController::new(Position { x: 6, y: 7 });
+        cursor.set_size(Size::square(8));
+
+        cursor.update(KeyInput::RIGHT);
+        cursor.update(KeyInput::NONE);
+        cursor.update(KeyInput::RIGHT);
+        cursor.update(KeyInput::NONE);
+        cursor.update(KeyInput::DOWN);
+
+        assert_eq!(cursor.position(), Position { x: 7, y: 7 });
+    }
+
+    #[test]
+    fn set_size_moves_cursor_onto_board() {
+        let mut cursor = CursorController::new(Position { x: 10, y: 3 });
+
+        cursor.set_size(Size::square(8));
+
+        assert_eq!(cursor.position(), Position { x: 7, y: 3 });
+    }
+
+    #[test]
+    fn update_submits_turn_on_press() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        let turn = cursor.update(KeyInput::A);
+
+        assert_eq!(turn.map(|turn| turn.rotate), Some(Position { x: 2, y: 3 }));
+    }
+
+    #[test]
+    fn update_does_not_submit_turn_while_held() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        cursor.update(KeyInput::A);
+
+        assert!(cursor.update(KeyInput::A).is_none());
+    }
+
+    #[test]
+    fn latch_a_ignores_held_press() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        cursor.latch_a();
+
+        assert!(cursor.update(KeyInput::A).is_none());
+        cursor.update(KeyInput::NONE);
+        assert!(cursor.update(KeyInput::A).is_some());
+    }
+
+    #[test]
+    fn update_r_cycles_rotations() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+        cursor.set_multi_rotation(true);
+
+        cursor.update(KeyInput::R);
+        assert_eq!(cursor.rotations().get(), 2);
+        cursor.update(KeyInput::NONE);
+        cursor.update(KeyInput::R);
+        assert_eq!(cursor.rotations().get(), 3);
+        cursor.update(KeyInput::NONE);
+        cursor.update(KeyInput::R);
+        assert_eq!(cursor.rotations().get(), 1);
+    }
+
+    #[test]
+    fn update_r_ignored_without_multi_rotation() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        cursor.update(KeyInput::R);
+
+        assert_eq!(cursor.rotations(), Turn::ONCE);
+    }
+
+    #[test]
+    fn update_submits_rotations() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+        cursor.set_multi_rotation(true);
+        cursor.update(KeyInput::R);
+        cursor.update(KeyInput::NONE);
+
+        let turn = cursor.update(KeyInput::A);
+
+        assert_eq!(turn.map(|turn| turn.times.get()), Some(2));
+        // The next turn starts over from a single rotation.
+        assert_eq!(cursor.rotations(), Turn::ONCE);
+    }
+
+    #[test]
+    fn update_does_not_submit_while_turn_in_flight() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        assert!(cursor.update(KeyInput::A).is_some());
+        cursor.update(KeyInput::NONE);
+
+        assert!(cursor.update(KeyInput::A).is_none());
+    }
+
+    #[test]
+    fn update_submits_after_turn_complete() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        assert!(cursor.update(KeyInput::A).is_some());
+        cursor.turn_complete();
+        cursor.update(KeyInput::NONE);
+
+        assert!(cursor.update(KeyInput::A).is_some());
+    }
+
+    /// Regression test for a held A submitting a second turn once the first finished presenting.
+    #[test]
+    fn update_held_a_across_presentation() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        assert!(cursor.update(KeyInput::A).is_some());
+        // The board redraws over several frames, during which input isn't read.
+        cursor.turn_complete();
+        // A is still held on the next frame input is read.
+        assert!(cursor.update(KeyInput::A).is_none());
+        assert!(cursor.update(KeyInput::A).is_none());
+    }
+
+    /// A and right held together.
+    const A_RIGHT: KeyInput = unsafe { transmute::<u16, KeyInput>(0b0000_0011_1110_1110) };
+
+    fn hold_cursor() -> CursorController {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+        cursor.set_confirm(Confirm::Hold);
+        cursor
+    }
+
+    #[test]
+    fn hold_submits_at_threshold() {
+        let mut cursor = hold_cursor();
+
+        for _ in 1..HOLD_FRAMES {
+            assert!(cursor.update(KeyInput::A).is_none());
+        }
+        let turn = cursor.update(KeyInput::A);
+
+        assert_eq!(turn.map(|turn| turn.rotate), Some(Position { x: 2, y: 3 }));
+        assert_eq!(cursor.hold_progress(), None);
+    }
+
+    #[test]
+    fn hold_submits_once() {
+        let mut cursor = hold_cursor();
+        for _ in 0..HOLD_FRAMES {
+            cursor.update(KeyInput::A);
+        }
+        cursor.turn_complete();
+
+        for _ in 0..HOLD_FRAMES * 2 {
+            assert!(cursor.update(KeyInput::A).is_none());
+        }
+    }
+
+    #[test]
+    fn hold_early_release_cancels() {
+        let mut cursor = hold_cursor();
+
+        for _ in 1..HOLD_FRAMES {
+            assert!(cursor.update(KeyInput::A).is_none());
+        }
+        assert_eq!(cursor.hold_progress(), Some(HOLD_FRAMES - 1));
+        assert!(cursor.update(KeyInput::NONE).is_none());
+        assert_eq!(cursor.hold_progress(), None);
+
+        // The hold starts over from the beginning.
+        for _ in 1..HOLD_FRAMES {
+            assert!(cursor.update(KeyInput::A).is_none());
+        }
+        assert!(cursor.update(KeyInput::A).is_some());
+    }
+
+    #[test]
+    fn hold_moving_starts_over() {
+        let mut cursor = hold_cursor();
+        for _ in 0..HOLD_FRAMES / 2 {
+            cursor.update(KeyInput::A);
+        }
+
+        cursor.update(A_RIGHT);
+
+        assert_eq!(cursor.hold_progress(), Some(1));
+    }
+
+    #[test]
+    fn hold_with_direction_held() {
+        let mut cursor = hold_cursor();
+
+        // Holding a direction moves the cursor once, without repeating or interrupting the hold.
+        let mut turn = None;
+        for _ in 0..HOLD_FRAMES {
+            turn = cursor.update(A_RIGHT);
+        }
+
+        assert_eq!(turn.map(|turn| turn.rotate), Some(Position { x: 3, y: 3 }));
+    }
+
+    #[test]
+    fn hold_does_not_progress_while_turn_in_flight() {
+        let mut cursor = hold_cursor();
+        for _ in 0..HOLD_FRAMES {
+            cursor.update(KeyInput::A);
+        }
+        cursor.update(KeyInput::NONE);
+
+        for _ in 0..HOLD_FRAMES {
+            assert!(cursor.update(KeyInput::A).is_none());
+        }
+        assert_eq!(cursor.hold_progress(), None);
+    }
+
+    #[test]
+    fn tap_has_no_progress() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        cursor.update(KeyInput::A);
+
+        assert_eq!(cursor.hold_progress(), None);
+    }
+
+    #[test]
+    fn progress_frame_bounds() {
+        assert_eq!(progress_frame(1), 0);
+        assert_eq!(progress_frame(HOLD_FRAMES - 1), PROGRESS_FRAMES - 2);
+        assert_eq!(progress_frame(HOLD_FRAMES), PROGRESS_FRAMES - 1);
+    }
+
+    #[test]
+    fn progress_tile_fills_from_bottom() {
+        let tile = progress_tile(2);
+
+        assert_eq!(tile[7], 0x0011_1100);
+        assert_eq!(tile[6], 0x0011_1100);
+        assert_eq!(tile[5], 0x0010_0100);
+        assert_eq!(tile[0], 0x0010_0100);
+    }
+
+    #[test]
+    fn update_no_input() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        assert!(cursor.update(KeyInput::NONE).is_none());
+        assert_eq!(cursor.position(), Position { x: 2, y: 3 });
+    }
+
+    #[test]
+    fn is_idle_after_threshold() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        for _ in 0..3 {
+            cursor.update(KeyInput::NONE);
+        }
+
+        assert!(cursor.is_idle(NonZeroU16::new(3)));
+    }
+
+    #[test]
+    fn is_not_idle_before_threshold() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        for _ in 0..2 {
+            cursor.update(KeyInput::NONE);
+        }
+
+        assert!(!cursor.is_idle(NonZeroU16::new(3)));
+    }
+
+    #[test]
+    fn is_idle_resets_on_input() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        cursor.update(KeyInput::NONE);
+        cursor.update(KeyInput::NONE);
+        cursor.update(KeyInput::B);
+        cursor.update(KeyInput::NONE);
+        cursor.update(KeyInput::NONE);
+
+        assert!(!cursor.is_idle(NonZeroU16::new(3)));
+    }
+
+    #[test]
+    fn is_idle_held_key_is_input() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        for _ in 0..5 {
+            cursor.update(KeyInput::B);
+        }
+
+        assert!(!cursor.is_idle(NonZeroU16::new(3)));
+    }
+
+    #[test]
+    fn is_idle_disabled() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+
+        for _ in 0..5 {
+            cursor.update(KeyInput::NONE);
+        }
+
+        assert!(!cursor.is_idle(None));
+    }
+
+    #[test]
+    fn reset_idle() {
+        let mut cursor = CursorController::new(Position { x: 2, y: 3 });
+        for _ in 0..3 {
+            cursor.update(KeyInput::NONE);
+        }
+
+        cursor.reset_idle();
+
+        assert!(!cursor.is_idle(NonZeroU16::new(3)));
+    }
+
+    #[test]
+    fn nearest_empty() {
+        assert_eq!(nearest([].into_iter(), Position { x: 2, y: 3 }), None);
+    }
+
+    #[test]
+    fn nearest_closest() {
+        assert_eq!(
+            nearest(
+                [
+                    Position { x: 0, y: 0 },
+                    Position { x: 4, y: 4 },
+                    Position { x: 9, y: 3 },
+                ]
+                .into_iter(),
+                Position { x: 5, y: 2 }
+            ),
+            Some(Position { x: 4, y: 4 })
+        );
+    }
+
+    #[test]
+    fn nearest_tie_goes_to_first() {
+        assert_eq!(
+            nearest(
+                [Position { x: 1, y: 2 }, Position { x: 3, y: 2 }].into_iter(),
+                Position { x: 2, y: 2 }
+            ),
+            Some(Position { x: 1, y: 2 })
+        );
+    }
+
+    #[test]
+    fn cursor_sprite_neutral_palette() {
+        let sprite = CursorSprite::new();
+
+        assert_eq!(
+            sprite.attributes((10, 20)),
+            ObjectAttributes::new()
+                .with_x(10)
+                .with_y(20)
+                .with_size(RangedU8::new_static::<1>())
+        );
+    }
+
+    #[test]
+    fn cursor_sprite_progress_uses_cursor_palette() {
+        let mut sprite = CursorSprite::new();
+        sprite.set_player(Some(Color::Yellow));
+
+        let attributes = sprite.progress_attributes((10, 20), 0);
+
+        assert_eq!(
+            attributes,
+            attributes.with_palette(ObjectPalette::Player(Color::Yellow).bank())
+        );
+    }
+
+    #[test]
+    fn cursor_sprite_palette_follows_player() {
+        let mut sprite = CursorSprite::new();
+
+        sprite.set_player(Some(Color::Red));
+        let red = sprite.attributes((10, 20));
+        sprite.set_player(Some(Color::Blue));
+        let blue = sprite.attributes((10, 20));
+
+        assert_eq!(red, blue.with_palette(Color::Red.palette_bank()));
+        assert_eq!(blue, red.with_palette(RangedU8::new_static::<2>()));
+    }
+
+    #[test]
+    fn draw_palette_bank_follows_hotseat_turn() {
+        let position = Position { x: 3, y: 4 };
+        let camera = CameraController::new(position);
+        let mut sprite = CursorSprite::new();
+
+        // Red holds the console, then hands it to blue.
+        sprite.set_player(Some(Color::Red));
+        sprite.draw(&camera, position);
+        let red = unsafe { OBJ_ATTRS.read_volatile() };
+        sprite.set_player(Some(Color::Blue));
+        sprite.draw(&camera, position);
+        let blue = unsafe { OBJ_ATTRS.read_volatile() };
+
+        assert_ne!(red, blue);
+        assert_eq!(red, red.with_palette(Color::Red.palette_bank()));
+        assert_eq!(blue, red.with_palette(Color::Blue.palette_bank()));
+    }
+}
@@ -0,0 +1,118 @@
+//! Blinking frames drawn around single nodes to point them out.
+
+use super::{camera::CameraController, palette::ObjectPalette};
+use crate::{
+    game::Position,
+    mmio::{vram::ObjectAttributes, OBJ_ATTRS},
+};
+use deranged::{RangedU16, RangedU8};
+
+/// The number of ticks a marker spends shown, and then hidden, in each blink.
+const BLINK_LENGTH: u32 = 16;
+
+/// Whether a blinking marker is shown at tick `now`.
+fn blink_on(now: u32) -> bool {
+    now / BLINK_LENGTH % 2 == 0
+}
+
+/// A frame drawn with the cursor's art, which blinks so it isn't mistaken for the cursor.
+#[derive(Debug)]
+pub(super) struct Marker {
+    /// The OAM slot the marker is drawn with.
+    slot: usize,
+    palette: ObjectPalette,
+}
+
+impl Marker {
+    pub(super) const fn new(slot: usize, palette: ObjectPalette) -> Self {
+        Self { slot, palette }
+    }
+
+    fn attributes(&self, obj_pixel_pos: (u16, u16)) -> ObjectAttributes {
+        self.palette
+            .attributes()
+            .with_x(obj_pixel_pos.0)
+            .with_y(obj_pixel_pos.1 as u8)
+            .with_tile(RangedU16::new_static::<0>())
+            .with_size(RangedU8::new_static::<1>())
+    }
+
+    /// Draw the marker over `position`, relative to the camera, as of tick `now`.
+    ///
+    /// The marker is hidden if `position` is `None` or off screen, or if it is between blinks.
+    pub(super) fn draw(&self, camera: &CameraController, position: Option<Position>, now: u32) {
+        let attributes = position
+            .filter(|_| blink_on(now))
+            .and_then(|position| camera.relative_sprite_location(position))
+            .map_or(
+                ObjectAttributes::new().with_disabled(true),
+                |obj_pixel_pos| self.attributes(obj_pixel_pos),
+            );
+        unsafe {
+            OBJ_ATTRS.add(self.slot).write_volatile(attributes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blink_on, Marker, BLINK_LENGTH};
+    use crate::{
+        game::Position,
+        mmio::{vram::ObjectAttributes, OBJ_ATTRS},
+        screen::game::{camera::CameraController, palette::ObjectPalette},
+    };
+    use gba_test::test;
+
+    #[test]
+    fn blink_on_alternates() {
+        assert!(blink_on(0));
+        assert!(blink_on(BLINK_LENGTH - 1));
+        assert!(!blink_on(BLINK_LENGTH));
+        assert!(!blink_on(BLINK_LENGTH * 2 - 1));
+        assert!(blink_on(BLINK_LENGTH * 2));
+    }
+
+    #[test]
+    fn draw_shown_during_blink() {
+        let marker = Marker::new(9, ObjectPalette::Effects);
+        let position = Position { x: 3, y: 4 };
+        let camera = CameraController::new(position);
+
+        marker.draw(&camera, Some(position), 0);
+
+        assert_eq!(
+            unsafe { OBJ_ATTRS.add(9).read_volatile() },
+            marker.attributes(camera.relative_sprite_location(position).unwrap())
+        );
+    }
+
+    #[test]
+    fn draw_hidden_between_blinks() {
+        let marker = Marker::new(9, ObjectPalette::Effects);
+        let position = Position { x: 3, y: 4 };
+
+        marker.draw(
+            &CameraController::new(position),
+            Some(position),
+            BLINK_LENGTH,
+        );
+
+        assert_eq!(
+            unsafe { OBJ_ATTRS.add(9).read_volatile() },
+            ObjectAttributes::new().with_disabled(true)
+        );
+    }
+
+    #[test]
+    fn draw_hidden_without_position() {
+        let marker = Marker::new(9, ObjectPalette::Effects);
+
+        marker.draw(&CameraController::new(Position { x: 3, y: 4 }), None, 0);
+
+        assert_eq!(
+            unsafe { OBJ_ATTRS.add(9).read_volatile() },
+            ObjectAttributes::new().with_disabled(true)
+        );
+    }
+}
@@ -0,0 +1,1018 @@
+mod ai;
+mod big_number;
+mod board;
+mod camera;
+mod clock;
+mod cursor;
+mod marker;
+mod palette;
+mod replay;
+mod sparks;
+mod suspend;
+mod transposition;
+
+use super::{
+    game_over::{PlayerResult, Summary},
+    Screen,
+};
+use crate::{
+    bios::wait_for_vblank_checked,
+    export,
+    game::{
+        self, BoardCode, Color, Direction, Ending, Position, Recording, Turn, TurnOutcome,
+        TurnPreview,
+    },
+    include_bytes_aligned,
+    mmio::{
+        keys::KeyInput,
+        vram::{
+            BackgroundControl, BlendControl, ColorEffect, DisplayControl, ObjectAttributes,
+            TextScreenEntry,
+        },
+        BG0CNT, BG1CNT, BG2CNT, BG3CNT, BG_PALETTE, BLDCNT, BLDY, CHARBLOCK0, DISPCNT, KEYINPUT,
+        OBJ_ATTRS, OBJ_TILES, TEXT_SCREENBLOCK16, TEXT_SCREENBLOCK24, TEXT_SCREENBLOCK28,
+    },
+    rating::{self, Rating},
+    records::{Outcome, Record, Records},
+    settings::Settings,
+    text, timing,
+};
+pub use ai::AiLevel;
+use ai::{cooldown_finished, AiDriver};
+use big_number::BigNumber;
+use board::{set_tile, BoardRenderer};
+use camera::CameraController;
+use clock::Clock;
+use core::mem::transmute;
+use cursor::{nearest, CursorController, CursorSprite};
+use deranged::{RangedU16, RangedU8};
+use marker::Marker;
+use palette::ObjectPalette;
+pub use replay::Replay;
+use suspend::Suspended;
+
+macro_rules! load_tiles {
+    ($file_name:literal, $offset:expr, $len:expr) => {
+        unsafe {
+            CHARBLOCK0
+                .add($offset)
+                .cast::<[[u32; 8]; $len]>()
+                .write_volatile(transmute(include_bytes_aligned!($file_name).0));
+        }
+    };
+}
+
+fn wait_frames(num: usize) {
+    for _ in 0..num {
+        wait_for_vblank_checked();
+    }
+}
+
+/// Shown when a turn captured more nodes than can be presented one at a time.
+const MEGA_CAPTURE: &str = "MEGA CAPTURE!";
+
+/// Shown across the top of the screen while a new player's first turn points out a choke point.
+const JUNCTION_HINT: &str = "CONTROL THIS JUNCTION!";
+
+/// The OAM slot of the marker over the choke point pointed out to new players. Slot 8 is the hold
+/// progress bar.
+const JUNCTION_SLOT: usize = 9;
+
+/// The OAM slot of the marker over the node the last turn rotated.
+const LAST_MOVE_SLOT: usize = 10;
+
+/// Draws `parts` one after another, centered across the middle of the screen.
+fn draw_banner(parts: &[&str]) {
+    text::draw_centered(TEXT_SCREENBLOCK28, 9, parts);
+}
+
+fn clear_banner() {
+    text::clear(TEXT_SCREENBLOCK28, 0, 9, 30);
+}
+
+/// Shown when a turn leaves `color` without any nodes.
+fn draw_elimination(color: Color) {
+    draw_banner(&[color.name(), " ELIMINATED"]);
+}
+
+/// Shown while the console is handed to the next human player.
+fn draw_handoff(color: Color) {
+    draw_banner(&["PASS TO ", color.name()]);
+    text::draw(TEXT_SCREENBLOCK28, 11, 10, "PRESS A");
+}
+
+fn clear_handoff() {
+    clear_banner();
+    text::clear(TEXT_SCREENBLOCK28, 0, 10, 30);
+}
+
+/// Shown in the bottom right corner, below the rotations: how many nodes a turn captures.
+///
+/// This shows what the player's last turn captured while it can still be undone.
+fn draw_captured(captured: Option<u16>) {
+    text::clear(TEXT_SCREENBLOCK28, 21, 19, 8);
+    if let Some(captured) = captured {
+        let mut buffer = [0; 5];
+        text::draw(TEXT_SCREENBLOCK28, 21, 19, "+");
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            22,
+            19,
+            text::format_number(captured, &mut buffer),
+        );
+    }
+}
+
+/// Shown in the same place as `draw_captured()`: how many nodes the turn under the cursor would
+/// capture, or whether it would win the game outright.
+///
+/// Nothing is shown if the cursor isn't on a node the player could turn.
+fn draw_preview(preview: Option<TurnPreview>) {
+    match preview {
+        Some(preview) if preview.wins() => {
+            text::clear(TEXT_SCREENBLOCK28, 21, 19, 8);
+            text::draw(TEXT_SCREENBLOCK28, 21, 19, "WIN!");
+        }
+        _ => draw_captured(preview.map(|preview| preview.captured())),
+    }
+}
+
+/// Shown in the bottom right corner when nodes can be rotated more than once: how many times the
+/// next turn rotates its node, and where the node will point afterward.
+fn draw_rotations(times: RangedU8<1, 3>, direction: Option<Direction>) {
+    text::clear(TEXT_SCREENBLOCK28, 21, 18, 8);
+    text::draw(
+        TEXT_SCREENBLOCK28,
+        21,
+        18,
+        match times.get() {
+            1 => "X1",
+            2 => "X2",
+            _ => "X3",
+        },
+    );
+    if let Some(direction) = direction {
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            24,
+            18,
+            match direction {
+                Direction::Left => "LEFT",
+                Direction::Up => "UP",
+                Direction::Right => "RIGHT",
+                Direction::Down => "DOWN",
+            },
+        );
+    }
+}
+
+/// Fades every layer by brightening it toward white by `BLDY`.
+fn fade_control() -> BlendControl {
+    BlendControl::new()
+        .with_target1_bg0(true)
+        .with_target1_bg1(true)
+        .with_target1_bg2(true)
+        .with_target1_bg3(true)
+        .with_target1_obj(true)
+        .with_target1_backdrop(true)
+        .with_color_effect(ColorEffect::Brighten)
+}
+
+/// Dim everything but the text on BG3, so the game is clearly stopped while paused.
+fn draw_pause() {
+    unsafe {
+        BLDCNT.write_volatile(
+            BlendControl::new()
+                .with_target1_bg0(true)
+                .with_target1_bg1(true)
+                .with_target1_bg2(true)
+                .with_target1_obj(true)
+                .with_target1_backdrop(true)
+                .with_color_effect(ColorEffect::Darken),
+        );
+        BLDY.write_volatile(RangedU8::new_static::<8>());
+    }
+    text::draw(TEXT_SCREENBLOCK28, 1, 2, "PAUSED");
+}
+
+fn clear_pause() {
+    unsafe {
+        BLDCNT.write_volatile(fade_control());
+        BLDY.write_volatile(RangedU8::new_static::<0>());
+    }
+    text::clear(TEXT_SCREENBLOCK28, 1, 2, 6);
+}
+
+/// Set up the display for drawing a game, leaving the screen faded out.
+fn init_display() {
+    wait_for_vblank_checked();
+
+    unsafe {
+        // Initialize fade.
+        BLDCNT.write_volatile(fade_control());
+        // Fade out while we set up the screen.
+        BLDY.write_volatile(RangedU8::new_static::<16>());
+
+        // Set up background layers.
+        BG0CNT.write_volatile(
+            BackgroundControl::new()
+                .with_screenblock(RangedU8::new_static::<8>())
+                .with_priority(RangedU8::new_static::<3>()),
+        );
+        BG1CNT.write_volatile(
+            BackgroundControl::new()
+                .with_screenblock(RangedU8::new_static::<16>())
+                .with_priority(RangedU8::new_static::<2>())
+                .with_screen_size(RangedU8::new_static::<3>()),
+        );
+        BG2CNT.write_volatile(
+            BackgroundControl::new()
+                .with_screenblock(RangedU8::new_static::<24>())
+                .with_priority(RangedU8::new_static::<1>())
+                .with_screen_size(RangedU8::new_static::<3>()),
+        );
+        // Banners are drawn on BG3.
+        BG3CNT.write_volatile(
+            BackgroundControl::new()
+                .with_screenblock(RangedU8::new_static::<28>())
+                .with_priority(RangedU8::new_static::<0>()),
+        );
+        DISPCNT.write_volatile(
+            DisplayControl::new()
+                .with_bg0(true)
+                .with_bg1(true)
+                .with_bg2(true)
+                .with_bg3(true)
+                .with_obj(true)
+                .with_obj_vram_1d(true),
+        );
+
+        // Load palettes.
+        BG_PALETTE.write_volatile(transmute(
+            include_bytes_aligned!("../../../res/neutral.pal").0,
+        ));
+        BG_PALETTE
+            .add(1)
+            .write_volatile(transmute(include_bytes_aligned!("../../../res/red.pal").0));
+        BG_PALETTE
+            .add(2)
+            .write_volatile(transmute(include_bytes_aligned!("../../../res/blue.pal").0));
+        BG_PALETTE.add(3).write_volatile(transmute(
+            include_bytes_aligned!("../../../res/yellow.pal").0,
+        ));
+        BG_PALETTE.add(4).write_volatile(transmute(
+            include_bytes_aligned!("../../../res/green.pal").0,
+        ));
+    }
+    palette::load();
+
+    // Define the game tiles.
+    load_tiles!("../../../res/empty.4bpp", 0, 1);
+    load_tiles!("../../../res/wall.4bpp", 1, 4);
+    // Left and down arrows are drawn by flipping the right and up arrows. Reverse arrows use the
+    // tiles that left and down arrows would have.
+    load_tiles!("../../../res/arrow_right.4bpp", 5, 4);
+    load_tiles!("../../../res/reverse_arrow_right.4bpp", 9, 4);
+    load_tiles!("../../../res/reverse_arrow_up.4bpp", 13, 4);
+    load_tiles!("../../../res/arrow_up.4bpp", 17, 4);
+    load_tiles!("../../../res/grid0.4bpp", 21, 1);
+    load_tiles!("../../../res/grid0_left.4bpp", 22, 1);
+    load_tiles!("../../../res/grid0_up.4bpp", 23, 1);
+    load_tiles!("../../../res/grid0_left_up.4bpp", 24, 1);
+    load_tiles!("../../../res/grid1.4bpp", 25, 1);
+    load_tiles!("../../../res/grid1_right.4bpp", 26, 1);
+    load_tiles!("../../../res/grid1_up.4bpp", 27, 1);
+    load_tiles!("../../../res/grid1_right_up.4bpp", 28, 1);
+    load_tiles!("../../../res/grid2.4bpp", 29, 1);
+    load_tiles!("../../../res/grid2_left.4bpp", 30, 1);
+    load_tiles!("../../../res/grid2_down.4bpp", 31, 1);
+    load_tiles!("../../../res/grid2_left_down.4bpp", 32, 1);
+    load_tiles!("../../../res/grid3.4bpp", 33, 1);
+    load_tiles!("../../../res/grid3_right.4bpp", 34, 1);
+    load_tiles!("../../../res/grid3_down.4bpp", 35, 1);
+    load_tiles!("../../../res/grid3_right_down.4bpp", 36, 1);
+    load_tiles!("../../../res/background.4bpp", 37, 1);
+    load_tiles!("../../../res/arrow_all.4bpp", 38, 4);
+    load_tiles!("../../../res/teleporter.4bpp", 42, 4);
+    // Tiles 54 through 57 are unused, as with the plain arrows.
+    load_tiles!("../../../res/super_arrow_up.4bpp", 46, 4);
+    load_tiles!("../../../res/super_arrow_right.4bpp", 50, 4);
+    text::load();
+
+    // Define the cursor tiles.
+    unsafe {
+        OBJ_TILES
+            .cast::<[[u32; 8]; 4]>()
+            .write_volatile(transmute::<_, [[u32; 8]; 4]>(
+                include_bytes_aligned!("../../../res/cursor.4bpp").0,
+            ))
+    }
+    sparks::load_tiles();
+    big_number::load_tiles();
+    cursor::load_tiles();
+
+    // Draw background.
+    for y in 0..16 {
+        for x in 0..16 {
+            set_tile(
+                x,
+                y,
+                RangedU16::new_static::<37>(),
+                8,
+                RangedU8::new_static::<1>(),
+            );
+        }
+    }
+
+    // Clear BGs.
+    unsafe {
+        TEXT_SCREENBLOCK16
+            .cast::<[TextScreenEntry; 4096]>()
+            .write_volatile(
+                [TextScreenEntry::new()
+                    .with_tile(RangedU16::new_static::<0>())
+                    .with_palette(RangedU8::new_static::<1>()); 4096],
+            );
+        TEXT_SCREENBLOCK24
+            .cast::<[TextScreenEntry; 4096]>()
+            .write_volatile(
+                [TextScreenEntry::new()
+                    .with_tile(RangedU16::new_static::<0>())
+                    .with_palette(RangedU8::new_static::<0>()); 4096],
+            );
+    }
+    for y in 0..32 {
+        text::clear(TEXT_SCREENBLOCK28, 0, y, 32);
+    }
+
+    // Hide unused objects.
+    unsafe {
+        OBJ_ATTRS
+            .add(1)
+            .cast::<[ObjectAttributes; 127]>()
+            .write_volatile([ObjectAttributes::new().with_disabled(true); 127])
+    }
+}
+
+#[derive(Debug)]
+pub struct Game {
+    cursor: CursorController,
+    cursor_sprite: CursorSprite,
+    camera: CameraController,
+    board: BoardRenderer,
+    /// The number of turns left, shown in the top right corner if the game has a turn limit.
+    countdown: BigNumber,
+    ai: AiDriver,
+    /// Whether the AI is currently taking the player's turn for them.
+    auto_playing: bool,
+
+    state: game::Game,
+    /// The human player currently holding the console.
+    player_color: game::Color,
+    /// Whether each color is played by a human, indexed by the color's `u8` value.
+    humans: [bool; 4],
+    /// Whether the game is waiting for the console to be handed to the next human player.
+    handing_off: bool,
+    settings: Settings,
+    code: BoardCode,
+    /// The AI level of each color, indexed by the color's `u8` value.
+    ///
+    /// A human's entry is only used if the AI takes over for them.
+    levels: [AiLevel; 4],
+    /// The number of turns the player has taken.
+    turns: u16,
+    /// Every turn taken so far, by any player, so the game can be exported once it's over.
+    recording: Recording,
+    /// Drives every animation, and stops while the game is paused.
+    clock: Clock,
+    /// The tick until which the AI holds off responding, so the player's turn can be undone.
+    undo_until: Option<u32>,
+    /// What the last turn did, until any eliminations it caused have been announced.
+    unannounced: Option<TurnOutcome>,
+    /// The choke point pointed out to a new player, until they take their first turn.
+    junction: Option<Position>,
+    junction_marker: Marker,
+    /// The turn whose preview is shown, so it is only worked out again when the cursor changes.
+    previewed: Option<Turn>,
+    prev_keys: KeyInput,
+}
+
+impl Game {
+    /// Start a game played by `player_color`, which must be one of the `humans`.
+    ///
+    /// The player doesn't need to go first; any AI players before them take their turns as soon
+    /// as the game starts.
+    ///
+    /// # Panics
+    /// Panics if `player_color` isn't one of `humans`.
+    pub fn new(
+        cursor: Position,
+        game: game::Game,
+        player_color: game::Color,
+        humans: [bool; 4],
+        settings: Settings,
+        code: BoardCode,
+        levels: [AiLevel; 4],
+    ) -> Self {
+        assert!(
+            humans[u8::from(player_color) as usize],
+            "{player_color:?} is not played by a human"
+        );
+        init_display();
+
+        let first = game.turn_color();
+        let mut state = Self {
+            cursor: CursorController::new(cursor),
+            cursor_sprite: CursorSprite::new(),
+            camera: CameraController::new(cursor),
+            board: BoardRenderer::new(),
+            countdown: BigNumber::new(232, 8),
+            ai: AiDriver::new(code.seed),
+            auto_playing: false,
+
+            state: game,
+            player_color,
+            humans,
+            handing_off: false,
+            settings,
+            code,
+            levels,
+            turns: 0,
+            recording: Recording::new(code, first),
+            clock: Clock::new(settings.game_speed),
+            undo_until: None,
+            unannounced: None,
+            junction: None,
+            junction_marker: Marker::new(JUNCTION_SLOT, ObjectPalette::Effects),
+            previewed: None,
+            // A is still held from the setup screen.
+            prev_keys: KeyInput::A,
+        };
+
+        // Humans sharing the console can tell whose turn it is by the cursor's color.
+        state
+            .cursor_sprite
+            .set_player(state.is_hotseat().then_some(player_color));
+        state.cursor.set_confirm(settings.confirm);
+        state.cursor.set_size(state.state.grid().size());
+        state
+            .cursor
+            .set_multi_rotation(state.state.rules().multi_rotation);
+
+        // New players are pointed at the choke point nearest them, since whoever holds one can cut
+        // the board in two.
+        if !state.is_hotseat() && state.state.turn_number() == 0 && Records::load().is_empty() {
+            let choke_points = state.state.grid().choke_points(state.state.rules());
+            state.junction = nearest(choke_points.iter(), cursor);
+            if state.junction.is_some() {
+                text::draw_centered(TEXT_SCREENBLOCK28, 0, &[JUNCTION_HINT]);
+            }
+        }
+
+        // Draw the initial game state.
+        state.board.draw(&state.state);
+        state.draw_hud();
+
+        // Draw the cursor.
+        state.cursor_sprite.draw(&state.camera, cursor);
+
+        // Scroll.
+        state.camera.apply();
+
+        // Fade in. At least one frame is always waited, so the screen is drawn before play starts.
+        let frames = timing::scaled(timing::FADE, settings.game_speed).max(1);
+        for frame in (0..frames).rev() {
+            wait_for_vblank_checked();
+            unsafe {
+                BLDY.write_volatile(RangedU8::new_unchecked(
+                    (frame * timing::FADE / frames / 2) as u8,
+                ));
+            }
+        }
+
+        state
+    }
+
+    /// Whether SRAM holds a single player game that `resume()` can pick back up.
+    pub fn can_resume() -> bool {
+        Suspended::load().is_some() && game::Game::load().is_some()
+    }
+
+    /// Resume the single player game saved to SRAM, if there is one.
+    ///
+    /// The resumed game can't be exported, since the turns played before it was saved aren't kept.
+    pub fn resume(settings: Settings) -> Option<Self> {
+        let suspended = Suspended::load()?;
+        let game = game::Game::load()?;
+        let player_color = suspended.player_color;
+        let mut humans = [false; 4];
+        humans[u8::from(player_color) as usize] = true;
+        let cursor = game
+            .grid()
+            .positions_of_color(player_color)
+            .next()
+            .unwrap_or(Position { x: 0, y: 0 });
+
+        let mut state = Self::new(
+            cursor,
+            game,
+            player_color,
+            humans,
+            settings,
+            suspended.code,
+            suspended.levels,
+        );
+        state.turns = suspended.turns;
+        state.recording.truncate();
+        // START is still held from the title screen.
+        state.prev_keys = KeyInput::START;
+        Some(state)
+    }
+
+    /// Save a single player game to SRAM, so that it can be resumed if the console is turned off.
+    ///
+    /// This is only done at the start of the player's turn, once nothing is left to undo.
+    fn suspend(&self) {
+        if self.is_hotseat() {
+            return;
+        }
+        self.state.save();
+        Suspended {
+            code: self.code,
+            player_color: self.player_color,
+            levels: self.levels,
+            turns: self.turns,
+        }
+        .save();
+    }
+
+    fn is_human(&self, color: Color) -> bool {
+        self.humans[u8::from(color) as usize]
+    }
+
+    /// Whether more than one human is playing, passing the console between them.
+    fn is_hotseat(&self) -> bool {
+        self.humans.iter().filter(|&&human| human).count() > 1
+    }
+
+    /// Lists the opponents from most to least nodes owned, along with their AI levels, and counts
+    /// down the turns left if the game has a turn limit.
+    ///
+    /// Other human players are marked with an `H`. Below them, every color in the game is listed
+    /// in turn order with the number of nodes it owns.
+    ///
+    /// This is called after every change to the game, so it also clears the turn preview, which
+    /// is drawn again on the player's next frame.
+    fn draw_hud(&mut self) {
+        self.previewed = None;
+        draw_preview(None);
+
+        match self.state.turns_remaining() {
+            Some(remaining) => self.countdown.set(remaining.min(u16::MAX as u32) as u16),
+            None => self.countdown.hide(),
+        }
+
+        let mut x = 1;
+        for standing in self.state.standings().iter().filter(|standing| {
+            standing.color != self.player_color && standing.color.plays(self.code.players)
+        }) {
+            text::draw(
+                TEXT_SCREENBLOCK28,
+                x,
+                18,
+                match standing.color {
+                    Color::Red => "R",
+                    Color::Blue => "B",
+                    Color::Yellow => "Y",
+                    Color::Green => "G",
+                },
+            );
+            if standing.alive {
+                text::draw(
+                    TEXT_SCREENBLOCK28,
+                    x + 1,
+                    18,
+                    if self.is_human(standing.color) {
+                        "H"
+                    } else {
+                        self.levels[u8::from(standing.color) as usize].icon()
+                    },
+                );
+            } else {
+                text::draw(TEXT_SCREENBLOCK28, x + 1, 18, "-");
+            }
+            x += 3;
+        }
+
+        text::clear(TEXT_SCREENBLOCK28, 1, 19, 20);
+        let mut x = 1;
+        for (color, count) in self
+            .state
+            .counts()
+            .into_iter()
+            .filter(|(color, _)| color.plays(self.code.players))
+        {
+            let mut buffer = [0; 5];
+            let digits = text::format_number(count, &mut buffer);
+            text::draw(TEXT_SCREENBLOCK28, x, 19, &color.name()[..1]);
+            text::draw(TEXT_SCREENBLOCK28, x + 1, 19, digits);
+            x += digits.len() + 2;
+        }
+    }
+
+    fn draw_junction(&self) {
+        self.junction_marker
+            .draw(&self.camera, self.junction, self.clock.now());
+    }
+
+    /// Point out the node the last turn rotated, in the color of whoever rotated it, so the player
+    /// can find what the AI just did.
+    fn draw_last_move(&self) {
+        let position = self.state.last_move().map(|(position, _)| position);
+        let palette = position
+            .and_then(|position| self.state.grid()[position].color())
+            .map_or(ObjectPalette::Effects, ObjectPalette::Player);
+        Marker::new(LAST_MOVE_SLOT, palette).draw(&self.camera, position, self.clock.now());
+    }
+
+    /// Stop pointing out the choke point, once the player has taken their first turn.
+    fn dismiss_junction(&mut self) {
+        if self.junction.take().is_some() {
+            text::clear(TEXT_SCREENBLOCK28, 0, 0, 30);
+            self.draw_junction();
+        }
+    }
+
+    /// Whether the AI is still holding off so the player can undo their turn.
+    fn undo_window_open(&self) -> bool {
+        self.state.can_undo()
+            && self
+                .undo_until
+                .is_some_and(|until| !cooldown_finished(until, self.clock.now()))
+    }
+
+    /// Take back the player's last turn, making it their turn again.
+    fn undo_turn(&mut self) {
+        if self.state.undo() {
+            self.recording.remove_last();
+            self.turns = self.turns.saturating_sub(1);
+            self.board.mark_dirty();
+        }
+        self.undo_until = None;
+    }
+
+    /// Finish a game that ended before one color was left, either by reaching its turn limit or
+    /// because nobody could capture anything more.
+    ///
+    /// Both are won by `leader`, the color owning the most nodes, or drawn if the lead is shared.
+    fn finish_with_leader(&self, leader: Option<Color>) -> Screen {
+        let result = match leader {
+            Some(leader) if self.is_human(leader) => PlayerResult::Win,
+            Some(_) => PlayerResult::Lose,
+            None => PlayerResult::Draw,
+        };
+        self.finish(result, leader)
+    }
+
+    /// Shows a banner for each color eliminated by the last turn, once it has been presented.
+    ///
+    /// Returns what the turn did, or `None` if it was already announced.
+    fn announce_eliminations(&mut self) -> Option<TurnOutcome> {
+        let outcome = self.unannounced.take()?;
+        for color in outcome.eliminated() {
+            wait_for_vblank_checked();
+            draw_elimination(color);
+            wait_frames(
+                timing::scaled(timing::ELIMINATION_BANNER, self.settings.game_speed) as usize,
+            );
+            clear_banner();
+        }
+        Some(outcome)
+    }
+
+    /// Shows a banner explaining that the current player has no legal moves, then passes their
+    /// turn.
+    fn pass_turn(&mut self) {
+        wait_for_vblank_checked();
+        draw_banner(&["NO MOVES - PASSING"]);
+        wait_frames(timing::scaled(timing::PASS_BANNER, self.settings.game_speed) as usize);
+        clear_banner();
+
+        self.state.pass_turn();
+        self.previewed = None;
+        draw_preview(None);
+        if self.state.turn_color() == self.player_color {
+            self.camera.start_player_turn();
+        }
+    }
+
+    /// Record the result of the game and move on to the game over screen.
+    ///
+    /// `winner` is the color left once every other color was eliminated, or the color leading when
+    /// the game ended early, if there is one. It is only announced when humans share the
+    /// console, since a single player knows whether they won. Hotseat games aren't recorded or
+    /// rated, since those assume a single player against the AI.
+    fn finish(&self, result: PlayerResult, winner: Option<Color>) -> Screen {
+        game::Game::delete_save();
+        export::log(&self.recording, self.state.state_hash());
+
+        let standings = self.state.standings();
+        if self.is_hotseat() {
+            let rating = Rating::load();
+            return Screen::GameOver(super::GameOver::new(Summary {
+                result,
+                winner,
+                code: self.code,
+                standings,
+                rankings: self.state.rankings(),
+                previous: rating,
+                rating,
+                turns: self.state.turn_number(),
+            }));
+        }
+
+        let outcome = match result {
+            PlayerResult::Win => Outcome::Won,
+            PlayerResult::Lose => Outcome::Lost,
+            PlayerResult::Draw => Outcome::Drawn,
+        };
+        let mut records = Records::load();
+        records.update(Record {
+            seed: self.code.seed,
+            turns: self.turns,
+            outcome,
+        });
+        records.save();
+
+        let mut opponents = [Rating::INITIAL; 3];
+        let mut len = 0;
+        for color in Color::ALL
+            .into_iter()
+            .filter(|&color| color != self.player_color && color.plays(self.code.players))
+        {
+            opponents[len] = self.levels[u8::from(color) as usize].rating();
+            len += 1;
+        }
+        let previous = Rating::load();
+        let rating = previous.update(
+            &opponents[..len],
+            rating::score(&standings, self.player_color, outcome),
+        );
+        rating.save();
+
+        Screen::GameOver(super::GameOver::new(Summary {
+            result,
+            winner: None,
+            code: self.code,
+            standings,
+            rankings: self.state.rankings(),
+            previous,
+            rating,
+            turns: self.state.turn_number(),
+        }))
+    }
+
+    /// Moves the cursor to the nearest node that can make a capture if the player lost the node
+    /// the cursor was on.
+    fn snap_cursor(&mut self) {
+        let position = self.cursor.position();
+        if self
+            .state
+            .grid()
+            .get(position)
+            .is_some_and(|node| node.is_color(self.player_color))
+        {
+            return;
+        }
+        if let Some(position) = nearest(self.state.frontier(self.player_color), position) {
+            self.cursor.set_position(position);
+        }
+    }
+
+    /// Wait for the console to be handed to the human whose turn it is, which they confirm by
+    /// pressing A.
+    fn hand_off(&mut self, a_pressed: bool) {
+        wait_for_vblank_checked();
+        if !self.handing_off {
+            self.handing_off = true;
+            self.undo_until = None;
+            draw_handoff(self.state.turn_color());
+            return;
+        }
+        if !a_pressed {
+            return;
+        }
+        self.handing_off = false;
+        clear_handoff();
+        self.player_color = self.state.turn_color();
+        self.cursor_sprite.set_player(Some(self.player_color));
+        // The press that dismissed the banner shouldn't also take a turn.
+        self.cursor.latch_a();
+        self.cursor.reset_idle();
+        self.snap_cursor();
+        self.camera.start_player_turn();
+        self.draw_hud();
+    }
+
+    pub fn run(&mut self) -> Option<Screen> {
+        let keys = unsafe { KEYINPUT.read_volatile() };
+
+        let undo_pressed = keys.just_pressed(self.prev_keys, KeyInput::B);
+        let a_pressed = keys.just_pressed(self.prev_keys, KeyInput::A);
+        // START pauses at any point, including in the middle of presenting a turn.
+        if keys.just_pressed(self.prev_keys, KeyInput::START) {
+            self.clock.set_paused(!self.clock.is_paused());
+            wait_for_vblank_checked();
+            if self.clock.is_paused() {
+                draw_pause();
+            } else {
+                clear_pause();
+            }
+        }
+        // SELECT logs the board and the turns that led to it, to help debug in an emulator.
+        if keys.just_pressed(self.prev_keys, KeyInput::SELECT) {
+            self.state.grid().log();
+            self.state.history().log();
+        }
+        self.prev_keys = keys;
+        if self.clock.is_paused() {
+            wait_for_vblank_checked();
+            return None;
+        }
+        self.clock.advance(1);
+
+        // An AI turn is presented in full before anything else happens.
+        if self.ai.is_busy() {
+            if self.ai.present(
+                &mut self.camera,
+                &self.cursor_sprite,
+                self.cursor.position(),
+                &mut self.board,
+                &self.state,
+                &self.clock,
+            ) {
+                if self.auto_playing {
+                    self.auto_playing = false;
+                    text::clear(TEXT_SCREENBLOCK28, 1, 1, 4);
+                }
+                self.announce_eliminations();
+                self.draw_hud();
+                self.snap_cursor();
+                self.camera.start_player_turn();
+                self.cursor.reset_idle();
+                if self.state.turn_color() == self.player_color && !self.state.is_over() {
+                    self.suspend();
+                }
+            }
+            // The camera scrolls to each AI turn, so the markers have to follow.
+            self.draw_junction();
+            self.draw_last_move();
+            return None;
+        }
+
+        // A single player loses as soon as they are eliminated. When humans share the console, the
+        // AI plays on once they are all out, so that the winner can be announced.
+        if !self.is_hotseat() && self.state.is_eliminated(self.player_color) {
+            return Some(self.finish(PlayerResult::Lose, None));
+        }
+        // The last turn may have been the AI's, including one played on a human's behalf.
+        if self.state.is_over() {
+            let winner = Color::ALL
+                .into_iter()
+                .find(|&color| !self.state.is_eliminated(color));
+            return Some(self.finish(
+                if winner.is_some_and(|winner| self.is_human(winner)) {
+                    PlayerResult::Win
+                } else {
+                    PlayerResult::Lose
+                },
+                winner,
+            ));
+        }
+        // A stalemate is when nobody can capture anything anymore, whether or not they can still
+        // turn their arrows.
+        if self.state.is_turn_limit_reached() || self.state.is_stalemate() {
+            return Some(self.finish_with_leader(self.state.leader()));
+        }
+        if !self.state.has_legal_move(self.state.turn_color()) {
+            self.pass_turn();
+            return None;
+        }
+
+        let turn_color = self.state.turn_color();
+        if self.is_human(turn_color) && turn_color != self.player_color {
+            self.hand_off(a_pressed);
+            return None;
+        }
+        if !self.is_human(turn_color) {
+            if !self.undo_window_open() {
+                self.undo_until = None;
+                let level = self.levels[u8::from(turn_color) as usize];
+                let (turn, outcome) = self.ai.take_turn(&mut self.state, level);
+                self.recording.record(turn);
+                self.unannounced = Some(outcome);
+                return None;
+            }
+            // B takes the turn back before the AI responds. No other input is taken, since it
+            // isn't the player's turn.
+            if undo_pressed {
+                self.undo_turn();
+            }
+        } else if let Some(turn) = self.cursor.update(keys) {
+            if let Ok(outcome) = self.state.execute_turn(turn) {
+                self.dismiss_junction();
+                self.recording.record(turn);
+                self.unannounced = Some(outcome);
+                self.turns = self.turns.saturating_add(1);
+                self.board.mark_dirty();
+                self.undo_until = Some(self.clock.now().wrapping_add(timing::UNDO_WINDOW as u32));
+                if let Some(ending) = outcome.ending() {
+                    wait_for_vblank_checked();
+
+                    self.board.draw(&self.state);
+
+                    return Some(match ending {
+                        Ending::Winner(winner) => self.finish(PlayerResult::Win, Some(winner)),
+                        Ending::Stalemate { leader } | Ending::TurnLimit { leader } => {
+                            self.finish_with_leader(leader)
+                        }
+                    });
+                }
+            } else {
+                // Nothing to present for a rejected turn.
+                self.cursor.turn_complete();
+            }
+        } else if self.cursor.is_idle(self.settings.auto_play_after) {
+            self.auto_playing = true;
+            self.dismiss_junction();
+            self.turns = self.turns.saturating_add(1);
+            wait_for_vblank_checked();
+            text::draw(TEXT_SCREENBLOCK28, 1, 1, "AUTO");
+            let (turn, outcome) = self.ai.take_turn(&mut self.state, AiLevel::Greedy);
+            self.recording.record(turn);
+            self.unannounced = Some(outcome);
+            return None;
+        }
+
+        wait_for_vblank_checked();
+
+        self.camera
+            .follow(self.cursor.position(), self.settings.game_speed);
+        self.cursor_sprite
+            .draw(&self.camera, self.cursor.position());
+        self.cursor_sprite.draw_progress(
+            &self.camera,
+            self.cursor.position(),
+            self.cursor.hold_progress(),
+        );
+        self.draw_junction();
+        self.draw_last_move();
+        if self.state.rules().multi_rotation {
+            let position = self.cursor.position();
+            let grid = self.state.grid();
+            // Only preview the direction of a node the player could actually turn.
+            let direction = if grid.is_valid_turn_position(position, self.state.turn_color()) {
+                let mut node = grid[position];
+                for _ in 0..self.cursor.rotations().get() {
+                    node.rotate();
+                }
+                node.direction()
+            } else {
+                None
+            };
+            draw_rotations(self.cursor.rotations(), direction);
+        }
+        // The AI's turn is held off while the player can undo, but that isn't the player's turn to
+        // preview.
+        if self.state.turn_color() == self.player_color {
+            let turn = Turn {
+                rotate: self.cursor.position(),
+                times: self.cursor.rotations(),
+            };
+            if self.previewed != Some(turn) {
+                self.previewed = Some(turn);
+                draw_preview(self.state.preview_turn(turn).ok());
+            }
+        }
+        self.board
+            .draw_sparks(&self.camera, &self.state, self.clock.now());
+        if self.board.draw_if_dirty(&self.state) {
+            if self.state.turn_events().is_truncated() {
+                draw_banner(&[MEGA_CAPTURE]);
+                wait_frames(
+                    timing::scaled(timing::MEGA_CAPTURE_BANNER, self.settings.game_speed) as usize,
+                );
+                clear_banner();
+            }
+            let outcome = self.announce_eliminations();
+            self.draw_hud();
+            // The preview is replaced by what the turn actually captured, until the AI responds.
+            if let Some(outcome) = outcome.filter(|_| self.undo_window_open()) {
+                draw_captured(Some(outcome.captured()));
+            }
+            self.cursor.turn_complete();
+        }
+
+        None
+    }
+}
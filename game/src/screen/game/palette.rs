@@ -0,0 +1,113 @@
+//! Assignments of the object palette banks.
+//!
+//! Every sprite on the game screen is drawn with one of these named banks, so a new kind of sprite
+//! can't pick up another's colors by reusing a bank number.
+
+use crate::{
+    game::Color,
+    include_bytes_aligned,
+    mmio::{
+        vram::{self, ObjectAttributes},
+        OBJ_PALETTE,
+    },
+};
+use core::mem::transmute;
+use deranged::RangedU8;
+
+/// An object palette bank, named by what is drawn with it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum ObjectPalette {
+    /// The neutral cursor.
+    Cursor,
+    /// A player's color, for coloring the cursor by whose turn it is.
+    ///
+    /// These are loaded into the same banks as the players' background palettes.
+    Player(Color),
+    /// Effects drawn over the board, such as sparks.
+    Effects,
+    /// Interface elements, such as big numbers.
+    Ui,
+}
+
+impl ObjectPalette {
+    pub(super) const fn bank(self) -> RangedU8<0, 15> {
+        match self {
+            Self::Cursor => RangedU8::new_static::<0>(),
+            Self::Player(color) => color.palette_bank(),
+            Self::Effects => RangedU8::new_static::<5>(),
+            Self::Ui => RangedU8::new_static::<6>(),
+        }
+    }
+
+    /// The attributes every sprite drawn with this palette starts from.
+    pub(super) const fn attributes(self) -> ObjectAttributes {
+        ObjectAttributes::new().with_palette(self.bank())
+    }
+}
+
+/// Load every assigned object palette bank.
+pub(super) fn load() {
+    let cursor = include_bytes_aligned!("../../../res/cursor.pal").0;
+    for (palette, colors) in [
+        (ObjectPalette::Cursor, cursor),
+        (
+            ObjectPalette::Player(Color::Red),
+            include_bytes_aligned!("../../../res/red.pal").0,
+        ),
+        (
+            ObjectPalette::Player(Color::Blue),
+            include_bytes_aligned!("../../../res/blue.pal").0,
+        ),
+        (
+            ObjectPalette::Player(Color::Yellow),
+            include_bytes_aligned!("../../../res/yellow.pal").0,
+        ),
+        (
+            ObjectPalette::Player(Color::Green),
+            include_bytes_aligned!("../../../res/green.pal").0,
+        ),
+        // The effects and interface art was drawn with the cursor's colors, so they start out as
+        // copies of it.
+        (ObjectPalette::Effects, cursor),
+        (ObjectPalette::Ui, cursor),
+    ] {
+        unsafe {
+            OBJ_PALETTE
+                .add(palette.bank().get() as usize)
+                .write_volatile(transmute::<[u8; 32], [vram::Color; 16]>(colors));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ObjectPalette;
+    use crate::game::Color;
+    use gba_test::test;
+
+    #[test]
+    fn banks_are_distinct() {
+        let palettes = [
+            ObjectPalette::Cursor,
+            ObjectPalette::Player(Color::Red),
+            ObjectPalette::Player(Color::Blue),
+            ObjectPalette::Player(Color::Yellow),
+            ObjectPalette::Player(Color::Green),
+            ObjectPalette::Effects,
+            ObjectPalette::Ui,
+        ];
+
+        for (index, palette) in palettes.iter().enumerate() {
+            for other in &palettes[index + 1..] {
+                assert_ne!(palette.bank(), other.bank());
+            }
+        }
+    }
+
+    #[test]
+    fn player_banks_match_background() {
+        for color in [Color::Red, Color::Blue, Color::Yellow, Color::Green] {
+            assert_eq!(ObjectPalette::Player(color).bank(), color.palette_bank());
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Playback of recorded games.
+
+use super::{
+    ai::AiDriver, board::BoardRenderer, camera::CameraController, clock::Clock,
+    cursor::CursorSprite, init_display,
+};
+use crate::{
+    bios::wait_for_vblank_checked,
+    game::{replay::Step, Playback, Position},
+    mmio::{keys::KeyInput, BLDY, KEYINPUT, TEXT_SCREENBLOCK28},
+    screen::{Screen, Title},
+    settings::GameSpeed,
+    text,
+};
+use deranged::RangedU8;
+
+/// Plays back a replay using the same presentation as live games.
+///
+/// A pauses and resumes, Right steps a single turn while paused, holding R plays at 4x speed, and
+/// B exits to the title screen.
+#[derive(Debug)]
+pub struct Replay {
+    playback: Playback,
+    cursor_sprite: CursorSprite,
+    camera: CameraController,
+    board: BoardRenderer,
+    driver: AiDriver,
+    /// The position rotated by the turn being presented.
+    cursor: Position,
+    clock: Clock,
+
+    paused: bool,
+    /// Whether a single turn should be played even though playback is paused.
+    step_once: bool,
+    prev_keys: KeyInput,
+}
+
+impl Replay {
+    pub fn new(replay: &'static crate::game::Replay) -> Self {
+        init_display();
+
+        let cursor = Position { x: 0, y: 0 };
+        let mut state = Self {
+            playback: Playback::new(replay),
+            cursor_sprite: CursorSprite::new(),
+            camera: CameraController::new(cursor),
+            board: BoardRenderer::new(),
+            driver: AiDriver::new(replay.code.seed),
+            cursor,
+            clock: Clock::new(GameSpeed::Normal),
+
+            paused: false,
+            step_once: false,
+            // The key used to open the replay may still be held.
+            prev_keys: unsafe { KEYINPUT.read_volatile() },
+        };
+
+        state.board.draw(state.playback.game());
+        state.cursor_sprite.draw(&state.camera, cursor);
+        state.camera.apply();
+        text::draw(TEXT_SCREENBLOCK28, 1, 1, "REPLAY");
+
+        // Fade in.
+        for fade in (0..31).rev() {
+            wait_for_vblank_checked();
+            unsafe {
+                BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+            }
+        }
+
+        state
+    }
+
+    fn pressed(&self, keys: KeyInput, key: KeyInput) -> bool {
+        keys.just_pressed(self.prev_keys, key)
+    }
+
+    pub fn run(&mut self) -> Option<Screen> {
+        let keys = unsafe { KEYINPUT.read_volatile() };
+
+        if self.pressed(keys, KeyInput::B) {
+            // Fade out.
+            wait_for_vblank_checked();
+            for fade in 0..31 {
+                wait_for_vblank_checked();
+                unsafe {
+                    BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+                }
+            }
+            return Some(Screen::Title(Title::new()));
+        }
+        if self.pressed(keys, KeyInput::A) {
+            self.paused = !self.paused;
+            if self.paused {
+                text::draw(TEXT_SCREENBLOCK28, 1, 2, "PAUSED");
+            } else {
+                text::clear(TEXT_SCREENBLOCK28, 1, 2, 6);
+            }
+        }
+        if self.paused && self.pressed(keys, KeyInput::RIGHT) {
+            self.step_once = true;
+        }
+        self.prev_keys = keys;
+
+        if self.driver.is_busy() {
+            self.clock
+                .advance(if keys.contains(KeyInput::R) { 4 } else { 1 });
+            self.driver.present(
+                &mut self.camera,
+                &self.cursor_sprite,
+                self.cursor,
+                &mut self.board,
+                self.playback.game(),
+                &self.clock,
+            );
+            return None;
+        }
+
+        if (!self.paused || self.step_once) && !self.playback.is_finished() {
+            self.step_once = false;
+            match self.playback.step() {
+                Some(Step::Turn(position)) => {
+                    self.cursor = position;
+                    self.driver.present_turn(position);
+                }
+                Some(Step::Pass) | None => wait_for_vblank_checked(),
+            }
+            if self.playback.is_finished() && !self.playback.matches_recording() {
+                log::warn!("replay finished in a different state than it was recorded in");
+            }
+        } else {
+            wait_for_vblank_checked();
+        }
+
+        None
+    }
+}
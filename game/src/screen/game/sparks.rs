@@ -0,0 +1,363 @@
+//! Sparks flashed over the nodes captured by a turn.
+
+use super::{camera::CameraController, clock::elapsed, palette::ObjectPalette};
+use crate::{
+    game::TurnEvents,
+    mmio::{vram::ObjectAttributes, OBJ_ATTRS, OBJ_TILES},
+};
+use deranged::{RangedU16, RangedU8};
+
+/// The number of sparks that can be shown at once.
+const POOL: usize = 4;
+
+/// The OAM slot of the first spark. Slot 0 is the cursor.
+const FIRST_SLOT: usize = 1;
+
+/// The object tile of the first animation frame. Tiles 0 through 3 are the cursor.
+const FIRST_TILE: u16 = 4;
+
+/// The radius of the ring drawn in each animation frame, in pixels.
+const RADII: [u8; 4] = [2, 4, 5, 7];
+
+/// The number of frames each animation frame is shown for.
+const FRAME_LENGTH: u16 = 3;
+
+/// The number of frames a single spark lasts.
+const DURATION: u16 = RADII.len() as u16 * FRAME_LENGTH;
+
+/// The most waves of sparks shown for a single turn.
+///
+/// Captures beyond what these waves can show are skipped, oldest first, so the nodes converted
+/// last are always flashed.
+const MAX_WAVES: usize = 3;
+
+/// Whether the pixel at `(x, y)` of a 16x16 sprite lies on a ring of the given radius.
+fn on_ring(x: u8, y: u8, radius: u8) -> bool {
+    // Measured in half pixels from the center of the sprite, so the center lies between pixels.
+    let dx = (2 * x as i16 - 15).unsigned_abs();
+    let dy = (2 * y as i16 - 15).unsigned_abs();
+    let distance = dx * dx + dy * dy;
+    let inner = 2 * radius as u16 - 1;
+    let outer = 2 * radius as u16 + 1;
+    distance >= inner * inner && distance < outer * outer
+}
+
+/// Draw the ring animation into object tile memory.
+///
+/// The art is simple enough to be generated rather than stored as an asset.
+pub(super) fn load_tiles() {
+    for (frame, &radius) in RADII.iter().enumerate() {
+        // A 16x16 sprite is stored as its top left, top right, bottom left, then bottom right
+        // tiles.
+        for tile in 0..4 {
+            let mut rows = [0u32; 8];
+            for (row, pixels) in rows.iter_mut().enumerate() {
+                for column in 0..8 {
+                    let x = (tile % 2) * 8 + column;
+                    let y = (tile / 2) * 8 + row as u8;
+                    if on_ring(x, y, radius) {
+                        *pixels |= 1 << (column * 4);
+                    }
+                }
+            }
+            unsafe {
+                OBJ_TILES
+                    .add(FIRST_TILE as usize + frame * 4 + tile as usize)
+                    .write_volatile(rows);
+            }
+        }
+    }
+}
+
+/// What a single spark shows on a given frame.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Assignment {
+    /// The index of the capture in the turn's events.
+    capture: usize,
+    /// The animation frame, as an index into `RADII`.
+    frame: usize,
+}
+
+/// Determine which capture each spark shows `frame` frames after the turn, out of `captures`
+/// total captures.
+///
+/// Captures are shown in waves of `POOL`, one after another. Returns `None` once every wave has
+/// finished.
+fn schedule(frame: u16, captures: usize) -> Option<[Option<Assignment>; POOL]> {
+    let first = captures.saturating_sub(POOL * MAX_WAVES);
+    let wave = (frame / DURATION) as usize;
+    let start = first + wave * POOL;
+    if start >= captures {
+        return None;
+    }
+    let mut assignments = [None; POOL];
+    for (spark, assignment) in assignments.iter_mut().enumerate() {
+        if start + spark < captures {
+            *assignment = Some(Assignment {
+                capture: start + spark,
+                frame: ((frame % DURATION) / FRAME_LENGTH) as usize,
+            });
+        }
+    }
+    Some(assignments)
+}
+
+/// The attributes of a spark at screen `location` showing animation frame `frame`.
+fn attributes(location: (u16, u16), frame: usize) -> ObjectAttributes {
+    ObjectPalette::Effects
+        .attributes()
+        .with_x(location.0)
+        .with_y(location.1 as u8)
+        .with_tile(unsafe { RangedU16::new_unchecked(FIRST_TILE + frame as u16 * 4) })
+        .with_size(RangedU8::new_static::<1>())
+}
+
+/// When the sparks started showing.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Start {
+    /// The sparks aren't showing.
+    Hidden,
+    /// The sparks start on the next tick they're drawn on.
+    Pending,
+    /// The sparks started on the given tick.
+    At(u32),
+}
+
+/// Flashes a spark over each node captured by a turn.
+#[derive(Debug)]
+pub(super) struct Sparks {
+    start: Start,
+}
+
+impl Sparks {
+    pub(super) fn new() -> Self {
+        Self {
+            start: Start::Hidden,
+        }
+    }
+
+    /// Begin flashing the captures of the turn that was just played.
+    pub(super) fn start(&mut self) {
+        self.start = Start::Pending;
+    }
+
+    /// The animation frame to show at tick `now`, if the sparks are showing.
+    ///
+    /// The first tick this is called on after `start()` is frame 0.
+    fn frame(&mut self, now: u32) -> Option<u16> {
+        let start = match self.start {
+            Start::Hidden => return None,
+            Start::Pending => {
+                self.start = Start::At(now);
+                now
+            }
+            Start::At(start) => start,
+        };
+        Some(elapsed(start, now).min(u16::MAX as u32) as u16)
+    }
+
+    /// Stop flashing, hiding every spark.
+    pub(super) fn hide(&mut self) {
+        self.start = Start::Hidden;
+        unsafe {
+            OBJ_ATTRS
+                .add(FIRST_SLOT)
+                .cast::<[ObjectAttributes; POOL]>()
+                .write_volatile([ObjectAttributes::new().with_disabled(true); POOL]);
+        }
+    }
+
+    /// Draw the sparks over the captures in `events` as they are at tick `now`.
+    pub(super) fn draw(&mut self, camera: &CameraController, events: &TurnEvents, now: u32) {
+        let Some(frame) = self.frame(now) else {
+            return;
+        };
+        let Some(assignments) = schedule(frame, events.len()) else {
+            self.hide();
+            return;
+        };
+        let captures = events.iter().as_slice();
+        for (slot, assignment) in (FIRST_SLOT..).zip(assignments) {
+            let attributes = assignment
+                .and_then(|assignment| {
+                    camera
+                        .relative_sprite_location(captures[assignment.capture].position)
+                        .map(|location| (assignment, location))
+                })
+                .map_or(
+                    ObjectAttributes::new().with_disabled(true),
+                    |(assignment, location)| attributes(location, assignment.frame),
+                );
+            unsafe {
+                OBJ_ATTRS.add(slot).write_volatile(attributes);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        attributes, on_ring, schedule, Assignment, Sparks, DURATION, FRAME_LENGTH, MAX_WAVES, POOL,
+    };
+    use crate::{
+        screen::game::{clock::Clock, palette::ObjectPalette},
+        settings::GameSpeed,
+    };
+    use gba_test::test;
+
+    /// The frames shown on each of `frames` frames, with the clock paused for `gap` frames
+    /// starting at frame `pause_at`.
+    fn frames_with_pause(frames: usize, pause_at: usize, gap: usize) -> [Option<u16>; 64] {
+        let mut clock = Clock::new(GameSpeed::Normal);
+        let mut sparks = Sparks::new();
+        let mut shown = [None; 64];
+        // Some time passes before the sparks start.
+        clock.advance(5);
+        sparks.start();
+        for (index, shown) in shown.iter_mut().enumerate().take(frames) {
+            clock.set_paused(index >= pause_at && index < pause_at + gap);
+            clock.advance(1);
+            *shown = sparks.frame(clock.now());
+        }
+        shown
+    }
+
+    #[test]
+    fn attributes_use_effects_palette() {
+        let attributes = attributes((10, 20), 2);
+
+        assert_eq!(
+            attributes,
+            attributes.with_palette(ObjectPalette::Effects.bank())
+        );
+    }
+
+    #[test]
+    fn on_ring_center_empty() {
+        assert!(!on_ring(7, 7, 4));
+        assert!(!on_ring(8, 8, 4));
+    }
+
+    #[test]
+    fn on_ring_edge() {
+        // Just above the center, four and a half pixels to the left.
+        assert!(on_ring(3, 7, 5));
+        assert!(!on_ring(3, 7, 2));
+    }
+
+    #[test]
+    fn schedule_no_captures() {
+        assert_eq!(schedule(0, 0), None);
+    }
+
+    #[test]
+    fn schedule_fewer_captures_than_sparks() {
+        assert_eq!(
+            schedule(0, 2),
+            Some([
+                Some(Assignment {
+                    capture: 0,
+                    frame: 0
+                }),
+                Some(Assignment {
+                    capture: 1,
+                    frame: 0
+                }),
+                None,
+                None,
+            ])
+        );
+        assert_eq!(schedule(DURATION, 2), None);
+    }
+
+    #[test]
+    fn schedule_animation_frames() {
+        assert_eq!(schedule(FRAME_LENGTH - 1, 1).unwrap()[0].unwrap().frame, 0);
+        assert_eq!(schedule(FRAME_LENGTH, 1).unwrap()[0].unwrap().frame, 1);
+        assert_eq!(schedule(DURATION - 1, 1).unwrap()[0].unwrap().frame, 3);
+    }
+
+    #[test]
+    fn schedule_waves() {
+        let captures = POOL + 1;
+
+        assert_eq!(
+            schedule(0, captures).unwrap()[POOL - 1].unwrap().capture,
+            POOL - 1
+        );
+        assert_eq!(
+            schedule(DURATION, captures),
+            Some([
+                Some(Assignment {
+                    capture: POOL,
+                    frame: 0
+                }),
+                None,
+                None,
+                None,
+            ])
+        );
+        assert_eq!(schedule(2 * DURATION, captures), None);
+    }
+
+    #[test]
+    fn schedule_skips_oldest_captures() {
+        let captures = POOL * MAX_WAVES + 5;
+
+        // The first wave starts after the captures that don't fit.
+        assert_eq!(schedule(0, captures).unwrap()[0].unwrap().capture, 5);
+        // The last wave ends with the last capture.
+        assert_eq!(
+            schedule(MAX_WAVES as u16 * DURATION - 1, captures).unwrap()[POOL - 1]
+                .unwrap()
+                .capture,
+            captures - 1
+        );
+        assert_eq!(schedule(MAX_WAVES as u16 * DURATION, captures), None);
+    }
+
+    #[test]
+    fn schedule_shows_each_capture_once() {
+        for captures in 0..40 {
+            let mut shown = [0; 40];
+            let mut frame = 0;
+            while let Some(assignments) = schedule(frame, captures) {
+                if frame % DURATION == 0 {
+                    for assignment in assignments.into_iter().flatten() {
+                        shown[assignment.capture] += 1;
+                    }
+                }
+                frame += 1;
+            }
+
+            let first = captures.saturating_sub(POOL * MAX_WAVES);
+            assert!(shown[..first].iter().all(|&count| count == 0));
+            assert!(shown[first..captures].iter().all(|&count| count == 1));
+        }
+    }
+
+    #[test]
+    fn frame_starts_at_zero() {
+        let mut sparks = Sparks::new();
+
+        assert_eq!(sparks.frame(100), None);
+        sparks.start();
+        assert_eq!(sparks.frame(100), Some(0));
+        assert_eq!(sparks.frame(103), Some(3));
+    }
+
+    #[test]
+    fn frame_pause_shifts_without_drift() {
+        let gap = 7;
+        let uninterrupted = frames_with_pause(40, 40, 0);
+        let paused = frames_with_pause(40 + gap, 10, gap);
+
+        assert_eq!(paused[..10], uninterrupted[..10]);
+        // The frame shown when the pause began is held for the whole pause.
+        assert!(paused[10..10 + gap]
+            .iter()
+            .all(|&frame| frame == uninterrupted[9]));
+        assert_eq!(paused[10 + gap..40 + gap], uninterrupted[10..40]);
+    }
+}
@@ -0,0 +1,142 @@
+//! What a single player game needs besides its `game::Game` to be resumed, kept in SRAM alongside
+//! the saved game.
+
+use super::ai::AiLevel;
+use crate::{
+    game::{board_code, BoardCode, Color},
+    mmio::{sram_read, sram_write},
+    records::checksum,
+};
+
+/// Identifies a valid suspended game, and its layout version.
+const MAGIC: [u8; 4] = *b"TORS";
+
+/// Where the suspended game is stored in SRAM, after the saved game.
+const OFFSET: usize = 0x400;
+
+/// The number of bytes used by a suspended game: the magic, the board code, the player's color, the
+/// AI levels, the number of turns the player has taken, and a checksum.
+const LEN: usize = MAGIC.len() + board_code::LEN + 1 + 4 + 2 + 2;
+
+/// Every AI level, in the order they are stored as.
+const LEVELS: [AiLevel; 4] = [
+    AiLevel::Random,
+    AiLevel::Greedy,
+    AiLevel::Minimax,
+    AiLevel::Lookahead,
+];
+
+/// A single player game, minus the game state itself, which is saved by `game::Game::save()`.
+#[derive(Debug, Eq, PartialEq)]
+pub(super) struct Suspended {
+    pub(super) code: BoardCode,
+    pub(super) player_color: Color,
+    pub(super) levels: [AiLevel; 4],
+    pub(super) turns: u16,
+}
+
+impl Suspended {
+    fn to_bytes(&self) -> [u8; LEN] {
+        let mut bytes = [0; LEN];
+        let (magic, rest) = bytes.split_at_mut(MAGIC.len());
+        magic.copy_from_slice(&MAGIC);
+        let (code, rest) = rest.split_at_mut(board_code::LEN);
+        code.copy_from_slice(&self.code.encode());
+        rest[0] = u8::from(self.player_color);
+        for (byte, level) in rest[1..5].iter_mut().zip(self.levels) {
+            // Every level is listed.
+            *byte = LEVELS.iter().position(|&other| other == level).unwrap() as u8;
+        }
+        rest[5..7].copy_from_slice(&self.turns.to_le_bytes());
+        let checksum = checksum(&bytes[..LEN - 2]);
+        bytes[LEN - 2..].copy_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Returns `None` if the bytes are not a valid suspended game.
+    fn from_bytes(bytes: &[u8; LEN]) -> Option<Self> {
+        if bytes[..MAGIC.len()] != MAGIC
+            || checksum(&bytes[..LEN - 2]).to_le_bytes() != bytes[LEN - 2..]
+        {
+            return None;
+        }
+        let (code, rest) = bytes[MAGIC.len()..].split_at(board_code::LEN);
+        let mut levels = [AiLevel::Random; 4];
+        for (level, &byte) in levels.iter_mut().zip(&rest[1..5]) {
+            *level = *LEVELS.get(byte as usize)?;
+        }
+        Some(Self {
+            code: BoardCode::decode(code).ok()?,
+            player_color: Color::try_from(rest[0]).ok()?,
+            levels,
+            turns: u16::from_le_bytes([rest[5], rest[6]]),
+        })
+    }
+
+    /// Save to SRAM, replacing any game suspended before.
+    pub(super) fn save(&self) {
+        for (index, byte) in self.to_bytes().into_iter().enumerate() {
+            sram_write(OFFSET + index, byte);
+        }
+    }
+
+    /// Load the game suspended to SRAM.
+    ///
+    /// Returns `None` if SRAM doesn't hold a valid suspended game.
+    pub(super) fn load() -> Option<Self> {
+        let mut bytes = [0; LEN];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = sram_read(OFFSET + index);
+        }
+        Self::from_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AiLevel, Suspended, LEN};
+    use crate::game::{BoardCode, Color};
+    use gba_test::test;
+
+    fn suspended() -> Suspended {
+        Suspended {
+            code: BoardCode::new(1234),
+            player_color: Color::Yellow,
+            levels: [
+                AiLevel::Lookahead,
+                AiLevel::Random,
+                AiLevel::Minimax,
+                AiLevel::Greedy,
+            ],
+            turns: 300,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let suspended = suspended();
+
+        assert_eq!(
+            Suspended::from_bytes(&suspended.to_bytes()),
+            Some(suspended)
+        );
+    }
+
+    #[test]
+    fn blank_sram() {
+        assert_eq!(Suspended::from_bytes(&[0; LEN]), None);
+        assert_eq!(Suspended::from_bytes(&[0xff; LEN]), None);
+    }
+
+    #[test]
+    fn corrupted() {
+        let bytes = suspended().to_bytes();
+
+        for index in 0..LEN {
+            let mut corrupted = bytes;
+            corrupted[index] ^= 0x10;
+
+            assert_eq!(Suspended::from_bytes(&corrupted), None, "byte {index}");
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Caching of the scores the lookahead AI has already evaluated.
+
+use crate::game::Color;
+
+/// The number of cached scores.
+const CAPACITY: usize = 128;
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    /// The `Game::state_hash()` of the game state that was scored.
+    key: u64,
+    /// The player the state was scored for.
+    color: Color,
+    score: usize,
+}
+
+/// A fixed-size cache of evaluated game states.
+///
+/// Each state has exactly one slot it can be stored in. When two states share a slot, the newer
+/// one replaces the older one, since this is only a cache.
+#[derive(Debug)]
+pub(super) struct TranspositionTable {
+    entries: [Option<Entry>; CAPACITY],
+    /// The number of lookups that found a cached score.
+    #[cfg(test)]
+    hits: usize,
+}
+
+impl TranspositionTable {
+    pub(super) fn new() -> Self {
+        Self {
+            entries: [None; CAPACITY],
+            #[cfg(test)]
+            hits: 0,
+        }
+    }
+
+    /// The cached score for `color` of the state whose `Game::state_hash()` is `key`, if there is
+    /// one.
+    pub(super) fn get(&mut self, key: u64, color: Color) -> Option<usize> {
+        let score = self.entries[key as usize % CAPACITY]
+            .filter(|entry| entry.key == key && entry.color == color)
+            .map(|entry| entry.score);
+        #[cfg(test)]
+        if score.is_some() {
+            self.hits += 1;
+        }
+        score
+    }
+
+    /// Cache the score for `color` of the state whose `Game::state_hash()` is `key`.
+    pub(super) fn insert(&mut self, key: u64, color: Color, score: usize) {
+        self.entries[key as usize % CAPACITY] = Some(Entry { key, color, score });
+    }
+
+    /// The number of lookups that found a cached score.
+    #[cfg(test)]
+    pub(super) fn hits(&self) -> usize {
+        self.hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TranspositionTable;
+    use crate::game::{Color, Game, Grid, Position, Turn};
+    use gba_test::test;
+
+    #[test]
+    fn get_empty() {
+        let game = Game::builder().grid(Grid::generate(0)).build();
+
+        assert_eq!(
+            TranspositionTable::new().get(game.state_hash(), Color::Red),
+            None
+        );
+    }
+
+    #[test]
+    fn get_after_insert() {
+        let game = Game::builder().grid(Grid::generate(0)).build();
+        let mut table = TranspositionTable::new();
+
+        table.insert(game.state_hash(), Color::Red, 7);
+
+        assert_eq!(table.get(game.state_hash(), Color::Red), Some(7));
+        assert_eq!(table.hits(), 1);
+    }
+
+    #[test]
+    fn get_other_color() {
+        let game = Game::builder().grid(Grid::generate(0)).build();
+        let mut table = TranspositionTable::new();
+
+        table.insert(game.state_hash(), Color::Red, 7);
+
+        assert_eq!(table.get(game.state_hash(), Color::Blue), None);
+    }
+
+    #[test]
+    fn get_other_state() {
+        let game = Game::builder().grid(Grid::generate(0)).build();
+        let mut other = game.clone();
+        other
+            .execute_turn(Turn {
+                rotate: Position { x: 0, y: 0 },
+                times: Turn::ONCE,
+            })
+            .unwrap();
+        let mut table = TranspositionTable::new();
+
+        table.insert(game.state_hash(), Color::Red, 7);
+
+        assert_eq!(table.get(other.state_hash(), Color::Red), None);
+    }
+}
@@ -1,6 +1,7 @@
 use super::{Screen, Title};
 use crate::{
-    bios::wait_for_vblank,
+    bios::wait_for_vblank_checked,
+    game::{board_code, BoardCode, Color, Standings},
     include_bytes_aligned,
     mmio::{
         keys::KeyInput,
@@ -8,19 +9,51 @@ use crate::{
         BG0CNT, BG1CNT, BG1HOFS, BG1VOFS, BG2CNT, BG2HOFS, BG2VOFS, BG3CNT, BG_PALETTE, BLDY,
         CHARBLOCK0, DISPCNT, KEYINPUT, TEXT_SCREENBLOCK28,
     },
+    rating::Rating,
+    text,
 };
-use core::mem::transmute;
+use core::{mem::transmute, str};
 use deranged::{RangedU16, RangedU8};
 
 pub enum PlayerResult {
     Win,
     Lose,
+    /// The game ended early with the lead shared.
+    Draw,
+}
+
+/// How a finished game went, as shown on the game over screen.
+pub struct Summary {
+    pub result: PlayerResult,
+    /// The color announced above the result, if any.
+    pub winner: Option<Color>,
+    pub code: BoardCode,
+    pub standings: Standings,
+    /// The order the final standings are listed in, as given by `Game::rankings()`.
+    pub rankings: [Option<Color>; 4],
+    /// The player's rating before the game.
+    pub previous: Rating,
+    /// The player's rating after the game.
+    pub rating: Rating,
+    /// The number of turns the game went on for.
+    pub turns: u32,
 }
 
 pub struct GameOver;
 
 impl GameOver {
-    pub fn new(result: PlayerResult) -> Self {
+    pub fn new(summary: Summary) -> Self {
+        let Summary {
+            result,
+            winner,
+            code,
+            standings,
+            rankings,
+            previous,
+            rating,
+            turns,
+        } = summary;
+
         unsafe {
             // Set up background layers.
             BG0CNT.write_volatile(
@@ -111,6 +144,71 @@ impl GameOver {
                     }
                 }
             }
+            // There is no art for a draw, so it is spelled out instead.
+            PlayerResult::Draw => text::draw(TEXT_SCREENBLOCK28, 13, 9, "DRAW"),
+        }
+
+        text::clear(TEXT_SCREENBLOCK28, 0, 5, 30);
+        if let Some(winner) = winner {
+            text::draw_centered(TEXT_SCREENBLOCK28, 5, &[winner.name(), " WINS"]);
+        }
+
+        // Display the board's code so it can be shared.
+        let code = code.encode();
+        let x = (30 - 5 - board_code::LEN) / 2;
+        text::draw(TEXT_SCREENBLOCK28, x, 13, "CODE ");
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            x + 5,
+            13,
+            // The code is always ASCII.
+            str::from_utf8(&code).unwrap(),
+        );
+
+        // Display how long the game went on for.
+        let mut buffer = [0; 5];
+        let digits = text::format_number(u16::try_from(turns).unwrap_or(u16::MAX), &mut buffer);
+        let x = (30 - 6 - digits.len()) / 2;
+        text::clear(TEXT_SCREENBLOCK28, 0, 7, 30);
+        text::draw(TEXT_SCREENBLOCK28, x, 7, "TURNS ");
+        text::draw(TEXT_SCREENBLOCK28, x + 6, 7, digits);
+
+        // Display the rating and how much it changed.
+        text::clear(TEXT_SCREENBLOCK28, 0, 11, 30);
+        text::draw(TEXT_SCREENBLOCK28, 8, 11, "RATING");
+        let mut buffer = [0; 5];
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            15,
+            11,
+            text::format_number(rating.get(), &mut buffer),
+        );
+        let (sign, change) = if rating >= previous {
+            ("+", rating.get() - previous.get())
+        } else {
+            ("-", previous.get() - rating.get())
+        };
+        text::draw(TEXT_SCREENBLOCK28, 20, 11, sign);
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            21,
+            11,
+            text::format_number(change, &mut buffer),
+        );
+
+        // Display the final standings.
+        for (index, color) in rankings.into_iter().flatten().enumerate() {
+            let count = standings
+                .iter()
+                .find(|standing| standing.color == color)
+                .map_or(0, |standing| standing.count);
+            let y = 15 + index;
+            text::clear(TEXT_SCREENBLOCK28, 0, y, 30);
+            text::draw(TEXT_SCREENBLOCK28, 9, y, ["1", "2", "3", "4"][index]);
+            text::draw(TEXT_SCREENBLOCK28, 11, y, color.name());
+            let mut buffer = [0; 5];
+            let digits = text::format_number(count, &mut buffer);
+            text::draw(TEXT_SCREENBLOCK28, 21 - digits.len(), y, digits);
         }
 
         Self
@@ -120,9 +218,9 @@ impl GameOver {
         let keys = unsafe { KEYINPUT.read_volatile() };
         if keys.contains(KeyInput::A) {
             // Fade out.
-            wait_for_vblank();
+            wait_for_vblank_checked();
             for fade in 0..31 {
-                wait_for_vblank();
+                wait_for_vblank_checked();
                 unsafe {
                     BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
                 }
@@ -1,28 +1,72 @@
+mod code_entry;
+mod color_select;
+mod credits;
+mod debug;
 mod game;
 mod game_over;
+mod setup;
 mod splash;
 mod title;
 
-pub use game::Game;
+pub use code_entry::CodeEntry;
+pub use color_select::ColorSelect;
+pub use credits::Credits;
+pub use debug::Debug;
+pub use game::{Game, Replay};
 pub use game_over::GameOver;
+pub use setup::Setup;
 pub use splash::Splash;
 pub use title::Title;
 
+#[cfg(debug_assertions)]
+use crate::watchdog;
+
 pub enum Screen {
     Splash(Splash),
     Title(Title),
+    CodeEntry(CodeEntry),
+    ColorSelect(ColorSelect),
+    Setup(Setup),
     Game(Game),
+    Replay(Replay),
     GameOver(GameOver),
+    Credits(Credits),
+    Debug(Debug),
 }
 
 impl Screen {
+    /// The name of this screen, for logging.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Splash(_) => "splash",
+            Self::Title(_) => "title",
+            Self::CodeEntry(_) => "code entry",
+            Self::ColorSelect(_) => "color select",
+            Self::Setup(_) => "setup",
+            Self::Game(_) => "game",
+            Self::Replay(_) => "replay",
+            Self::GameOver(_) => "game over",
+            Self::Credits(_) => "credits",
+            Self::Debug(_) => "debug",
+        }
+    }
+
     // To be run continually in a loop.
     pub fn run(&mut self) {
+        #[cfg(debug_assertions)]
+        watchdog::begin(self.name());
+
         if let Some(new_screen) = match self {
             Self::Splash(splash) => splash.run(),
             Self::Title(title) => title.run(),
+            Self::CodeEntry(code_entry) => code_entry.run(),
+            Self::ColorSelect(color_select) => color_select.run(),
+            Self::Setup(setup) => setup.run(),
             Self::Game(game) => game.run(),
+            Self::Replay(replay) => replay.run(),
             Self::GameOver(game_over) => game_over.run(),
+            Self::Credits(credits) => credits.run(),
+            Self::Debug(debug) => debug.run(),
         } {
             *self = new_screen;
         }
@@ -0,0 +1,527 @@
+use super::{game::AiLevel, Game, Screen};
+use crate::{
+    bios::wait_for_vblank_checked,
+    game::{self, BoardCode, Color, Position},
+    levels,
+    mmio::{
+        keys::KeyInput,
+        vram::{BackgroundControl, DisplayControl},
+        BG3CNT, BLDY, DISPCNT, KEYINPUT, TEXT_SCREENBLOCK28,
+    },
+    rating::Rating,
+    records::{Outcome, Records},
+    settings::{Difficulty, Settings},
+    text, thumbnail,
+};
+use core::num::NonZeroU16;
+use deranged::RangedU8;
+
+/// The column the level names are drawn at.
+const LEVEL_X: usize = 14;
+
+/// The column the board's thumbnail is drawn at, right of the options.
+const THUMBNAIL_X: usize = 30 - thumbnail::SIZE - 1;
+
+/// The index of the row choosing the number of players, which follows the three opponents.
+const PLAYERS: usize = 3;
+
+/// The index of the row choosing between a generated board and each handcrafted level.
+const BOARD: usize = PLAYERS + 1;
+
+/// The index of the row toggling the wraparound rule.
+const WRAPAROUND: usize = BOARD + 1;
+
+/// The index of the row toggling whether the board must have a choke point in every quadrant.
+const CHOKE_POINTS: usize = WRAPAROUND + 1;
+
+/// The index of the row choosing the turn limit.
+const TURNS: usize = CHOKE_POINTS + 1;
+
+/// The index of the row choosing how turns are confirmed.
+const CONFIRM: usize = TURNS + 1;
+
+/// The index of the row choosing the game speed, which is the last row.
+const SPEED: usize = CONFIRM + 1;
+
+/// The turn limits that can be chosen, in the order they are cycled through.
+const TURN_LIMITS: [Option<NonZeroU16>; 4] = [
+    None,
+    NonZeroU16::new(50),
+    NonZeroU16::new(100),
+    NonZeroU16::new(200),
+];
+
+/// Pre-game setup, where the level of each AI opponent and the rules are chosen.
+pub struct Setup {
+    settings: Settings,
+    code: BoardCode,
+    /// The color the player chose to play as.
+    player: Color,
+    /// The colors the player plays against, each either an AI or another human sharing the
+    /// console, in turn order.
+    opponents: [Color; 3],
+    /// The level of each color, indexed by the color's `u8` value.
+    levels: [AiLevel; 4],
+    /// Whether each color is played by a human, indexed by the color's `u8` value.
+    ///
+    /// The player's color is always played by a human.
+    humans: [bool; 4],
+    /// The index into `TURN_LIMITS` of the chosen turn limit.
+    turn_limit: usize,
+    /// The index into `opponents` of the opponent being changed, `PLAYERS`, `BOARD`,
+    /// `WRAPAROUND`, `CHOKE_POINTS`, `TURNS`, `CONFIRM`, or `SPEED`.
+    selected: usize,
+    prev_keys: KeyInput,
+}
+
+impl Setup {
+    pub fn new(settings: Settings, code: BoardCode, player: Color) -> Self {
+        wait_for_vblank_checked();
+
+        unsafe {
+            BG3CNT.write_volatile(
+                BackgroundControl::new()
+                    .with_screenblock(RangedU8::new_static::<28>())
+                    .with_priority(RangedU8::new_static::<0>()),
+            );
+            DISPCNT.write_volatile(DisplayControl::new().with_bg3(true));
+        }
+        text::load();
+        for y in 0..32 {
+            text::clear(TEXT_SCREENBLOCK28, 0, y, 32);
+        }
+        text::draw(TEXT_SCREENBLOCK28, 10, 2, "OPPONENTS");
+        text::draw(TEXT_SCREENBLOCK28, 4, 15, "LEFT/RIGHT: CHANGE");
+        text::draw(TEXT_SCREENBLOCK28, 4, 16, "A: START");
+
+        let mut opponents = [player.next(); 3];
+        for index in 1..opponents.len() {
+            opponents[index] = opponents[index - 1].next();
+        }
+        let mut levels = [AiLevel::from(settings.difficulty); 4];
+        if settings.difficulty == Difficulty::Normal {
+            // One of each level, for variety, in the order the opponents play.
+            for (opponent, level) in
+                opponents
+                    .into_iter()
+                    .zip([AiLevel::Random, AiLevel::Greedy, AiLevel::Lookahead])
+            {
+                levels[u8::from(opponent) as usize] = level;
+            }
+        }
+        let mut humans = [false; 4];
+        humans[u8::from(player) as usize] = true;
+        let setup = Self {
+            settings,
+            code,
+            player,
+            opponents,
+            levels,
+            humans,
+            turn_limit: 0,
+            selected: 0,
+            // A is still held from the color select screen.
+            prev_keys: KeyInput::A,
+        };
+        for (index, color) in opponents.into_iter().enumerate() {
+            text::draw(TEXT_SCREENBLOCK28, 5, Self::row(index), color.name());
+            setup.draw_level(index);
+        }
+        text::draw(TEXT_SCREENBLOCK28, 5, Self::row(PLAYERS), "PLAYERS");
+        setup.draw_players();
+        text::draw(TEXT_SCREENBLOCK28, 5, Self::row(BOARD), "BOARD");
+        setup.draw_board();
+        text::draw(TEXT_SCREENBLOCK28, 5, Self::row(WRAPAROUND), "WRAP");
+        setup.draw_wraparound();
+        text::draw(TEXT_SCREENBLOCK28, 5, Self::row(CHOKE_POINTS), "JUNCTION");
+        setup.draw_choke_points();
+        text::draw(TEXT_SCREENBLOCK28, 5, Self::row(TURNS), "TURNS");
+        setup.draw_turn_limit();
+        text::draw(TEXT_SCREENBLOCK28, 5, Self::row(CONFIRM), "CONFIRM");
+        setup.draw_confirm();
+        text::draw(TEXT_SCREENBLOCK28, 5, Self::row(SPEED), "SPEED");
+        setup.draw_speed();
+        setup.draw_selection();
+
+        // Show the board, so the player can tell whether they've played it before.
+        thumbnail::load_palette();
+        setup.draw_thumbnail();
+
+        // Show the player's record on this board, if they've played it recently.
+        if let Some(record) = Records::load().lookup(code.seed) {
+            let mut buffer = [0; 5];
+            let turns = text::format_number(record.turns, &mut buffer);
+            let mut x = 4;
+            for part in [
+                match record.outcome {
+                    Outcome::Won => "BEST: WON IN ",
+                    Outcome::Lost => "BEST: LOST AFTER ",
+                    Outcome::Drawn => "BEST: DREW AFTER ",
+                },
+                turns,
+                " TURNS",
+            ] {
+                text::draw(TEXT_SCREENBLOCK28, x, 14, part);
+                x += part.len();
+            }
+        }
+
+        // Show the player's rating.
+        text::draw(TEXT_SCREENBLOCK28, 4, 18, "RATING");
+        let mut buffer = [0; 5];
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            11,
+            18,
+            text::format_number(Rating::load().get(), &mut buffer),
+        );
+
+        // Fade in.
+        for fade in (0..31).rev() {
+            wait_for_vblank_checked();
+            unsafe {
+                BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+            }
+        }
+
+        setup
+    }
+
+    fn row(index: usize) -> usize {
+        4 + index
+    }
+
+    /// Draw the level of the opponent at `index`, or a dash if they don't play.
+    fn draw_level(&self, index: usize) {
+        let opponent = self.opponents[index];
+        let color = u8::from(opponent) as usize;
+        text::clear(TEXT_SCREENBLOCK28, LEVEL_X, Self::row(index), 9);
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            LEVEL_X,
+            Self::row(index),
+            if !opponent.plays(self.code.players) {
+                "-"
+            } else if self.humans[color] {
+                "HUMAN"
+            } else {
+                self.levels[color].name()
+            },
+        );
+    }
+
+    fn draw_players(&self) {
+        let mut buffer = [0; 5];
+        text::clear(TEXT_SCREENBLOCK28, LEVEL_X, Self::row(PLAYERS), 1);
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            LEVEL_X,
+            Self::row(PLAYERS),
+            text::format_number(self.code.players.get() as u16, &mut buffer),
+        );
+    }
+
+    fn draw_thumbnail(&self) {
+        thumbnail::draw(
+            &self.code.grid(),
+            TEXT_SCREENBLOCK28,
+            THUMBNAIL_X,
+            Self::row(0),
+        );
+    }
+
+    /// Change the number of players to the next count, or the previous one if `forward` is false,
+    /// that the player's color plays in.
+    ///
+    /// Opponents left out of the game are no longer played by humans.
+    fn cycle_players(&mut self, forward: bool) {
+        let mut players = self.code.players.get();
+        loop {
+            players = if forward {
+                players % 4 + 1
+            } else {
+                (players + 2) % 4 + 1
+            };
+            if let Some(players) = RangedU8::new(players) {
+                if self.player.plays(players) {
+                    self.code.players = players;
+                    break;
+                }
+            }
+        }
+        for opponent in self.opponents {
+            if !opponent.plays(self.code.players) {
+                self.humans[u8::from(opponent) as usize] = false;
+            }
+        }
+
+        self.draw_players();
+        for index in 0..self.opponents.len() {
+            self.draw_level(index);
+        }
+        self.draw_thumbnail();
+    }
+
+    fn draw_board(&self) {
+        text::clear(TEXT_SCREENBLOCK28, LEVEL_X, Self::row(BOARD), 7);
+        match self.code.level {
+            Some(level) => {
+                let mut buffer = [0; 5];
+                text::draw(TEXT_SCREENBLOCK28, LEVEL_X, Self::row(BOARD), "LEVEL ");
+                text::draw(
+                    TEXT_SCREENBLOCK28,
+                    LEVEL_X + 6,
+                    Self::row(BOARD),
+                    text::format_number(level as u16 + 1, &mut buffer),
+                );
+            }
+            None => text::draw(TEXT_SCREENBLOCK28, LEVEL_X, Self::row(BOARD), "RANDOM"),
+        }
+    }
+
+    /// Change the board to the next level, or the previous one if `forward` is false, with the
+    /// generated board coming after the last level and before the first.
+    fn cycle_board(&mut self, forward: bool) {
+        self.code.level = match (self.code.level, forward) {
+            (None, true) => Some(0),
+            (None, false) => Some(levels::COUNT as u8 - 1),
+            (Some(level), true) => (level as usize + 1 < levels::COUNT).then_some(level + 1),
+            (Some(level), false) => level.checked_sub(1),
+        };
+
+        self.draw_board();
+        // Levels are played as they were made, so they never guarantee choke points.
+        self.draw_choke_points();
+        self.draw_thumbnail();
+    }
+
+    fn draw_wraparound(&self) {
+        text::clear(TEXT_SCREENBLOCK28, LEVEL_X, Self::row(WRAPAROUND), 3);
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            LEVEL_X,
+            Self::row(WRAPAROUND),
+            if self.code.rules.wraparound {
+                "ON"
+            } else {
+                "OFF"
+            },
+        );
+    }
+
+    fn draw_choke_points(&self) {
+        text::clear(TEXT_SCREENBLOCK28, LEVEL_X, Self::row(CHOKE_POINTS), 3);
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            LEVEL_X,
+            Self::row(CHOKE_POINTS),
+            if self.code.level.is_some() {
+                "-"
+            } else if self.code.choke_points {
+                "ON"
+            } else {
+                "OFF"
+            },
+        );
+    }
+
+    fn draw_turn_limit(&self) {
+        text::clear(TEXT_SCREENBLOCK28, LEVEL_X, Self::row(TURNS), 3);
+        let mut buffer = [0; 5];
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            LEVEL_X,
+            Self::row(TURNS),
+            match TURN_LIMITS[self.turn_limit] {
+                Some(max_turns) => text::format_number(max_turns.get(), &mut buffer),
+                None => "OFF",
+            },
+        );
+    }
+
+    fn draw_confirm(&self) {
+        text::clear(TEXT_SCREENBLOCK28, LEVEL_X, Self::row(CONFIRM), 4);
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            LEVEL_X,
+            Self::row(CONFIRM),
+            self.settings.confirm.name(),
+        );
+    }
+
+    fn draw_speed(&self) {
+        text::clear(TEXT_SCREENBLOCK28, LEVEL_X, Self::row(SPEED), 7);
+        text::draw(
+            TEXT_SCREENBLOCK28,
+            LEVEL_X,
+            Self::row(SPEED),
+            self.settings.game_speed.name(),
+        );
+    }
+
+    fn draw_selection(&self) {
+        for index in 0..=SPEED {
+            if index == self.selected {
+                text::draw(TEXT_SCREENBLOCK28, 3, Self::row(index), ">");
+            } else {
+                text::clear(TEXT_SCREENBLOCK28, 3, Self::row(index), 1);
+            }
+        }
+    }
+
+    fn pressed(&self, keys: KeyInput, key: KeyInput) -> bool {
+        keys.just_pressed(self.prev_keys, key)
+    }
+
+    pub fn run(&mut self) -> Option<Screen> {
+        let keys = unsafe { KEYINPUT.read_volatile() };
+
+        if self.pressed(keys, KeyInput::A) {
+            // Fade out.
+            wait_for_vblank_checked();
+            for fade in 0..31 {
+                wait_for_vblank_checked();
+                unsafe {
+                    BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+                }
+            }
+
+            let game = game::Game::from_code(self.code, self.player)
+                .max_turns(TURN_LIMITS[self.turn_limit])
+                .build();
+            // The player's only node is their starting corner.
+            let cursor = game
+                .grid()
+                .positions_of_color(self.player)
+                .next()
+                .unwrap_or(Position { x: 0, y: 0 });
+            return Some(Screen::Game(Game::new(
+                cursor,
+                game,
+                self.player,
+                self.humans,
+                self.settings,
+                self.code,
+                self.levels,
+            )));
+        }
+
+        wait_for_vblank_checked();
+        if self.pressed(keys, KeyInput::UP) && self.selected > 0 {
+            self.selected -= 1;
+            self.draw_selection();
+        }
+        if self.pressed(keys, KeyInput::DOWN) && self.selected < SPEED {
+            self.selected += 1;
+            self.draw_selection();
+        }
+        if self.selected == PLAYERS {
+            if self.pressed(keys, KeyInput::LEFT) {
+                self.cycle_players(false);
+            }
+            if self.pressed(keys, KeyInput::RIGHT) {
+                self.cycle_players(true);
+            }
+            self.prev_keys = keys;
+            return None;
+        }
+        if self.selected == BOARD {
+            if self.pressed(keys, KeyInput::LEFT) {
+                self.cycle_board(false);
+            }
+            if self.pressed(keys, KeyInput::RIGHT) {
+                self.cycle_board(true);
+            }
+            self.prev_keys = keys;
+            return None;
+        }
+        if self.selected == WRAPAROUND {
+            if self.pressed(keys, KeyInput::LEFT) || self.pressed(keys, KeyInput::RIGHT) {
+                self.code.rules.wraparound = !self.code.rules.wraparound;
+                self.draw_wraparound();
+                // Wrapping around changes where the choke points are.
+                if self.code.choke_points && self.code.level.is_none() {
+                    self.draw_thumbnail();
+                }
+            }
+            self.prev_keys = keys;
+            return None;
+        }
+        if self.selected == CHOKE_POINTS {
+            if (self.pressed(keys, KeyInput::LEFT) || self.pressed(keys, KeyInput::RIGHT))
+                && self.code.level.is_none()
+            {
+                self.code.choke_points = !self.code.choke_points;
+                self.draw_choke_points();
+                self.draw_thumbnail();
+            }
+            self.prev_keys = keys;
+            return None;
+        }
+        if self.selected == TURNS {
+            if self.pressed(keys, KeyInput::LEFT) {
+                self.turn_limit = (self.turn_limit + TURN_LIMITS.len() - 1) % TURN_LIMITS.len();
+                self.draw_turn_limit();
+            }
+            if self.pressed(keys, KeyInput::RIGHT) {
+                self.turn_limit = (self.turn_limit + 1) % TURN_LIMITS.len();
+                self.draw_turn_limit();
+            }
+            self.prev_keys = keys;
+            return None;
+        }
+        if self.selected == CONFIRM {
+            if self.pressed(keys, KeyInput::LEFT) || self.pressed(keys, KeyInput::RIGHT) {
+                self.settings.confirm = self.settings.confirm.toggle();
+                self.draw_confirm();
+            }
+            self.prev_keys = keys;
+            return None;
+        }
+        if self.selected == SPEED {
+            if self.pressed(keys, KeyInput::LEFT) {
+                self.settings.game_speed = self.settings.game_speed.previous();
+                self.draw_speed();
+            }
+            if self.pressed(keys, KeyInput::RIGHT) {
+                self.settings.game_speed = self.settings.game_speed.next();
+                self.draw_speed();
+            }
+            self.prev_keys = keys;
+            return None;
+        }
+        // Opponents left out of the game have nothing to change.
+        if !self.opponents[self.selected].plays(self.code.players) {
+            self.prev_keys = keys;
+            return None;
+        }
+        // A human comes after the last level and before the first.
+        let index = u8::from(self.opponents[self.selected]) as usize;
+        if self.pressed(keys, KeyInput::LEFT) {
+            if self.humans[index] {
+                self.humans[index] = false;
+                self.levels[index] = AiLevel::Random.previous();
+            } else if self.levels[index] == AiLevel::Random {
+                self.humans[index] = true;
+            } else {
+                self.levels[index] = self.levels[index].previous();
+            }
+            self.draw_level(self.selected);
+        }
+        if self.pressed(keys, KeyInput::RIGHT) {
+            if self.humans[index] {
+                self.humans[index] = false;
+                self.levels[index] = AiLevel::Random;
+            } else if self.levels[index] == AiLevel::Random.previous() {
+                self.humans[index] = true;
+            } else {
+                self.levels[index] = self.levels[index].next();
+            }
+            self.draw_level(self.selected);
+        }
+
+        self.prev_keys = keys;
+
+        None
+    }
+}
@@ -1,9 +1,8 @@
 use super::{Screen, Title};
 use crate::{
-    bios::wait_for_vblank,
+    bios::wait_for_vblank_checked,
     include_bytes_aligned,
     mmio::{
-        keys::KeyInput,
         vram::{
             BackgroundControl, BlendControl, Color, ColorEffect, DisplayControl, TextScreenEntry,
         },
@@ -75,7 +74,7 @@ impl Splash {
 
         // Fade in.
         for fade in (0..31).rev() {
-            wait_for_vblank();
+            wait_for_vblank_checked();
             unsafe {
                 BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
             }
@@ -86,13 +85,13 @@ impl Splash {
 
     pub fn run(&mut self) -> Option<Screen> {
         let keys = unsafe { KEYINPUT.read_volatile() };
-        if self.frame_count > 180 || keys.contains(KeyInput::A) {
-            wait_for_vblank();
+        if self.frame_count > 180 || keys.any_pressed() {
+            wait_for_vblank_checked();
 
             // Fade out.
-            wait_for_vblank();
+            wait_for_vblank_checked();
             for fade in 0..31 {
-                wait_for_vblank();
+                wait_for_vblank_checked();
                 unsafe {
                     BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
                 }
@@ -100,7 +99,7 @@ impl Splash {
 
             Some(Screen::Title(Title::new()))
         } else {
-            wait_for_vblank();
+            wait_for_vblank_checked();
             self.frame_count += 1;
             None
         }
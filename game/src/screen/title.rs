@@ -1,8 +1,7 @@
-use super::{Game, Screen};
+use super::{CodeEntry, ColorSelect, Credits, Debug, Game, Replay, Screen};
 use crate::{
-    bios::wait_for_vblank,
-    game,
-    game::{Color, Grid, Position},
+    bios::wait_for_vblank_checked,
+    game::{self, board_code, BoardCode},
     include_bytes_aligned,
     mmio::{
         keys::KeyInput,
@@ -10,10 +9,18 @@ use crate::{
         BG1CNT, BG2CNT, BG_PALETTE, BLDCNT, BLDY, CHARBLOCK0, DISPCNT, KEYINPUT,
         TEXT_SCREENBLOCK16, TEXT_SCREENBLOCK8,
     },
+    settings::Settings,
+    text,
 };
-use core::mem::transmute;
+use core::{mem::transmute, str};
 use deranged::{RangedU16, RangedU8};
 
+/// The column the board code starts at, after its label.
+const CODE_X: usize = (30 - 5 - board_code::LEN) / 2;
+
+/// The row the board code is drawn on, between the logo and the press a.
+const CODE_ROW: usize = 13;
+
 pub struct Title {
     random_seed: u64,
 }
@@ -128,9 +135,15 @@ impl Title {
             }
         }
 
+        // Show the code of the board A would start, and how to enter someone else's.
+        text::load();
+        text::draw(TEXT_SCREENBLOCK16, CODE_X, CODE_ROW, "CODE ");
+        draw_code(BoardCode::new(0));
+        text::draw_centered(TEXT_SCREENBLOCK16, 19, &["L: ENTER CODE"]);
+
         // Fade in.
         for fade in (0..31).rev() {
-            wait_for_vblank();
+            wait_for_vblank_checked();
             unsafe {
                 BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
             }
@@ -143,25 +156,98 @@ impl Title {
         let keys = unsafe { KEYINPUT.read_volatile() };
         if keys.contains(KeyInput::A) {
             // Fade out.
-            wait_for_vblank();
+            wait_for_vblank_checked();
             for fade in 0..31 {
-                wait_for_vblank();
+                wait_for_vblank_checked();
                 unsafe {
                     BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
                 }
             }
 
-            return Some(Screen::Game(Game::new(
-                Position { x: 0, y: 0 },
-                game::Game::builder()
-                    .grid(Grid::generate(self.random_seed))
-                    .build(),
-                Color::Red,
+            return Some(Screen::ColorSelect(ColorSelect::new(
+                // Holding SELECT while starting sets the game up for unattended demos.
+                if keys.contains(KeyInput::SELECT) {
+                    Settings::DEMO
+                } else {
+                    Settings::default()
+                },
+                BoardCode::new(self.random_seed),
             )));
         }
 
+        if keys.contains(KeyInput::B) {
+            // Fade out.
+            wait_for_vblank_checked();
+            for fade in 0..31 {
+                wait_for_vblank_checked();
+                unsafe {
+                    BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+                }
+            }
+
+            // Holding SELECT opens the hidden debug screen instead.
+            if keys.contains(KeyInput::SELECT) {
+                return Some(Screen::Debug(Debug::new()));
+            }
+            return Some(Screen::Replay(Replay::new(&game::Replay::DEMO)));
+        }
+
+        // START resumes the game saved when the console was last turned off, if there is one.
+        if keys.contains(KeyInput::START) && Game::can_resume() {
+            // Fade out.
+            wait_for_vblank_checked();
+            for fade in 0..31 {
+                wait_for_vblank_checked();
+                unsafe {
+                    BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+                }
+            }
+
+            return Game::resume(Settings::default()).map(Screen::Game);
+        }
+
+        if keys.contains(KeyInput::L) {
+            // Fade out.
+            wait_for_vblank_checked();
+            for fade in 0..31 {
+                wait_for_vblank_checked();
+                unsafe {
+                    BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+                }
+            }
+
+            return Some(Screen::CodeEntry(CodeEntry::new(BoardCode::new(
+                self.random_seed,
+            ))));
+        }
+
+        if keys.contains(KeyInput::R) {
+            // Fade out.
+            wait_for_vblank_checked();
+            for fade in 0..31 {
+                wait_for_vblank_checked();
+                unsafe {
+                    BLDY.write_volatile(RangedU8::new_unchecked(fade / 2));
+                }
+            }
+
+            return Some(Screen::Credits(Credits::new()));
+        }
+
         self.random_seed += 1;
+        // The code rolls along with the seed, and stops on the board that is played.
+        draw_code(BoardCode::new(self.random_seed));
 
         None
     }
 }
+
+fn draw_code(code: BoardCode) {
+    text::draw(
+        TEXT_SCREENBLOCK16,
+        CODE_X + 5,
+        CODE_ROW,
+        // The code is always ASCII.
+        str::from_utf8(&code.encode()).unwrap(),
+    );
+}
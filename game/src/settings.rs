@@ -0,0 +1,130 @@
+//! Options that change how a game is played.
+
+use core::num::NonZeroU16;
+
+/// How the player confirms the turn they want to take.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Confirm {
+    /// The turn is taken as soon as A is pressed.
+    #[default]
+    Tap,
+    /// A must be held for a moment before the turn is taken, so that accidental presses can be
+    /// released without taking a turn.
+    Hold,
+}
+
+impl Confirm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Tap => "TAP",
+            Self::Hold => "HOLD",
+        }
+    }
+
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Tap => Self::Hold,
+            Self::Hold => Self::Tap,
+        }
+    }
+}
+
+/// How quickly games are presented.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum GameSpeed {
+    #[default]
+    Normal,
+    /// Pauses, scrolling, and fades take half as long.
+    Fast,
+    /// Purely cosmetic waits are skipped entirely.
+    Instant,
+}
+
+impl GameSpeed {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "NORMAL",
+            Self::Fast => "FAST",
+            Self::Instant => "INSTANT",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Normal => Self::Fast,
+            Self::Fast => Self::Instant,
+            Self::Instant => Self::Normal,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            Self::Normal => Self::Instant,
+            Self::Fast => Self::Normal,
+            Self::Instant => Self::Fast,
+        }
+    }
+}
+
+/// How strong the AI opponents are by default.
+///
+/// Each opponent's level can still be changed individually before the game starts.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Difficulty {
+    /// Opponents play any legal move.
+    Easy,
+    /// Opponents play a mix of levels, mostly playing the move that captures the most nodes.
+    #[default]
+    Normal,
+    /// Opponents search two turns ahead.
+    Hard,
+}
+
+impl Difficulty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "EASY",
+            Self::Normal => "NORMAL",
+            Self::Hard => "HARD",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Easy => Self::Normal,
+            Self::Normal => Self::Hard,
+            Self::Hard => Self::Easy,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            Self::Easy => Self::Hard,
+            Self::Normal => Self::Easy,
+            Self::Hard => Self::Normal,
+        }
+    }
+}
+
+/// Options that change how a game is played.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Settings {
+    /// Number of frames the player can go without input on their turn before the AI takes the
+    /// turn for them.
+    ///
+    /// If this is `None`, the AI never takes over.
+    pub auto_play_after: Option<NonZeroU16>,
+    pub confirm: Confirm,
+    pub difficulty: Difficulty,
+    pub game_speed: GameSpeed,
+}
+
+impl Settings {
+    /// Settings for unattended demos, where the AI takes over after 30 seconds of no input.
+    pub const DEMO: Self = Self {
+        auto_play_after: NonZeroU16::new(30 * 60),
+        confirm: Confirm::Tap,
+        difficulty: Difficulty::Normal,
+        game_speed: GameSpeed::Normal,
+    };
+}
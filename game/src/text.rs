@@ -0,0 +1,268 @@
+//! Text rendering on tiled backgrounds.
+//!
+//! The font is stored as 1bpp 8x8 glyphs and expanded into 4bpp tiles when loaded, so no asset
+//! files are needed for it. Glyphs are drawn opaque: color 1 of the text palette bank is used for
+//! the glyph itself and color 2 for the space around it, which keeps text legible over the board.
+
+use crate::mmio::{
+    vram::{Color, TextScreenEntry},
+    BG_PALETTE, CHARBLOCK0,
+};
+use deranged::{RangedU16, RangedU8};
+
+/// The tile index of the blank (fully transparent) tile, directly followed by the glyph tiles.
+///
+/// These are the last tiles of charblock 0, which is otherwise only partially used.
+const BLANK_TILE: u16 = 447;
+const GLYPH_TILE_START: u16 = BLANK_TILE + 1;
+
+/// The background palette bank used for text.
+pub const PALETTE: RangedU8<0, 15> = RangedU8::new_static::<15>();
+
+/// Glyphs for ASCII `' '` through `'_'`.
+///
+/// Each byte is a row, with the least significant bit being the leftmost pixel.
+const FONT: [[u8; 8]; 64] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00], // '!'
+    [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00], // '#'
+    [0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00], // '$'
+    [0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00], // '%'
+    [0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00], // '&'
+    [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // '''
+    [0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00], // '('
+    [0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00], // ')'
+    [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00], // '*'
+    [0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ','
+    [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // '.'
+    [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // '/'
+    [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00], // '0'
+    [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00], // '1'
+    [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00], // '2'
+    [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00], // '3'
+    [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00], // '4'
+    [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00], // '5'
+    [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00], // '6'
+    [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00], // '7'
+    [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00], // '8'
+    [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ';'
+    [0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00], // '<'
+    [0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00], // '='
+    [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00], // '>'
+    [0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00], // '?'
+    [0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00], // '@'
+    [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00], // 'A'
+    [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00], // 'B'
+    [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00], // 'C'
+    [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00], // 'D'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00], // 'E'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00], // 'F'
+    [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00], // 'G'
+    [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00], // 'H'
+    [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'I'
+    [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00], // 'J'
+    [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00], // 'K'
+    [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00], // 'L'
+    [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00], // 'M'
+    [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00], // 'N'
+    [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00], // 'O'
+    [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00], // 'P'
+    [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00], // 'Q'
+    [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00], // 'R'
+    [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00], // 'S'
+    [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'T'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00], // 'U'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'V'
+    [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00], // 'W'
+    [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00], // 'X'
+    [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00], // 'Y'
+    [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00], // 'Z'
+    [0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00], // '['
+    [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00], // '\'
+    [0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00], // ']'
+    [0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF], // '_'
+];
+
+/// Expands a 1bpp glyph into a 4bpp tile.
+const fn expand_glyph(glyph: [u8; 8]) -> [u32; 8] {
+    let mut tile = [0; 8];
+    let mut y = 0;
+    while y < 8 {
+        let mut x = 0;
+        while x < 8 {
+            let color = if glyph[y] & (1 << x) != 0 { 1 } else { 2 };
+            tile[y] |= color << (x * 4);
+            x += 1;
+        }
+        y += 1;
+    }
+    tile
+}
+
+/// Returns the 1bpp glyph for the given character, as stored in the font.
+///
+/// Characters without a glyph are given the glyph for `'?'`.
+pub fn glyph(character: char) -> [u8; 8] {
+    match character.to_ascii_uppercase() {
+        character @ ' '..='_' => FONT[character as usize - ' ' as usize],
+        _ => FONT['?' as usize - ' ' as usize],
+    }
+}
+
+/// Returns the tile used to draw the given character.
+///
+/// Lowercase letters are drawn as uppercase. Characters without a glyph are drawn as `'?'`.
+fn glyph_tile(character: char) -> RangedU16<0, 1023> {
+    let index = match character.to_ascii_uppercase() {
+        character @ ' '..='_' => character as u16 - ' ' as u16,
+        _ => '?' as u16 - ' ' as u16,
+    };
+    unsafe { RangedU16::new_unchecked(GLYPH_TILE_START + index) }
+}
+
+/// Loads the font tiles and the text palette.
+pub fn load() {
+    unsafe {
+        CHARBLOCK0.add(BLANK_TILE as usize).write_volatile([0; 8]);
+        for (index, glyph) in FONT.into_iter().enumerate() {
+            CHARBLOCK0
+                .add(GLYPH_TILE_START as usize + index)
+                .write_volatile(expand_glyph(glyph));
+        }
+
+        let palette = BG_PALETTE.add(PALETTE.get() as usize).cast::<Color>();
+        palette.add(1).write_volatile(Color::new(
+            RangedU8::new_static::<31>(),
+            RangedU8::new_static::<31>(),
+            RangedU8::new_static::<31>(),
+        ));
+        palette.add(2).write_volatile(Color::new(
+            RangedU8::new_static::<2>(),
+            RangedU8::new_static::<2>(),
+            RangedU8::new_static::<5>(),
+        ));
+    }
+}
+
+/// Draws `text` on a 32x32 screenblock, beginning at tile `(x, y)`.
+///
+/// Text is not wrapped; the caller is responsible for keeping it within the screenblock.
+pub fn draw(screenblock: *mut TextScreenEntry, x: usize, y: usize, text: &str) {
+    for (offset, character) in text.chars().enumerate() {
+        unsafe {
+            screenblock.add(y * 32 + x + offset).write_volatile(
+                TextScreenEntry::new()
+                    .with_tile(glyph_tile(character))
+                    .with_palette(PALETTE),
+            );
+        }
+    }
+}
+
+/// Draws `parts` one after another, centered across the 30 visible tiles of row `y`.
+pub fn draw_centered(screenblock: *mut TextScreenEntry, y: usize, parts: &[&str]) {
+    let len: usize = parts.iter().map(|part| part.len()).sum();
+    let mut x = (30 - len) / 2;
+    for part in parts {
+        draw(screenblock, x, y, part);
+        x += part.len();
+    }
+}
+
+/// Clears `len` tiles on a 32x32 screenblock, beginning at tile `(x, y)`.
+pub fn clear(screenblock: *mut TextScreenEntry, x: usize, y: usize, len: usize) {
+    for offset in 0..len {
+        unsafe {
+            screenblock.add(y * 32 + x + offset).write_volatile(
+                TextScreenEntry::new().with_tile(RangedU16::new_static::<{ BLANK_TILE }>()),
+            );
+        }
+    }
+}
+
+/// Formats `value` as decimal digits, using `buffer` as storage.
+pub fn format_number(value: u16, buffer: &mut [u8; 5]) -> &str {
+    let mut value = value;
+    let mut start = buffer.len();
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    // The digits are always ASCII.
+    core::str::from_utf8(&buffer[start..]).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_glyph, format_number, glyph_tile, FONT, GLYPH_TILE_START};
+    use gba_test::test;
+
+    #[test]
+    fn expand_glyph_empty() {
+        assert_eq!(expand_glyph([0; 8]), [0x2222_2222; 8]);
+    }
+
+    #[test]
+    fn expand_glyph_leftmost_pixel() {
+        assert_eq!(expand_glyph([0x01, 0, 0, 0, 0, 0, 0, 0])[0], 0x2222_2221);
+    }
+
+    #[test]
+    fn expand_glyph_rightmost_pixel() {
+        assert_eq!(expand_glyph([0, 0, 0, 0, 0, 0, 0, 0x80])[7], 0x1222_2222);
+    }
+
+    #[test]
+    fn glyph_tile_space() {
+        assert_eq!(glyph_tile(' ').get(), GLYPH_TILE_START);
+    }
+
+    #[test]
+    fn glyph_tile_uppercase() {
+        assert_eq!(glyph_tile('A').get(), GLYPH_TILE_START + 33);
+    }
+
+    #[test]
+    fn glyph_tile_lowercase() {
+        assert_eq!(glyph_tile('a'), glyph_tile('A'));
+    }
+
+    #[test]
+    fn glyph_tile_unsupported() {
+        assert_eq!(glyph_tile('~'), glyph_tile('?'));
+        assert_eq!(glyph_tile('—'), glyph_tile('?'));
+    }
+
+    #[test]
+    fn glyph_tile_last() {
+        assert_eq!(
+            glyph_tile('_').get(),
+            GLYPH_TILE_START + FONT.len() as u16 - 1
+        );
+    }
+
+    #[test]
+    fn format_number_zero() {
+        assert_eq!(format_number(0, &mut [0; 5]), "0");
+    }
+
+    #[test]
+    fn format_number_digits() {
+        assert_eq!(format_number(230, &mut [0; 5]), "230");
+    }
+
+    #[test]
+    fn format_number_max() {
+        assert_eq!(format_number(u16::MAX, &mut [0; 5]), "65535");
+    }
+}
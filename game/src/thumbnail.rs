@@ -0,0 +1,168 @@
+//! Small pictures of whole boards, so the player can recognize a board at a glance.
+//!
+//! Each node is drawn as a 2x2 block of pixels, so a 16x16 board fits in a 4x4 patch of tiles.
+
+use crate::{
+    game::{Color as NodeColor, Grid, Node},
+    mmio::{
+        vram::{Color, TextScreenEntry},
+        BG_PALETTE, CHARBLOCK0,
+    },
+};
+use deranged::{RangedU16, RangedU8};
+
+/// The width and height of a thumbnail, in tiles.
+pub const SIZE: usize = 4;
+
+/// The first of the tiles a thumbnail is drawn into.
+///
+/// Screens that draw thumbnails only use charblock 0 for text, which is at its end.
+const FIRST_TILE: u16 = 0;
+
+/// The background palette bank thumbnails are drawn with.
+const PALETTE: RangedU8<0, 15> = RangedU8::new_static::<14>();
+
+/// The palette color a node is drawn with.
+fn node_color(node: &Node) -> u32 {
+    match node {
+        // The same as the space around text.
+        Node::Empty => 1,
+        Node::Wall => 2,
+        _ => match node.color() {
+            None => 3,
+            Some(NodeColor::Red) => 4,
+            Some(NodeColor::Blue) => 5,
+            Some(NodeColor::Yellow) => 6,
+            Some(NodeColor::Green) => 7,
+        },
+    }
+}
+
+/// Rasterize `grid` into tiles, ordered left to right and then top to bottom.
+fn tiles(grid: &Grid) -> [[u32; 8]; SIZE * SIZE] {
+    let mut tiles = [[0; 8]; SIZE * SIZE];
+    for (y, row) in grid.iter().enumerate() {
+        for (x, node) in row.iter().enumerate() {
+            // Each tile is 4 nodes across and 4 down.
+            let tile = &mut tiles[y / 4 * SIZE + x / 4];
+            let color = node_color(node);
+            let block = color << ((x % 4) * 8) | color << ((x % 4) * 8 + 4);
+            tile[(y % 4) * 2] |= block;
+            tile[(y % 4) * 2 + 1] |= block;
+        }
+    }
+    tiles
+}
+
+/// Load the colors thumbnails are drawn with.
+pub fn load_palette() {
+    let colors = [
+        (1, (2, 2, 5)),
+        (2, (10, 10, 12)),
+        (3, (28, 28, 28)),
+        (4, (31, 6, 6)),
+        (5, (6, 10, 31)),
+        (6, (31, 28, 4)),
+        (7, (6, 26, 6)),
+    ];
+    let palette = unsafe { BG_PALETTE.add(PALETTE.get() as usize).cast::<Color>() };
+    for (index, (red, green, blue)) in colors {
+        unsafe {
+            palette.add(index).write_volatile(Color::new(
+                RangedU8::new_unchecked(red),
+                RangedU8::new_unchecked(green),
+                RangedU8::new_unchecked(blue),
+            ));
+        }
+    }
+}
+
+/// Draw a thumbnail of `grid` on a 32x32 screenblock, with its top left corner at tile `(x, y)`.
+///
+/// Only one thumbnail can be shown at a time, since each one is drawn into the same tiles.
+pub fn draw(grid: &Grid, screenblock: *mut TextScreenEntry, x: usize, y: usize) {
+    for (index, tile) in tiles(grid).into_iter().enumerate() {
+        unsafe {
+            CHARBLOCK0
+                .add(FIRST_TILE as usize + index)
+                .write_volatile(tile);
+            screenblock
+                .add((y + index / SIZE) * 32 + x + index % SIZE)
+                .write_volatile(
+                    TextScreenEntry::new()
+                        .with_tile(RangedU16::new_unchecked(FIRST_TILE + index as u16))
+                        .with_palette(PALETTE),
+                );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tiles, SIZE};
+    use crate::game::{Color, Direction, Grid, Node};
+    use gba_test::test;
+
+    #[test]
+    fn tiles_empty() {
+        assert_eq!(
+            tiles(&Grid::new([[Node::Empty; 16]; 16])),
+            [[0x1111_1111; 8]; SIZE * SIZE]
+        );
+    }
+
+    #[test]
+    fn tiles_top_left_node() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[0][0] = Node::Arrow {
+            alignment: Some(Color::Red),
+            direction: Direction::Up,
+        };
+
+        let tiles = tiles(&Grid::new(nodes));
+
+        assert_eq!(tiles[0][0], 0x1111_1144);
+        assert_eq!(tiles[0][1], 0x1111_1144);
+        assert_eq!(tiles[0][2], 0x1111_1111);
+    }
+
+    #[test]
+    fn tiles_bottom_right_node() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        nodes[15][15] = Node::Wall;
+
+        let tiles = tiles(&Grid::new(nodes));
+
+        assert_eq!(tiles[SIZE * SIZE - 1][6], 0x2211_1111);
+        assert_eq!(tiles[SIZE * SIZE - 1][7], 0x2211_1111);
+        assert_eq!(tiles[SIZE * SIZE - 1][5], 0x1111_1111);
+    }
+
+    #[test]
+    fn tiles_node_in_second_tile_row() {
+        let mut nodes = [[Node::Empty; 16]; 16];
+        // The second node down in the tile below the top left one.
+        nodes[5][1] = Node::AllDirection { alignment: None };
+
+        let tiles = tiles(&Grid::new(nodes));
+
+        assert_eq!(tiles[SIZE][2], 0x1111_3311);
+        assert_eq!(tiles[SIZE][3], 0x1111_3311);
+    }
+
+    #[test]
+    fn tiles_neutral_and_aligned_differ() {
+        let mut neutral = [[Node::Empty; 16]; 16];
+        neutral[0][0] = Node::SuperArrow {
+            alignment: None,
+            direction: Direction::Left,
+        };
+        let mut aligned = neutral;
+        aligned[0][0] = Node::SuperArrow {
+            alignment: Some(Color::Green),
+            direction: Direction::Left,
+        };
+
+        assert_ne!(tiles(&Grid::new(neutral)), tiles(&Grid::new(aligned)));
+    }
+}
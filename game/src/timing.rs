@@ -0,0 +1,93 @@
+//! How long presentation takes, and how the game speed setting scales it.
+//!
+//! Durations are in frames and velocities in pixels per frame, both at normal speed.
+
+use crate::settings::GameSpeed;
+
+/// How long to pause after an AI turn, so the player can see what happened.
+pub const AI_COOLDOWN: u16 = 30;
+/// How long to pause after an AI turn that captured too many nodes to present one at a time.
+pub const MEGA_CAPTURE_COOLDOWN: u16 = 60;
+/// How long the mega capture banner is shown after the player's own turn.
+pub const MEGA_CAPTURE_BANNER: u16 = 60;
+/// How long the banner is shown when a player's turn is passed.
+pub const PASS_BANNER: u16 = 90;
+/// How long the banner is shown for each color a turn eliminates.
+pub const ELIMINATION_BANNER: u16 = 90;
+/// How long the AI waits to respond to the player's turn, during which the turn can be undone.
+///
+/// This isn't scaled by the game speed, since it's the player's chance to react rather than
+/// something to watch.
+pub const UNDO_WINDOW: u16 = 45;
+/// How long fading the game screen in takes.
+pub const FADE: u16 = 31;
+
+/// How fast the camera scrolls to the node an AI player rotated.
+pub const AI_SCROLL: u16 = 2;
+/// How fast the camera catches back up to the cursor at the start of the player's turn.
+pub const CATCH_UP_SCROLL: u16 = 2;
+/// How fast the camera follows the cursor.
+pub const FOLLOW_SCROLL: u16 = 1;
+
+/// Scale a duration by the game speed.
+///
+/// Instant speed skips the wait entirely.
+pub fn scaled(frames: u16, speed: GameSpeed) -> u16 {
+    match speed {
+        GameSpeed::Normal => frames,
+        GameSpeed::Fast => frames.div_ceil(2),
+        GameSpeed::Instant => 0,
+    }
+}
+
+/// Scale a velocity by the game speed.
+///
+/// Instant speed arrives at any destination in a single frame.
+pub fn scaled_velocity(pixels: u16, speed: GameSpeed) -> u16 {
+    match speed {
+        GameSpeed::Normal => pixels,
+        GameSpeed::Fast => pixels.saturating_mul(2),
+        GameSpeed::Instant => u16::MAX,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scaled, scaled_velocity, AI_COOLDOWN, FADE, PASS_BANNER};
+    use crate::settings::GameSpeed;
+    use gba_test::test;
+
+    #[test]
+    fn scaled_normal_unchanged() {
+        assert_eq!(scaled(AI_COOLDOWN, GameSpeed::Normal), AI_COOLDOWN);
+    }
+
+    #[test]
+    fn scaled_fast_halves() {
+        assert_eq!(scaled(PASS_BANNER, GameSpeed::Fast), PASS_BANNER / 2);
+    }
+
+    #[test]
+    fn scaled_fast_rounds_up() {
+        // An odd number of frames still waits for at least half of them.
+        assert_eq!(scaled(FADE, GameSpeed::Fast), 16);
+        assert_eq!(scaled(1, GameSpeed::Fast), 1);
+    }
+
+    #[test]
+    fn scaled_instant_skips() {
+        assert_eq!(scaled(PASS_BANNER, GameSpeed::Instant), 0);
+    }
+
+    #[test]
+    fn scaled_velocity_by_speed() {
+        assert_eq!(scaled_velocity(2, GameSpeed::Normal), 2);
+        assert_eq!(scaled_velocity(2, GameSpeed::Fast), 4);
+        assert_eq!(scaled_velocity(2, GameSpeed::Instant), u16::MAX);
+    }
+
+    #[test]
+    fn scaled_velocity_saturates() {
+        assert_eq!(scaled_velocity(u16::MAX, GameSpeed::Fast), u16::MAX);
+    }
+}
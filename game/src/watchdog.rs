@@ -0,0 +1,299 @@
+//! Detection of frames whose work doesn't finish before the next v-blank.
+//!
+//! The game assumes everything between two calls to `wait_for_vblank()` fits in a single frame.
+//! When it doesn't, the game just slows down, which is easy to miss. In debug builds, every wait
+//! checks how long the work before it took and logs the screen that overran.
+
+use crate::mmio::VCOUNT;
+use core::ptr::{addr_of, addr_of_mut};
+use log::warn;
+
+/// The number of scanlines in a frame, including those drawn during v-blank.
+const SCANLINES: u32 = 228;
+/// The scanline at which v-blank starts, and at which `FRAME_COUNT` is incremented.
+const VBLANK_START: u16 = 160;
+/// The minimum number of frames between two logged overruns.
+const REPORT_INTERVAL: u32 = 60;
+
+/// The number of v-blank interrupts since startup.
+///
+/// This is incremented by the interrupt handler in `runtime`.
+pub static mut FRAME_COUNT: u32 = 0;
+
+static mut WATCHDOG: Watchdog = Watchdog::new();
+
+/// A point in time, as precise as a scanline.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Mark {
+    frame: u32,
+    scanline: u16,
+}
+
+impl Mark {
+    fn now() -> Self {
+        unsafe {
+            Self {
+                frame: addr_of!(FRAME_COUNT).read_volatile(),
+                scanline: VCOUNT.read_volatile(),
+            }
+        }
+    }
+
+    /// The number of scanlines since `FRAME_COUNT` was last incremented.
+    fn scanlines_into_frame(self) -> u32 {
+        ((self.scanline + SCANLINES as u16 - VBLANK_START) % SCANLINES as u16) as u32
+    }
+}
+
+/// The number of frames between two values of `FRAME_COUNT`, accounting for wraparound.
+fn frames_between(start: u32, end: u32) -> u32 {
+    end.wrapping_sub(start)
+}
+
+/// The number of scanlines drawn between two marks.
+fn scanlines_between(start: Mark, end: Mark) -> u32 {
+    frames_between(start.frame, end.frame)
+        .saturating_mul(SCANLINES)
+        .saturating_add(end.scanlines_into_frame())
+        .saturating_sub(start.scanlines_into_frame())
+}
+
+/// An overrun that should be logged.
+#[derive(Debug, Eq, PartialEq)]
+struct Overrun {
+    scanlines: u32,
+    start_scanline: u16,
+}
+
+#[derive(Debug)]
+struct Watchdog {
+    /// The screen whose `run()` is being executed.
+    screen: &'static str,
+    /// When the work being timed started, if any work is being timed.
+    start: Option<Mark>,
+    overruns: u16,
+    /// The frame the last overrun was logged on.
+    last_report: Option<u32>,
+}
+
+impl Watchdog {
+    const fn new() -> Self {
+        Self {
+            screen: "",
+            start: None,
+            overruns: 0,
+            last_report: None,
+        }
+    }
+
+    /// Start timing work at `now`.
+    fn start(&mut self, now: Mark) {
+        self.start = Some(now);
+    }
+
+    /// Stop timing work at `now`, returning the overrun if it took more than a frame and one hasn't
+    /// been reported recently.
+    fn stop(&mut self, now: Mark) -> Option<Overrun> {
+        let start = self.start.take()?;
+        let scanlines = scanlines_between(start, now);
+        if scanlines <= SCANLINES {
+            return None;
+        }
+        self.overruns = self.overruns.saturating_add(1);
+
+        if self
+            .last_report
+            .is_some_and(|last| frames_between(last, now.frame) < REPORT_INTERVAL)
+        {
+            return None;
+        }
+        self.last_report = Some(now.frame);
+        Some(Overrun {
+            scanlines,
+            start_scanline: start.scanline,
+        })
+    }
+}
+
+/// Start timing the work of a screen's `run()`.
+pub fn begin(screen: &'static str) {
+    unsafe {
+        let watchdog = &mut *addr_of_mut!(WATCHDOG);
+        watchdog.screen = screen;
+        watchdog.start(Mark::now());
+    }
+}
+
+/// Check the work since the last wait, or since `begin()`, before waiting for v-blank.
+pub fn check() {
+    let watchdog = unsafe { &mut *addr_of_mut!(WATCHDOG) };
+    if let Some(overrun) = watchdog.stop(Mark::now()) {
+        warn!(
+            "frame overrun in {}: took {} scanlines, starting at scanline {} ({} total)",
+            watchdog.screen, overrun.scanlines, overrun.start_scanline, watchdog.overruns
+        );
+    }
+}
+
+/// Start timing the work following a wait for v-blank.
+pub fn resume() {
+    unsafe { (*addr_of_mut!(WATCHDOG)).start(Mark::now()) }
+}
+
+/// The number of overruns detected since startup.
+pub fn overruns() -> u16 {
+    unsafe { (*addr_of!(WATCHDOG)).overruns }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{frames_between, scanlines_between, Mark, Overrun, Watchdog, REPORT_INTERVAL};
+    use gba_test::test;
+
+    #[test]
+    fn frames_between_wraps() {
+        assert_eq!(frames_between(u32::MAX, 1), 2);
+    }
+
+    #[test]
+    fn scanlines_within_frame() {
+        assert_eq!(
+            scanlines_between(
+                Mark {
+                    frame: 5,
+                    scanline: 10
+                },
+                Mark {
+                    frame: 5,
+                    scanline: 100
+                }
+            ),
+            90
+        );
+    }
+
+    #[test]
+    fn scanlines_across_vblank() {
+        // The frame count is incremented when v-blank starts, at scanline 160.
+        assert_eq!(
+            scanlines_between(
+                Mark {
+                    frame: 5,
+                    scanline: 150
+                },
+                Mark {
+                    frame: 6,
+                    scanline: 170
+                }
+            ),
+            20
+        );
+    }
+
+    #[test]
+    fn scanlines_across_line_zero() {
+        assert_eq!(
+            scanlines_between(
+                Mark {
+                    frame: 5,
+                    scanline: 220
+                },
+                Mark {
+                    frame: 5,
+                    scanline: 4
+                }
+            ),
+            12
+        );
+    }
+
+    #[test]
+    fn scanlines_across_frame_count_wraparound() {
+        assert_eq!(
+            scanlines_between(
+                Mark {
+                    frame: u32::MAX,
+                    scanline: 100
+                },
+                Mark {
+                    frame: 1,
+                    scanline: 100
+                }
+            ),
+            456
+        );
+    }
+
+    #[test]
+    fn stop_within_frame() {
+        let mut watchdog = Watchdog::new();
+        watchdog.start(Mark {
+            frame: 0,
+            scanline: 0,
+        });
+
+        assert_eq!(
+            watchdog.stop(Mark {
+                frame: 1,
+                scanline: 200
+            }),
+            None
+        );
+        assert_eq!(watchdog.overruns, 0);
+    }
+
+    #[test]
+    fn stop_after_overrun() {
+        let mut watchdog = Watchdog::new();
+        watchdog.start(Mark {
+            frame: 0,
+            scanline: 10,
+        });
+
+        assert_eq!(
+            watchdog.stop(Mark {
+                frame: 2,
+                scanline: 10
+            }),
+            Some(Overrun {
+                scanlines: 456,
+                start_scanline: 10
+            })
+        );
+        assert_eq!(watchdog.overruns, 1);
+    }
+
+    #[test]
+    fn stop_without_start() {
+        let mut watchdog = Watchdog::new();
+
+        assert_eq!(
+            watchdog.stop(Mark {
+                frame: 100,
+                scanline: 0
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn reports_rate_limited() {
+        let mut watchdog = Watchdog::new();
+        let mut reports = 0;
+        for frame in (0..REPORT_INTERVAL * 3).step_by(2) {
+            watchdog.start(Mark { frame, scanline: 0 });
+            if watchdog
+                .stop(Mark {
+                    frame: frame + 2,
+                    scanline: 0,
+                })
+                .is_some()
+            {
+                reports += 1;
+            }
+        }
+
+        assert_eq!(reports, 3);
+        assert_eq!(watchdog.overruns as u32, REPORT_INTERVAL * 3 / 2);
+    }
+}